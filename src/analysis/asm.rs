@@ -0,0 +1,83 @@
+//! Inventory of the inline assembly of a crate.
+//!
+//! Every `asm!` and `naked_asm!` invocation in a function body and every
+//! module-level `global_asm!` is listed with its templates, operands and
+//! options, so that hand-written assembly can be audited in one place. Since
+//! assembly is almost always written for one target, each entry also carries
+//! the `#[cfg]` predicates that gate it, from the enclosing modules, impl or
+//! trait, function and statements.
+
+use serde::Serialize;
+
+use super::callgraph::item_path;
+use crate::entities::asm::RustInlineAsm;
+use crate::entities::module::RustModule;
+use crate::entities::rcrate::RustCrate;
+
+/// One inline assembly invocation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AsmEntry {
+    /// Path of the module the invocation is in.
+    pub module: String,
+    /// Item path of the function whose body holds the invocation; `None` for
+    /// `global_asm!` at module level.
+    pub containing_callable: Option<String>,
+    pub file_path: Option<String>,
+    #[serde(flatten)]
+    pub asm: RustInlineAsm,
+}
+
+/// The inline assembly of one crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AsmReport {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub entries: Vec<AsmEntry>,
+}
+
+impl AsmReport {
+    /// Collects the inline assembly of `krate`, in module order.
+    pub fn build(krate: &RustCrate) -> Self {
+        let mut report = AsmReport {
+            krate: krate.name.clone(),
+            entries: Vec::new(),
+        };
+        for root in &krate.modules {
+            report.add_module(root, &root.name, &[]);
+        }
+        report
+    }
+
+    fn add_module(&mut self, module: &RustModule, path: &str, outer_cfg: &[String]) {
+        let mut cfg = outer_cfg.to_vec();
+        cfg.extend(
+            module
+                .attributes
+                .iter()
+                .filter(|attr| attr.name == "cfg")
+                .flat_map(|attr| attr.arguments.iter().cloned()),
+        );
+        let mut push = |containing_callable: Option<String>, asm: &RustInlineAsm| {
+            let mut asm = asm.clone();
+            asm.cfg.splice(0..0, cfg.iter().cloned());
+            self.entries.push(AsmEntry {
+                module: path.to_owned(),
+                containing_callable,
+                file_path: module.file_path.clone(),
+                asm,
+            });
+        };
+        for asm in &module.global_asm {
+            push(None, asm);
+        }
+        for (owner, callable) in module.callables() {
+            for asm in &callable.inline_asm {
+                let item = item_path(path, owner.as_deref(), &callable.name);
+                push(Some(item), asm);
+            }
+        }
+        for sub in module.submodules.values() {
+            self.add_module(sub, &format!("{}::{}", path, sub.name), &cfg);
+        }
+    }
+}
@@ -0,0 +1,1022 @@
+//! Call graph over the callables of a crate.
+//!
+//! Call sites record the callee as it is written in the source, so they are
+//! resolved here by name, the way `rustc` would resolve the path as far as the
+//! entity model allows: `crate::`, `self::`, `super::` and `Self::` prefixes,
+//! `use` declarations of the calling module, paths relative to the calling
+//! module and `Type::method` paths. Method calls (`receiver.method()`) carry
+//! no type information and resolve to every method of that name, narrowed to
+//! the methods of `Self` for calls on `self`. Calls into other crates are not
+//! part of the graph.
+//!
+//! Method calls are dispatched dynamically when the receiver is a parameter,
+//! annotated local or field of `self` whose type is a trait object
+//! (`&dyn Trait`, `Box<dyn Trait>`), an `impl Trait` or a generic parameter
+//! bounded by traits, or when it is `self` in a default method of a trait.
+//! Such a call resolves to the method of every impl of the trait that
+//! declares it, directly or as a supertrait, and to the trait's default
+//! method for impls that do not override it (class hierarchy analysis).
+//! With [`Dispatch::Rta`], only impls for types the crate constructs are
+//! kept (rapid type analysis); this is only sound for whole programs, as a
+//! library's types are constructed by its users. A call that resolves by
+//! name to a method declared in a trait, such as `shape.area()` on an
+//! untyped local or `Shape::area(&shape)`, is dispatched the same way, except
+//! on `self` in an impl of the trait, which calls the impl's own method. The
+//! edges of these calls are marked [`CallEdge::is_virtual`].
+//!
+//! Callables are identified by their item path, e.g. `my_crate::util::parse`
+//! or `my_crate::<Config as Default>::default`, the same path used by the
+//! NDJSON ids and [`RustCrate::callable`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use serde::Serialize;
+use syn::{Type, TypeParamBound, WherePredicate};
+
+use crate::entities::callable::RustCallable;
+use crate::entities::callsite::CallSite;
+use crate::entities::module::RustModule;
+use crate::entities::rcrate::RustCrate;
+
+/// A callable of the crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CallableNode {
+    /// Item path of the callable.
+    pub path: String,
+    /// Path of the module declaring the callable.
+    pub module: String,
+    /// Owner segment of methods, see [`RustModule::callables`].
+    pub owner: Option<String>,
+    pub name: String,
+    pub file_path: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub is_async: bool,
+}
+
+/// A resolved call from one callable to another.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CallEdge {
+    pub caller: String,
+    pub callee: String,
+    /// Line of the call site.
+    pub line: u32,
+    /// The callee as written at the call site.
+    pub call: String,
+    /// Whether an async fn or async block calls an async callable.
+    pub is_async: bool,
+    /// Whether the call is dispatched dynamically through a trait.
+    pub is_virtual: bool,
+    /// Number of callables the call resolves to.
+    pub candidates: usize,
+}
+
+/// How method calls dispatched through a trait are resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dispatch {
+    /// Class hierarchy analysis: every implementation of the method.
+    #[default]
+    Cha,
+    /// Rapid type analysis: the implementations for the types that the crate
+    /// constructs.
+    Rta,
+}
+
+/// The resolved calls between the callables of a crate.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    nodes: BTreeMap<String, CallableNode>,
+    edges: Vec<CallEdge>,
+    outgoing: BTreeMap<String, Vec<usize>>,
+    incoming: BTreeMap<String, Vec<usize>>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of `krate`. Call sites are only recorded from
+    /// analysis level 2 on; at level 1 the graph has no edges.
+    pub fn build(krate: &RustCrate) -> Self {
+        CallGraph::build_with(krate, Dispatch::Cha)
+    }
+
+    /// Builds the call graph of `krate`, resolving calls dispatched through
+    /// a trait with `dispatch`.
+    pub fn build_with(krate: &RustCrate, dispatch: Dispatch) -> Self {
+        let resolver = Resolver::new(krate, dispatch);
+        let mut graph = CallGraph {
+            nodes: resolver.nodes.clone(),
+            ..Default::default()
+        };
+        let mut edges = BTreeSet::new();
+        resolver.resolve_all(krate, |caller, site, callees, is_virtual| {
+            let candidates = callees.len();
+            for callee in callees {
+                let is_async = site.is_async_context
+                    && resolver.nodes.get(&callee).is_some_and(|n| n.is_async);
+                edges.insert(CallEdge {
+                    caller: caller.to_string(),
+                    callee,
+                    line: site.line_number,
+                    call: site.callee.clone().unwrap_or_default(),
+                    is_async,
+                    is_virtual,
+                    candidates,
+                });
+            }
+        });
+        for (i, edge) in edges.into_iter().enumerate() {
+            graph
+                .outgoing
+                .entry(edge.caller.clone())
+                .or_default()
+                .push(i);
+            graph
+                .incoming
+                .entry(edge.callee.clone())
+                .or_default()
+                .push(i);
+            graph.edges.push(edge);
+        }
+        graph
+    }
+
+    /// Every callable of the crate, ordered by path.
+    pub fn nodes(&self) -> impl Iterator<Item = &CallableNode> {
+        self.nodes.values()
+    }
+
+    pub fn node(&self, path: &str) -> Option<&CallableNode> {
+        self.nodes.get(path)
+    }
+
+    /// Every resolved call, ordered by caller, callee and line.
+    pub fn edges(&self) -> &[CallEdge] {
+        &self.edges
+    }
+
+    /// Calls made by the callable at `path`.
+    pub fn callees(&self, path: &str) -> impl Iterator<Item = &CallEdge> {
+        self.lookup(&self.outgoing, path)
+    }
+
+    /// Calls made to the callable at `path`.
+    pub fn callers(&self, path: &str) -> impl Iterator<Item = &CallEdge> {
+        self.lookup(&self.incoming, path)
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        index: &'a BTreeMap<String, Vec<usize>>,
+        path: &str,
+    ) -> impl Iterator<Item = &'a CallEdge> {
+        index
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.edges[i])
+    }
+}
+
+/// The callables of a crate and the resolved calls between them, indexed for
+/// walking the callees of every callable in turn.
+pub struct CallWalker<'a> {
+    nodes: Vec<(String, &'a RustCallable)>,
+    callees: Vec<Vec<usize>>,
+    /// For every callable reached by the current walk, the callable it was
+    /// first reached from; the start of the walk points to itself.
+    parent: Vec<Option<usize>>,
+}
+
+impl<'a> CallWalker<'a> {
+    /// Indexes the callables of `krate`, ordered by item path, and the edges
+    /// of `graph` between them.
+    pub fn new(krate: &'a RustCrate, graph: &CallGraph) -> Self {
+        let mut callables: BTreeMap<String, &RustCallable> = BTreeMap::new();
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (owner, callable) in module.callables() {
+                    callables.insert(item_path(&path, owner.as_deref(), &callable.name), callable);
+                }
+            }
+        }
+        let nodes: Vec<(String, &RustCallable)> = callables.into_iter().collect();
+        let index: HashMap<&str, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, (path, _))| (path.as_str(), i))
+            .collect();
+        let mut callees: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+        for edge in graph.edges() {
+            let (Some(&caller), Some(&callee)) = (
+                index.get(edge.caller.as_str()),
+                index.get(edge.callee.as_str()),
+            ) else {
+                continue;
+            };
+            if !callees[caller].contains(&callee) {
+                callees[caller].push(callee);
+            }
+        }
+        let parent = vec![None; nodes.len()];
+        CallWalker {
+            nodes,
+            callees,
+            parent,
+        }
+    }
+
+    /// Number of callables.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the node of the callable at `path`.
+    pub fn node(&self, path: &str) -> Option<usize> {
+        self.nodes
+            .binary_search_by(|(p, _)| p.as_str().cmp(path))
+            .ok()
+    }
+
+    /// Item path of the callable `node`.
+    pub fn path(&self, node: usize) -> &str {
+        &self.nodes[node].0
+    }
+
+    pub fn callable(&self, node: usize) -> &'a RustCallable {
+        self.nodes[node].1
+    }
+
+    /// Calls `visit` with `start` and every callable it reaches, breadth
+    /// first, so that [`call_path`](Self::call_path) gives a shortest path
+    /// to each of them while they are visited.
+    pub fn walk(&mut self, start: usize, mut visit: impl FnMut(&Self, usize)) {
+        let mut order = vec![start];
+        self.parent[start] = Some(start);
+        let mut next = 0;
+        while let Some(&current) = order.get(next) {
+            next += 1;
+            for &callee in &self.callees[current] {
+                if self.parent[callee].is_none() {
+                    self.parent[callee] = Some(current);
+                    order.push(callee);
+                }
+            }
+        }
+        for &node in &order {
+            visit(self, node);
+        }
+        for node in order {
+            self.parent[node] = None;
+        }
+    }
+
+    /// Item paths of the callables from the start of the current walk to
+    /// `node`.
+    pub fn call_path(&self, node: usize) -> Vec<String> {
+        let mut out = vec![self.nodes[node].0.clone()];
+        let mut current = node;
+        while let Some(caller) = self.parent[current].filter(|&caller| caller != current) {
+            out.push(self.nodes[caller].0.clone());
+            current = caller;
+        }
+        out.reverse();
+        out
+    }
+}
+
+/// Records on every call site of `krate` the callables it resolves to with
+/// `dispatch`, see [`CallSite::callees`]. Call sites are only recorded from
+/// analysis level 2 on.
+pub fn resolve_calls(krate: &mut RustCrate, dispatch: Dispatch) {
+    let mut resolved: HashMap<String, Vec<(Vec<String>, bool)>> = HashMap::new();
+    Resolver::new(krate, dispatch).resolve_all(krate, |caller, _, mut callees, is_virtual| {
+        callees.sort();
+        callees.dedup();
+        resolved
+            .entry(caller.to_string())
+            .or_default()
+            .push((callees, is_virtual));
+    });
+    update_callables(krate, |path, callable| {
+        let Some(sites) = resolved.get(path) else {
+            return;
+        };
+        for (site, (callees, is_virtual)) in callable.call_sites.iter_mut().zip(sites) {
+            site.callees = callees.clone();
+            site.is_virtual = *is_virtual;
+        }
+    });
+}
+
+/// Calls `update` with the item path of every callable of `krate` and the
+/// callable, including the copies of functions in `safe_functions` and
+/// `unsafe_functions`.
+pub fn update_callables(krate: &mut RustCrate, mut update: impl FnMut(&str, &mut RustCallable)) {
+    for root in &mut krate.modules {
+        let path = root.name.clone();
+        update_module(root, &path, &mut update);
+    }
+}
+
+fn update_module(
+    module: &mut RustModule,
+    path: &str,
+    update: &mut impl FnMut(&str, &mut RustCallable),
+) {
+    for function in module
+        .functions
+        .values_mut()
+        .chain(module.safe_functions.values_mut())
+        .chain(module.unsafe_functions.values_mut())
+    {
+        update(&item_path(path, None, &function.name), function);
+    }
+    for imp in &mut module.impls {
+        let owner = imp.owner_name();
+        for method in imp.methods.values_mut() {
+            update(&item_path(path, Some(&owner), &method.name), method);
+        }
+    }
+    for rtrait in module.traits.values_mut() {
+        for method in rtrait.methods.values_mut() {
+            update(&item_path(path, Some(&rtrait.name), &method.name), method);
+        }
+    }
+    for sub in module.submodules.values_mut() {
+        update_module(sub, &format!("{}::{}", path, sub.name), update);
+    }
+}
+
+/// Returns the item path of a callable declared in the module at `module`.
+pub fn item_path(module: &str, owner: Option<&str>, name: &str) -> String {
+    match owner {
+        Some(owner) => format!("{}::{}::{}", module, owner, name),
+        None => format!("{}::{}", module, name),
+    }
+}
+
+/// Returns the type an owner segment belongs to: `Type` for `<Type as Trait>`
+/// and for `Type` itself.
+fn owner_type(owner: &str) -> &str {
+    match owner.strip_prefix('<') {
+        Some(qualified) => qualified.split(" as ").next().unwrap_or(qualified),
+        None => owner,
+    }
+}
+
+struct Resolver {
+    nodes: BTreeMap<String, CallableNode>,
+    /// Callables by the paths they can be called through: `module::function`,
+    /// `module::Type::method` and `module::Trait::method`.
+    by_path: BTreeMap<String, Vec<String>>,
+    /// Methods by name, with the type or trait they belong to.
+    methods: BTreeMap<String, Vec<(String, String)>>,
+    /// Traits of the crate by name.
+    traits: BTreeMap<String, TraitDecl>,
+    /// The trait declaring each trait method, by the method's item path.
+    trait_methods: BTreeMap<String, String>,
+    /// Trait impls by the name of the trait.
+    implementations: BTreeMap<String, Vec<TraitImpl>>,
+    /// The implementing type of each method of a trait impl, by the
+    /// method's item path.
+    impl_types: BTreeMap<String, String>,
+    /// Declared types of struct fields, by struct and field name.
+    fields: BTreeMap<(String, String), String>,
+    /// With [`Dispatch::Rta`], the types the crate constructs.
+    instantiated: Option<BTreeSet<String>>,
+}
+
+/// A trait of the crate.
+struct TraitDecl {
+    /// Methods by name, with whether they have a default body.
+    methods: BTreeMap<String, (String, bool)>,
+    supertraits: Vec<String>,
+}
+
+/// An impl of a trait.
+struct TraitImpl {
+    /// Name of the implementing type.
+    ty: String,
+    methods: BTreeMap<String, String>,
+}
+
+impl Resolver {
+    fn new(krate: &RustCrate, dispatch: Dispatch) -> Self {
+        let mut resolver = Resolver {
+            nodes: BTreeMap::new(),
+            by_path: BTreeMap::new(),
+            methods: BTreeMap::new(),
+            traits: BTreeMap::new(),
+            trait_methods: BTreeMap::new(),
+            implementations: BTreeMap::new(),
+            impl_types: BTreeMap::new(),
+            fields: BTreeMap::new(),
+            instantiated: (dispatch == Dispatch::Rta).then(BTreeSet::new),
+        };
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for function in module.functions.values() {
+                    let id = resolver.add(&path, module, None, function);
+                    resolver.by_path.entry(id.clone()).or_default().push(id);
+                }
+                for imp in &module.impls {
+                    let owner = imp.owner_name();
+                    let ty = imp.self_type_name().to_owned();
+                    let tr = imp.trait_name.as_deref().map(last_segment);
+                    let mut methods = BTreeMap::new();
+                    for method in imp.methods.values() {
+                        let id = resolver.add(&path, module, Some(&owner), method);
+                        resolver.add_path(&path, &ty, &method.name, &id);
+                        resolver.add_method(&ty, &method.name, &id);
+                        if let Some(tr) = tr {
+                            resolver.add_method(tr, &method.name, &id);
+                        }
+                        methods.insert(method.name.clone(), id);
+                    }
+                    if let Some(tr) = tr.filter(|_| !imp.is_negative) {
+                        for id in methods.values() {
+                            resolver.impl_types.insert(id.clone(), ty.clone());
+                        }
+                        resolver
+                            .implementations
+                            .entry(tr.to_owned())
+                            .or_default()
+                            .push(TraitImpl {
+                                ty: ty.clone(),
+                                methods,
+                            });
+                    }
+                }
+                for rtrait in module.traits.values() {
+                    let mut methods = BTreeMap::new();
+                    for method in rtrait.methods.values() {
+                        let id = resolver.add(&path, module, Some(&rtrait.name), method);
+                        resolver.add_path(&path, &rtrait.name, &method.name, &id);
+                        resolver.add_method(&rtrait.name, &method.name, &id);
+                        let has_body = method.cyclomatic_complexity.is_some();
+                        resolver
+                            .trait_methods
+                            .insert(id.clone(), rtrait.name.clone());
+                        methods.insert(method.name.clone(), (id, has_body));
+                    }
+                    let supertraits = rtrait
+                        .super_traits
+                        .iter()
+                        .map(|bound| last_segment(&bound.trait_name).to_owned())
+                        .collect();
+                    resolver.traits.insert(
+                        rtrait.name.clone(),
+                        TraitDecl {
+                            methods,
+                            supertraits,
+                        },
+                    );
+                }
+                for rstruct in module.structs.values() {
+                    for field in &rstruct.fields {
+                        resolver.fields.insert(
+                            (rstruct.name.clone(), field.name.clone()),
+                            field.ty.name.clone(),
+                        );
+                    }
+                }
+                if let Some(instantiated) = &mut resolver.instantiated {
+                    for (_, callable) in module.callables() {
+                        instantiated.extend(callable.instantiated_types.iter().cloned());
+                    }
+                }
+            }
+        }
+        resolver
+    }
+
+    fn add(
+        &mut self,
+        module_path: &str,
+        module: &RustModule,
+        owner: Option<&str>,
+        callable: &RustCallable,
+    ) -> String {
+        let path = item_path(module_path, owner, &callable.name);
+        self.nodes.insert(
+            path.clone(),
+            CallableNode {
+                path: path.clone(),
+                module: module_path.to_owned(),
+                owner: owner.map(str::to_owned),
+                name: callable.name.clone(),
+                file_path: module.file_path.clone(),
+                start_line: callable.start_line,
+                end_line: callable.end_line,
+                is_async: callable.is_async,
+            },
+        );
+        path
+    }
+
+    fn add_path(&mut self, module: &str, ty: &str, name: &str, id: &str) {
+        self.by_path
+            .entry(format!("{}::{}::{}", module, ty, name))
+            .or_default()
+            .push(id.to_owned());
+    }
+
+    fn add_method(&mut self, ty: &str, name: &str, id: &str) {
+        self.methods
+            .entry(name.to_owned())
+            .or_default()
+            .push((ty.to_owned(), id.to_owned()));
+    }
+
+    /// Calls `visit` with the item path of the caller, the call site, the
+    /// callables it may call and whether it is dispatched through a trait,
+    /// for every call site of `krate`.
+    fn resolve_all(
+        &self,
+        krate: &RustCrate,
+        mut visit: impl FnMut(&str, &CallSite, Vec<String>, bool),
+    ) {
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (owner, callable) in module.callables() {
+                    let caller = item_path(&path, owner.as_deref(), &callable.name);
+                    let self_type = owner.as_deref().map(owner_type);
+                    let receivers = Receivers::new(self, module, owner.as_deref(), callable);
+                    for site in &callable.call_sites {
+                        let (callees, is_virtual) =
+                            self.resolve(site, &path, module, self_type, &receivers);
+                        visit(&caller, site, callees, is_virtual);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the callables `site` may call, and whether the call is
+    /// dispatched through a trait.
+    fn resolve(
+        &self,
+        site: &CallSite,
+        module_path: &str,
+        module: &RustModule,
+        self_type: Option<&str>,
+        receivers: &Receivers,
+    ) -> (Vec<String>, bool) {
+        let Some(callee) = site.callee.as_deref() else {
+            return (Vec::new(), false);
+        };
+        if site.is_method_call {
+            let dispatched = site
+                .receiver
+                .as_deref()
+                .and_then(|receiver| receivers.traits(self, receiver))
+                .and_then(|traits| self.dispatch(&traits, callee));
+            if let Some(targets) = dispatched {
+                return (targets, true);
+            }
+        }
+        let targets = self.resolve_static(site, callee, module_path, module, self_type);
+        let on_self = site.receiver.as_deref() == Some("self");
+        let self_impl = self_type.filter(|_| on_self && receivers.self_trait.is_none());
+        self.expand_trait_methods(targets, self_impl)
+    }
+
+    /// Replaces the trait methods among `targets` with their implementations,
+    /// the call then being dispatched through the trait. A call on `self` in
+    /// an impl for `self_impl` calls that impl's method instead.
+    ///
+    /// Methods of trait impls that resolved by name are dispatched too, so
+    /// with [`Dispatch::Rta`] those of types that are never constructed are
+    /// dropped from a dispatched call.
+    fn expand_trait_methods(
+        &self,
+        targets: Vec<String>,
+        self_impl: Option<&str>,
+    ) -> (Vec<String>, bool) {
+        let trait_method = |id: &String| {
+            let tr = self.trait_methods.get(id)?;
+            Some((tr, &self.nodes.get(id)?.name))
+        };
+        if let Some(ty) = self_impl {
+            let own: BTreeSet<String> = targets
+                .iter()
+                .filter_map(trait_method)
+                .filter_map(|(tr, name)| self.implementation(tr, ty, name))
+                .collect();
+            if !own.is_empty() {
+                return (own.into_iter().collect(), false);
+            }
+        }
+        let mut expanded = BTreeSet::new();
+        let mut is_virtual = false;
+        for id in targets {
+            let Some((tr, name)) = trait_method(&id) else {
+                expanded.insert(id);
+                continue;
+            };
+            let implementations = self
+                .dispatch(std::slice::from_ref(tr), name)
+                .unwrap_or_default();
+            if implementations.is_empty() {
+                expanded.insert(id);
+            } else {
+                is_virtual = true;
+                expanded.extend(implementations);
+            }
+        }
+        if is_virtual {
+            expanded.retain(|id| {
+                self.impl_types
+                    .get(id)
+                    .is_none_or(|ty| self.is_constructed(ty))
+            });
+        }
+        (expanded.into_iter().collect(), is_virtual)
+    }
+
+    /// Returns the method `name` of the impl of `tr` for `ty`, or the
+    /// trait's default method if the impl does not override it.
+    fn implementation(&self, tr: &str, ty: &str, name: &str) -> Option<String> {
+        let imp = self
+            .implementations
+            .get(tr)?
+            .iter()
+            .find(|imp| imp.ty == ty)?;
+        let default = self
+            .traits
+            .get(tr)
+            .and_then(|decl| decl.methods.get(name))
+            .filter(|(_, has_body)| *has_body)
+            .map(|(id, _)| id);
+        imp.methods.get(name).or(default).cloned()
+    }
+
+    /// Returns the implementations of the method `name` of `traits`, or
+    /// `None` if none of them is known to declare it.
+    fn dispatch(&self, traits: &[String], name: &str) -> Option<Vec<String>> {
+        let mut targets = BTreeSet::new();
+        let mut declared = false;
+        for owner in traits.iter().flat_map(|tr| self.declaring_traits(tr, name)) {
+            let default = self
+                .traits
+                .get(&owner)
+                .and_then(|decl| decl.methods.get(name))
+                .inspect(|_| declared = true)
+                .filter(|(_, has_body)| *has_body)
+                .map(|(id, _)| id);
+            for imp in self.implementations.get(&owner).into_iter().flatten() {
+                let constructed = self.is_constructed(&imp.ty);
+                if let Some(id) = imp.methods.get(name).or(default).filter(|_| constructed) {
+                    targets.insert(id.clone());
+                }
+            }
+        }
+        (declared || !targets.is_empty()).then(|| targets.into_iter().collect())
+    }
+
+    /// Whether `ty` may be the type of a receiver: always with
+    /// [`Dispatch::Cha`], and only if the crate constructs it with
+    /// [`Dispatch::Rta`].
+    fn is_constructed(&self, ty: &str) -> bool {
+        self.instantiated
+            .as_ref()
+            .is_none_or(|types| types.contains(ty))
+    }
+
+    /// Returns the trait that declares the method `name` for a receiver
+    /// bounded by `tr`: `tr` or one of its supertraits. Traits of other
+    /// crates may declare any method, so they are all returned when no
+    /// trait of the crate declares it.
+    fn declaring_traits(&self, tr: &str, name: &str) -> Vec<String> {
+        let mut external = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([tr.to_owned()]);
+        while let Some(current) = queue.pop_front() {
+            if !seen.insert(current.clone()) {
+                continue;
+            }
+            match self.traits.get(&current) {
+                Some(decl) if decl.methods.contains_key(name) => return vec![current],
+                Some(decl) => queue.extend(decl.supertraits.iter().cloned()),
+                None => external.push(current),
+            }
+        }
+        external
+    }
+
+    /// Returns the callables `site` may call, from the name of the callee.
+    fn resolve_static(
+        &self,
+        site: &CallSite,
+        callee: &str,
+        module_path: &str,
+        module: &RustModule,
+        self_type: Option<&str>,
+    ) -> Vec<String> {
+        if site.is_method_call {
+            let candidates = self.methods.get(callee).into_iter().flatten();
+            let on_self: BTreeSet<&String> = candidates
+                .clone()
+                .filter(|(ty, _)| {
+                    site.receiver.as_deref() == Some("self") && Some(ty.as_str()) == self_type
+                })
+                .map(|(_, id)| id)
+                .collect();
+            if !on_self.is_empty() {
+                return on_self.into_iter().cloned().collect();
+            }
+            let all: BTreeSet<&String> = candidates.map(|(_, id)| id).collect();
+            return all.into_iter().cloned().collect();
+        }
+
+        let segments = path_segments(callee);
+        if segments.is_empty() {
+            return Vec::new();
+        }
+        let module_segments: Vec<&str> = module_path.split("::").collect();
+        for candidate in candidate_paths(&segments, &module_segments, module, self_type) {
+            if let Some(ids) = self.by_path.get(&candidate.join("::")) {
+                return ids.clone();
+            }
+        }
+        // `Type::method` with `Type` from another module or crate prelude.
+        if let [.., ty, name] = segments.as_slice() {
+            let ty = if *ty == "Self" {
+                self_type.unwrap_or(ty)
+            } else {
+                ty
+            };
+            let ids: BTreeSet<&String> = self
+                .methods
+                .get(*name)
+                .into_iter()
+                .flatten()
+                .filter(|(t, _)| t == ty)
+                .map(|(_, id)| id)
+                .collect();
+            return ids.into_iter().cloned().collect();
+        }
+        Vec::new()
+    }
+}
+
+/// Returns the absolute paths `segments` may refer to from the module at
+/// `module_segments`, most likely first.
+pub(crate) fn candidate_paths(
+    segments: &[&str],
+    module_segments: &[&str],
+    module: &RustModule,
+    self_type: Option<&str>,
+) -> Vec<Vec<String>> {
+    let mut out = Vec::new();
+    let first = segments[0];
+    if first == "Self" {
+        if let Some(ty) = self_type {
+            let mut path = to_owned(module_segments);
+            path.push(ty.to_owned());
+            path.extend(to_owned(&segments[1..]));
+            out.push(path);
+        }
+        return out;
+    }
+    if let Some(path) = absolute(segments, module_segments) {
+        out.push(path);
+        return out;
+    }
+    let mut globs = Vec::new();
+    for used in &module.use_declarations {
+        let (target, alias) = match used.split_once(" as ") {
+            Some((target, alias)) => (target, alias),
+            None => (used.as_str(), last_segment(used)),
+        };
+        let mut target: Vec<&str> = target.split("::").collect();
+        if alias == "*" {
+            target.pop();
+            target.extend_from_slice(segments);
+            globs.extend(absolute(&target, module_segments));
+            continue;
+        }
+        if alias != first {
+            continue;
+        }
+        if target.last() == Some(&"self") {
+            target.pop();
+        }
+        target.extend_from_slice(&segments[1..]);
+        out.push(absolute(&target, module_segments).unwrap_or_else(|| to_owned(&target)));
+    }
+    let mut relative = to_owned(module_segments);
+    relative.extend(to_owned(segments));
+    out.push(relative);
+    out.extend(globs);
+    if first != module_segments[0] {
+        let mut from_root = vec![module_segments[0].to_owned()];
+        from_root.extend(to_owned(segments));
+        out.push(from_root);
+    }
+    out.push(to_owned(segments));
+    out
+}
+
+/// What the caller knows about the types of method receivers.
+struct Receivers<'a> {
+    /// Declared types of the parameters and annotated locals.
+    locals: BTreeMap<&'a str, &'a str>,
+    /// Trait bounds of the generic parameters of the callable and its impl.
+    bounds: BTreeMap<String, Vec<String>>,
+    self_type: Option<&'a str>,
+    /// The trait of a default method, which `self` is dispatched through.
+    self_trait: Option<&'a str>,
+}
+
+impl<'a> Receivers<'a> {
+    fn new(
+        resolver: &Resolver,
+        module: &'a RustModule,
+        owner: Option<&'a str>,
+        callable: &'a RustCallable,
+    ) -> Self {
+        let mut locals = BTreeMap::new();
+        for param in &callable.parameters {
+            locals.insert(param.name.as_str(), param.rust_type.name.as_str());
+        }
+        for local in &callable.variable_declarations {
+            if let Some(ty) = &local.type_info {
+                locals.insert(local.name.as_str(), ty.name.as_str());
+            }
+        }
+        let imp = owner.and_then(|owner| module.impls.iter().find(|imp| imp.owner_name() == owner));
+        let generics = callable
+            .generic_params
+            .iter()
+            .chain(imp.into_iter().flat_map(|imp| &imp.generic_params));
+        let mut bounds: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for param in generics.filter(|param| !param.is_const) {
+            let traits = param
+                .bounds
+                .iter()
+                .filter_map(|bound| syn::parse_str::<TypeParamBound>(bound).ok())
+                .filter_map(|bound| bound_trait(&bound));
+            bounds.entry(param.name.clone()).or_default().extend(traits);
+        }
+        let where_clauses = callable
+            .where_clauses
+            .iter()
+            .chain(imp.into_iter().flat_map(|imp| &imp.where_clauses));
+        for clause in where_clauses {
+            let Ok(WherePredicate::Type(predicate)) = syn::parse_str(clause) else {
+                continue;
+            };
+            let Type::Path(ty) = &predicate.bounded_ty else {
+                continue;
+            };
+            if let Some(name) = ty.path.get_ident() {
+                let traits = predicate.bounds.iter().filter_map(bound_trait);
+                bounds.entry(name.to_string()).or_default().extend(traits);
+            }
+        }
+        let self_trait =
+            owner.filter(|owner| resolver.traits.contains_key(*owner) && imp.is_none());
+        Receivers {
+            locals,
+            bounds,
+            self_type: owner.map(owner_type),
+            self_trait,
+        }
+    }
+
+    /// Returns the traits a method call on `receiver` is dispatched through,
+    /// or `None` if it is dispatched statically or its type is unknown.
+    fn traits(&self, resolver: &Resolver, receiver: &str) -> Option<Vec<String>> {
+        let receiver = receiver
+            .trim_start_matches(['&', '*', '(', ' '])
+            .trim_end_matches([')', ' ']);
+        let receiver = receiver.strip_prefix("mut ").unwrap_or(receiver).trim();
+        if receiver == "self" {
+            return self.self_trait.map(|tr| vec![tr.to_owned()]);
+        }
+        let ty = match receiver.strip_prefix("self.") {
+            Some(field) => resolver
+                .fields
+                .get(&(self.self_type?.to_owned(), field.to_owned()))?
+                .as_str(),
+            None => self.locals.get(receiver)?,
+        };
+        let traits = self.type_traits(&syn::parse_str(ty).ok()?)?;
+        (!traits.is_empty()).then_some(traits)
+    }
+
+    /// Returns the traits of a trait object, `impl Trait` or generic
+    /// parameter type, behind references and smart pointers.
+    fn type_traits(&self, ty: &Type) -> Option<Vec<String>> {
+        match ty {
+            Type::Reference(r) => self.type_traits(&r.elem),
+            Type::Paren(p) => self.type_traits(&p.elem),
+            Type::Group(g) => self.type_traits(&g.elem),
+            Type::TraitObject(t) => Some(t.bounds.iter().filter_map(bound_trait).collect()),
+            Type::ImplTrait(t) => Some(t.bounds.iter().filter_map(bound_trait).collect()),
+            Type::Path(p) if p.qself.is_none() => {
+                if let Some(name) = p.path.get_ident() {
+                    return self.bounds.get(&name.to_string()).cloned();
+                }
+                let last = p.path.segments.last()?;
+                if !matches!(last.ident.to_string().as_str(), "Box" | "Rc" | "Arc") {
+                    return None;
+                }
+                let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+                    return None;
+                };
+                match args.args.first()? {
+                    syn::GenericArgument::Type(inner) => self.type_traits(inner),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the name of the trait of a bound, e.g. `Shape` for
+/// `shapes::Shape<T>`; `None` for lifetimes and `?Sized`.
+fn bound_trait(bound: &TypeParamBound) -> Option<String> {
+    match bound {
+        TypeParamBound::Trait(t) if matches!(t.modifier, syn::TraitBoundModifier::None) => {
+            t.path.segments.last().map(|s| s.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Resolves `crate::`, `self::` and `super::` prefixes against the module at
+/// `module_segments`. Returns `None` for paths without such a prefix.
+pub(crate) fn absolute(segments: &[&str], module_segments: &[&str]) -> Option<Vec<String>> {
+    let mut base = match segments.first()? {
+        &"crate" => vec![module_segments[0]],
+        &"self" | &"super" => module_segments.to_vec(),
+        _ => return None,
+    };
+    let mut rest = if segments[0] == "crate" {
+        &segments[1..]
+    } else {
+        segments
+    };
+    if rest.first() == Some(&"self") {
+        rest = &rest[1..];
+    }
+    while rest.first() == Some(&"super") {
+        if base.len() > 1 {
+            base.pop();
+        }
+        rest = &rest[1..];
+    }
+    base.extend_from_slice(rest);
+    Some(to_owned(&base))
+}
+
+/// Splits a path as written at a call site into its segments, dropping
+/// generic arguments and a leading `::`.
+fn path_segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    let bytes = path.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' => {
+                if depth == 0 {
+                    if let Some(s) = start.take() {
+                        segments.push(&path[s..i]);
+                    }
+                }
+                depth += 1;
+            }
+            b'>' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                if let Some(s) = start.take() {
+                    segments.push(&path[s..i]);
+                }
+                i += 1;
+            }
+            _ if depth == 0 && start.is_none() => start = Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    if let Some(s) = start {
+        segments.push(&path[s..]);
+    }
+    segments
+}
+
+fn last_segment(path: &str) -> &str {
+    let path = path.split('<').next().unwrap_or(path);
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+fn to_owned(segments: &[&str]) -> Vec<String> {
+    segments.iter().map(|s| (*s).to_owned()).collect()
+}
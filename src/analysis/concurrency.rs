@@ -0,0 +1,351 @@
+//! Checks of async code and locks.
+//!
+//! The extraction records the calls that block the current thread: `std::fs`,
+//! `std::thread::sleep`, locks that are not awaited, blocking HTTP clients
+//! and `block_on`. In an async fn or async block such a call blocks the
+//! executor thread and every task scheduled on it. The
+//! `async/blocking_call` rule reports them, and, from analysis level 2 on,
+//! calls from async code to synchronous functions that reach a blocking call
+//! through their callees. Async callees are not followed, since calling them
+//! only creates a future; their own bodies are checked where they are.
+//!
+//! Blocking calls are recognized by name, so a `lock()` on an async mutex
+//! that is not awaited right away, for instance, is reported as well.
+//!
+//! The `async/guard_across_await` rule reports guards of synchronous locks,
+//! such as a `std::sync::MutexGuard`, that are still held at an `.await`:
+//! the task may be suspended with the lock held, and a `std` guard is not
+//! `Send`.
+//!
+//! The [`LockOrderGraph`] has an edge from lock `A` to lock `B` wherever `B`
+//! is acquired while a guard of `A` is held, directly or, from analysis
+//! level 2 on, in a callable reached from a call made with the guard held.
+//! Locks are keyed by field path, e.g. `Cache.entries`, so all instances of
+//! a type share a key. A cycle between two or more locks means that two
+//! threads can acquire them in opposite orders and deadlock; the
+//! `lock/order_inversion` rule reports each cycle once. A lock acquired
+//! again while it is held is not reported, since it may be the same field of
+//! another instance.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use serde::Serialize;
+
+use super::callgraph::{CallGraph, CallWalker, item_path};
+use super::{Diagnostic, ItemKind, Level, Rule};
+use crate::entities::callable::RustCallable;
+use crate::entities::concurrency::BlockingCall;
+use crate::entities::rcrate::RustCrate;
+
+pub static BLOCKING_CALL: Rule = Rule {
+    id: "async/blocking_call",
+    name: "BlockingCall",
+    short_description: "Call that blocks the executor thread in async code.",
+    help: "Use the async counterpart of the call, or move the blocking work to `spawn_blocking`.",
+    default_level: Level::Warning,
+};
+
+pub static GUARD_ACROSS_AWAIT: Rule = Rule {
+    id: "async/guard_across_await",
+    name: "GuardAcrossAwait",
+    short_description: "Guard of a synchronous lock held across an `.await`.",
+    help: "Drop the guard before the `.await`, e.g. by moving the locked code into a block, or use an async lock.",
+    default_level: Level::Warning,
+};
+
+pub static LOCK_ORDER_INVERSION: Rule = Rule {
+    id: "lock/order_inversion",
+    name: "LockOrderInversion",
+    short_description: "Locks acquired in inconsistent orders.",
+    help: "Acquire the locks in the same order everywhere, or merge them into one lock.",
+    default_level: Level::Warning,
+};
+
+/// Rules reported by this check.
+pub static RULES: [&Rule; 3] = [&BLOCKING_CALL, &GUARD_ACROSS_AWAIT, &LOCK_ORDER_INVERSION];
+
+/// Lock `acquired` is acquired while a guard of lock `held` is held.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockOrderEdge {
+    pub held: String,
+    pub acquired: String,
+    /// Item path of the callable holding the guard.
+    pub function: String,
+    /// Line of the acquisition, or of the call that leads to it.
+    pub line: usize,
+    /// Item paths of the callables from `function` to the one acquiring the
+    /// lock; empty if `function` acquires it itself.
+    pub via: Vec<String>,
+}
+
+/// The order in which the locks of a crate are acquired.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LockOrderGraph {
+    /// One edge per pair of locks and callable, ordered by the locks.
+    pub edges: Vec<LockOrderEdge>,
+}
+
+impl LockOrderGraph {
+    /// Builds the lock order graph of `krate`.
+    pub fn build(krate: &RustCrate, graph: &CallGraph) -> Self {
+        let mut edges: BTreeMap<(String, String, String), LockOrderEdge> = BTreeMap::new();
+        let mut add = |edge: LockOrderEdge| {
+            if edge.held != edge.acquired {
+                let key = (
+                    edge.held.clone(),
+                    edge.acquired.clone(),
+                    edge.function.clone(),
+                );
+                edges.entry(key).or_insert(edge);
+            }
+        };
+        let mut walker = CallWalker::new(krate, graph);
+        for node in 0..walker.len() {
+            let function = walker.path(node).to_owned();
+            let callable = walker.callable(node);
+            for acquisition in &callable.lock_acquisitions {
+                for held in &acquisition.held {
+                    add(LockOrderEdge {
+                        held: held.clone(),
+                        acquired: acquisition.lock.clone(),
+                        function: function.clone(),
+                        line: acquisition.line,
+                        via: Vec::new(),
+                    });
+                }
+            }
+            for site in callable
+                .call_sites
+                .iter()
+                .filter(|s| !s.held_locks.is_empty())
+            {
+                let callees: Vec<usize> = graph
+                    .callees(&function)
+                    .filter(|edge| {
+                        edge.line == site.line_number
+                            && site.callee.as_deref() == Some(edge.call.as_str())
+                    })
+                    .filter_map(|edge| walker.node(&edge.callee))
+                    .collect();
+                for callee in callees {
+                    walker.walk(callee, |walker, reached| {
+                        for acquisition in &walker.callable(reached).lock_acquisitions {
+                            for held in &site.held_locks {
+                                add(LockOrderEdge {
+                                    held: held.clone(),
+                                    acquired: acquisition.lock.clone(),
+                                    function: function.clone(),
+                                    line: site.line_number as usize,
+                                    via: walker.call_path(reached),
+                                });
+                            }
+                        }
+                    });
+                }
+            }
+        }
+        LockOrderGraph {
+            edges: edges.into_values().collect(),
+        }
+    }
+
+    /// Returns one cycle for every group of locks that are acquired in
+    /// inconsistent orders, as the edges along the cycle.
+    pub fn cycles(&self) -> Vec<Vec<&LockOrderEdge>> {
+        let mut next: BTreeMap<&str, Vec<&LockOrderEdge>> = BTreeMap::new();
+        for edge in &self.edges {
+            next.entry(edge.held.as_str()).or_default().push(edge);
+        }
+        let mut covered = BTreeSet::new();
+        let mut cycles = Vec::new();
+        for &start in next.keys() {
+            if covered.contains(start) {
+                continue;
+            }
+            // The shortest path from `start` back to itself.
+            let mut parent: HashMap<&str, &LockOrderEdge> = HashMap::new();
+            let mut queue = VecDeque::from([start]);
+            let mut closing = None;
+            'search: while let Some(lock) = queue.pop_front() {
+                for &edge in next.get(lock).into_iter().flatten() {
+                    if edge.acquired == start {
+                        closing = Some(edge);
+                        break 'search;
+                    }
+                    if !parent.contains_key(edge.acquired.as_str()) {
+                        parent.insert(&edge.acquired, edge);
+                        queue.push_back(&edge.acquired);
+                    }
+                }
+            }
+            let Some(mut edge) = closing else {
+                continue;
+            };
+            let mut cycle = vec![edge];
+            while edge.held != start {
+                edge = parent[edge.held.as_str()];
+                cycle.push(edge);
+            }
+            cycle.reverse();
+            covered.extend(cycle.iter().map(|edge| edge.held.as_str()));
+            cycles.push(cycle);
+        }
+        cycles
+    }
+}
+
+/// Reports the blocking calls in async code, the guards held across
+/// `.await` and the lock order inversions of `krate`.
+pub fn check(krate: &RustCrate) -> Vec<Diagnostic> {
+    let graph = CallGraph::build(krate);
+    let mut callables = BTreeMap::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            for (owner, callable) in module.callables() {
+                let item = item_path(&path, owner.as_deref(), &callable.name);
+                callables.insert(item, (callable, module.file_path.clone()));
+            }
+        }
+    }
+    let mut blocking = BlockingPaths {
+        graph: &graph,
+        callables: &callables,
+        memo: HashMap::new(),
+    };
+    let mut diagnostics = Vec::new();
+    for (item, (callable, file_path)) in &callables {
+        let mut report = |rule: &Rule, message: String, line: usize| {
+            let mut diagnostic = Diagnostic::new(rule, message, line, line);
+            diagnostic.file_path = file_path.clone();
+            diagnostic.set_item(ItemKind::Function, item.clone());
+            diagnostics.push(diagnostic);
+        };
+        for guard in &callable.guards_across_await {
+            report(
+                &GUARD_ACROSS_AWAIT,
+                format!(
+                    "Guard `{}` of `{}`, acquired at line {}, is held across an `.await` in `{}`",
+                    guard.guard, guard.lock, guard.lock_line, callable.name
+                ),
+                guard.await_line,
+            );
+        }
+        for call in callable
+            .blocking_calls
+            .iter()
+            .filter(|c| c.is_async_context)
+        {
+            report(
+                &BLOCKING_CALL,
+                format!(
+                    "`{}` blocks the executor in async code of `{}` ({})",
+                    call.code,
+                    callable.name,
+                    call.kind.as_str()
+                ),
+                call.line,
+            );
+        }
+        for site in callable.call_sites.iter().filter(|s| s.is_async_context) {
+            let callees = graph.callees(item).filter(|edge| {
+                edge.line == site.line_number && site.callee.as_deref() == Some(edge.call.as_str())
+            });
+            for edge in callees {
+                let Some((path, call)) = blocking.find(&edge.callee) else {
+                    continue;
+                };
+                report(
+                    &BLOCKING_CALL,
+                    format!(
+                        "`{}` calls `{}` in async code, which blocks: `{}` ({}) via {}",
+                        callable.name,
+                        edge.call,
+                        call.code,
+                        call.kind.as_str(),
+                        path.iter()
+                            .map(|p| format!("`{}`", p))
+                            .collect::<Vec<_>>()
+                            .join(" -> ")
+                    ),
+                    edge.line as usize,
+                );
+            }
+        }
+    }
+    for cycle in LockOrderGraph::build(krate, &graph).cycles() {
+        let first = cycle[0];
+        let steps: Vec<String> = cycle
+            .iter()
+            .map(|edge| {
+                format!(
+                    "`{}` -> `{}` in `{}` at line {}",
+                    edge.held, edge.acquired, edge.function, edge.line
+                )
+            })
+            .collect();
+        let mut diagnostic = Diagnostic::new(
+            &LOCK_ORDER_INVERSION,
+            format!(
+                "Locks are acquired in inconsistent orders: {}",
+                steps.join(", ")
+            ),
+            first.line,
+            first.line,
+        );
+        diagnostic.file_path = callables
+            .get(&first.function)
+            .and_then(|(_, file_path)| file_path.clone());
+        diagnostic.set_item(ItemKind::Function, first.function.clone());
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
+
+/// Finds, for synchronous callables, the shortest call path to a blocking
+/// call that does not pass through an async callable.
+struct BlockingPaths<'a> {
+    graph: &'a CallGraph,
+    callables: &'a BTreeMap<String, (&'a RustCallable, Option<String>)>,
+    memo: HashMap<String, Option<(Vec<String>, &'a BlockingCall)>>,
+}
+
+impl<'a> BlockingPaths<'a> {
+    fn find(&mut self, start: &str) -> Option<(Vec<String>, &'a BlockingCall)> {
+        if let Some(found) = self.memo.get(start) {
+            return found.clone();
+        }
+        let found = self.search(start);
+        self.memo.insert(start.to_owned(), found.clone());
+        found
+    }
+
+    fn search(&self, start: &str) -> Option<(Vec<String>, &'a BlockingCall)> {
+        let mut parent: HashMap<&str, &str> = HashMap::from([(start, start)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(current) = queue.pop_front() {
+            let Some((callable, _)) = self.callables.get(current) else {
+                continue;
+            };
+            if callable.is_async {
+                continue;
+            }
+            if let Some(call) = callable.blocking_calls.iter().find(|c| !c.is_async_context) {
+                let mut path = vec![current.to_owned()];
+                let mut node = current;
+                while parent[node] != node {
+                    node = parent[node];
+                    path.push(node.to_owned());
+                }
+                path.reverse();
+                return Some((path, call));
+            }
+            for edge in self.graph.callees(current) {
+                if !parent.contains_key(edge.callee.as_str()) {
+                    parent.insert(&edge.callee, current);
+                    queue.push_back(&edge.callee);
+                }
+            }
+        }
+        None
+    }
+}
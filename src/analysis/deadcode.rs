@@ -0,0 +1,445 @@
+//! Reports private and crate-visible items that no entry point reaches.
+//!
+//! Reachability starts from the entry points of the crate:
+//!
+//! - `main` in a crate root;
+//! - the callables, structs, unions and enums a library crate exports: `pub`
+//!   items of modules that are `pub` all the way from the crate root, and
+//!   items named by a `use` declaration of such a module, which may be a
+//!   `pub use` re-export;
+//! - `#[test]`, `#[bench]` and procedural macro functions;
+//! - functions exported with `#[no_mangle]` or `#[export_name]`, and
+//!   `extern` functions;
+//! - methods declared in traits, and the methods of trait impls for types
+//!   defined outside the crate. Trait impls for the crate's own types become
+//!   reachable once the type is, since they may be called through `dyn` or
+//!   generics without a visible call.
+//!
+//! From a reached callable, its resolved callees are reached, as well as any
+//! callable or type whose name appears in its code. Matching names is
+//! deliberately generous: it covers calls inside macro invocations and
+//! functions passed by name, so an item is only reported when nothing that is
+//! reached could possibly refer to it. For the same reason, whatever the
+//! body of a `macro_rules!` macro mentions counts as reached. Reaching a
+//! struct, union or enum reaches the types of its fields.
+//!
+//! Unlike rustc's `dead_code` lint, an item used only by other unreached
+//! items is reported as well, whatever its visibility inside the crate. So is
+//! a `pub` item that the crate does not export, such as one in a private
+//! module or in a binary crate.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::callgraph::{CallGraph, absolute, item_path};
+use super::{Diagnostic, ItemKind, Level, Rule};
+use crate::entities::RustVisibility;
+use crate::entities::attr::RustAttribute;
+use crate::entities::callable::RustCallable;
+use crate::entities::module::RustModule;
+use crate::entities::rcrate::RustCrate;
+
+pub static UNREACHABLE_FUNCTION: Rule = Rule {
+    id: "dead_code/unreachable_function",
+    name: "UnreachableFunction",
+    short_description: "Function or method not reachable from any entry point.",
+    help: "Remove the function, or call it from code that is used.",
+    default_level: Level::Warning,
+};
+
+pub static UNREACHABLE_STRUCT: Rule = Rule {
+    id: "dead_code/unreachable_struct",
+    name: "UnreachableStruct",
+    short_description: "Struct not reachable from any entry point.",
+    help: "Remove the struct together with its impls, or use it from code that is used.",
+    default_level: Level::Warning,
+};
+
+pub static UNREACHABLE_ENUM: Rule = Rule {
+    id: "dead_code/unreachable_enum",
+    name: "UnreachableEnum",
+    short_description: "Enum not reachable from any entry point.",
+    help: "Remove the enum together with its impls, or use it from code that is used.",
+    default_level: Level::Warning,
+};
+
+/// Rules reported by this check.
+pub static RULES: [&Rule; 3] = [
+    &UNREACHABLE_FUNCTION,
+    &UNREACHABLE_STRUCT,
+    &UNREACHABLE_ENUM,
+];
+
+/// Attributes that make a function an entry point.
+const ENTRY_ATTRIBUTES: [&str; 7] = [
+    "test",
+    "bench",
+    "no_mangle",
+    "export_name",
+    "proc_macro",
+    "proc_macro_derive",
+    "proc_macro_attribute",
+];
+
+/// What is reachable from the entry points of a crate.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reachability {
+    /// Item paths of the reached callables.
+    pub callables: BTreeSet<String>,
+    /// Names of the reached structs, unions and enums.
+    pub types: BTreeSet<String>,
+}
+
+/// What a library crate exports.
+#[derive(Debug, Default)]
+struct Exports {
+    /// Paths of the modules whose `pub` items are exported.
+    modules: BTreeSet<String>,
+    /// Item paths named by the `use` declarations of exported modules.
+    items: BTreeSet<String>,
+    /// Names of the exported structs, unions and enums, whose `pub`
+    /// methods are exported too.
+    types: BTreeSet<String>,
+}
+
+impl Exports {
+    fn new(krate: &RustCrate) -> Self {
+        let mut exports = Exports::default();
+        if !krate.is_lib {
+            return exports;
+        }
+        let mut modules: BTreeMap<String, &RustModule> = BTreeMap::new();
+        for root in &krate.modules {
+            modules.extend(root.module_tree());
+        }
+        let mut queue: Vec<String> = krate.modules.iter().map(|m| m.name.clone()).collect();
+        while let Some(path) = queue.pop() {
+            let Some(module) = modules.get(&path) else {
+                continue;
+            };
+            if !exports.modules.insert(path.clone()) {
+                continue;
+            }
+            for sub in module.submodules.values() {
+                if sub.visibility == RustVisibility::Public {
+                    queue.push(format!("{}::{}", path, sub.name));
+                }
+            }
+            let module_segments: Vec<&str> = path.split("::").collect();
+            for used in &module.use_declarations {
+                let target = used.split(" as ").next().unwrap_or(used);
+                let segments: Vec<&str> = target.split("::").collect();
+                let mut item = absolute(&segments, &module_segments).unwrap_or_else(|| {
+                    let mut relative = module_segments.clone();
+                    relative.extend_from_slice(&segments);
+                    relative.iter().map(|s| (*s).to_owned()).collect()
+                });
+                if item
+                    .last()
+                    .is_some_and(|last| last == "*" || last == "self")
+                {
+                    item.pop();
+                }
+                // A re-exported module exports its `pub` items.
+                let item = item.join("::");
+                queue.push(item.clone());
+                exports.items.insert(item);
+            }
+        }
+        for (path, module) in &modules {
+            let names = module
+                .structs
+                .iter()
+                .map(|(name, s)| (name, &s.visibility))
+                .chain(module.unions.iter().map(|(name, u)| (name, &u.visibility)))
+                .chain(module.enums.iter().map(|(name, e)| (name, &e.visibility)));
+            for (name, visibility) in names {
+                if exports.contains(path, name, visibility) {
+                    exports.types.insert(name.clone());
+                }
+            }
+        }
+        exports
+    }
+
+    /// Whether the item `name` of the module at `module_path` is exported.
+    fn contains(&self, module_path: &str, name: &str, visibility: &RustVisibility) -> bool {
+        (*visibility == RustVisibility::Public && self.modules.contains(module_path))
+            || self.items.contains(&format!("{}::{}", module_path, name))
+    }
+
+    /// Whether `method`, of an inherent impl for `self_type`, is exported.
+    fn contains_method(&self, self_type: &str, method: &RustCallable) -> bool {
+        method.visibility == RustVisibility::Public && self.types.contains(self_type)
+    }
+}
+
+/// A callable as seen by the reachability pass.
+struct Callable<'a> {
+    callable: &'a RustCallable,
+    /// Self type of a trait impl method.
+    trait_impl_for: Option<&'a str>,
+    entry: bool,
+}
+
+/// Computes what the entry points of `krate` reach.
+pub fn reachable<'a>(krate: &'a RustCrate, graph: &CallGraph) -> Reachability {
+    let exports = Exports::new(krate);
+    let mut callables: BTreeMap<String, Callable> = BTreeMap::new();
+    let mut macro_rules: Vec<&str> = Vec::new();
+    // Local struct, union and enum names, with the type names in their fields.
+    let mut types: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut public_types = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            for mac in module.macros.values() {
+                macro_rules.extend(mac.rules.iter().map(String::as_str));
+            }
+            for s in module.structs.values() {
+                let fields = s.fields.iter().map(|f| f.ty.name.as_str());
+                types.entry(&s.name).or_default().extend(fields);
+                if exports.contains(&path, &s.name, &s.visibility) {
+                    public_types.push(s.name.as_str());
+                }
+            }
+            for u in module.unions.values() {
+                let fields = u.fields.iter().map(|f| f.ty.name.as_str());
+                types.entry(&u.name).or_default().extend(fields);
+                if exports.contains(&path, &u.name, &u.visibility) {
+                    public_types.push(u.name.as_str());
+                }
+            }
+            for e in module.enums.values() {
+                let entry = types.entry(&e.name).or_default();
+                for variant in &e.variants {
+                    for field in variant.fields.iter().flatten() {
+                        entry.push(&field.ty.name);
+                    }
+                    for ty in variant.tuple_types.iter().flatten() {
+                        entry.push(&ty.name);
+                    }
+                }
+                if exports.contains(&path, &e.name, &e.visibility) {
+                    public_types.push(e.name.as_str());
+                }
+            }
+            for function in module.functions.values() {
+                let entry = (function.name == "main" && module.is_root_module)
+                    || exports.contains(&path, &function.name, &function.visibility)
+                    || is_entry(function);
+                callables.insert(
+                    item_path(&path, None, &function.name),
+                    Callable {
+                        callable: function,
+                        trait_impl_for: None,
+                        entry,
+                    },
+                );
+            }
+            for imp in &module.impls {
+                let owner = imp.owner_name();
+                let trait_impl_for = imp.trait_name.as_ref().map(|_| imp.self_type_name());
+                for method in imp.methods.values() {
+                    callables.insert(
+                        item_path(&path, Some(&owner), &method.name),
+                        Callable {
+                            callable: method,
+                            trait_impl_for,
+                            entry: exports.contains_method(imp.self_type_name(), method)
+                                || is_entry(method),
+                        },
+                    );
+                }
+            }
+            for rtrait in module.traits.values() {
+                for method in rtrait.methods.values() {
+                    callables.insert(
+                        item_path(&path, Some(&rtrait.name), &method.name),
+                        Callable {
+                            callable: method,
+                            trait_impl_for: None,
+                            entry: true,
+                        },
+                    );
+                }
+            }
+        }
+    }
+    // Trait impls for foreign types are entry points as well.
+    for callable in callables.values_mut() {
+        if let Some(ty) = callable.trait_impl_for {
+            callable.entry |= !types.contains_key(ty);
+        }
+    }
+
+    let mut by_name: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut impls_by_type: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (path, c) in &callables {
+        by_name.entry(&c.callable.name).or_default().push(path);
+        if let Some(ty) = c.trait_impl_for {
+            impls_by_type.entry(ty).or_default().push(path);
+        }
+    }
+
+    let mut reached = Reachability::default();
+    let mut queue: Vec<String> = callables
+        .iter()
+        .filter(|(_, c)| c.entry)
+        .map(|(path, _)| path.clone())
+        .collect();
+    let mut type_queue: Vec<&str> = public_types;
+    let mention = |text: &'a str, queue: &mut Vec<String>, type_queue: &mut Vec<&'a str>| {
+        for name in identifiers(text) {
+            if let Some((ty, _)) = types.get_key_value(name) {
+                type_queue.push(ty);
+            }
+            for path in by_name.get(name).into_iter().flatten() {
+                queue.push((*path).to_owned());
+            }
+        }
+    };
+    // Macro bodies are not analyzed; whatever they mention may be used.
+    for rules in &macro_rules {
+        mention(rules, &mut queue, &mut type_queue);
+    }
+    loop {
+        if let Some(ty) = type_queue.pop() {
+            if !reached.types.insert(ty.to_owned()) {
+                continue;
+            }
+            for path in impls_by_type.get(ty).into_iter().flatten() {
+                queue.push((*path).to_owned());
+            }
+            for field in types.get(ty).into_iter().flatten() {
+                type_queue.extend(identifiers(field).filter(|t| types.contains_key(t)));
+            }
+            continue;
+        }
+        let Some(path) = queue.pop() else {
+            break;
+        };
+        if !reached.callables.insert(path.clone()) {
+            continue;
+        }
+        queue.extend(graph.callees(&path).map(|edge| edge.callee.clone()));
+        let Some(c) = callables.get(path.as_str()) else {
+            continue;
+        };
+        mention(&c.callable.code, &mut queue, &mut type_queue);
+    }
+    reached
+}
+
+/// Whether an attribute or the ABI of `callable` makes it an entry point.
+fn is_entry(callable: &RustCallable) -> bool {
+    callable.is_extern || callable.attributes.iter().any(is_entry_attribute)
+}
+
+fn is_entry_attribute(attr: &RustAttribute) -> bool {
+    let name = attr.name.rsplit("::").next().unwrap_or(&attr.name);
+    // `#[unsafe(no_mangle)]` in the 2024 edition.
+    if name == "unsafe" {
+        return attr
+            .arguments
+            .iter()
+            .any(|arg| ENTRY_ATTRIBUTES.iter().any(|a| arg.starts_with(a)));
+    }
+    ENTRY_ATTRIBUTES.contains(&name)
+}
+
+/// Splits source text into the identifiers it mentions.
+fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+}
+
+/// Reports the callables, structs and enums of `krate` that it does not
+/// export and that no entry point reaches.
+pub fn check(krate: &RustCrate) -> Vec<Diagnostic> {
+    let graph = CallGraph::build(krate);
+    let reached = reachable(krate, &graph);
+    let exports = Exports::new(krate);
+    let mut diagnostics = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            let mut push = |rule: &Rule, kind: (ItemKind, &str), name: &str, item_path, lines| {
+                let (start_line, end_line) = lines;
+                let mut diagnostic = Diagnostic::new(
+                    rule,
+                    format!("{} `{}` is never reached from an entry point", kind.1, name),
+                    start_line,
+                    end_line,
+                );
+                diagnostic.file_path = module.file_path.clone();
+                diagnostic.set_item(kind.0, item_path);
+                diagnostics.push(diagnostic);
+            };
+            for s in module.structs.values() {
+                if !exports.contains(&path, &s.name, &s.visibility)
+                    && !reached.types.contains(&s.name)
+                {
+                    let item = format!("{}::{}", path, s.name);
+                    let lines = (s.start_line, s.end_line);
+                    push(
+                        &UNREACHABLE_STRUCT,
+                        (ItemKind::Struct, "Struct"),
+                        &s.name,
+                        item,
+                        lines,
+                    );
+                }
+            }
+            for e in module.enums.values() {
+                if !exports.contains(&path, &e.name, &e.visibility)
+                    && !reached.types.contains(&e.name)
+                {
+                    let item = format!("{}::{}", path, e.name);
+                    let lines = (e.start_line as usize, e.end_line as usize);
+                    push(
+                        &UNREACHABLE_ENUM,
+                        (ItemKind::Enum, "Enum"),
+                        &e.name,
+                        item,
+                        lines,
+                    );
+                }
+            }
+            let functions = module.functions.values().map(|f| (None, f));
+            // Methods of trait impls share the fate of their type, which is
+            // reported on its own.
+            let methods = module
+                .impls
+                .iter()
+                .filter(|imp| imp.trait_name.is_none())
+                .flat_map(|imp| {
+                    let owner = imp.owner_name();
+                    imp.methods
+                        .values()
+                        .map(move |m| (Some((owner.clone(), imp.self_type_name())), m))
+                });
+            for (owner, callable) in functions.chain(methods) {
+                let exported = match &owner {
+                    Some((_, ty)) => exports.contains_method(ty, callable),
+                    None => exports.contains(&path, &callable.name, &callable.visibility),
+                };
+                let owner = owner.map(|(owner, _)| owner);
+                let item = item_path(&path, owner.as_deref(), &callable.name);
+                if !exported && !reached.callables.contains(&item) {
+                    let kind = if owner.is_some() {
+                        "Method"
+                    } else {
+                        "Function"
+                    };
+                    let lines = (callable.start_line, callable.end_line);
+                    push(
+                        &UNREACHABLE_FUNCTION,
+                        (ItemKind::Function, kind),
+                        &callable.name,
+                        item,
+                        lines,
+                    );
+                }
+            }
+        }
+    }
+    diagnostics
+}
@@ -0,0 +1,157 @@
+//! The system dependence graph of a crate, from analysis level 3 on.
+//!
+//! The program dependence graph of every callable is stored in
+//! [`RustCallable::dependence`]. This module joins them through the call
+//! graph: a parameter edge links the variables a call passes as an argument
+//! to the parameter of each resolved callee, and a return edge links a
+//! callee that returns a value back to the call. The receiver of a method
+//! call is passed to `self`.
+//!
+//! As in the call graph, a method call resolves to every method of that
+//! name, so a call may have edges to callees it never reaches.
+//!
+//! [`RustCallable::dependence`]: crate::entities::callable::RustCallable::dependence
+
+use serde::Serialize;
+
+use super::callgraph::{CallGraph, CallWalker, item_path};
+use crate::entities::dependence::{Argument, CallArguments, ProgramDependenceGraph};
+use crate::entities::rcrate::RustCrate;
+
+/// The program dependence graph of one callable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionDependence {
+    /// Item path of the callable.
+    pub function: String,
+    pub file_path: Option<String>,
+    #[serde(flatten)]
+    pub graph: ProgramDependenceGraph,
+}
+
+/// An argument of a call, passed to a parameter of the callee.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParameterEdge {
+    /// Item path of the calling callable.
+    pub caller: String,
+    pub line: usize,
+    /// Basic block of the call in the caller.
+    pub block: usize,
+    pub call: String,
+    /// Item path of the callee.
+    pub callee: String,
+    /// Position of the argument; `None` for the receiver of a method call.
+    pub argument: Option<usize>,
+    pub parameter: String,
+    /// The caller's variables used in the argument.
+    pub variables: Vec<String>,
+}
+
+/// The value a callee returns to a call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReturnEdge {
+    /// Item path of the callee.
+    pub callee: String,
+    /// Item path of the calling callable.
+    pub caller: String,
+    pub line: usize,
+    /// Basic block of the call in the caller.
+    pub block: usize,
+}
+
+/// The system dependence graph of one crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SystemDependenceGraph {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// One entry per callable with a body, ordered by item path.
+    pub functions: Vec<FunctionDependence>,
+    pub parameter_edges: Vec<ParameterEdge>,
+    pub return_edges: Vec<ReturnEdge>,
+}
+
+impl SystemDependenceGraph {
+    /// Builds the system dependence graph of `krate`.
+    pub fn build(krate: &RustCrate) -> Self {
+        let graph = CallGraph::build(krate);
+        let walker = CallWalker::new(krate, &graph);
+        let mut sdg = SystemDependenceGraph {
+            krate: krate.name.clone(),
+            functions: Vec::new(),
+            parameter_edges: Vec::new(),
+            return_edges: Vec::new(),
+        };
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (owner, callable) in module.callables() {
+                    let Some(pdg) = &callable.dependence else {
+                        continue;
+                    };
+                    sdg.functions.push(FunctionDependence {
+                        function: item_path(&path, owner.as_deref(), &callable.name),
+                        file_path: module.file_path.clone(),
+                        graph: pdg.clone(),
+                    });
+                }
+            }
+        }
+        sdg.functions.sort_by(|a, b| a.function.cmp(&b.function));
+        for function in &sdg.functions {
+            for call in &function.graph.calls {
+                let callees = graph
+                    .callees(&function.function)
+                    .filter(|edge| edge.line as usize == call.line && edge.call == call.call);
+                for edge in callees {
+                    let Some(node) = walker.node(&edge.callee) else {
+                        continue;
+                    };
+                    let callee = walker.callable(node);
+                    let params: Vec<&str> =
+                        callee.parameters.iter().map(|p| p.name.as_str()).collect();
+                    for (argument, parameter, value) in bind_arguments(call, &params) {
+                        sdg.parameter_edges.push(ParameterEdge {
+                            caller: function.function.clone(),
+                            line: call.line,
+                            block: call.block,
+                            call: call.call.clone(),
+                            callee: edge.callee.clone(),
+                            argument,
+                            parameter: parameter.to_owned(),
+                            variables: value.variables.clone(),
+                        });
+                    }
+                    if callee.return_type.is_some() {
+                        sdg.return_edges.push(ReturnEdge {
+                            callee: edge.callee.clone(),
+                            caller: function.function.clone(),
+                            line: call.line,
+                            block: call.block,
+                        });
+                    }
+                }
+            }
+        }
+        sdg
+    }
+}
+
+/// Pairs the receiver and arguments of `call` with the parameters of a
+/// callee.
+pub(crate) fn bind_arguments<'a>(
+    call: &'a CallArguments,
+    params: &[&'a str],
+) -> Vec<(Option<usize>, &'a str, &'a Argument)> {
+    let mut bound = Vec::new();
+    let mut params = params.iter().copied();
+    if let Some(receiver) = &call.receiver {
+        match params.next() {
+            Some(parameter @ "self") => bound.push((None, parameter, receiver)),
+            // An associated function called through a method call cannot
+            // be the callee.
+            _ => return Vec::new(),
+        }
+    }
+    for ((argument, value), parameter) in call.arguments.iter().enumerate().zip(params) {
+        bound.push((Some(argument), parameter, value));
+    }
+    bound
+}
@@ -0,0 +1,526 @@
+//! Graph of how errors flow through a crate.
+//!
+//! The extraction records every `?` of a function body with the call it is
+//! applied to, and the expressions that discard an error: `.ok()`,
+//! `let _ = call()` and `.unwrap_or_default()`. This pass reads the error
+//! type of every function from its return type and turns each `?` into an
+//! edge from the error type of the called function to that of the caller,
+//! classified by how the error is converted on the way:
+//!
+//! - `identity` when both functions return the same error type;
+//! - `from` when the caller's error type has a `From` impl, or a `#[from]`
+//!   field, for the callee's error type;
+//! - `boxed` when the caller returns `Box<dyn Error>`, `anyhow::Error` or
+//!   another type that every error converts into;
+//! - `mapped` when an adapter such as `map_err` or `context` replaces the
+//!   error before the `?`;
+//! - `unknown` otherwise, e.g. for calls into other crates.
+//!
+//! Error types are the structs and enums of the crate that implement
+//! `std::error::Error`, directly or through `#[derive(Error)]`. For every
+//! public function, the graph lists the error types that can reach it: those
+//! returned by the function itself and by the functions whose errors it
+//! propagates, transitively, without mapping them.
+//!
+//! Return types are resolved by name, like the rest of the entity model:
+//! `Result<T, E>`, `io::Result<T>`, `fmt::Result` and type aliases of the
+//! crate such as `type Result<T> = std::result::Result<T, Error>`. Callees
+//! come from the call graph, so they are only known from analysis level 2 on.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use serde::Serialize;
+use syn::{GenericArgument, PathArguments, Type};
+
+use super::callgraph::{CallGraph, item_path};
+use crate::entities::RustVisibility;
+use crate::entities::attr::RustAttribute;
+use crate::entities::errors::SwallowedError;
+use crate::entities::module::RustModule;
+use crate::entities::rcrate::RustCrate;
+use crate::entities::rtype::RustType;
+
+/// A struct or enum of the crate that implements `std::error::Error`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorType {
+    /// Item path of the type.
+    pub path: String,
+    /// `struct` or `enum`.
+    pub kind: &'static str,
+    pub file_path: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Names of the variants of an enum.
+    pub variants: Vec<String>,
+    /// The error types converted into this one by a `From` impl or a
+    /// `#[from]` field.
+    pub from: Vec<String>,
+}
+
+/// How the error of a callee becomes the error of its caller at a `?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Conversion {
+    Identity,
+    From,
+    Boxed,
+    Mapped,
+    Unknown,
+}
+
+/// A `?` in the body of a function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PropagationEdge {
+    pub line: usize,
+    /// The call the `?` is applied to, as written at the call site.
+    pub call: Option<String>,
+    /// Item paths of the callables the call resolves to.
+    pub callees: Vec<String>,
+    /// Method between the call and the `?` that replaces the error.
+    pub adapter: Option<String>,
+    /// Error type of the callees, if they agree on one.
+    pub source: Option<String>,
+    /// Error type of the function the `?` is in.
+    pub target: Option<String>,
+    pub conversion: Conversion,
+    /// Source text of the operand of the `?`.
+    pub code: String,
+}
+
+/// A function that returns, propagates or discards errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorFunction {
+    /// Item path of the function.
+    pub path: String,
+    pub file_path: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub is_public: bool,
+    /// The error type of the function's `Result`.
+    pub error_type: Option<String>,
+    pub propagations: Vec<PropagationEdge>,
+    pub swallowed: Vec<SwallowedError>,
+    /// Item paths of the error types of the crate that can reach a public
+    /// function; `None` for other functions.
+    pub reachable_errors: Option<Vec<String>>,
+}
+
+/// The error types of one crate and the functions they flow through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorGraph {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub error_types: Vec<ErrorType>,
+    pub functions: Vec<ErrorFunction>,
+}
+
+impl ErrorGraph {
+    /// Builds the error graph of `krate`.
+    pub fn build(krate: &RustCrate) -> Self {
+        let types = TypeNames::new(krate);
+        let graph = CallGraph::build(krate);
+        let mut error_types = Vec::new();
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                add_error_types(&mut error_types, &types, &path, module);
+            }
+        }
+        let from: BTreeMap<&str, &[String]> = error_types
+            .iter()
+            .map(|ty| (ty.path.as_str(), ty.from.as_slice()))
+            .collect();
+
+        let mut functions = Vec::new();
+        let mut errors_by_path = BTreeMap::new();
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (owner, callable) in module.callables() {
+                    let function = ErrorFunction {
+                        path: item_path(&path, owner.as_deref(), &callable.name),
+                        file_path: module.file_path.clone(),
+                        start_line: callable.start_line,
+                        end_line: callable.end_line,
+                        is_public: callable.visibility == RustVisibility::Public,
+                        error_type: callable
+                            .return_type
+                            .as_ref()
+                            .and_then(|ty| types.error_of(ty)),
+                        propagations: Vec::new(),
+                        swallowed: callable.swallowed_errors.clone(),
+                        reachable_errors: None,
+                    };
+                    if let Some(error) = &function.error_type {
+                        errors_by_path.insert(function.path.clone(), error.clone());
+                    }
+                    functions.push((function, callable));
+                }
+            }
+        }
+
+        for (function, callable) in &mut functions {
+            for propagation in &callable.error_propagations {
+                let callees: Vec<String> = graph
+                    .callees(&function.path)
+                    .filter(|edge| {
+                        edge.line as usize == propagation.line
+                            && Some(&edge.call) == propagation.call.as_ref()
+                    })
+                    .map(|edge| edge.callee.clone())
+                    .collect();
+                let sources: BTreeSet<&String> = callees
+                    .iter()
+                    .filter_map(|callee| errors_by_path.get(callee))
+                    .collect();
+                let source = match sources.len() {
+                    1 => sources.into_iter().next().cloned(),
+                    _ => None,
+                };
+                let target = function.error_type.clone();
+                let conversion = match (&source, &target) {
+                    _ if propagation.adapter.is_some() => Conversion::Mapped,
+                    (_, Some(target)) if is_boxed(target) => Conversion::Boxed,
+                    (Some(source), Some(target)) if source == target => Conversion::Identity,
+                    (Some(source), Some(target))
+                        if from
+                            .get(target.as_str())
+                            .is_some_and(|from| from.contains(source)) =>
+                    {
+                        Conversion::From
+                    }
+                    _ => Conversion::Unknown,
+                };
+                function.propagations.push(PropagationEdge {
+                    line: propagation.line,
+                    call: propagation.call.clone(),
+                    callees,
+                    adapter: propagation.adapter.clone(),
+                    source,
+                    target,
+                    conversion,
+                    code: propagation.code.clone(),
+                });
+            }
+        }
+
+        let mut functions: Vec<ErrorFunction> = functions
+            .into_iter()
+            .map(|(function, _)| function)
+            .filter(|function| {
+                function.error_type.is_some()
+                    || !function.propagations.is_empty()
+                    || !function.swallowed.is_empty()
+            })
+            .collect();
+        let reachable: Vec<Option<Vec<String>>> = functions
+            .iter()
+            .map(|function| {
+                function
+                    .is_public
+                    .then(|| reachable_errors(&functions, &from, &function.path))
+            })
+            .collect();
+        for (function, reachable) in functions.iter_mut().zip(reachable) {
+            function.reachable_errors = reachable;
+        }
+
+        ErrorGraph {
+            krate: krate.name.clone(),
+            error_types,
+            functions,
+        }
+    }
+}
+
+/// Collects the error types of the crate that reach the function at `start`
+/// through `?` edges that do not map the error.
+fn reachable_errors(
+    functions: &[ErrorFunction],
+    error_types: &BTreeMap<&str, &[String]>,
+    start: &str,
+) -> Vec<String> {
+    let by_path: BTreeMap<&str, &ErrorFunction> = functions
+        .iter()
+        .map(|function| (function.path.as_str(), function))
+        .collect();
+    let mut reached = BTreeSet::new();
+    let mut seen = BTreeSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(path) = queue.pop_front() {
+        let Some(function) = by_path.get(path) else {
+            continue;
+        };
+        if let Some(error) = &function.error_type {
+            if error_types.contains_key(error.as_str()) {
+                reached.insert(error.clone());
+            }
+        }
+        for edge in &function.propagations {
+            if edge.conversion == Conversion::Mapped {
+                continue;
+            }
+            for callee in &edge.callees {
+                if seen.insert(callee) {
+                    queue.push_back(callee);
+                }
+            }
+        }
+    }
+    reached.into_iter().collect()
+}
+
+fn add_error_types(
+    error_types: &mut Vec<ErrorType>,
+    types: &TypeNames,
+    path: &str,
+    module: &RustModule,
+) {
+    let mut push = |name: &str, kind, start_line, end_line, variants, mut sources: Vec<String>| {
+        if !types.errors.contains_key(name) {
+            return;
+        }
+        sources.extend(types.from.get(name).into_iter().flatten().cloned());
+        sources.sort();
+        sources.dedup();
+        error_types.push(ErrorType {
+            path: format!("{}::{}", path, name),
+            kind,
+            file_path: module.file_path.clone(),
+            start_line,
+            end_line,
+            variants,
+            from: sources,
+        });
+    };
+    for s in module.structs.values() {
+        let sources = s
+            .fields
+            .iter()
+            .filter(|field| has_from(&field.attributes))
+            .filter_map(|field| types.normalize_str(&field.ty))
+            .collect();
+        push(
+            &s.name,
+            "struct",
+            s.start_line,
+            s.end_line,
+            Vec::new(),
+            sources,
+        );
+    }
+    for e in module.enums.values() {
+        let mut sources = Vec::new();
+        for variant in &e.variants {
+            for field in variant.fields.iter().flatten() {
+                if has_from(&field.attributes) {
+                    sources.extend(types.normalize_str(&field.ty));
+                }
+            }
+            let tuple = variant.tuple_types.iter().flatten();
+            let attributes = variant.tuple_attributes.iter().flatten();
+            for (ty, attributes) in tuple.zip(attributes) {
+                if has_from(attributes) {
+                    sources.extend(types.normalize_str(ty));
+                }
+            }
+        }
+        let variants = e.variants.iter().map(|v| v.name.clone()).collect();
+        push(
+            &e.name,
+            "enum",
+            e.start_line as usize,
+            e.end_line as usize,
+            variants,
+            sources,
+        );
+    }
+}
+
+/// Returns `T` for the trait `From<T>`.
+fn from_source(trait_name: &str) -> Option<Type> {
+    let path: syn::Path = syn::parse_str(trait_name).ok()?;
+    let last = path.segments.last()?;
+    if last.ident != "From" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    }
+}
+
+/// Returns true for the `#[from]` attribute of `thiserror`.
+fn has_from(attributes: &[RustAttribute]) -> bool {
+    attributes.iter().any(|attr| attr.name == "from")
+}
+
+/// Returns true if every error converts into `error` through a blanket
+/// `From` impl.
+fn is_boxed(error: &str) -> bool {
+    error.starts_with("Box<dyn")
+        || matches!(
+            error,
+            "anyhow::Error" | "eyre::Report" | "color_eyre::Report" | "Report"
+        )
+}
+
+/// The error types and type aliases of a crate, by name.
+struct TypeNames {
+    /// Item paths of the error types.
+    errors: BTreeMap<String, String>,
+    aliases: BTreeMap<String, String>,
+    /// The types named in the `From` impls of each type.
+    from: BTreeMap<String, Vec<String>>,
+}
+
+impl TypeNames {
+    fn new(krate: &RustCrate) -> Self {
+        let mut names = TypeNames {
+            errors: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            from: BTreeMap::new(),
+        };
+        let mut from_impls = Vec::new();
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                let mut implemented: BTreeSet<&str> = module
+                    .impls
+                    .iter()
+                    .filter(|imp| {
+                        imp.trait_name
+                            .as_deref()
+                            .is_some_and(|name| last_segment(name) == "Error")
+                    })
+                    .map(|imp| imp.self_type_name())
+                    .collect();
+                let derived =
+                    |derives: &[String]| derives.iter().any(|d| last_segment(d) == "Error");
+                for s in module.structs.values() {
+                    if derived(&s.derives) {
+                        implemented.insert(&s.name);
+                    }
+                }
+                for e in module.enums.values() {
+                    if derived(&e.derives) {
+                        implemented.insert(&e.name);
+                    }
+                }
+                for name in implemented {
+                    if module.structs.contains_key(name) || module.enums.contains_key(name) {
+                        names
+                            .errors
+                            .insert(name.to_owned(), format!("{}::{}", path, name));
+                    }
+                }
+                for alias in module.type_aliases.values() {
+                    names
+                        .aliases
+                        .insert(alias.name.clone(), alias.target_type.name.clone());
+                }
+                for imp in &module.impls {
+                    if let Some(source) = imp.trait_name.as_deref().and_then(from_source) {
+                        from_impls.push((imp.self_type_name().to_owned(), source));
+                    }
+                }
+            }
+        }
+        // The sources are named once every error type is known.
+        for (target, source) in from_impls {
+            let source = names.normalize(&source);
+            names.from.entry(target).or_default().push(source);
+        }
+        names
+    }
+
+    /// Returns the error type of a function returning `ty`.
+    fn error_of(&self, ty: &RustType) -> Option<String> {
+        let ty: Type = syn::parse_str(&ty.name).ok()?;
+        self.error_of_type(&ty, &mut BTreeSet::new())
+    }
+
+    fn error_of_type(&self, ty: &Type, seen: &mut BTreeSet<String>) -> Option<String> {
+        let Type::Path(p) = ty else {
+            return None;
+        };
+        let segments = &p.path.segments;
+        let last = segments.last()?;
+        let name = last.ident.to_string();
+        let args: Vec<&Type> = match &last.arguments {
+            PathArguments::AngleBracketed(args) => args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        if name == "Result" {
+            if let Some(error) = args.get(1) {
+                return Some(self.normalize(error));
+            }
+            // `io::Result<T>`, `fmt::Result` and the like.
+            if segments.len() > 1 {
+                let module = &segments[segments.len() - 2].ident;
+                if module != "result" {
+                    return Some(format!("{}::Error", module));
+                }
+            }
+        }
+        if !seen.insert(name.clone()) {
+            return None;
+        }
+        let target: Type = syn::parse_str(self.aliases.get(&name)?).ok()?;
+        self.error_of_type(&target, seen)
+    }
+
+    fn normalize_str(&self, ty: &RustType) -> Option<String> {
+        let ty: Type = syn::parse_str(&ty.name).ok()?;
+        Some(self.normalize(&ty))
+    }
+
+    /// Names an error type: the item path for error types of the crate, and
+    /// the type as written without `std::`, `core::` or `crate::` otherwise.
+    fn normalize(&self, ty: &Type) -> String {
+        if let Type::Path(p) = ty {
+            if let Some(last) = p.path.segments.last() {
+                if let Some(path) = self.errors.get(&last.ident.to_string()) {
+                    return path.clone();
+                }
+            }
+        }
+        let text = compact(&quote::ToTokens::to_token_stream(ty).to_string());
+        ["std::", "core::", "alloc::", "crate::"]
+            .iter()
+            .find_map(|prefix| text.strip_prefix(prefix))
+            .unwrap_or(&text)
+            .to_owned()
+    }
+}
+
+/// Removes the spaces of rendered tokens, except between words.
+fn compact(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ' ' {
+            let word = |c: char| c.is_alphanumeric() || c == '_';
+            let before = out.chars().last().is_some_and(word);
+            let after = chars.peek().is_some_and(|&c| word(c));
+            if !(before && after) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Returns the last segment of a path, without generic arguments.
+fn last_segment(path: &str) -> &str {
+    let path = path.split('<').next().unwrap_or(path);
+    path.rsplit("::").next().unwrap_or(path).trim()
+}
@@ -0,0 +1,569 @@
+//! Inventory of the symbols that cross the foreign function interface.
+//!
+//! Symbols cross the boundary in two directions:
+//!
+//! - imports are the functions and statics declared in `extern` blocks,
+//!   linked against the libraries named by the block's `#[link]` attributes;
+//! - exports are the Rust functions and statics made visible to foreign code
+//!   with `#[no_mangle]` or `#[export_name]`, and the functions with a
+//!   non-Rust ABI, which foreign code may call through function pointers
+//!   without knowing their symbol.
+//!
+//! The types in the signatures of foreign functions, foreign statics and
+//! functions with a non-Rust ABI are also checked for a C-compatible layout.
+//! A type passes if it is a primitive, a C type alias such as `c_int`, a
+//! pointer to a sized type, or a local struct, union or enum with `#[repr(C)]`,
+//! `#[repr(transparent)]` or, for enums, a primitive representation, whose
+//! fields pass in turn. `String`, `Vec` and other standard containers, `str`,
+//! slices and trait objects, tuples, `char`, function pointers with the Rust
+//! ABI and `Option`s without a null niche are reported. Types the crate does
+//! not define, such as those of other crates, are assumed to be fine.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use syn::{GenericArgument, PathArguments, Type};
+
+use super::callgraph::item_path;
+use super::{Diagnostic, ItemKind, Level, Rule};
+use crate::entities::{
+    attr::{RustAttribute, exported_symbol},
+    module::RustModule,
+    param::RustParameter,
+    rcrate::RustCrate,
+    renum::RustEnum,
+    rstruct::{RustStruct, RustStructField},
+    rtype::RustType,
+    runion::RustUnion,
+};
+
+pub static IMPROPER_TYPE: Rule = Rule {
+    id: "ffi/improper_type",
+    name: "ImproperFfiType",
+    short_description: "Type without a C-compatible layout in an FFI signature.",
+    help: "Pass raw pointers and lengths, `*const c_char` or `#[repr(C)]` types instead.",
+    default_level: Level::Warning,
+};
+
+pub static MISSING_REPR_C: Rule = Rule {
+    id: "ffi/missing_repr_c",
+    name: "MissingReprC",
+    short_description: "Struct or enum in an FFI signature without a C-compatible `#[repr]`.",
+    help: "Add `#[repr(C)]`, `#[repr(transparent)]` or, for fieldless enums, an integer `#[repr]`.",
+    default_level: Level::Warning,
+};
+
+pub static NULLABLE_OPTION: Rule = Rule {
+    id: "ffi/nullable_option",
+    name: "NullableOption",
+    short_description: "`Option` of a type without a null niche in an FFI signature.",
+    help: "Only `Option` of references, `Box`, `NonNull` and function pointers has a C layout; use a raw pointer or a flag.",
+    default_level: Level::Warning,
+};
+
+/// Rules reported by this check.
+pub static RULES: [&Rule; 3] = [&IMPROPER_TYPE, &MISSING_REPR_C, &NULLABLE_OPTION];
+
+/// Integer types, which are also valid enum representations.
+const INTEGERS: [&str; 10] = [
+    "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize",
+];
+
+/// Standard library types without a C-compatible layout.
+const RUST_TYPES: [&str; 14] = [
+    "String", "Vec", "VecDeque", "CString", "OsString", "PathBuf", "HashMap", "HashSet",
+    "BTreeMap", "BTreeSet", "Rc", "Arc", "RefCell", "Cow",
+];
+
+/// Wrappers with the layout of their single type argument.
+const TRANSPARENT_WRAPPERS: [&str; 5] = [
+    "ManuallyDrop",
+    "MaybeUninit",
+    "Cell",
+    "UnsafeCell",
+    "Wrapping",
+];
+
+/// Direction in which a symbol crosses the boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FfiDirection {
+    /// Defined outside of Rust and used by the crate.
+    Import,
+    /// Defined by the crate and callable from foreign code.
+    Export,
+}
+
+/// What kind of item a symbol names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FfiSymbolKind {
+    Function,
+    Static,
+}
+
+/// A function or static crossing the boundary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FfiSymbol {
+    pub direction: FfiDirection,
+    pub kind: FfiSymbolKind,
+    /// The linker symbol, or `None` for an `extern` function that is not
+    /// exported under a fixed name.
+    pub symbol: Option<String>,
+    /// Item path of the declaration or definition.
+    pub item_path: String,
+    /// The ABI of functions; `"Rust"` for exported functions without one.
+    pub abi: Option<String>,
+    /// Parameters of functions.
+    pub parameters: Vec<RustParameter>,
+    /// Return type of functions, type of statics.
+    pub rust_type: Option<RustType>,
+    /// True for variadic foreign functions.
+    pub is_variadic: bool,
+    /// True for mutable statics.
+    pub is_mut: bool,
+    /// Native libraries the symbol is imported from.
+    pub libraries: Vec<String>,
+    pub file_path: Option<String>,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// The symbols one crate imports and exports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FfiReport {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    pub imports: Vec<FfiSymbol>,
+    pub exports: Vec<FfiSymbol>,
+}
+
+impl FfiReport {
+    /// Collects the symbols of `krate`, in module order.
+    pub fn build(krate: &RustCrate) -> Self {
+        let mut report = FfiReport {
+            krate: krate.name.clone(),
+            imports: Vec::new(),
+            exports: Vec::new(),
+        };
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                report.add_imports(&path, module);
+                report.add_exports(&path, module);
+            }
+        }
+        report
+    }
+
+    fn add_imports(&mut self, path: &str, module: &RustModule) {
+        for block in &module.foreign_blocks {
+            let libraries: Vec<String> = block.links.iter().map(|l| l.name.clone()).collect();
+            for f in block.functions.values() {
+                self.imports.push(FfiSymbol {
+                    direction: FfiDirection::Import,
+                    kind: FfiSymbolKind::Function,
+                    symbol: Some(f.symbol().to_owned()),
+                    item_path: item_path(path, None, &f.name),
+                    abi: Some(block.abi.clone()),
+                    parameters: f.parameters.clone(),
+                    rust_type: f.return_type.clone(),
+                    is_variadic: f.is_variadic,
+                    is_mut: false,
+                    libraries: libraries.clone(),
+                    file_path: module.file_path.clone(),
+                    start_line: f.start_line,
+                    end_line: f.end_line,
+                });
+            }
+            for s in block.statics.values() {
+                self.imports.push(FfiSymbol {
+                    direction: FfiDirection::Import,
+                    kind: FfiSymbolKind::Static,
+                    symbol: Some(s.symbol().to_owned()),
+                    item_path: item_path(path, None, &s.name),
+                    abi: None,
+                    parameters: Vec::new(),
+                    rust_type: Some(s.rust_type.clone()),
+                    is_variadic: false,
+                    is_mut: s.is_mut,
+                    libraries: libraries.clone(),
+                    file_path: module.file_path.clone(),
+                    start_line: s.start_line,
+                    end_line: s.end_line,
+                });
+            }
+        }
+    }
+
+    fn add_exports(&mut self, path: &str, module: &RustModule) {
+        for (owner, callable) in module.callables() {
+            if callable.export_name.is_none() && !callable.is_extern {
+                continue;
+            }
+            self.exports.push(FfiSymbol {
+                direction: FfiDirection::Export,
+                kind: FfiSymbolKind::Function,
+                symbol: callable.export_name.clone(),
+                item_path: item_path(path, owner.as_deref(), &callable.name),
+                abi: Some(
+                    callable
+                        .extern_abi
+                        .clone()
+                        .unwrap_or_else(|| "Rust".to_owned()),
+                ),
+                parameters: callable.parameters.clone(),
+                rust_type: callable.return_type.clone(),
+                is_variadic: false,
+                is_mut: false,
+                libraries: Vec::new(),
+                file_path: module.file_path.clone(),
+                start_line: callable.start_line,
+                end_line: callable.end_line,
+            });
+        }
+        for decl in module.constants.iter().filter(|c| c.is_static) {
+            let Some(symbol) = exported_symbol(&decl.attributes, &decl.name) else {
+                continue;
+            };
+            self.exports.push(FfiSymbol {
+                direction: FfiDirection::Export,
+                kind: FfiSymbolKind::Static,
+                symbol: Some(symbol),
+                item_path: item_path(path, None, &decl.name),
+                abi: None,
+                parameters: Vec::new(),
+                rust_type: decl.type_info.clone(),
+                is_variadic: false,
+                is_mut: decl.is_mut,
+                libraries: Vec::new(),
+                file_path: module.file_path.clone(),
+                start_line: decl.line_number,
+                end_line: decl.line_number,
+            });
+        }
+    }
+}
+
+/// Reports the types without a C-compatible layout in the FFI signatures of
+/// `krate`.
+pub fn check(krate: &RustCrate) -> Vec<Diagnostic> {
+    let checker = TypeChecker::new(krate);
+    let mut diagnostics = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            let mut push = |types: Vec<(String, &RustType)>,
+                            item: (ItemKind, String),
+                            lines: (usize, usize)| {
+                for (what, ty) in types {
+                    let Some((rule, reason)) = checker.check(&ty.name) else {
+                        continue;
+                    };
+                    let mut diagnostic = Diagnostic::new(
+                        rule,
+                        format!("{} has type `{}`: {}", what, ty.name, reason),
+                        lines.0,
+                        lines.1,
+                    );
+                    diagnostic.file_path = module.file_path.clone();
+                    diagnostic.set_item(item.0, item.1.clone());
+                    diagnostics.push(diagnostic);
+                }
+            };
+            for block in &module.foreign_blocks {
+                for f in block.functions.values() {
+                    let types = signature(&f.name, &f.parameters, &f.return_type);
+                    push(
+                        types,
+                        (ItemKind::Function, item_path(&path, None, &f.name)),
+                        (f.start_line, f.end_line),
+                    );
+                }
+                for s in block.statics.values() {
+                    let types = vec![(format!("Foreign static `{}`", s.name), &s.rust_type)];
+                    push(
+                        types,
+                        (ItemKind::Static, item_path(&path, None, &s.name)),
+                        (s.start_line, s.end_line),
+                    );
+                }
+            }
+            for (owner, callable) in module.callables() {
+                if callable.extern_abi.as_deref().is_none_or(is_rust_abi) {
+                    continue;
+                }
+                let types = signature(&callable.name, &callable.parameters, &callable.return_type);
+                let item = item_path(&path, owner.as_deref(), &callable.name);
+                push(
+                    types,
+                    (ItemKind::Function, item),
+                    (callable.start_line, callable.end_line),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Returns the parameter and return types of a function, each with a
+/// description for messages.
+fn signature<'a>(
+    name: &str,
+    parameters: &'a [RustParameter],
+    return_type: &'a Option<RustType>,
+) -> Vec<(String, &'a RustType)> {
+    let parameters = parameters.iter().filter(|p| !p.is_self).map(|p| {
+        (
+            format!("Parameter `{}` of `{}`", p.name, name),
+            &p.rust_type,
+        )
+    });
+    let return_type = return_type
+        .iter()
+        .map(|ty| (format!("Return type of `{}`", name), ty));
+    parameters.chain(return_type).collect()
+}
+
+/// ABIs that use the Rust calling convention and layout.
+fn is_rust_abi(abi: &str) -> bool {
+    matches!(abi, "Rust" | "rust-call" | "rust-intrinsic")
+}
+
+/// Returns true if `attributes` give a type a layout foreign code can rely
+/// on: `#[repr(C)]`, `#[repr(transparent)]` or, with `integer`, a primitive
+/// representation.
+fn has_c_repr(attributes: &[RustAttribute], integer: bool) -> bool {
+    attributes
+        .iter()
+        .filter(|attr| attr.name == "repr")
+        .flat_map(|attr| &attr.arguments)
+        .any(|arg| match arg.as_str() {
+            "C" | "transparent" => true,
+            arg => integer && INTEGERS.contains(&arg),
+        })
+}
+
+/// Checks types against the structs, unions, enums and type aliases of a
+/// crate.
+struct TypeChecker<'a> {
+    structs: BTreeMap<&'a str, &'a RustStruct>,
+    unions: BTreeMap<&'a str, &'a RustUnion>,
+    enums: BTreeMap<&'a str, &'a RustEnum>,
+    aliases: BTreeMap<&'a str, &'a RustType>,
+}
+
+impl<'a> TypeChecker<'a> {
+    fn new(krate: &'a RustCrate) -> Self {
+        let mut checker = TypeChecker {
+            structs: BTreeMap::new(),
+            unions: BTreeMap::new(),
+            enums: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+        };
+        for root in &krate.modules {
+            for (_, module) in root.module_tree() {
+                for s in module.structs.values() {
+                    checker.structs.insert(&s.name, s);
+                }
+                for u in module.unions.values() {
+                    checker.unions.insert(&u.name, u);
+                }
+                for e in module.enums.values() {
+                    checker.enums.insert(&e.name, e);
+                }
+                for alias in module.type_aliases.values() {
+                    checker.aliases.insert(&alias.name, &alias.target_type);
+                }
+            }
+        }
+        checker
+    }
+
+    /// Returns the rule a type written as `ty` violates and why.
+    fn check(&self, ty: &str) -> Option<(&'static Rule, String)> {
+        let ty: Type = syn::parse_str(ty).ok()?;
+        self.check_type(&ty, &mut BTreeSet::new())
+    }
+
+    fn check_type(
+        &self,
+        ty: &Type,
+        seen: &mut BTreeSet<String>,
+    ) -> Option<(&'static Rule, String)> {
+        let improper = |reason: &str| Some((&IMPROPER_TYPE, reason.to_owned()));
+        match ty {
+            Type::Paren(p) => self.check_type(&p.elem, seen),
+            Type::Group(g) => self.check_type(&g.elem, seen),
+            Type::Ptr(p) => self.check_pointee(&p.elem),
+            Type::Reference(r) => self.check_pointee(&r.elem),
+            Type::Slice(_) => improper("slices have no C equivalent"),
+            Type::TraitObject(_) | Type::ImplTrait(_) => improper("trait objects have no C layout"),
+            Type::Tuple(t) if t.elems.is_empty() => None,
+            Type::Tuple(_) => improper("tuples have no guaranteed layout"),
+            Type::BareFn(f) => match &f.abi {
+                Some(abi) if !abi.name.as_ref().is_some_and(|n| is_rust_abi(&n.value())) => None,
+                _ => improper(
+                    "function pointers without an `extern` ABI use the Rust calling convention",
+                ),
+            },
+            Type::Path(p) if p.qself.is_none() => {
+                let segment = p.path.segments.last()?;
+                let name = segment.ident.to_string();
+                let args: Vec<&Type> = match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => args
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                self.check_named(&name, &args, seen)
+            }
+            _ => None,
+        }
+    }
+
+    /// Pointers and references are thin, and so FFI-safe, unless they point
+    /// to an unsized type.
+    fn check_pointee(&self, pointee: &Type) -> Option<(&'static Rule, String)> {
+        match pointee {
+            Type::Paren(p) => self.check_pointee(&p.elem),
+            Type::Slice(_) => Some((
+                &IMPROPER_TYPE,
+                "pointers to slices are fat pointers; pass a pointer and a length".to_owned(),
+            )),
+            Type::TraitObject(_) => Some((
+                &IMPROPER_TYPE,
+                "pointers to trait objects are fat pointers".to_owned(),
+            )),
+            Type::Path(p) if p.path.is_ident("str") => Some((
+                &IMPROPER_TYPE,
+                "`str` pointers are fat pointers and not NUL-terminated; use `*const c_char`"
+                    .to_owned(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn check_named(
+        &self,
+        name: &str,
+        args: &[&Type],
+        seen: &mut BTreeSet<String>,
+    ) -> Option<(&'static Rule, String)> {
+        if INTEGERS.contains(&name) || name.starts_with("c_") || name.starts_with("NonZero") {
+            return None;
+        }
+        match name {
+            "f32" | "f64" | "bool" => return None,
+            "char" => {
+                return Some((
+                    &IMPROPER_TYPE,
+                    "`char` is a 32-bit Unicode scalar value, not a C `char`; use `c_char`"
+                        .to_owned(),
+                ));
+            }
+            "str" => return Some((&IMPROPER_TYPE, "`str` has no C equivalent".to_owned())),
+            "NonNull" | "PhantomData" | "PhantomPinned" => return None,
+            "Box" => return args.first().and_then(|ty| self.check_pointee(ty)),
+            "Option" => {
+                let inner = args.first()?;
+                return if has_null_niche(inner) {
+                    self.check_type(inner, seen)
+                } else {
+                    Some((
+                        &NULLABLE_OPTION,
+                        "`Option` has a C layout only around a non-nullable pointer".to_owned(),
+                    ))
+                };
+            }
+            _ => {}
+        }
+        if RUST_TYPES.contains(&name) {
+            return Some((
+                &IMPROPER_TYPE,
+                format!("`{}` has no C-compatible layout", name),
+            ));
+        }
+        if TRANSPARENT_WRAPPERS.contains(&name) {
+            return args.first().and_then(|ty| self.check_type(ty, seen));
+        }
+        if !seen.insert(name.to_owned()) {
+            return None;
+        }
+        if let Some(target) = self.aliases.get(name) {
+            let target: Type = syn::parse_str(&target.name).ok()?;
+            return self.check_type(&target, seen);
+        }
+        if let Some(s) = self.structs.get(name) {
+            if !has_c_repr(&s.attributes, false) {
+                return Some((
+                    &MISSING_REPR_C,
+                    format!(
+                        "struct `{}` is not `#[repr(C)]` or `#[repr(transparent)]`",
+                        name
+                    ),
+                ));
+            }
+            return self.check_fields(name, &s.fields, seen);
+        }
+        if let Some(u) = self.unions.get(name) {
+            if !has_c_repr(&u.attributes, false) {
+                return Some((
+                    &MISSING_REPR_C,
+                    format!(
+                        "union `{}` is not `#[repr(C)]` or `#[repr(transparent)]`",
+                        name
+                    ),
+                ));
+            }
+            return self.check_fields(name, &u.fields, seen);
+        }
+        if let Some(e) = self.enums.get(name) {
+            let fieldless = e
+                .variants
+                .iter()
+                .all(|v| v.fields.is_none() && v.tuple_types.is_none());
+            if !has_c_repr(&e.attributes, fieldless) {
+                return Some((
+                    &MISSING_REPR_C,
+                    format!("enum `{}` has no `#[repr(C)]` or integer `#[repr]`", name),
+                ));
+            }
+        }
+        None
+    }
+
+    /// Checks the fields of the struct or union `name`.
+    fn check_fields(
+        &self,
+        name: &str,
+        fields: &[RustStructField],
+        seen: &mut BTreeSet<String>,
+    ) -> Option<(&'static Rule, String)> {
+        fields.iter().find_map(|field| {
+            let ty: Type = syn::parse_str(&field.ty.name).ok()?;
+            let (rule, reason) = self.check_type(&ty, seen)?;
+            Some((
+                rule,
+                format!("field `{}` of `{}`: {}", field.name, name, reason),
+            ))
+        })
+    }
+}
+
+/// Returns true if `ty` can never be null, so that `Option<ty>` uses null
+/// for `None`.
+fn has_null_niche(ty: &Type) -> bool {
+    match ty {
+        Type::Paren(p) => has_null_niche(&p.elem),
+        Type::Reference(_) | Type::BareFn(_) => true,
+        Type::Path(p) => p.path.segments.last().is_some_and(|segment| {
+            let name = segment.ident.to_string();
+            matches!(name.as_str(), "Box" | "NonNull") || name.starts_with("NonZero")
+        }),
+        _ => false,
+    }
+}
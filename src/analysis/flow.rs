@@ -0,0 +1,151 @@
+//! Export of the per-function control-flow graphs of a crate.
+//!
+//! The graphs are built during extraction from analysis level 2 on and
+//! stored in [`RustCallable::control_flow`]. This module collects them by
+//! item path, as JSON, and renders them as a Graphviz DOT digraph with one
+//! cluster per function.
+//!
+//! [`RustCallable::control_flow`]: crate::entities::callable::RustCallable::control_flow
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use super::callgraph::item_path;
+use crate::entities::flow::{BlockKind, ControlFlowGraph, EdgeKind};
+use crate::entities::rcrate::RustCrate;
+
+/// Conditions and arm patterns longer than this are cut in DOT labels.
+const MAX_LABEL_LEN: usize = 60;
+
+/// The control-flow graph of one function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionCfg {
+    /// Item path of the function.
+    pub function: String,
+    pub file_path: Option<String>,
+    pub cyclomatic_complexity: usize,
+    #[serde(flatten)]
+    pub graph: ControlFlowGraph,
+}
+
+/// The control-flow graphs of one crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CfgReport {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// One entry per function with a body, ordered by item path.
+    pub functions: Vec<FunctionCfg>,
+}
+
+impl CfgReport {
+    /// Collects the control-flow graphs of `krate`.
+    pub fn build(krate: &RustCrate) -> Self {
+        let mut functions = Vec::new();
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (owner, callable) in module.callables() {
+                    let Some(graph) = &callable.control_flow else {
+                        continue;
+                    };
+                    functions.push(FunctionCfg {
+                        function: item_path(&path, owner.as_deref(), &callable.name),
+                        file_path: module.file_path.clone(),
+                        cyclomatic_complexity: graph.cyclomatic_complexity(),
+                        graph: graph.clone(),
+                    });
+                }
+            }
+        }
+        functions.sort_by(|a, b| a.function.cmp(&b.function));
+        CfgReport {
+            krate: krate.name.clone(),
+            functions,
+        }
+    }
+}
+
+/// Renders the graphs of `reports` as one DOT digraph.
+pub fn to_dot(reports: &[CfgReport]) -> String {
+    let mut dot = String::from("digraph cfg {\n    node [shape=box, fontname=monospace];\n");
+    let mut cluster = 0;
+    for report in reports {
+        for function in &report.functions {
+            let graph = &function.graph;
+            let _ = writeln!(dot, "    subgraph cluster_{} {{", cluster);
+            let _ = writeln!(
+                dot,
+                "        label=\"{} (complexity {})\";",
+                escape(&function.function),
+                function.cyclomatic_complexity
+            );
+            for block in &graph.blocks {
+                let mut label = match block.kind {
+                    BlockKind::Entry => "entry".to_owned(),
+                    BlockKind::Exit => "exit".to_owned(),
+                    _ => format!("B{}", block.id),
+                };
+                match (block.start_line, block.end_line) {
+                    (Some(start), Some(end)) if start != end => {
+                        let _ = write!(label, " L{}-{}", start, end);
+                    }
+                    (Some(line), _) => {
+                        let _ = write!(label, " L{}", line);
+                    }
+                    _ => {}
+                }
+                if let Some(condition) = &block.condition {
+                    label.push('\n');
+                    label.push_str(&shorten(condition));
+                }
+                let shape = match block.kind {
+                    BlockKind::Entry | BlockKind::Exit => "ellipse",
+                    _ if block.condition.is_some() => "diamond",
+                    _ => "box",
+                };
+                let _ = writeln!(
+                    dot,
+                    "        c{}_b{} [label=\"{}\", shape={}];",
+                    cluster,
+                    block.id,
+                    escape(&label),
+                    shape
+                );
+            }
+            for edge in &graph.edges {
+                let label = match (&edge.kind, &edge.label) {
+                    (_, Some(label)) => shorten(label),
+                    (EdgeKind::Next, None) => String::new(),
+                    (kind, None) => kind.as_str().to_owned(),
+                };
+                let _ = write!(
+                    dot,
+                    "        c{}_b{} -> c{}_b{}",
+                    cluster, edge.from, cluster, edge.to
+                );
+                if label.is_empty() {
+                    dot.push_str(";\n");
+                } else {
+                    let _ = writeln!(dot, " [label=\"{}\"];", escape(&label));
+                }
+            }
+            dot.push_str("    }\n");
+            cluster += 1;
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(MAX_LABEL_LEN) {
+        Some((at, _)) => format!("{}...", &text[..at]),
+        None => text.to_owned(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,162 @@
+//! Built-in checks that run over the extracted entity model.
+//!
+//! Every check describes the rules it can report through a static [`Rule`]
+//! table and produces [`Diagnostic`]s that reference those rules. Output
+//! formats such as SARIF only ever deal with rules and diagnostics, so a new
+//! check becomes visible everywhere once it is registered in [`rules`] and
+//! [`run_checks`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::rcrate::RustCrate;
+
+pub mod asm;
+pub mod callgraph;
+pub mod concurrency;
+pub mod deadcode;
+pub mod dependence;
+pub mod errors;
+pub mod ffi;
+pub mod flow;
+pub mod panics;
+pub mod safety;
+pub mod taint;
+pub mod unsafety;
+
+/// Severity of a diagnostic, mirroring the SARIF result levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Note,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Note => "note",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+}
+
+/// Kind of the item a diagnostic is reported on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    /// A function or method, including foreign functions.
+    Function,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    /// An impl, named by the path of its self type.
+    Impl,
+    /// A static, including foreign statics.
+    Static,
+}
+
+/// Static description of something a check can report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// Stable identifier, e.g. `unsafe/raw_pointer_deref`.
+    pub id: &'static str,
+    /// Human-readable name in PascalCase.
+    pub name: &'static str,
+    /// One-line description of the rule.
+    pub short_description: &'static str,
+    /// Guidance on how to address a finding.
+    pub help: &'static str,
+    /// Level used for findings of this rule.
+    pub default_level: Level,
+}
+
+/// A single finding produced by a check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// Identifier of the [`Rule`] this finding belongs to.
+    pub rule_id: String,
+    /// Severity of the finding.
+    pub level: Level,
+    /// Message describing this particular finding.
+    pub message: String,
+    /// Path of the file containing the finding, if known.
+    pub file_path: Option<String>,
+    /// The starting line of the finding.
+    pub start_line: usize,
+    /// The ending line of the finding.
+    pub end_line: usize,
+    /// Fully qualified name of the enclosing item, if any.
+    pub item_path: Option<String>,
+    /// Kind of the enclosing item, if any.
+    pub item_kind: Option<ItemKind>,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic for `rule` using the rule's default level.
+    pub fn new(rule: &Rule, message: String, start_line: usize, end_line: usize) -> Self {
+        Self {
+            rule_id: rule.id.to_owned(),
+            level: rule.default_level,
+            message,
+            file_path: None,
+            start_line,
+            end_line,
+            item_path: None,
+            item_kind: None,
+        }
+    }
+
+    /// Sets the enclosing item to the `kind` item at `path`.
+    pub fn set_item(&mut self, kind: ItemKind, path: String) {
+        self.item_kind = Some(kind);
+        self.item_path = Some(path);
+    }
+}
+
+/// Returns the rules of every built-in check.
+pub fn rules() -> Vec<&'static Rule> {
+    safety::RULES
+        .iter()
+        .chain(unsafety::RULES.iter())
+        .chain(ffi::RULES.iter())
+        .chain(panics::RULES.iter())
+        .chain(concurrency::RULES.iter())
+        .chain(deadcode::RULES.iter())
+        .chain(taint::RULES.iter())
+        .copied()
+        .collect()
+}
+
+/// Runs every built-in check over `krate`.
+///
+/// Diagnostics are sorted by file, line and rule so that reports are stable
+/// across runs.
+pub fn run_checks(krate: &RustCrate) -> Vec<Diagnostic> {
+    let mut diagnostics = safety::check(krate);
+    diagnostics.extend(unsafety::check(krate));
+    diagnostics.extend(ffi::check(krate));
+    diagnostics.extend(panics::check(krate));
+    diagnostics.extend(concurrency::check(krate));
+    diagnostics.extend(deadcode::check(krate));
+    diagnostics.extend(taint::check(krate));
+    diagnostics.sort_by(|a, b| {
+        (
+            &a.file_path,
+            a.start_line,
+            a.end_line,
+            &a.rule_id,
+            &a.message,
+        )
+            .cmp(&(
+                &b.file_path,
+                b.start_line,
+                b.end_line,
+                &b.rule_id,
+                &b.message,
+            ))
+    });
+    diagnostics
+}
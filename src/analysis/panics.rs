@@ -0,0 +1,139 @@
+//! Propagates panic sites along the call graph.
+//!
+//! The extraction records the expressions of every body that can panic:
+//! `unwrap` and `expect`, the panicking macros, indexing, integer division
+//! and `borrow_mut`. This pass gives every callable the list of callables
+//! with panic sites that it reaches through its resolved callees, each with
+//! the shortest call path. A callable reaches itself, so its own panic sites
+//! are part of the list.
+//!
+//! As with [`unsafety`](super::unsafety), method calls resolve to every
+//! method of that name, so the list may include callables that are not
+//! actually reached.
+//!
+//! The lists back the `panic/missing_panics_doc` rule, which reports public
+//! functions that may panic but whose documentation has no `# Panics`
+//! section.
+
+use std::collections::BTreeMap;
+
+use super::callgraph::{CallGraph, CallWalker, item_path, update_callables};
+use super::{Diagnostic, ItemKind, Level, Rule};
+use crate::analyzer::items::doc_section;
+use crate::entities::RustVisibility;
+use crate::entities::callable::RustCallable;
+use crate::entities::panic::{PanicKind, PanicPath};
+use crate::entities::rcrate::RustCrate;
+
+pub static MISSING_PANICS_DOC: Rule = Rule {
+    id: "panic/missing_panics_doc",
+    name: "MissingPanicsDoc",
+    short_description: "Public function that may panic without a `# Panics` section.",
+    help: "Describe when the function panics in a `# Panics` section of its documentation, or return an error instead.",
+    default_level: Level::Warning,
+};
+
+/// Rules reported by this check.
+pub static RULES: [&Rule; 1] = [&MISSING_PANICS_DOC];
+
+/// Stores the callables with panic sites that every callable of `krate`
+/// reaches in its [`RustCallable::transitive_panics`].
+pub fn propagate(krate: &mut RustCrate) {
+    let graph = CallGraph::build(krate);
+    let summaries = summarize(krate, &graph);
+    update_callables(krate, |path, callable| {
+        callable.transitive_panics = summaries.get(path).cloned();
+    });
+}
+
+/// Computes the callables with panic sites that every callable of `krate`
+/// reaches, keyed by item path.
+pub fn summarize(krate: &RustCrate, graph: &CallGraph) -> BTreeMap<String, Vec<PanicPath>> {
+    let mut walker = CallWalker::new(krate, graph);
+    let mut summaries = BTreeMap::new();
+    for start in 0..walker.len() {
+        let mut paths = Vec::new();
+        walker.walk(start, |walker, node| {
+            let sites = &walker.callable(node).panic_sites;
+            if sites.is_empty() {
+                return;
+            }
+            let mut kinds: Vec<PanicKind> = sites.iter().map(|site| site.kind).collect();
+            kinds.sort();
+            kinds.dedup();
+            paths.push(PanicPath {
+                target: walker.path(node).to_owned(),
+                path: walker.call_path(node),
+                kinds,
+            });
+        });
+        summaries.insert(walker.path(start).to_owned(), paths);
+    }
+    summaries
+}
+
+/// Reports the public functions of `krate` that may panic and have no
+/// `# Panics` section. From analysis level 2 on, panics reached through
+/// callees count as well.
+///
+/// Methods of trait impls are skipped, since they are documented on the
+/// trait.
+pub fn check(krate: &RustCrate) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            for (owner, callable) in module.callables() {
+                if callable.visibility != RustVisibility::Public
+                    || owner.as_deref().is_some_and(|owner| owner.starts_with('<'))
+                    || has_panics_section(callable)
+                {
+                    continue;
+                }
+                let Some(reason) = panic_reason(callable) else {
+                    continue;
+                };
+                let mut diagnostic = Diagnostic::new(
+                    &MISSING_PANICS_DOC,
+                    format!(
+                        "Public function `{}` may panic but has no `# Panics` section: {}",
+                        callable.name, reason
+                    ),
+                    callable.start_line,
+                    callable.end_line,
+                );
+                diagnostic.file_path = module.file_path.clone();
+                diagnostic.set_item(
+                    ItemKind::Function,
+                    item_path(&path, owner.as_deref(), &callable.name),
+                );
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    diagnostics
+}
+
+fn has_panics_section(callable: &RustCallable) -> bool {
+    callable
+        .doc_comment
+        .as_deref()
+        .is_some_and(|doc| doc_section(doc, "Panics").is_some())
+}
+
+/// Describes the first way `callable` may panic: one of its own panic
+/// sites, or else the call path to a callable with panic sites.
+fn panic_reason(callable: &RustCallable) -> Option<String> {
+    if let Some(site) = callable.panic_sites.first() {
+        return Some(format!("`{}` at line {}", site.code, site.line));
+    }
+    let reached = callable.transitive_panics.as_ref()?.first()?;
+    Some(format!(
+        "it calls {}",
+        reached
+            .path
+            .iter()
+            .map(|p| format!("`{}`", p))
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    ))
+}
@@ -0,0 +1,175 @@
+//! Reports unsafe code recorded in [`SafetyAnalysis`](crate::entities::safety::SafetyAnalysis).
+//!
+//! Every [`UnsafeReason`] of an unsafe block becomes a note under its own
+//! rule, unsafe trait impls are reported under the `unsafe_trait_impl` rule,
+//! and unsafe blocks without a `SAFETY:` comment are reported as warnings.
+
+use super::{Diagnostic, ItemKind, Level, Rule};
+use crate::entities::{UnsafeReason, rcrate::RustCrate};
+
+pub static RAW_POINTER_DEREF: Rule = Rule {
+    id: "unsafe/raw_pointer_deref",
+    name: "RawPointerDeref",
+    short_description: "Dereference of a raw pointer.",
+    help: "Make sure the pointer is non-null, aligned and points to a live, initialized value.",
+    default_level: Level::Note,
+};
+
+pub static MUTABLE_STATIC: Rule = Rule {
+    id: "unsafe/mutable_static",
+    name: "MutableStatic",
+    short_description: "Access to a mutable static.",
+    help: "Prefer atomics, `OnceLock` or a `Mutex` over `static mut`.",
+    default_level: Level::Note,
+};
+
+pub static FFI_CALL: Rule = Rule {
+    id: "unsafe/ffi_call",
+    name: "FfiCall",
+    short_description: "Call through a foreign function interface.",
+    help: "Check that the foreign function's contract matches the declared signature.",
+    default_level: Level::Note,
+};
+
+pub static UNION_FIELD_ACCESS: Rule = Rule {
+    id: "unsafe/union_field_access",
+    name: "UnionFieldAccess",
+    short_description: "Read of a union field.",
+    help: "Make sure the field being read is the one that was last written.",
+    default_level: Level::Note,
+};
+
+pub static INLINE_ASSEMBLY: Rule = Rule {
+    id: "unsafe/inline_assembly",
+    name: "InlineAssembly",
+    short_description: "Use of inline assembly.",
+    help: "Review the operands, clobbers and options of the assembly block.",
+    default_level: Level::Note,
+};
+
+pub static UNSAFE_TRAIT_IMPL: Rule = Rule {
+    id: "unsafe/unsafe_trait_impl",
+    name: "UnsafeTraitImpl",
+    short_description: "Implementation of an unsafe trait.",
+    help: "Document why the type upholds the invariants required by the trait.",
+    default_level: Level::Note,
+};
+
+pub static CUSTOM: Rule = Rule {
+    id: "unsafe/custom",
+    name: "CustomUnsafe",
+    short_description: "Unsafe operation not covered by another rule.",
+    help: "Review the unsafe operation and document its invariants.",
+    default_level: Level::Note,
+};
+
+pub static MISSING_SAFETY_COMMENT: Rule = Rule {
+    id: "unsafe/missing_safety_comment",
+    name: "MissingSafetyComment",
+    short_description: "Unsafe block without a `SAFETY:` comment.",
+    help: "Add a `// SAFETY:` comment explaining why the unsafe block is sound.",
+    default_level: Level::Warning,
+};
+
+/// Rules reported by this check.
+pub static RULES: [&Rule; 8] = [
+    &RAW_POINTER_DEREF,
+    &MUTABLE_STATIC,
+    &FFI_CALL,
+    &UNION_FIELD_ACCESS,
+    &INLINE_ASSEMBLY,
+    &UNSAFE_TRAIT_IMPL,
+    &CUSTOM,
+    &MISSING_SAFETY_COMMENT,
+];
+
+/// Returns the rule that reports `reason`.
+pub fn rule_for(reason: &UnsafeReason) -> &'static Rule {
+    match reason {
+        UnsafeReason::RawPointerDeref => &RAW_POINTER_DEREF,
+        UnsafeReason::MutableStatic => &MUTABLE_STATIC,
+        UnsafeReason::FfiCall => &FFI_CALL,
+        UnsafeReason::UnionFieldAccess => &UNION_FIELD_ACCESS,
+        UnsafeReason::InlineAssembly => &INLINE_ASSEMBLY,
+        UnsafeReason::UnsafeTraitImpl => &UNSAFE_TRAIT_IMPL,
+        UnsafeReason::Custom(_) => &CUSTOM,
+    }
+}
+
+/// Reports every unsafe block, unsafe reason and unsafe trait impl in `krate`.
+pub fn check(krate: &RustCrate) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            let mut push = |mut diagnostic: Diagnostic, kind: ItemKind, item_path: String| {
+                diagnostic.file_path = module.file_path.clone();
+                diagnostic.set_item(kind, item_path);
+                diagnostics.push(diagnostic);
+            };
+
+            for (owner, callable) in module.callables() {
+                let item_path = match owner {
+                    Some(owner) => format!("{}::{}::{}", path, owner, callable.name),
+                    None => format!("{}::{}", path, callable.name),
+                };
+                for block in &callable.safety_analysis.unsafe_blocks {
+                    for reason in &block.reasons {
+                        let message = match reason {
+                            UnsafeReason::Custom(text) => {
+                                format!("Unsafe operation in `{}`: {}", callable.name, text)
+                            }
+                            _ => format!(
+                                "{} in `{}`",
+                                rule_for(reason).short_description.trim_end_matches('.'),
+                                callable.name
+                            ),
+                        };
+                        push(
+                            Diagnostic::new(
+                                rule_for(reason),
+                                message,
+                                block.start_line,
+                                block.end_line,
+                            ),
+                            ItemKind::Function,
+                            item_path.clone(),
+                        );
+                    }
+                    if block.explanation.is_none() {
+                        push(
+                            Diagnostic::new(
+                                &MISSING_SAFETY_COMMENT,
+                                format!(
+                                    "Unsafe block in `{}` has no `SAFETY:` comment",
+                                    callable.name
+                                ),
+                                block.start_line,
+                                block.end_line,
+                            ),
+                            ItemKind::Function,
+                            item_path.clone(),
+                        );
+                    }
+                }
+            }
+
+            for imp in module.impls.iter().filter(|i| i.is_unsafe) {
+                let trait_name = imp.trait_name.as_deref().unwrap_or("<inherent>");
+                push(
+                    Diagnostic::new(
+                        &UNSAFE_TRAIT_IMPL,
+                        format!(
+                            "Unsafe implementation of `{}` for `{}`",
+                            trait_name, imp.type_name
+                        ),
+                        imp.start_line,
+                        imp.end_line,
+                    ),
+                    ItemKind::Impl,
+                    format!("{}::{}", path, imp.type_name),
+                );
+            }
+        }
+    }
+    diagnostics
+}
@@ -0,0 +1,886 @@
+//! Taint analysis over the system dependence graph, from analysis level 3 on.
+//!
+//! A [`TaintConfig`] names the calls whose results are untrusted (sources),
+//! the calls that must not receive untrusted data (sinks) and the calls that
+//! make data safe again (sanitizers). It is read from TOML:
+//!
+//! ```toml
+//! # Keep the built-in sources, sinks and sanitizers (the default).
+//! defaults = true
+//!
+//! [[sources]]
+//! call = "std::env::args"
+//!
+//! [[sources]]
+//! # Parameters of this type, e.g. of web handlers.
+//! parameter_type = "axum::extract::Query"
+//!
+//! [[sinks]]
+//! call = "sqlx::query"
+//! # Only the first argument, and only when it is built with `format!`.
+//! arguments = [0]
+//! format = true
+//!
+//! [[sanitizers]]
+//! call = "shell_escape::escape"
+//! ```
+//!
+//! Calls are matched by path after resolving the first segment through the
+//! `use` declarations of the module, so `env::args()` after `use std::env`
+//! matches `std::env::args`. A call to a callable of the crate also matches
+//! its item path, which may start with `crate::` or with the name of the
+//! crate, e.g. `my_crate::shell::quote`. A method call matches a pattern
+//! whose last but one segment is a type, e.g. `std::process::Command::arg`,
+//! by the method name alone, since the type of the receiver is not known.
+//!
+//! Taint starts at the result of a source call or at a parameter of a source
+//! type and follows the definitions computed from tainted values. A value
+//! passed to a callee of the crate taints its parameter, and a tainted return
+//! value taints the result of every call to that callee. Values passed to a
+//! sanitizer, or to a callee of the crate that does not return them, stop
+//! there; a sanitizer of the crate is not entered at all. Each flow into a
+//! sink is reported once per source and path, with the shortest path from
+//! the source to each tainted value that reaches the sink.
+//!
+//! Like the call graph, the analysis is context-insensitive and resolves a
+//! method call to every method of that name; variables are identified by
+//! name and fields are not told apart.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::callgraph::{CallGraph, item_path};
+use super::{Diagnostic, ItemKind, Level, Rule};
+use crate::entities::callable::RustCallable;
+use crate::entities::dependence::{
+    Argument, CallArguments, DefinitionKind, ProgramDependenceGraph,
+};
+use crate::entities::module::RustModule;
+use crate::entities::rcrate::RustCrate;
+
+pub static TAINTED_FLOW: Rule = Rule {
+    id: "taint/tainted_flow",
+    name: "TaintedFlow",
+    short_description: "Untrusted data reaches a sensitive call.",
+    help: "Validate or sanitize the data before passing it on, or declare the function \
+           that does so as a sanitizer in the taint configuration.",
+    default_level: Level::Warning,
+};
+
+/// Rules reported by this check.
+pub static RULES: [&Rule; 1] = [&TAINTED_FLOW];
+
+/// Built-in sources: calls to these return untrusted data.
+const DEFAULT_SOURCE_CALLS: [&str; 7] = [
+    "std::env::args",
+    "std::env::args_os",
+    "std::env::var",
+    "std::env::var_os",
+    "std::env::vars",
+    "std::io::stdin",
+    "std::fs::read_to_string",
+];
+
+/// Built-in sources: parameters of these types hold untrusted data.
+const DEFAULT_SOURCE_TYPES: [&str; 4] = [
+    "axum::extract::Query",
+    "axum::extract::Form",
+    "axum::extract::Json",
+    "axum::extract::Path",
+];
+
+/// Built-in sinks, with the arguments that must not be tainted (all if
+/// empty) and whether the argument must be built with `format!`.
+const DEFAULT_SINKS: [(&str, &[usize], bool); 12] = [
+    ("std::process::Command::new", &[], false),
+    ("std::process::Command::arg", &[], false),
+    ("std::process::Command::args", &[], false),
+    ("sqlx::query", &[0], true),
+    ("sqlx::query_as", &[0], true),
+    ("sqlx::query_scalar", &[0], true),
+    ("std::fs::File::open", &[], false),
+    ("std::fs::File::create", &[], false),
+    ("std::fs::read", &[], false),
+    ("std::fs::write", &[0], false),
+    ("std::fs::remove_file", &[], false),
+    ("std::fs::remove_dir_all", &[], false),
+];
+
+/// Built-in sanitizers.
+const DEFAULT_SANITIZERS: [&str; 2] = ["shell_escape::escape", "shlex::try_quote"];
+
+/// The macro a sink with `format = true` requires on the path.
+const FORMAT_MACRO: &str = "format!";
+
+/// Something that produces untrusted data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaintSource {
+    /// Path of a function or method whose result is untrusted.
+    pub call: Option<String>,
+    /// Path of a type whose parameters are untrusted, e.g. an extractor of
+    /// a web framework.
+    pub parameter_type: Option<String>,
+}
+
+/// A call that must not receive untrusted data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaintSink {
+    /// Path of the function or method.
+    pub call: String,
+    /// Positions of the checked arguments, not counting the receiver of a
+    /// method call; all arguments if empty.
+    #[serde(default)]
+    pub arguments: Vec<usize>,
+    /// Only report data that was formatted into a string with `format!`,
+    /// e.g. for SQL queries, which are safe with bound parameters.
+    #[serde(default)]
+    pub format: bool,
+}
+
+/// A call whose result is safe whatever its arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaintSanitizer {
+    /// Path of the function or method.
+    pub call: String,
+}
+
+/// The sources, sinks and sanitizers of the taint analysis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaintConfig {
+    /// Whether the built-in entries are kept in addition to those of the
+    /// file.
+    #[serde(default = "default_true")]
+    pub defaults: bool,
+    #[serde(default)]
+    pub sources: Vec<TaintSource>,
+    #[serde(default)]
+    pub sinks: Vec<TaintSink>,
+    #[serde(default)]
+    pub sanitizers: Vec<TaintSanitizer>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for TaintConfig {
+    /// The built-in sources, sinks and sanitizers.
+    fn default() -> Self {
+        let sources = DEFAULT_SOURCE_CALLS
+            .iter()
+            .map(|call| TaintSource {
+                call: Some((*call).to_owned()),
+                parameter_type: None,
+            })
+            .chain(DEFAULT_SOURCE_TYPES.iter().map(|ty| TaintSource {
+                call: None,
+                parameter_type: Some((*ty).to_owned()),
+            }))
+            .collect();
+        let sinks = DEFAULT_SINKS
+            .iter()
+            .map(|(call, arguments, format)| TaintSink {
+                call: (*call).to_owned(),
+                arguments: arguments.to_vec(),
+                format: *format,
+            })
+            .collect();
+        let sanitizers = DEFAULT_SANITIZERS
+            .iter()
+            .map(|call| TaintSanitizer {
+                call: (*call).to_owned(),
+            })
+            .collect();
+        TaintConfig {
+            defaults: true,
+            sources,
+            sinks,
+            sanitizers,
+        }
+    }
+}
+
+impl TaintConfig {
+    /// Parses a configuration, adding the built-in entries unless it sets
+    /// `defaults = false`.
+    pub fn from_toml(text: &str) -> Result<Self, TaintConfigError> {
+        let mut config: TaintConfig =
+            toml::from_str(text).map_err(|e| TaintConfigError::Parse {
+                path: None,
+                message: e.message().to_owned(),
+            })?;
+        for source in &config.sources {
+            if source.call.is_some() == source.parameter_type.is_some() {
+                return Err(TaintConfigError::Parse {
+                    path: None,
+                    message: "a source needs exactly one of `call` and `parameter_type`".to_owned(),
+                });
+            }
+        }
+        if config.defaults {
+            let defaults = TaintConfig::default();
+            config.sources.extend(defaults.sources);
+            config.sinks.extend(defaults.sinks);
+            config.sanitizers.extend(defaults.sanitizers);
+        }
+        Ok(config)
+    }
+
+    /// Reads a configuration file.
+    pub fn load(path: &Path) -> Result<Self, TaintConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| TaintConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        TaintConfig::from_toml(&text).map_err(|e| match e {
+            TaintConfigError::Parse { message, .. } => TaintConfigError::Parse {
+                path: Some(path.to_path_buf()),
+                message,
+            },
+            e => e,
+        })
+    }
+}
+
+/// Errors reading a [`TaintConfig`].
+#[derive(Debug)]
+pub enum TaintConfigError {
+    /// The file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The file is not a valid configuration.
+    Parse {
+        path: Option<PathBuf>,
+        message: String,
+    },
+}
+
+impl fmt::Display for TaintConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaintConfigError::Io { path, source } => {
+                write!(f, "cannot read {}: {}", path.display(), source)
+            }
+            TaintConfigError::Parse {
+                path: Some(path),
+                message,
+            } => write!(
+                f,
+                "invalid taint configuration {}: {}",
+                path.display(),
+                message
+            ),
+            TaintConfigError::Parse {
+                path: None,
+                message,
+            } => write!(f, "invalid taint configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for TaintConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TaintConfigError::Io { source, .. } => Some(source),
+            TaintConfigError::Parse { .. } => None,
+        }
+    }
+}
+
+/// One step of a [`TaintFlow`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaintStep {
+    /// Item path of the callable the step is in.
+    pub function: String,
+    pub file_path: Option<String>,
+    pub line: usize,
+    /// Variable that holds the tainted value after the step; `None` when
+    /// the value is the result of `call`.
+    pub variable: Option<String>,
+    /// Call whose result is tainted, or the call that passes the value to a
+    /// parameter, as written at the call site.
+    pub call: Option<String>,
+}
+
+/// Untrusted data that reaches a sink.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaintFlow {
+    /// The source, as configured.
+    pub source: String,
+    /// The sink, as configured.
+    pub sink: String,
+    /// Item path of the callable that calls the sink.
+    pub function: String,
+    pub file_path: Option<String>,
+    /// Line of the call to the sink.
+    pub line: usize,
+    /// Position of the tainted argument of the sink.
+    pub argument: usize,
+    /// The steps from the source to the call to the sink, which is the last.
+    pub path: Vec<TaintStep>,
+}
+
+/// The taint flows of one crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaintReport {
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// Ordered by file, line and argument of the sink.
+    pub flows: Vec<TaintFlow>,
+}
+
+impl TaintReport {
+    /// Finds the flows of `krate` from the sources of `config` to its sinks.
+    pub fn build(krate: &RustCrate, config: &TaintConfig) -> Self {
+        let mut flows = Analysis::new(krate, config).run();
+        flows.sort_by(|a, b| {
+            (&a.file_path, a.line, a.argument, &a.source).cmp(&(
+                &b.file_path,
+                b.line,
+                b.argument,
+                &b.source,
+            ))
+        });
+        TaintReport {
+            krate: krate.name.clone(),
+            flows,
+        }
+    }
+}
+
+/// Reports the flows of `krate` from the sources of `config` to its sinks.
+pub fn check_with(krate: &RustCrate, config: &TaintConfig) -> Vec<Diagnostic> {
+    TaintReport::build(krate, config)
+        .flows
+        .into_iter()
+        .map(|flow| {
+            let through: Vec<&str> = flow
+                .path
+                .iter()
+                .filter_map(|step| step.variable.as_deref())
+                .collect();
+            let mut message = format!(
+                "data from `{}` reaches argument {} of `{}`",
+                flow.source, flow.argument, flow.sink
+            );
+            if !through.is_empty() {
+                message.push_str(&format!(" through `{}`", through.join("` -> `")));
+            }
+            let mut diagnostic = Diagnostic::new(&TAINTED_FLOW, message, flow.line, flow.line);
+            diagnostic.file_path = flow.file_path;
+            diagnostic.set_item(ItemKind::Function, flow.function);
+            diagnostic
+        })
+        .collect()
+}
+
+/// Reports the flows of `krate` between the built-in sources and sinks.
+pub fn check(krate: &RustCrate) -> Vec<Diagnostic> {
+    check_with(krate, &TaintConfig::default())
+}
+
+/// A tainted value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Node {
+    /// A definition of a callable.
+    Def(usize, usize),
+    /// The result of a call of a callable.
+    Call(usize, usize),
+}
+
+impl Node {
+    fn function(self) -> usize {
+        match self {
+            Node::Def(function, _) | Node::Call(function, _) => function,
+        }
+    }
+}
+
+/// A callable with a dependence graph.
+struct Function<'a> {
+    path: String,
+    module: &'a RustModule,
+    callable: &'a RustCallable,
+    pdg: &'a ProgramDependenceGraph,
+    /// Functions each call resolves to.
+    callees: Vec<Vec<usize>>,
+    /// Whether each call is to a sanitizer.
+    sanitizers: Vec<bool>,
+}
+
+struct Analysis<'a> {
+    config: &'a TaintConfig,
+    /// The names paths of the crate can start with: `crate` and the crate
+    /// roots.
+    roots: Vec<String>,
+    functions: Vec<Function<'a>>,
+    /// The calls to each function, as (caller, call index).
+    callers: Vec<Vec<(usize, usize)>>,
+    /// The node each tainted node was reached from; `None` for sources.
+    reached: HashMap<Node, Option<Node>>,
+    /// The source each seed matched.
+    seeds: HashMap<Node, String>,
+    queue: VecDeque<Node>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(krate: &'a RustCrate, config: &'a TaintConfig) -> Self {
+        let mut functions = Vec::new();
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (owner, callable) in module.callables() {
+                    let Some(pdg) = &callable.dependence else {
+                        continue;
+                    };
+                    functions.push(Function {
+                        path: item_path(&path, owner.as_deref(), &callable.name),
+                        module,
+                        callable,
+                        pdg,
+                        callees: vec![Vec::new(); pdg.calls.len()],
+                        sanitizers: vec![false; pdg.calls.len()],
+                    });
+                }
+            }
+        }
+        functions.sort_by(|a, b| a.path.cmp(&b.path));
+        let index: HashMap<&str, usize> = functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.path.as_str(), i))
+            .collect();
+        let graph = CallGraph::build(krate);
+        let mut callees = Vec::with_capacity(functions.len());
+        let mut callers = vec![Vec::new(); functions.len()];
+        for (caller, function) in functions.iter().enumerate() {
+            let mut resolved = vec![Vec::new(); function.pdg.calls.len()];
+            for edge in graph.callees(&function.path) {
+                let Some(&callee) = index.get(edge.callee.as_str()) else {
+                    continue;
+                };
+                for (i, call) in function.pdg.calls.iter().enumerate() {
+                    if edge.line as usize == call.line && edge.call == call.call {
+                        resolved[i].push(callee);
+                        callers[callee].push((caller, i));
+                    }
+                }
+            }
+            callees.push(resolved);
+        }
+        for (function, resolved) in functions.iter_mut().zip(callees) {
+            function.callees = resolved;
+        }
+        let mut roots = vec!["crate".to_owned(), krate.name.replace('-', "_")];
+        roots.extend(krate.modules.iter().map(|root| root.name.clone()));
+        let mut analysis = Analysis {
+            config,
+            roots,
+            functions,
+            callers,
+            reached: HashMap::new(),
+            seeds: HashMap::new(),
+            queue: VecDeque::new(),
+        };
+        let sanitizers: Vec<Vec<bool>> = (0..analysis.functions.len())
+            .map(|f| {
+                (0..analysis.functions[f].pdg.calls.len())
+                    .map(|k| {
+                        config
+                            .sanitizers
+                            .iter()
+                            .any(|s| analysis.matches_call(&s.call, f, k))
+                    })
+                    .collect()
+            })
+            .collect();
+        for (function, sanitizers) in analysis.functions.iter_mut().zip(sanitizers) {
+            function.sanitizers = sanitizers;
+        }
+        analysis
+    }
+
+    fn run(mut self) -> Vec<TaintFlow> {
+        self.seed();
+        let mut flows = Vec::new();
+        let mut reported = BTreeSet::new();
+        while let Some(node) = self.queue.pop_front() {
+            let f = node.function();
+            let function = &self.functions[f];
+            let mut next = Vec::new();
+            for def in &function.pdg.definitions {
+                let flows_in = match node {
+                    Node::Def(_, d) => def.inputs.contains(&d),
+                    Node::Call(_, i) => def.calls.contains(&i),
+                };
+                if flows_in && !self.stopped(f, node, &def.calls) {
+                    next.push(Node::Def(f, def.id));
+                }
+            }
+            for (j, call) in function.pdg.calls.iter().enumerate() {
+                if node == Node::Call(f, j) {
+                    continue;
+                }
+                let receiver = call.receiver.iter().map(|arg| (None, arg));
+                let arguments = call.arguments.iter().enumerate().map(|(i, a)| (Some(i), a));
+                for (position, argument) in receiver.chain(arguments) {
+                    if !carries(argument, node) || self.stopped(f, node, &argument.calls) {
+                        continue;
+                    }
+                    if let Some(position) = position {
+                        for sink in self.sinks(f, j, position) {
+                            let path = self.path(node);
+                            if sink.format && !self.formatted(&path, argument) {
+                                continue;
+                            }
+                            let source = self.source(node);
+                            let key = (f, j, position, source.clone(), &sink.call, path.clone());
+                            if reported.insert(key) {
+                                flows.push(self.flow(node, path, source, sink, j, position));
+                            }
+                        }
+                    }
+                    // The result of a sanitizer is clean whatever it does
+                    // with its parameters.
+                    if function.sanitizers[j] {
+                        continue;
+                    }
+                    for &callee in &function.callees[j] {
+                        next.extend(self.parameters(callee, call, position));
+                    }
+                }
+            }
+            if let Node::Def(_, d) = node {
+                if function.pdg.definitions[d].kind == DefinitionKind::Return {
+                    next.extend(
+                        self.callers[f]
+                            .iter()
+                            .filter(|&&(caller, j)| !self.functions[caller].sanitizers[j])
+                            .map(|&(caller, j)| Node::Call(caller, j)),
+                    );
+                }
+            }
+            for to in next {
+                if let Entry::Vacant(entry) = self.reached.entry(to) {
+                    entry.insert(Some(node));
+                    self.queue.push_back(to);
+                }
+            }
+        }
+        flows
+    }
+
+    /// Queues the results of source calls and the parameters of source
+    /// types.
+    fn seed(&mut self) {
+        let mut seeds = Vec::new();
+        for (f, function) in self.functions.iter().enumerate() {
+            for i in 0..function.pdg.calls.len() {
+                let source = self.config.sources.iter().find_map(|source| {
+                    let pattern = source.call.as_deref()?;
+                    self.matches_call(pattern, f, i).then_some(pattern)
+                });
+                if let Some(source) = source {
+                    seeds.push((Node::Call(f, i), source.to_owned()));
+                }
+            }
+            for def in &function.pdg.definitions {
+                let Some(parameter) = def.parameter else {
+                    continue;
+                };
+                let Some(param) = function.callable.parameters.get(parameter) else {
+                    continue;
+                };
+                let ty = resolve(function.module, &strip_generics(&param.rust_type.name));
+                let source = self.config.sources.iter().find_map(|source| {
+                    let pattern = source.parameter_type.as_deref()?;
+                    matches_path(self.local(pattern), self.local(&ty)).then_some(pattern)
+                });
+                if let Some(source) = source {
+                    seeds.push((Node::Def(f, def.id), source.to_owned()));
+                }
+            }
+        }
+        for (node, source) in seeds {
+            if let Entry::Vacant(entry) = self.reached.entry(node) {
+                entry.insert(None);
+                self.seeds.insert(node, source);
+                self.queue.push_back(node);
+            }
+        }
+    }
+
+    /// Whether the value of `node` is passed to a sanitizer or to a callee
+    /// of the crate among `calls`, whose result then stands for it.
+    fn stopped(&self, f: usize, node: Node, calls: &[usize]) -> bool {
+        let function = &self.functions[f];
+        calls.iter().any(|&k| {
+            let call = &function.pdg.calls[k];
+            node != Node::Call(f, k)
+                && (function.sanitizers[k] || !function.callees[k].is_empty())
+                && call
+                    .receiver
+                    .iter()
+                    .chain(&call.arguments)
+                    .any(|argument| carries(argument, node))
+        })
+    }
+
+    /// The sinks that argument `position` of call `k` in function `f` is
+    /// checked by.
+    fn sinks(&self, f: usize, k: usize, position: usize) -> Vec<&'a TaintSink> {
+        self.config
+            .sinks
+            .iter()
+            .filter(|sink| sink.arguments.is_empty() || sink.arguments.contains(&position))
+            .filter(|sink| self.matches_call(&sink.call, f, k))
+            .collect()
+    }
+
+    /// Whether call `k` of function `f` calls the function or method
+    /// `pattern`.
+    fn matches_call(&self, pattern: &str, f: usize, k: usize) -> bool {
+        let function = &self.functions[f];
+        let call = &function.pdg.calls[k];
+        let pattern = self.local(pattern);
+        if call.call.ends_with('!') {
+            return pattern.ends_with('!') && last_segment(pattern) == last_segment(&call.call);
+        }
+        if function.callees[k]
+            .iter()
+            .any(|&callee| self.local(&self.functions[callee].path) == pattern)
+        {
+            return true;
+        }
+        if call.receiver.is_some() {
+            let mut segments = pattern.rsplit("::");
+            let method = segments.next();
+            let is_method = segments
+                .next()
+                .is_some_and(|ty| ty.starts_with(char::is_uppercase));
+            return is_method && method == Some(call.call.as_str());
+        }
+        let path = resolve(function.module, &strip_generics(&call.call));
+        matches_path(pattern, self.local(&path))
+    }
+
+    /// Removes the leading `crate::` or crate name from `path`, so that the
+    /// paths of the crate compare equal however they are written.
+    fn local<'p>(&self, path: &'p str) -> &'p str {
+        let path = path.trim_start_matches("::");
+        match path.split_once("::") {
+            Some((first, rest)) if self.roots.iter().any(|root| root == first) => rest,
+            _ => path,
+        }
+    }
+
+    /// The parameter definitions of `callee` that the argument `position`
+    /// of `call`, or its receiver, is passed to.
+    fn parameters(
+        &self,
+        callee: usize,
+        call: &CallArguments,
+        position: Option<usize>,
+    ) -> Vec<Node> {
+        let function = &self.functions[callee];
+        let has_self = function
+            .callable
+            .parameters
+            .first()
+            .is_some_and(|p| p.is_self);
+        let parameter = match (call.receiver.is_some(), has_self, position) {
+            (true, true, None) => 0,
+            (true, true, Some(i)) => i + 1,
+            (false, _, Some(i)) => i,
+            // An associated function called through a method call cannot be
+            // the callee.
+            _ => return Vec::new(),
+        };
+        function
+            .pdg
+            .definitions
+            .iter()
+            .filter(|def| def.parameter == Some(parameter))
+            .map(|def| Node::Def(callee, def.id))
+            .collect()
+    }
+
+    /// The nodes from a source to `node`.
+    fn path(&self, node: Node) -> Vec<Node> {
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some(Some(previous)) = self.reached.get(&current) {
+            path.push(*previous);
+            current = *previous;
+        }
+        path.reverse();
+        path
+    }
+
+    fn source(&self, node: Node) -> String {
+        let path = self.path(node);
+        self.seeds[&path[0]].clone()
+    }
+
+    /// Whether the value was formatted with `format!` on `path` or in the
+    /// argument.
+    fn formatted(&self, path: &[Node], argument: &Argument) -> bool {
+        let is_format = |f: usize, k: usize| {
+            let call = &self.functions[f].pdg.calls[k].call;
+            call == FORMAT_MACRO || call.ends_with(&format!("::{}", FORMAT_MACRO))
+        };
+        let f = path.last().map_or(0, |node| node.function());
+        argument.calls.iter().any(|&k| is_format(f, k))
+            || path.iter().any(|node| match *node {
+                Node::Call(f, k) => is_format(f, k),
+                Node::Def(f, d) => self.functions[f].pdg.definitions[d]
+                    .calls
+                    .iter()
+                    .any(|&k| is_format(f, k)),
+            })
+    }
+
+    fn step(&self, node: Node) -> TaintStep {
+        let function = &self.functions[node.function()];
+        let (line, variable, call) = match node {
+            Node::Def(_, d) => {
+                let def = &function.pdg.definitions[d];
+                (def.line, Some(def.variable.clone()), None)
+            }
+            Node::Call(_, k) => {
+                let call = &function.pdg.calls[k];
+                (call.line, None, Some(call.call.clone()))
+            }
+        };
+        TaintStep {
+            function: function.path.clone(),
+            file_path: function.module.file_path.clone(),
+            line,
+            variable,
+            call,
+        }
+    }
+
+    fn flow(
+        &self,
+        node: Node,
+        path: Vec<Node>,
+        source: String,
+        sink: &TaintSink,
+        j: usize,
+        position: usize,
+    ) -> TaintFlow {
+        let f = node.function();
+        let function = &self.functions[f];
+        let call = &function.pdg.calls[j];
+        let mut steps = Vec::with_capacity(path.len() + 1);
+        for (i, &node) in path.iter().enumerate() {
+            let mut step = self.step(node);
+            // A parameter is reached through the call in the caller.
+            if let (Node::Def(callee, d), Some(&previous)) =
+                (node, i.checked_sub(1).map(|i| &path[i]))
+            {
+                if previous.function() != callee
+                    && self.functions[callee].pdg.definitions[d].kind == DefinitionKind::Parameter
+                {
+                    step.call = self.callers[callee]
+                        .iter()
+                        .find(|(caller, _)| *caller == previous.function())
+                        .map(|&(caller, k)| self.functions[caller].pdg.calls[k].call.clone());
+                }
+            }
+            steps.push(step);
+        }
+        steps.push(TaintStep {
+            function: function.path.clone(),
+            file_path: function.module.file_path.clone(),
+            line: call.line,
+            variable: None,
+            call: Some(call.call.clone()),
+        });
+        TaintFlow {
+            source,
+            sink: sink.call.clone(),
+            function: function.path.clone(),
+            file_path: function.module.file_path.clone(),
+            line: call.line,
+            argument: position,
+            path: steps,
+        }
+    }
+}
+
+/// Whether the value of `node` is used in `argument`.
+fn carries(argument: &Argument, node: Node) -> bool {
+    match node {
+        Node::Def(_, d) => argument.definitions.contains(&d),
+        Node::Call(_, k) => argument.calls.contains(&k),
+    }
+}
+
+/// Whether the resolved path `path` names `pattern`: it is the same path,
+/// or a suffix of at least two segments, e.g. `Command::new` when the
+/// import of `Command` is not known.
+fn matches_path(pattern: &str, path: &str) -> bool {
+    let path = path.trim_start_matches("::");
+    pattern == path
+        || (path.contains("::")
+            && pattern
+                .strip_suffix(path)
+                .is_some_and(|prefix| prefix.ends_with("::")))
+}
+
+/// Resolves the first segment of `path` through the `use` declarations of
+/// `module`.
+fn resolve(module: &RustModule, path: &str) -> String {
+    let (first, rest) = match path.split_once("::") {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (path, None),
+    };
+    for used in &module.use_declarations {
+        let (target, alias) = match used.split_once(" as ") {
+            Some((target, alias)) => (target, alias),
+            None => (used.as_str(), last_segment(used)),
+        };
+        if alias != first {
+            continue;
+        }
+        let target = target.strip_suffix("::self").unwrap_or(target);
+        return match rest {
+            Some(rest) => format!("{}::{}", target, rest),
+            None => target.to_owned(),
+        };
+    }
+    path.to_owned()
+}
+
+/// Removes the generic arguments of a path, e.g. `Vec::<u8>::new`.
+fn strip_generics(path: &str) -> String {
+    let mut stripped = String::with_capacity(path.len());
+    let mut depth = 0usize;
+    for c in path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+        .replace("::::", "::")
+        .trim_end_matches("::")
+        .to_owned()
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
@@ -0,0 +1,122 @@
+//! Propagates unsafe code along the call graph.
+//!
+//! The [`SafetyAnalysis`] of a callable only covers its own body. This pass
+//! gives every callable a [`TransitiveSafety`] summary of what it reaches
+//! through its resolved callees: the unsafe blocks, the callables that contain
+//! them or are `unsafe fn`s, and the foreign functions called on the way,
+//! each with the shortest call path that leads there. A callable reaches
+//! itself, so its own unsafe blocks and FFI calls are part of its summary.
+//!
+//! Method calls resolve to every method of that name (see
+//! [`callgraph`](super::callgraph)), so a summary may include code that is
+//! not actually reached; it never misses a call the graph resolves.
+//!
+//! The summaries also back the `unsafe/safe_api_reaches_ffi` rule, which
+//! reports public safe functions that can end up in a foreign function.
+
+use std::collections::BTreeMap;
+
+use super::callgraph::{CallGraph, CallWalker, item_path, update_callables};
+use super::{Diagnostic, ItemKind, Level, Rule};
+use crate::entities::RustVisibility;
+use crate::entities::rcrate::RustCrate;
+use crate::entities::safety::{SafetyPath, TransitiveSafety};
+
+pub static SAFE_API_REACHES_FFI: Rule = Rule {
+    id: "unsafe/safe_api_reaches_ffi",
+    name: "SafeApiReachesFfi",
+    short_description: "Public safe function that reaches a foreign function.",
+    help: "Check that the function upholds the foreign function's contract for every input.",
+    default_level: Level::Note,
+};
+
+/// Rules reported by this check.
+pub static RULES: [&Rule; 1] = [&SAFE_API_REACHES_FFI];
+
+/// Stores the transitive safety summary of every callable of `krate` in its
+/// [`SafetyAnalysis::transitive`].
+pub fn propagate(krate: &mut RustCrate) {
+    let graph = CallGraph::build(krate);
+    let summaries = summarize(krate, &graph);
+    update_callables(krate, |path, callable| {
+        callable.safety_analysis.transitive = summaries.get(path).cloned();
+    });
+}
+
+/// Computes the transitive safety summary of every callable of `krate`,
+/// keyed by item path.
+pub fn summarize(krate: &RustCrate, graph: &CallGraph) -> BTreeMap<String, TransitiveSafety> {
+    let mut walker = CallWalker::new(krate, graph);
+    let mut summaries = BTreeMap::new();
+    for start in 0..walker.len() {
+        let mut summary = TransitiveSafety::default();
+        let mut ffi_reached: Vec<&str> = Vec::new();
+        walker.walk(start, |walker, node| {
+            let callable = walker.callable(node);
+            let analysis = &callable.safety_analysis;
+            summary.unsafe_blocks += analysis.unsafe_blocks.len();
+            if callable.is_unsafe || !analysis.unsafe_blocks.is_empty() {
+                summary.unsafe_callables.push(SafetyPath {
+                    target: walker.path(node).to_owned(),
+                    path: walker.call_path(node),
+                });
+            }
+            for name in &analysis.ffi_calls {
+                if !ffi_reached.contains(&name.as_str()) {
+                    ffi_reached.push(name);
+                    summary.ffi_functions.push(SafetyPath {
+                        target: name.clone(),
+                        path: walker.call_path(node),
+                    });
+                }
+            }
+        });
+        summaries.insert(walker.path(start).to_owned(), summary);
+    }
+    summaries
+}
+
+/// Reports every foreign function that a public, safe function of `krate`
+/// reaches, using the summaries stored by [`propagate`].
+pub fn check(krate: &RustCrate) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            for (owner, callable) in module.callables() {
+                if callable.visibility != RustVisibility::Public
+                    || callable.is_unsafe
+                    || callable.is_extern
+                {
+                    continue;
+                }
+                let Some(transitive) = &callable.safety_analysis.transitive else {
+                    continue;
+                };
+                for ffi in &transitive.ffi_functions {
+                    let mut diagnostic = Diagnostic::new(
+                        &SAFE_API_REACHES_FFI,
+                        format!(
+                            "Public safe function `{}` reaches foreign function `{}` through {}",
+                            callable.name,
+                            ffi.target,
+                            ffi.path
+                                .iter()
+                                .map(|p| format!("`{}`", p))
+                                .collect::<Vec<_>>()
+                                .join(" -> ")
+                        ),
+                        callable.start_line,
+                        callable.end_line,
+                    );
+                    diagnostic.file_path = module.file_path.clone();
+                    diagnostic.set_item(
+                        ItemKind::Function,
+                        item_path(&path, owner.as_deref(), &callable.name),
+                    );
+                    diagnostics.push(diagnostic);
+                }
+            }
+        }
+    }
+    diagnostics
+}
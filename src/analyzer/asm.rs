@@ -0,0 +1,190 @@
+//! Parsing of the arguments of `asm!`, `global_asm!` and `naked_asm!`.
+//!
+//! The arguments are not Rust expressions, so they are split into
+//! comma-separated token runs and classified by their leading tokens:
+//! string literals are templates, `options(...)` and `clobber_abi(...)` are
+//! recorded as such and everything else is an operand.
+
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use syn::LitStr;
+
+use super::source::SourceText;
+use crate::entities::asm::{AsmOperandKind, RustAsmOperand, RustInlineAsm};
+
+/// Macros that expand to inline assembly.
+pub const ASM_MACROS: &[&str] = &["asm", "global_asm", "naked_asm"];
+
+/// Returns true if `mac` invokes one of the [`ASM_MACROS`].
+pub fn is_asm_macro(mac: &syn::Macro) -> bool {
+    mac.path
+        .segments
+        .last()
+        .is_some_and(|s| ASM_MACROS.iter().any(|name| s.ident == name))
+}
+
+/// Extracts the invocation `mac` of an assembly macro.
+pub fn inline_asm(src: &SourceText, mac: &syn::Macro) -> RustInlineAsm {
+    let name = mac
+        .path
+        .segments
+        .last()
+        .map(|s| s.ident.to_string())
+        .unwrap_or_default();
+    let (start_line, end_line) = super::render::lines(mac);
+    let mut asm = RustInlineAsm::new(name, start_line, end_line);
+    for arg in split(mac.tokens.clone()) {
+        let arg = strip_attributes(&arg);
+        if arg.is_empty() {
+            continue;
+        }
+        if let Some(template) = template(src, arg) {
+            // Templates come first; anything after an operand that is not
+            // one is an unsupported argument.
+            if asm.operands.is_empty() {
+                asm.templates.push(template);
+            }
+        } else if let Some(options) = group_after(arg, "options") {
+            asm.options.extend(
+                split(options)
+                    .iter()
+                    .filter(|option| !option.is_empty())
+                    .map(|option| text(src, option)),
+            );
+        } else if let Some(abis) = group_after(arg, "clobber_abi") {
+            asm.clobber_abis.extend(split(abis).iter().map(|abi| {
+                syn::parse2::<LitStr>(abi.iter().cloned().collect())
+                    .map_or_else(|_| text(src, abi), |lit| lit.value())
+            }));
+        } else if let Some(operand) = operand(src, arg) {
+            asm.operands.push(operand);
+        }
+    }
+    asm
+}
+
+/// Splits `tokens` at its top-level commas.
+fn split(tokens: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut args = vec![Vec::new()];
+    for token in tokens {
+        match &token {
+            TokenTree::Punct(p) if p.as_char() == ',' => args.push(Vec::new()),
+            _ => args.last_mut().expect("never empty").push(token),
+        }
+    }
+    if args.last().is_some_and(Vec::is_empty) {
+        args.pop();
+    }
+    args
+}
+
+/// Skips the `#[...]` attributes in front of an argument.
+fn strip_attributes(mut arg: &[TokenTree]) -> &[TokenTree] {
+    while let [TokenTree::Punct(p), TokenTree::Group(g), rest @ ..] = arg {
+        if p.as_char() != '#' || g.delimiter() != Delimiter::Bracket {
+            break;
+        }
+        arg = rest;
+    }
+    arg
+}
+
+/// Returns the source text of a run of tokens.
+fn text(src: &SourceText, tokens: &[TokenTree]) -> String {
+    let (Some(first), Some(last)) = (tokens.first(), tokens.last()) else {
+        return String::new();
+    };
+    match src.slice_between(first.span(), last.span()) {
+        Some(text) if !text.is_empty() => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        _ => tokens.iter().cloned().collect::<TokenStream>().to_string(),
+    }
+}
+
+/// Returns the template an argument holds: the value of a string literal,
+/// or the text of a macro call such as `concat!(...)`.
+fn template(src: &SourceText, arg: &[TokenTree]) -> Option<String> {
+    match arg {
+        [TokenTree::Literal(_)] => {
+            let lit: LitStr = syn::parse2(arg.iter().cloned().collect()).ok()?;
+            Some(lit.value())
+        }
+        [
+            TokenTree::Ident(_),
+            TokenTree::Punct(bang),
+            TokenTree::Group(_),
+        ] if bang.as_char() == '!' => Some(text(src, arg)),
+        _ => None,
+    }
+}
+
+/// Returns the contents of `keyword(...)`.
+fn group_after(arg: &[TokenTree], keyword: &str) -> Option<TokenStream> {
+    match arg {
+        [TokenTree::Ident(ident), TokenTree::Group(group)]
+            if ident == keyword && group.delimiter() == Delimiter::Parenthesis =>
+        {
+            Some(group.stream())
+        }
+        _ => None,
+    }
+}
+
+fn operand(src: &SourceText, mut arg: &[TokenTree]) -> Option<RustAsmOperand> {
+    let mut name = None;
+    // `name = ...`, but not the `=>` of an `inout` operand.
+    if let [TokenTree::Ident(ident), TokenTree::Punct(eq), rest @ ..] = arg {
+        if eq.as_char() == '=' && eq.spacing() == Spacing::Alone {
+            name = Some(ident.to_string());
+            arg = rest;
+        }
+    }
+    let [TokenTree::Ident(keyword), rest @ ..] = arg else {
+        return None;
+    };
+    let kind = match keyword.to_string().as_str() {
+        "in" => AsmOperandKind::In,
+        "out" => AsmOperandKind::Out,
+        "lateout" => AsmOperandKind::LateOut,
+        "inout" => AsmOperandKind::InOut,
+        "inlateout" => AsmOperandKind::InLateOut,
+        "const" => AsmOperandKind::Const,
+        "sym" => AsmOperandKind::Sym,
+        "label" => AsmOperandKind::Label,
+        _ => return None,
+    };
+    let mut operand = RustAsmOperand {
+        name,
+        kind,
+        register: None,
+        expression: String::new(),
+        output: None,
+    };
+    let mut rest = rest;
+    if matches!(
+        kind,
+        AsmOperandKind::In
+            | AsmOperandKind::Out
+            | AsmOperandKind::LateOut
+            | AsmOperandKind::InOut
+            | AsmOperandKind::InLateOut
+    ) {
+        let [TokenTree::Group(register), tail @ ..] = rest else {
+            return None;
+        };
+        operand.register = Some(register.stream().to_string());
+        rest = tail;
+    }
+    let arrow = rest.windows(2).position(|pair| match pair {
+        [TokenTree::Punct(eq), TokenTree::Punct(gt)] => {
+            eq.as_char() == '=' && eq.spacing() == Spacing::Joint && gt.as_char() == '>'
+        }
+        _ => false,
+    });
+    match arrow {
+        Some(at) if matches!(kind, AsmOperandKind::InOut | AsmOperandKind::InLateOut) => {
+            operand.expression = text(src, &rest[..at]);
+            operand.output = Some(text(src, &rest[at + 2..]));
+        }
+        _ => operand.expression = text(src, rest),
+    }
+    Some(operand)
+}
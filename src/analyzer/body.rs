@@ -0,0 +1,1151 @@
+//! Analysis of function bodies: call sites, local variables, unsafe code,
+//! inline assembly, panic sites, error propagation, await points, spawned
+//! tasks, blocking calls and lock guards.
+//!
+//! Lock guards are tracked when they are bound to a local, e.g.
+//! `let guard = state.lock().unwrap();`, from the binding to the end of the
+//! enclosing block or to `drop(guard)`. Guards that are temporaries of a
+//! statement are not tracked.
+
+use std::collections::{BTreeSet, HashMap};
+
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Expr, Lit, Pat, Token, Type};
+
+use super::asm;
+use super::index::CrateIndex;
+use super::render;
+use super::source::SourceText;
+use crate::entities::{
+    UnsafeReason,
+    asm::RustInlineAsm,
+    callsite::CallSite,
+    concurrency::{
+        AwaitPoint, BlockingCall, BlockingKind, GuardAcrossAwait, LockAcquisition, SpawnKind,
+        SpawnSite,
+    },
+    errors::{ErrorPropagation, SwallowKind, SwallowedError},
+    panic::{PanicKind, PanicSite},
+    rtype::RustType,
+    safety::UnsafeBlock,
+    variables::RustVariableDeclaration,
+};
+
+/// Well-known unsafe functions of the standard library, matched by the last
+/// path segment of a call.
+const STD_UNSAFE_FNS: &[&str] = &[
+    "transmute",
+    "transmute_copy",
+    "zeroed",
+    "uninitialized",
+    "from_raw_parts",
+    "from_raw_parts_mut",
+    "from_utf8_unchecked",
+    "from_utf8_unchecked_mut",
+    "from_raw",
+    "from_raw_fd",
+    "copy_nonoverlapping",
+    "read_volatile",
+    "write_volatile",
+    "read_unaligned",
+    "write_unaligned",
+    "unreachable_unchecked",
+];
+
+/// Well-known unsafe methods of the standard library.
+const STD_UNSAFE_METHODS: &[&str] = &[
+    "get_unchecked",
+    "get_unchecked_mut",
+    "set_len",
+    "assume_init",
+    "assume_init_read",
+    "assume_init_ref",
+    "assume_init_mut",
+    "unwrap_unchecked",
+    "offset_from",
+    "as_ref_unchecked",
+    "read_volatile",
+    "write_volatile",
+    "read_unaligned",
+    "write_unaligned",
+    "from_utf8_unchecked",
+];
+
+/// Macros that panic, at least under some condition.
+const PANIC_MACROS: [(&str, PanicKind); 7] = [
+    ("panic", PanicKind::Panic),
+    ("unreachable", PanicKind::Unreachable),
+    ("todo", PanicKind::Todo),
+    ("unimplemented", PanicKind::Unimplemented),
+    ("assert", PanicKind::Assert),
+    ("assert_eq", PanicKind::Assert),
+    ("assert_ne", PanicKind::Assert),
+];
+
+/// Methods that replace the error of a `Result`, or turn an `Option` into
+/// one, before it is propagated with `?`.
+const ERROR_ADAPTERS: &[&str] = &[
+    "map_err",
+    "context",
+    "with_context",
+    "wrap_err",
+    "wrap_err_with",
+    "ok_or",
+    "ok_or_else",
+];
+
+/// Functions and methods that spawn a task, matched by name.
+const SPAWN_FNS: [(&str, SpawnKind); 3] = [
+    ("spawn", SpawnKind::Spawn),
+    ("spawn_local", SpawnKind::SpawnLocal),
+    ("spawn_blocking", SpawnKind::SpawnBlocking),
+];
+
+/// Everything learned from one function body.
+#[derive(Debug, Default)]
+pub struct BodyFacts {
+    pub call_sites: Vec<CallSite>,
+    pub variable_declarations: Vec<RustVariableDeclaration>,
+    pub accessed_variables: Vec<String>,
+    pub referenced_types: Vec<String>,
+    pub instantiated_types: Vec<String>,
+    pub unsafe_blocks: Vec<UnsafeBlock>,
+    pub unsafe_fn_calls: Vec<String>,
+    pub raw_pointer_usage: bool,
+    pub ffi_interactions: bool,
+    pub ffi_calls: Vec<String>,
+    pub mutable_statics: Vec<String>,
+    pub inline_asm: Vec<RustInlineAsm>,
+    pub panic_sites: Vec<PanicSite>,
+    pub error_propagations: Vec<ErrorPropagation>,
+    pub swallowed_errors: Vec<SwallowedError>,
+    pub await_points: Vec<AwaitPoint>,
+    pub spawned_tasks: Vec<SpawnSite>,
+    pub blocking_calls: Vec<BlockingCall>,
+    pub lock_acquisitions: Vec<LockAcquisition>,
+    pub guards_across_await: Vec<GuardAcrossAwait>,
+}
+
+/// Context a body is analyzed in.
+pub struct BodyContext<'a> {
+    pub src: &'a SourceText,
+    pub index: &'a CrateIndex,
+    /// Name of the function or method the body belongs to.
+    pub caller: &'a str,
+    /// `::`-separated path of the enclosing module.
+    pub module_path: &'a str,
+    /// Whether call sites should be collected.
+    pub collect_calls: bool,
+    /// Whether the whole body is an unsafe context (`unsafe fn`).
+    pub unsafe_fn: bool,
+    /// Whether the whole body is an async context (`async fn`).
+    pub async_fn: bool,
+    /// Name of the self type of methods.
+    pub self_type: Option<&'a str>,
+}
+
+struct BodyVisitor<'a> {
+    ctx: &'a BodyContext<'a>,
+    facts: BodyFacts,
+    /// Index of the innermost enclosing unsafe block.
+    current_block: Option<usize>,
+    /// Known types of parameters and annotated locals.
+    local_types: HashMap<String, RustType>,
+    /// Locals that hold raw pointers.
+    raw_pointers: BTreeSet<String>,
+    accessed: BTreeSet<String>,
+    referenced: BTreeSet<String>,
+    /// `#[cfg]` predicates of the enclosing statements, expressions and arms.
+    cfg: Vec<String>,
+    /// Whether the visitor is in an async fn, async block or async closure.
+    in_async: bool,
+    /// Start of the calls whose result is awaited.
+    awaited: BTreeSet<(usize, usize)>,
+    /// Lock guards bound to locals, by enclosing block.
+    guards: Vec<Vec<Guard>>,
+}
+
+/// A lock guard bound to a local.
+struct Guard {
+    name: String,
+    lock: String,
+    line: usize,
+    is_async: bool,
+}
+
+/// Analyzes `block`. `params` are the function's parameters with their
+/// types, used to infer argument types and raw pointer locals.
+pub fn analyze(ctx: &BodyContext, params: &[(String, RustType)], block: &syn::Block) -> BodyFacts {
+    let mut visitor = BodyVisitor {
+        ctx,
+        facts: BodyFacts::default(),
+        current_block: None,
+        local_types: HashMap::new(),
+        raw_pointers: BTreeSet::new(),
+        accessed: BTreeSet::new(),
+        referenced: BTreeSet::new(),
+        cfg: Vec::new(),
+        in_async: ctx.async_fn,
+        awaited: BTreeSet::new(),
+        guards: Vec::new(),
+    };
+    for (name, ty) in params {
+        if ty.contains_raw_pointers {
+            visitor.raw_pointers.insert(name.clone());
+            visitor.facts.raw_pointer_usage = true;
+        }
+        visitor.local_types.insert(name.clone(), ty.clone());
+    }
+    visitor.visit_block(block);
+    visitor.facts
+}
+
+/// Finds a `SAFETY:` comment on `line` or in the comment lines right above it.
+pub fn safety_comment(src: &SourceText, line: usize) -> Option<String> {
+    let mut comment = Vec::new();
+    if let Some(text) = src.line(line) {
+        if let Some(pos) = text.find("//") {
+            comment.push(text[pos + 2..].trim().to_owned());
+        }
+    }
+    let mut current = line;
+    while current > 1 {
+        current -= 1;
+        let text = src.line(current)?.trim();
+        let stripped = text
+            .strip_prefix("//")
+            .or_else(|| text.strip_prefix("/*"))
+            .or_else(|| text.strip_prefix('*'));
+        match stripped {
+            Some(rest) => comment.insert(0, rest.trim_end_matches("*/").trim().to_owned()),
+            None => break,
+        }
+    }
+    let joined = comment.join("\n");
+    let start = joined.to_ascii_uppercase().find("SAFETY:")?;
+    Some(joined[start + "SAFETY:".len()..].trim().to_owned())
+}
+
+impl BodyVisitor<'_> {
+    fn in_unsafe(&self) -> bool {
+        self.ctx.unsafe_fn || self.current_block.is_some()
+    }
+
+    fn add_reason(&mut self, reason: UnsafeReason) {
+        if let Some(block) = self.current_block {
+            let reasons = &mut self.facts.unsafe_blocks[block].reasons;
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+        }
+    }
+
+    fn panic_site<T: Spanned + ToTokens>(&mut self, kind: PanicKind, node: &T) {
+        self.facts.panic_sites.push(PanicSite {
+            kind,
+            line: node.span().start().line,
+            code: render::text(self.ctx.src, node),
+        });
+    }
+
+    /// Returns the line and callee of the call `expr` evaluates, looking
+    /// through `.await` and parentheses, the same way they are recorded in
+    /// the call sites.
+    fn call_target(&self, expr: &Expr) -> Option<(usize, String)> {
+        match expr {
+            Expr::Await(e) => self.call_target(&e.base),
+            Expr::Paren(e) => self.call_target(&e.expr),
+            Expr::Call(call) => match &*call.func {
+                Expr::Path(p) => Some((
+                    call.span().start().line,
+                    render::text(self.ctx.src, &p.path).replace(' ', ""),
+                )),
+                _ => None,
+            },
+            Expr::MethodCall(call) => {
+                Some((call.method.span().start().line, call.method.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the function passed to a spawn, as in `spawn_blocking(work)`,
+    /// or the first call made by a spawned future or closure.
+    fn spawn_target(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Path(p) => Some(render::text(self.ctx.src, &p.path).replace(' ', "")),
+            Expr::Async(e) => self.block_target(&e.block),
+            Expr::Block(e) => self.block_target(&e.block),
+            Expr::Closure(e) => self.spawn_target(&e.body),
+            Expr::Try(e) => self.spawn_target(&e.expr),
+            _ => self.call_target(expr).map(|(_, callee)| callee),
+        }
+    }
+
+    fn block_target(&self, block: &syn::Block) -> Option<String> {
+        block.stmts.iter().find_map(|stmt| match stmt {
+            syn::Stmt::Expr(e, _) => self.spawn_target(e),
+            syn::Stmt::Local(local) => local
+                .init
+                .as_ref()
+                .and_then(|init| self.spawn_target(&init.expr)),
+            _ => None,
+        })
+    }
+
+    fn spawn<T: ToTokens>(&mut self, kind: SpawnKind, site: (usize, String), arg: &Expr, node: &T) {
+        self.facts.spawned_tasks.push(SpawnSite {
+            kind,
+            line: site.0,
+            call: site.1,
+            target: self.spawn_target(arg),
+            code: render::text(self.ctx.src, node),
+        });
+    }
+
+    fn blocking<T: ToTokens>(&mut self, kind: BlockingKind, site: (usize, String), node: &T) {
+        self.facts.blocking_calls.push(BlockingCall {
+            kind,
+            line: site.0,
+            call: site.1,
+            is_async_context: self.in_async,
+            code: render::text(self.ctx.src, node),
+        });
+    }
+
+    fn is_awaited(&self, span: proc_macro2::Span) -> bool {
+        let start = span.start();
+        self.awaited.contains(&(start.line, start.column))
+    }
+
+    /// Returns the key of the lock `receiver` refers to, see
+    /// [`LockAcquisition::lock`].
+    fn lock_key(&self, receiver: &Expr) -> String {
+        let text = render::text(self.ctx.src, receiver).replace(' ', "");
+        let text = text.trim_start_matches(['&', '*']);
+        let (root, rest) = text.split_once('.').unwrap_or((text, ""));
+        let ty = match root {
+            "self" => self.ctx.self_type.map(str::to_owned),
+            _ => self
+                .local_types
+                .get(root)
+                .map(|ty| base_name(pointee(&ty.name)).to_owned()),
+        };
+        match ty {
+            Some(ty) if root == "self" && rest.is_empty() => ty,
+            Some(ty) if !rest.is_empty() && ty != "_" => format!("{}.{}", ty, rest),
+            // A static.
+            _ if root
+                .rsplit("::")
+                .next()
+                .is_some_and(|name| !name.chars().any(|c| c.is_ascii_lowercase())) =>
+            {
+                text.to_owned()
+            }
+            _ => format!("{}::{}", self.ctx.caller, text),
+        }
+    }
+
+    /// Returns the locks whose guards are live, without duplicates.
+    fn held_locks(&self) -> Vec<String> {
+        let mut held: Vec<String> = Vec::new();
+        for guard in self.guards.iter().flatten() {
+            if !held.contains(&guard.lock) {
+                held.push(guard.lock.clone());
+            }
+        }
+        held
+    }
+
+    /// Ends the guard bound to `name`, as for `drop(name)`.
+    fn release(&mut self, name: &str) {
+        for scope in self.guards.iter_mut().rev() {
+            if let Some(at) = scope.iter().rposition(|guard| guard.name == name) {
+                scope.remove(at);
+                return;
+            }
+        }
+    }
+
+    /// Runs `visit` with the visitor in or out of an async context.
+    fn with_async(&mut self, in_async: bool, visit: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.in_async, in_async);
+        visit(self);
+        self.in_async = outer;
+    }
+
+    /// Records `expr` as discarding the error of `call`, if `call` is a call.
+    fn swallowed<T: Spanned + ToTokens>(&mut self, kind: SwallowKind, call: &Expr, expr: &T) {
+        let Some((line, callee)) = self.call_target(call) else {
+            return;
+        };
+        self.facts.swallowed_errors.push(SwallowedError {
+            kind,
+            line,
+            call: Some(callee),
+            code: render::text(self.ctx.src, expr),
+        });
+    }
+
+    /// Returns true if dividing `left` by `right` cannot panic: the divisor
+    /// is a non-zero literal or the operands are floats.
+    fn is_safe_division(&self, left: &Expr, right: &Expr) -> bool {
+        if let Expr::Lit(lit) = right {
+            match &lit.lit {
+                Lit::Int(i) => return i.base10_parse::<u128>().is_ok_and(|v| v != 0),
+                Lit::Float(_) => return true,
+                _ => {}
+            }
+        }
+        [left, right]
+            .iter()
+            .any(|e| matches!(self.argument_type(e).name.as_str(), "f32" | "f64"))
+    }
+
+    fn reference_type(&mut self, name: String) {
+        if self.referenced.insert(name.clone()) {
+            self.facts.referenced_types.push(name);
+        }
+    }
+
+    /// Records the type constructed by the struct literal, constructor or
+    /// unit value at `path`: `Type` for `Type`, `Type(..)` and `Type { .. }`,
+    /// and `Enum` for its variants.
+    fn instantiate(&mut self, path: &syn::Path) {
+        if !self.ctx.collect_calls {
+            return;
+        }
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let is_type = |name: &str| {
+            name.starts_with(|c: char| c.is_ascii_uppercase())
+                && (name == "Self" || name.contains(|c: char| c.is_ascii_lowercase()))
+        };
+        let name = match segments.as_slice() {
+            [.., ty, variant]
+                if is_type(ty) && variant.starts_with(|c: char| c.is_ascii_uppercase()) =>
+            {
+                ty
+            }
+            [.., ty] if is_type(ty) => ty,
+            _ => return,
+        };
+        let name = match name.as_str() {
+            "Self" => match self.ctx.self_type {
+                Some(ty) => ty.to_owned(),
+                None => return,
+            },
+            // Variants of the prelude.
+            "Some" | "None" | "Ok" | "Err" => return,
+            name => name.to_owned(),
+        };
+        if !self.facts.instantiated_types.contains(&name) {
+            self.facts.instantiated_types.push(name);
+        }
+    }
+
+    fn access(&mut self, name: String) {
+        if self.accessed.insert(name.clone()) {
+            self.facts.accessed_variables.push(name);
+        }
+    }
+
+    fn argument_type(&self, arg: &Expr) -> RustType {
+        match arg {
+            Expr::Lit(lit) => RustType::new(match &lit.lit {
+                Lit::Str(_) => "&str",
+                Lit::ByteStr(_) => "&[u8]",
+                Lit::CStr(_) => "&CStr",
+                Lit::Byte(_) => "u8",
+                Lit::Char(_) => "char",
+                Lit::Bool(_) => "bool",
+                Lit::Int(i) if !i.suffix().is_empty() => i.suffix(),
+                Lit::Int(_) => "i32",
+                Lit::Float(f) if !f.suffix().is_empty() => f.suffix(),
+                Lit::Float(_) => "f64",
+                _ => "_",
+            }),
+            Expr::Path(p) if p.path.segments.len() == 1 => {
+                let name = p.path.segments[0].ident.to_string();
+                self.local_types
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_else(|| RustType::new("_"))
+            }
+            Expr::Reference(r) => {
+                let inner = self.argument_type(&r.expr);
+                if inner.name == "_" {
+                    return inner;
+                }
+                let mut ty = RustType::new(format!(
+                    "&{}{}",
+                    if r.mutability.is_some() { "mut " } else { "" },
+                    inner.name
+                ));
+                ty.is_reference = true;
+                ty.is_mutable = r.mutability.is_some();
+                ty
+            }
+            Expr::Cast(c) => render::rust_type(self.ctx.src, &c.ty),
+            _ => RustType::new("_"),
+        }
+    }
+
+    fn call_site(
+        &self,
+        span: proc_macro2::Span,
+        callee: String,
+        args: &Punctuated<Expr, Token![,]>,
+    ) -> CallSite {
+        let mut site = CallSite::new(span.start().line as u32);
+        site.callee = Some(callee);
+        site.caller_function = Some(self.ctx.caller.to_owned());
+        site.caller_module = Some(self.ctx.module_path.to_owned());
+        site.argument_types = args.iter().map(|a| self.argument_type(a)).collect();
+        site.is_unsafe_context = self.in_unsafe();
+        site.is_async_context = self.in_async;
+        site.held_locks = self.held_locks();
+        site
+    }
+
+    fn check_unsafe_callee(&mut self, name: &str, is_method: bool) {
+        if !is_method && self.ctx.index.foreign_fns.contains(name) {
+            self.facts.ffi_interactions = true;
+            if !self.facts.ffi_calls.iter().any(|c| c == name) {
+                self.facts.ffi_calls.push(name.to_owned());
+            }
+            self.add_reason(UnsafeReason::FfiCall);
+            return;
+        }
+        let known = if is_method {
+            STD_UNSAFE_METHODS.contains(&name)
+        } else {
+            STD_UNSAFE_FNS.contains(&name)
+        };
+        if known || (self.in_unsafe() && self.ctx.index.unsafe_fns.contains(name)) {
+            if !self.facts.unsafe_fn_calls.iter().any(|c| c == name) {
+                self.facts.unsafe_fn_calls.push(name.to_owned());
+            }
+            self.add_reason(UnsafeReason::Custom(format!(
+                "call to unsafe function `{}`",
+                name
+            )));
+        }
+    }
+
+    /// Returns true if `expr` evaluates to a raw pointer as far as can be
+    /// told syntactically.
+    fn is_raw_pointer(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Path(p) => p
+                .path
+                .get_ident()
+                .is_some_and(|i| self.raw_pointers.contains(&i.to_string())),
+            Expr::Cast(c) => matches!(*c.ty, Type::Ptr(_)),
+            Expr::Paren(p) => self.is_raw_pointer(&p.expr),
+            Expr::MethodCall(m) => {
+                let method = m.method.to_string();
+                matches!(
+                    method.as_str(),
+                    "as_ptr" | "as_mut_ptr" | "add" | "sub" | "offset" | "wrapping_add" | "cast"
+                ) && (method.starts_with("as_") || self.is_raw_pointer(&m.receiver))
+            }
+            Expr::Call(c) => match &*c.func {
+                Expr::Path(p) => p.path.segments.last().is_some_and(|s| {
+                    matches!(
+                        s.ident.to_string().as_str(),
+                        "null" | "null_mut" | "addr_of" | "addr_of_mut"
+                    )
+                }),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns true if `expr` is a union as far as can be told from the
+    /// types of parameters and locals.
+    fn is_union(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Paren(p) => self.is_union(&p.expr),
+            Expr::Unary(u) if matches!(u.op, syn::UnOp::Deref(_)) => self.is_union(&u.expr),
+            Expr::Path(p) => p.path.get_ident().is_some_and(|ident| {
+                let ty = if ident == "self" {
+                    "Self"
+                } else {
+                    match self.local_types.get(&ident.to_string()) {
+                        Some(ty) => base_name(&ty.name),
+                        None => return false,
+                    }
+                };
+                let ty = match ty {
+                    "Self" => self.ctx.self_type.unwrap_or(ty),
+                    ty => ty,
+                };
+                self.ctx.index.unions.contains(ty)
+            }),
+            _ => false,
+        }
+    }
+
+    fn declare(&mut self, pat: &Pat, ty: Option<&Type>, init: Option<&Expr>, line: usize) {
+        match pat {
+            Pat::Ident(ident) => {
+                let name = ident.ident.to_string();
+                let mut decl = RustVariableDeclaration::new(name.clone(), line);
+                decl.is_mut = ident.mutability.is_some();
+                decl.type_info = ty.map(|t| render::rust_type(self.ctx.src, t));
+                decl.initializer = init.map(|e| render::text(self.ctx.src, e));
+                let is_raw = ty.is_some_and(render::contains_raw_pointer)
+                    || init.is_some_and(|e| self.is_raw_pointer(e));
+                if is_raw {
+                    self.raw_pointers.insert(name.clone());
+                    self.facts.raw_pointer_usage = true;
+                }
+                if let Some(ty) = &decl.type_info {
+                    self.local_types.insert(name, ty.clone());
+                } else if let Some(Expr::Struct(init)) = init {
+                    // `let u = MyUnion { .. };`
+                    let ty = init.path.segments.last().map(|s| s.ident.to_string());
+                    if let Some(ty) = ty.filter(|ty| self.ctx.index.unions.contains(ty)) {
+                        self.local_types.insert(name, RustType::new(ty));
+                    }
+                }
+                self.facts.variable_declarations.push(decl);
+                if let Some((_, sub)) = &ident.subpat {
+                    self.declare(sub, None, None, line);
+                }
+            }
+            Pat::Type(pt) => self.declare(&pt.pat, Some(&pt.ty), init, line),
+            Pat::Reference(r) => self.declare(&r.pat, None, None, line),
+            Pat::Tuple(t) => t
+                .elems
+                .iter()
+                .for_each(|p| self.declare(p, None, None, line)),
+            Pat::TupleStruct(t) => t
+                .elems
+                .iter()
+                .for_each(|p| self.declare(p, None, None, line)),
+            Pat::Struct(s) => s
+                .fields
+                .iter()
+                .for_each(|f| self.declare(&f.pat, None, None, line)),
+            Pat::Slice(s) => s
+                .elems
+                .iter()
+                .for_each(|p| self.declare(p, None, None, line)),
+            Pat::Or(o) => {
+                if let Some(first) = o.cases.first() {
+                    self.declare(first, None, None, line);
+                }
+            }
+            Pat::Paren(p) => self.declare(&p.pat, ty, init, line),
+            _ => {}
+        }
+    }
+
+    /// Visits the arguments of a function-like macro when they parse as a
+    /// comma-separated expression list, as for `println!` or `vec!`.
+    /// Visits a node with the `#[cfg]` predicates among `attrs` in effect.
+    fn with_cfg(&mut self, attrs: &[syn::Attribute], visit: impl FnOnce(&mut Self)) {
+        let depth = self.cfg.len();
+        self.cfg.extend(render::cfg_predicates(self.ctx.src, attrs));
+        visit(self);
+        self.cfg.truncate(depth);
+    }
+
+    fn visit_macro_args(&mut self, mac: &syn::Macro) {
+        if let Some(kind) = PANIC_MACROS
+            .iter()
+            .find(|(name, _)| mac.path.segments.last().is_some_and(|s| s.ident == name))
+            .map(|(_, kind)| *kind)
+        {
+            self.panic_site(kind, mac);
+        }
+        if asm::is_asm_macro(mac) {
+            self.add_reason(UnsafeReason::InlineAssembly);
+            let mut asm = asm::inline_asm(self.ctx.src, mac);
+            asm.cfg = self.cfg.clone();
+            self.facts.inline_asm.push(asm);
+            return;
+        }
+        if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        } else if let Ok(block) = mac.parse_body_with(syn::Block::parse_within) {
+            for stmt in &block {
+                self.visit_stmt(stmt);
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for BodyVisitor<'_> {
+    // Items nested in a body are analyzed on their own, if at all.
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+
+    fn visit_stmt(&mut self, stmt: &'ast syn::Stmt) {
+        let attrs = match stmt {
+            syn::Stmt::Local(local) => &local.attrs[..],
+            syn::Stmt::Macro(mac) => &mac.attrs,
+            syn::Stmt::Expr(expr, _) => expr_attrs(expr),
+            syn::Stmt::Item(_) => &[],
+        };
+        self.with_cfg(attrs, |this| visit::visit_stmt(this, stmt));
+    }
+
+    fn visit_arm(&mut self, arm: &'ast syn::Arm) {
+        self.with_cfg(&arm.attrs, |this| visit::visit_arm(this, arm));
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        let init = local.init.as_ref().map(|i| &*i.expr);
+        if let (Pat::Wild(_), Some(init)) = (&local.pat, init) {
+            self.swallowed(SwallowKind::LetUnderscore, init, local);
+        }
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        self.declare(&local.pat, None, init, local.span().start().line);
+        self.visit_pat(&local.pat);
+        let mut pat = &local.pat;
+        if let Pat::Type(pt) = pat {
+            pat = &pt.pat;
+        }
+        if let (Pat::Ident(ident), Some((call, is_async))) = (pat, init.and_then(guard_call)) {
+            let name = ident.ident.to_string();
+            let lock = self.lock_key(&call.receiver);
+            let line = call.method.span().start().line;
+            if let Some(acquisition) = self
+                .facts
+                .lock_acquisitions
+                .iter_mut()
+                .rev()
+                .find(|a| a.line == line && a.lock == lock)
+            {
+                acquisition.guard = Some(name.clone());
+            }
+            if let Some(scope) = self.guards.last_mut() {
+                scope.push(Guard {
+                    name,
+                    lock,
+                    line,
+                    is_async,
+                });
+            }
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.guards.push(Vec::new());
+        visit::visit_block(self, block);
+        self.guards.pop();
+    }
+
+    fn visit_expr_unsafe(&mut self, expr: &'ast syn::ExprUnsafe) {
+        let (start, end) = render::lines(expr);
+        let mut block = UnsafeBlock::new(start, end);
+        block.explanation = safety_comment(self.ctx.src, start);
+        block.containing_function = Some(self.ctx.caller.to_owned());
+        self.facts.unsafe_blocks.push(block);
+        let outer = self
+            .current_block
+            .replace(self.facts.unsafe_blocks.len() - 1);
+        visit::visit_expr_unsafe(self, expr);
+        self.current_block = outer;
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        let mut in_async = self.in_async;
+        if let Expr::Path(p) = &*call.func {
+            let callee = render::text(self.ctx.src, &p.path).replace(' ', "");
+            if let Some(last) = p.path.segments.last() {
+                self.check_unsafe_callee(&last.ident.to_string(), false);
+            }
+            if let Some(name) = dropped_local(p, &call.args) {
+                self.release(&name);
+            }
+            let line = call.span().start().line;
+            let spawn = SPAWN_FNS
+                .iter()
+                .find(|(name, _)| p.path.segments.last().is_some_and(|s| s.ident == name))
+                .filter(|_| !p.path.segments.iter().any(|s| s.ident == "thread"));
+            match (spawn, call.args.first()) {
+                (Some(&(_, kind)), Some(arg)) => {
+                    self.spawn(kind, (line, callee.clone()), arg, call);
+                    // The closure of `spawn_blocking` runs on a thread of its own.
+                    in_async &= kind != SpawnKind::SpawnBlocking;
+                }
+                _ => {
+                    if let Some(kind) =
+                        blocking_fn(&callee).filter(|_| !self.is_awaited(call.span()))
+                    {
+                        self.blocking(kind, (line, callee.clone()), call);
+                    }
+                }
+            }
+            if self.ctx.collect_calls {
+                let site = self.call_site(call.span(), callee, &call.args);
+                self.facts.call_sites.push(site);
+            }
+        }
+        self.with_async(in_async, |this| visit::visit_expr_call(this, call));
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        let panics = match (method.as_str(), call.args.len()) {
+            ("unwrap" | "unwrap_err", 0) => Some(PanicKind::Unwrap),
+            ("expect" | "expect_err", 1) => Some(PanicKind::Expect),
+            ("borrow_mut", 0) => Some(PanicKind::BorrowMut),
+            _ => None,
+        };
+        if let Some(kind) = panics {
+            self.panic_site(kind, call);
+        }
+        match (method.as_str(), call.args.len()) {
+            ("ok", 0) => self.swallowed(SwallowKind::Ok, &call.receiver, call),
+            ("unwrap_or_default", 0) => {
+                self.swallowed(SwallowKind::UnwrapOrDefault, &call.receiver, call)
+            }
+            _ => {}
+        }
+        self.check_unsafe_callee(&method, true);
+        let mut in_async = self.in_async;
+        let line = call.method.span().start().line;
+        if is_lock_call(call) {
+            self.facts.lock_acquisitions.push(LockAcquisition {
+                lock: self.lock_key(&call.receiver),
+                method: method.clone(),
+                line,
+                guard: None,
+                is_async: self.is_awaited(call.method.span()),
+                held: self.held_locks(),
+            });
+        }
+        let spawn = SPAWN_FNS.iter().find(|(name, _)| method == *name);
+        match (spawn, method.as_str(), call.args.len()) {
+            (Some(&(_, kind)), _, 1) => {
+                self.spawn(kind, (line, method.clone()), &call.args[0], call);
+                in_async &= kind != SpawnKind::SpawnBlocking;
+            }
+            (_, "lock" | "read" | "write", 0) if !self.is_awaited(call.method.span()) => {
+                self.blocking(BlockingKind::Lock, (line, method.clone()), call)
+            }
+            (_, "block_on", _) => {
+                self.blocking(BlockingKind::BlockOn, (line, method.clone()), call)
+            }
+            _ => {}
+        }
+        if self.ctx.collect_calls {
+            let mut site = self.call_site(call.method.span(), method, &call.args);
+            site.is_method_call = true;
+            site.receiver = Some(render::text(self.ctx.src, &*call.receiver));
+            self.facts.call_sites.push(site);
+        }
+        self.with_async(in_async, |this| visit::visit_expr_method_call(this, call));
+    }
+
+    fn visit_expr_unary(&mut self, expr: &'ast syn::ExprUnary) {
+        if matches!(expr.op, syn::UnOp::Deref(_)) && self.is_raw_pointer(&expr.expr) {
+            self.facts.raw_pointer_usage = true;
+            self.add_reason(UnsafeReason::RawPointerDeref);
+        }
+        visit::visit_expr_unary(self, expr);
+    }
+
+    fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+        let segments = &expr.path.segments;
+        if segments.len() == 1 && expr.qself.is_none() {
+            let name = segments[0].ident.to_string();
+            if self.ctx.index.static_muts.contains(&name) {
+                if !self.facts.mutable_statics.contains(&name) {
+                    self.facts.mutable_statics.push(name.clone());
+                }
+                self.add_reason(UnsafeReason::MutableStatic);
+            }
+            self.access(name);
+        }
+        if expr.qself.is_none() {
+            self.instantiate(&expr.path);
+        }
+        for segment in segments.iter().take(segments.len().saturating_sub(1)) {
+            let name = segment.ident.to_string();
+            if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                self.reference_type(name);
+            }
+        }
+        visit::visit_expr_path(self, expr);
+    }
+
+    fn visit_expr_struct(&mut self, expr: &'ast syn::ExprStruct) {
+        if let Some(last) = expr.path.segments.last() {
+            self.reference_type(last.ident.to_string());
+        }
+        if expr.qself.is_none() {
+            self.instantiate(&expr.path);
+        }
+        visit::visit_expr_struct(self, expr);
+    }
+
+    fn visit_expr_assign(&mut self, expr: &'ast syn::ExprAssign) {
+        if let Expr::Infer(_) = &*expr.left {
+            self.swallowed(SwallowKind::LetUnderscore, &expr.right, expr);
+        }
+        // Writing a union field is safe; only reads are unsafe.
+        match &*expr.left {
+            Expr::Field(field) if self.is_union(&field.base) => self.visit_expr(&field.base),
+            left => self.visit_expr(left),
+        }
+        self.visit_expr(&expr.right);
+    }
+
+    fn visit_pat_struct(&mut self, pat: &'ast syn::PatStruct) {
+        let name = pat.path.segments.last().map(|s| s.ident.to_string());
+        if name.is_some_and(|name| self.ctx.index.unions.contains(&name)) {
+            self.add_reason(UnsafeReason::UnionFieldAccess);
+        }
+        visit::visit_pat_struct(self, pat);
+    }
+
+    fn visit_expr_field(&mut self, expr: &'ast syn::ExprField) {
+        if self.is_union(&expr.base) {
+            self.add_reason(UnsafeReason::UnionFieldAccess);
+        }
+        if let Expr::Path(base) = &*expr.base {
+            if base.path.is_ident("self") {
+                if let syn::Member::Named(field) = &expr.member {
+                    self.access(format!("self.{}", field));
+                }
+            }
+        }
+        visit::visit_expr_field(self, expr);
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        if let Some(name) = render::base_type_name(ty) {
+            self.reference_type(name);
+        }
+        if render::contains_raw_pointer(ty) {
+            self.facts.raw_pointer_usage = true;
+        }
+        visit::visit_type(self, ty);
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        if matches!(
+            expr.op,
+            syn::BinOp::Div(_)
+                | syn::BinOp::Rem(_)
+                | syn::BinOp::DivAssign(_)
+                | syn::BinOp::RemAssign(_)
+        ) && !self.is_safe_division(&expr.left, &expr.right)
+        {
+            self.panic_site(PanicKind::Division, expr);
+        }
+        visit::visit_expr_binary(self, expr);
+    }
+
+    fn visit_expr_index(&mut self, expr: &'ast syn::ExprIndex) {
+        self.panic_site(PanicKind::Index, expr);
+        visit::visit_expr_index(self, expr);
+    }
+
+    fn visit_expr_try(&mut self, expr: &'ast syn::ExprTry) {
+        let mut operand = &*expr.expr;
+        let mut adapter = None;
+        loop {
+            match operand {
+                Expr::Await(e) => operand = &e.base,
+                Expr::Paren(e) => operand = &e.expr,
+                Expr::MethodCall(call)
+                    if adapter.is_none()
+                        && ERROR_ADAPTERS.iter().any(|name| call.method == name) =>
+                {
+                    adapter = Some(call.method.to_string());
+                    operand = &call.receiver;
+                }
+                _ => break,
+            }
+        }
+        let target = self.call_target(operand);
+        self.facts.error_propagations.push(ErrorPropagation {
+            line: target
+                .as_ref()
+                .map_or(expr.question_token.span.start().line, |(line, _)| *line),
+            call: target.map(|(_, callee)| callee),
+            adapter,
+            code: render::text(self.ctx.src, &*expr.expr),
+        });
+        visit::visit_expr_try(self, expr);
+    }
+
+    fn visit_expr_async(&mut self, expr: &'ast syn::ExprAsync) {
+        // The future runs later, without the guards of the enclosing code.
+        let outer = std::mem::take(&mut self.guards);
+        self.with_async(true, |this| visit::visit_expr_async(this, expr));
+        self.guards = outer;
+    }
+
+    fn visit_expr_closure(&mut self, expr: &'ast syn::ExprClosure) {
+        if expr.asyncness.is_some() {
+            let outer = std::mem::take(&mut self.guards);
+            self.with_async(true, |this| visit::visit_expr_closure(this, expr));
+            self.guards = outer;
+        } else {
+            visit::visit_expr_closure(self, expr);
+        }
+    }
+
+    fn visit_expr_await(&mut self, expr: &'ast syn::ExprAwait) {
+        let mut base = &*expr.base;
+        while let Expr::Paren(e) = base {
+            base = &e.expr;
+        }
+        let start = match base {
+            Expr::Call(call) => Some(call.span().start()),
+            Expr::MethodCall(call) => Some(call.method.span().start()),
+            _ => None,
+        };
+        if let Some(start) = start {
+            self.awaited.insert((start.line, start.column));
+        }
+        let await_line = expr.await_token.span.start().line;
+        for guard in self.guards.iter().flatten().filter(|g| !g.is_async) {
+            self.facts.guards_across_await.push(GuardAcrossAwait {
+                lock: guard.lock.clone(),
+                guard: guard.name.clone(),
+                lock_line: guard.line,
+                await_line,
+            });
+        }
+        self.facts.await_points.push(AwaitPoint {
+            line: await_line,
+            call: self.call_target(base).map(|(_, callee)| callee),
+            code: render::text(self.ctx.src, base),
+        });
+        visit::visit_expr_await(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        self.visit_macro_args(mac);
+    }
+}
+
+/// Returns true for `lock()`, `read()` and `write()` without arguments.
+fn is_lock_call(call: &syn::ExprMethodCall) -> bool {
+    call.args.is_empty() && matches!(call.method.to_string().as_str(), "lock" | "read" | "write")
+}
+
+/// Returns the lock call whose guard `init` evaluates to, looking through
+/// `unwrap`, `expect` and `?`, and whether the call is awaited.
+fn guard_call(init: &Expr) -> Option<(&syn::ExprMethodCall, bool)> {
+    match init {
+        Expr::MethodCall(call) if is_lock_call(call) => Some((call, false)),
+        Expr::MethodCall(call)
+            if matches!(call.method.to_string().as_str(), "unwrap" | "expect") =>
+        {
+            guard_call(&call.receiver)
+        }
+        Expr::Try(e) => guard_call(&e.expr),
+        Expr::Paren(e) => guard_call(&e.expr),
+        Expr::Await(e) => match &*e.base {
+            Expr::MethodCall(call) if is_lock_call(call) => Some((call, true)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the local passed to a call of `drop` with `args`.
+fn dropped_local(func: &syn::ExprPath, args: &Punctuated<Expr, Token![,]>) -> Option<String> {
+    let is_drop = func.path.segments.last().is_some_and(|s| s.ident == "drop");
+    match (is_drop, args.first(), args.len()) {
+        (true, Some(Expr::Path(arg)), 1) => arg.path.get_ident().map(ToString::to_string),
+        _ => None,
+    }
+}
+
+/// Returns `T` for `Arc<T>`, `Rc<T>` and `Box<T>`, and `ty` otherwise.
+fn pointee(ty: &str) -> &str {
+    if let Some((wrapper, inner)) = ty.split_once('<') {
+        if matches!(base_name(wrapper), "Arc" | "Rc" | "Box") {
+            return inner.strip_suffix('>').unwrap_or(inner).trim();
+        }
+    }
+    ty
+}
+
+/// Classifies a call of the function at `path` that blocks the current
+/// thread when it is not awaited.
+fn blocking_fn(path: &str) -> Option<BlockingKind> {
+    let segments: Vec<&str> = path.split("::").collect();
+    let (&last, module) = segments.split_last()?;
+    if module
+        .iter()
+        .any(|s| matches!(*s, "tokio" | "async_std" | "smol"))
+    {
+        return None;
+    }
+    match (module, last) {
+        (["std", "fs", ..] | ["fs", ..], _) => Some(BlockingKind::Fs),
+        ([.., "File"], "open" | "create") => Some(BlockingKind::Fs),
+        (_, "sleep") => Some(BlockingKind::Sleep),
+        (["reqwest", "blocking", ..] | ["ureq", ..], _) => Some(BlockingKind::Http),
+        (_, "block_on") => Some(BlockingKind::BlockOn),
+        _ => None,
+    }
+}
+
+/// Returns the outer attributes of the statement-like expressions that are
+/// commonly gated with `#[cfg]`.
+fn expr_attrs(expr: &Expr) -> &[syn::Attribute] {
+    match expr {
+        Expr::Block(e) => &e.attrs,
+        Expr::Unsafe(e) => &e.attrs,
+        Expr::Macro(e) => &e.attrs,
+        Expr::Call(e) => &e.attrs,
+        Expr::MethodCall(e) => &e.attrs,
+        Expr::If(e) => &e.attrs,
+        Expr::Match(e) => &e.attrs,
+        Expr::Loop(e) => &e.attrs,
+        Expr::While(e) => &e.attrs,
+        Expr::ForLoop(e) => &e.attrs,
+        Expr::Assign(e) => &e.attrs,
+        _ => &[],
+    }
+}
+
+/// Returns the name of the type `ty` refers to, without references,
+/// pointers, generic arguments and path.
+fn base_name(ty: &str) -> &str {
+    let mut ty = ty.trim();
+    loop {
+        if let Some(rest) = ty.strip_prefix('\'') {
+            // The lifetime of a reference.
+            ty = rest
+                .split_once(' ')
+                .map_or("", |(_, rest)| rest)
+                .trim_start();
+            continue;
+        }
+        let stripped = ty
+            .strip_prefix('&')
+            .or_else(|| ty.strip_prefix("*const"))
+            .or_else(|| ty.strip_prefix("*mut"))
+            .or_else(|| ty.strip_prefix("mut "))
+            .map(str::trim_start);
+        match stripped {
+            Some(rest) => ty = rest,
+            None => break,
+        }
+    }
+    let ty = ty.split('<').next().unwrap_or(ty);
+    ty.rsplit("::").next().unwrap_or(ty).trim()
+}
@@ -0,0 +1,131 @@
+//! On-disk cache of per-file analysis results.
+//!
+//! Every analyzed file is stored as one JSON document named after a hash of
+//! everything its analysis depends on: the analyzer version, the file's
+//! content and path, its module path, the enabled cfg options and the
+//! analysis level. A changed file therefore gets a new key and stale entries
+//! are simply never read again; [`AnalysisCache::clear`] removes them.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::file::FileAnalysis;
+
+/// Bumped whenever the layout of cached entries changes.
+const CACHE_FORMAT: u32 = 15;
+
+/// Builds the cache key of a file from the inputs of its analysis.
+#[derive(Debug, Clone)]
+pub struct CacheKey(blake3::Hasher);
+
+impl CacheKey {
+    pub fn new() -> Self {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&CACHE_FORMAT.to_le_bytes());
+        let mut key = Self(hasher);
+        key.add(env!("CARGO_PKG_VERSION"));
+        key
+    }
+
+    /// Adds one input. Inputs are length-prefixed, so `("ab", "c")` and
+    /// `("a", "bc")` give different keys.
+    pub fn add(&mut self, part: &str) -> &mut Self {
+        self.0.update(&(part.len() as u64).to_le_bytes());
+        self.0.update(part.as_bytes());
+        self
+    }
+
+    pub fn finish(&self) -> String {
+        self.0.finalize().to_hex().to_string()
+    }
+}
+
+impl Default for CacheKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the hex digest of `text`, e.g. to fingerprint a serialized value.
+pub fn fingerprint(text: &str) -> String {
+    blake3::hash(text.as_bytes()).to_hex().to_string()
+}
+
+/// Whether `name` is the name of a shard directory: the first two hex digits
+/// of the keys stored in it.
+fn is_shard(name: &str) -> bool {
+    name.len() == 2 && name.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// A directory of cached [`FileAnalysis`] entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysisCache {
+    dir: PathBuf,
+}
+
+impl AnalysisCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Removes every cached entry.
+    ///
+    /// Only what the cache writes is removed: the `.json` entries and
+    /// leftover temporary files in the two-hex-digit shard directories, and
+    /// the shards once they are empty. Anything else in the directory, which
+    /// may well be a directory the user did not mean to hand over, is kept.
+    pub fn clear(&self) -> io::Result<()> {
+        let shards = match fs::read_dir(&self.dir) {
+            Ok(shards) => shards,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for shard in shards {
+            let shard = shard?;
+            if !shard.file_type()?.is_dir() || !is_shard(&shard.file_name().to_string_lossy()) {
+                continue;
+            }
+            for entry in fs::read_dir(shard.path())? {
+                let entry = entry?;
+                let path = entry.path();
+                let extension = path.extension().unwrap_or_default().to_string_lossy();
+                if entry.file_type()?.is_file()
+                    && (extension == "json" || extension.starts_with("tmp"))
+                {
+                    fs::remove_file(&path)?;
+                }
+            }
+            // A shard that still holds other files is kept along with them.
+            let _ = fs::remove_dir(shard.path());
+        }
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(&key[..2]).join(format!("{}.json", &key[2..]))
+    }
+
+    /// Returns the entry stored under `key`. Missing, unreadable and corrupt
+    /// entries are all treated as a miss.
+    pub fn load(&self, key: &str) -> Option<FileAnalysis> {
+        let text = fs::read_to_string(self.entry_path(key)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Stores `entry` under `key`. The entry is written to a temporary file
+    /// first, so concurrent readers never see a partial entry.
+    pub fn store(&self, key: &str, entry: &FileAnalysis) -> io::Result<()> {
+        let path = self.entry_path(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(entry)?)?;
+        fs::rename(&tmp, &path)
+    }
+}
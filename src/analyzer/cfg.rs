@@ -0,0 +1,90 @@
+//! Evaluation of `#[cfg(...)]` attributes against the configured feature set.
+//!
+//! Only `feature = "..."` and `test` can be decided; every other predicate
+//! (`target_os`, `unix`, ...) is unknown. Items whose predicate is unknown are
+//! kept, so that code for every target ends up in the symbol table. The
+//! symbol table holds one item per name and kind, though: when variants of
+//! an item for different targets share a name, the first one is kept and the
+//! others are listed in the module's `shadowed_items`.
+
+use std::collections::BTreeSet;
+
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, ExprLit, Lit, Meta, Token};
+
+/// The configuration that `#[cfg]` predicates are evaluated against.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CfgSet {
+    pub features: BTreeSet<String>,
+    /// Whether `cfg(test)` holds.
+    pub test: bool,
+}
+
+impl CfgSet {
+    pub fn new(features: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            features: features.into_iter().collect(),
+            test: false,
+        }
+    }
+
+    /// Describes the configuration, e.g. for cache keys.
+    pub fn describe(&self) -> String {
+        let features: Vec<&str> = self.features.iter().map(String::as_str).collect();
+        format!("features={};test={}", features.join(","), self.test)
+    }
+
+    /// Returns `false` only if one of the `#[cfg]` attributes is known to be
+    /// disabled under this configuration.
+    pub fn is_enabled(&self, attrs: &[Attribute]) -> bool {
+        attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+            .all(|attr| match attr.parse_args::<Meta>() {
+                Ok(meta) => self.eval(&meta) != Some(false),
+                Err(_) => true,
+            })
+    }
+
+    /// Evaluates a predicate; `None` means the result is unknown.
+    pub fn eval(&self, meta: &Meta) -> Option<bool> {
+        match meta {
+            Meta::Path(path) if path.is_ident("test") => Some(self.test),
+            Meta::Path(_) => None,
+            Meta::NameValue(nv) if nv.path.is_ident("feature") => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(self.features.contains(&s.value())),
+                _ => None,
+            },
+            Meta::NameValue(_) => None,
+            Meta::List(list) => {
+                let args = list
+                    .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                    .ok()?;
+                let values: Vec<Option<bool>> = args.iter().map(|m| self.eval(m)).collect();
+                if list.path.is_ident("not") {
+                    values.first().copied().flatten().map(|v| !v)
+                } else if list.path.is_ident("all") {
+                    if values.contains(&Some(false)) {
+                        Some(false)
+                    } else if values.iter().all(|v| *v == Some(true)) {
+                        Some(true)
+                    } else {
+                        None
+                    }
+                } else if list.path.is_ident("any") {
+                    if values.contains(&Some(true)) {
+                        Some(true)
+                    } else if values.iter().all(|v| *v == Some(false)) {
+                        Some(false)
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Dependence analysis of function bodies, from analysis level 3 on.
+//!
+//! Reaching definitions are computed over the control-flow graph with the
+//! usual gen/kill equations, iterated to a fixpoint. Every use of a local
+//! variable then depends on the definitions of that variable that reach it,
+//! and so do the definitions computed from it and the arguments of calls.
+//! A mutation, e.g. `v.push(x)` on a `mut` local, adds a definition without
+//! killing the earlier ones.
+//!
+//! Control dependences come from the post-dominator tree: a block depends on
+//! an edge `A -> S` if it post-dominates `S` but does not strictly
+//! post-dominate `A`. Blocks that cannot reach the exit, such as the body of
+//! a `loop` without a `break`, have no post-dominator and no control
+//! dependences.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::flow::{Access, LoweredBody};
+use crate::entities::dependence::{
+    ControlDependence, DataDependence, Definition, DefinitionKind, ProgramDependenceGraph,
+};
+use crate::entities::flow::{ControlFlowGraph, EXIT_BLOCK};
+
+/// Builds the program dependence graph of a lowered body.
+pub fn analyze(body: &LoweredBody) -> ProgramDependenceGraph {
+    let mut definitions = Vec::new();
+    let mut inputs = Vec::new();
+    let events = number_definitions(body, &mut definitions, &mut inputs);
+    let mut by_variable: HashMap<&str, Vec<usize>> = HashMap::new();
+    for def in &definitions {
+        by_variable.entry(&def.variable).or_default().push(def.id);
+    }
+    let reaching = |state: &BTreeSet<usize>, variables: &[String]| -> Vec<usize> {
+        let mut found: Vec<usize> = variables
+            .iter()
+            .filter_map(|variable| by_variable.get(variable.as_str()))
+            .flatten()
+            .copied()
+            .filter(|def| state.contains(def))
+            .collect();
+        found.sort_unstable();
+        found
+    };
+    let transfer = |state: &mut BTreeSet<usize>, id: usize| {
+        let def = &definitions[id];
+        if def.kind != DefinitionKind::Mutation {
+            for other in &by_variable[def.variable.as_str()] {
+                state.remove(other);
+            }
+        }
+        state.insert(id);
+    };
+
+    let blocks = body.graph.blocks.len();
+    let mut preds = vec![Vec::new(); blocks];
+    for edge in &body.graph.edges {
+        preds[edge.to].push(edge.from);
+    }
+    let mut reach_in = vec![BTreeSet::new(); blocks];
+    let mut reach_out = vec![BTreeSet::new(); blocks];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for block in 0..blocks {
+            let mut state: BTreeSet<usize> = preds[block]
+                .iter()
+                .flat_map(|&pred| reach_out[pred].iter().copied())
+                .collect();
+            reach_in[block].clone_from(&state);
+            for event in &events[block] {
+                if let Event::Def(id) = event {
+                    transfer(&mut state, *id);
+                }
+            }
+            if state != reach_out[block] {
+                reach_out[block] = state;
+                changed = true;
+            }
+        }
+    }
+
+    let mut data_dependences = Vec::new();
+    let mut def_inputs = vec![Vec::new(); definitions.len()];
+    let mut calls = body.calls.clone();
+    let mut seen = HashSet::new();
+    for (block, events) in events.iter().enumerate() {
+        let mut state = reach_in[block].clone();
+        for event in events {
+            match event {
+                Event::Def(id) => {
+                    def_inputs[*id] = reaching(&state, &inputs[*id]);
+                    transfer(&mut state, *id);
+                }
+                Event::Call(index) => {
+                    let call = &mut calls[*index];
+                    for argument in call.receiver.iter_mut().chain(&mut call.arguments) {
+                        argument.definitions = reaching(&state, &argument.variables);
+                    }
+                }
+                Event::Use(variable, line) => {
+                    let Some(defs) = by_variable.get(variable.as_str()) else {
+                        continue;
+                    };
+                    for &def in defs.iter().filter(|def| state.contains(def)) {
+                        if seen.insert((def, block, *line)) {
+                            data_dependences.push(DataDependence {
+                                definition: def,
+                                variable: variable.clone(),
+                                block,
+                                line: *line,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for (def, inputs) in definitions.iter_mut().zip(def_inputs) {
+        def.inputs = inputs;
+    }
+    ProgramDependenceGraph {
+        control_dependences: control_dependences(&body.graph),
+        calls,
+        data_dependences,
+        definitions,
+    }
+}
+
+/// An access of a block, with definitions replaced by their ids.
+enum Event {
+    Def(usize),
+    Use(String, usize),
+    Call(usize),
+}
+
+/// Numbers the definitions of `body`, with the variables each is computed
+/// from in `inputs`, and returns the events of every block.
+fn number_definitions(
+    body: &LoweredBody,
+    definitions: &mut Vec<Definition>,
+    inputs: &mut Vec<Vec<String>>,
+) -> Vec<Vec<Event>> {
+    let mut events: Vec<Vec<Event>> = Vec::with_capacity(body.accesses.len());
+    for (block, accesses) in body.accesses.iter().enumerate() {
+        let mut block_events = Vec::new();
+        for access in accesses {
+            block_events.push(match access {
+                Access::Def(def) => {
+                    let id = definitions.len();
+                    definitions.push(Definition {
+                        id,
+                        variable: def.variable.clone(),
+                        kind: def.kind,
+                        block,
+                        line: def.line,
+                        parameter: def.parameter,
+                        inputs: Vec::new(),
+                        calls: def.calls.clone(),
+                    });
+                    inputs.push(def.inputs.clone());
+                    Event::Def(id)
+                }
+                Access::Use { variable, line } => Event::Use(variable.clone(), *line),
+                Access::Call(index) => Event::Call(*index),
+            });
+        }
+        events.push(block_events);
+    }
+    events
+}
+
+/// Computes the control dependences of the blocks of `graph`.
+fn control_dependences(graph: &ControlFlowGraph) -> Vec<ControlDependence> {
+    let ipdom = post_dominators(graph);
+    let mut seen = HashSet::new();
+    let mut dependences = Vec::new();
+    for edge in &graph.edges {
+        let Some(stop) = ipdom[edge.from] else {
+            continue;
+        };
+        let mut runner = edge.to;
+        while runner != stop {
+            if seen.insert((runner, edge.from, edge.kind.as_str(), edge.label.clone())) {
+                dependences.push(ControlDependence {
+                    block: runner,
+                    controller: edge.from,
+                    kind: edge.kind,
+                    label: edge.label.clone(),
+                });
+            }
+            match ipdom[runner] {
+                Some(next) if next != runner => runner = next,
+                _ => break,
+            }
+        }
+    }
+    dependences.sort_by_key(|dep| (dep.controller, dep.block));
+    dependences
+}
+
+/// Returns the immediate post-dominator of every block, with the algorithm
+/// of Cooper, Harvey and Kennedy on the reversed graph. The exit is its own
+/// post-dominator; blocks that cannot reach it have none.
+fn post_dominators(graph: &ControlFlowGraph) -> Vec<Option<usize>> {
+    let blocks = graph.blocks.len();
+    let mut succs = vec![Vec::new(); blocks];
+    let mut preds = vec![Vec::new(); blocks];
+    for edge in &graph.edges {
+        succs[edge.from].push(edge.to);
+        preds[edge.to].push(edge.from);
+    }
+
+    // Postorder of the reversed graph, from the exit.
+    let mut order = Vec::with_capacity(blocks);
+    let mut visited = vec![false; blocks];
+    let mut stack = vec![(EXIT_BLOCK, 0)];
+    visited[EXIT_BLOCK] = true;
+    while let Some((block, next)) = stack.last_mut() {
+        match preds[*block].get(*next) {
+            Some(&pred) => {
+                *next += 1;
+                if !visited[pred] {
+                    visited[pred] = true;
+                    stack.push((pred, 0));
+                }
+            }
+            None => {
+                order.push(*block);
+                stack.pop();
+            }
+        }
+    }
+    let mut rank = vec![0; blocks];
+    for (position, &block) in order.iter().enumerate() {
+        rank[block] = position;
+    }
+
+    let mut ipdom: Vec<Option<usize>> = vec![None; blocks];
+    ipdom[EXIT_BLOCK] = Some(EXIT_BLOCK);
+    let intersect = |ipdom: &[Option<usize>], mut a: usize, mut b: usize| {
+        while a != b {
+            while rank[a] < rank[b] {
+                a = ipdom[a].expect("processed block");
+            }
+            while rank[b] < rank[a] {
+                b = ipdom[b].expect("processed block");
+            }
+        }
+        a
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &block in order.iter().rev().filter(|&&b| b != EXIT_BLOCK) {
+            let mut new = None;
+            for &succ in &succs[block] {
+                if ipdom[succ].is_some() {
+                    new = Some(match new {
+                        None => succ,
+                        Some(current) => intersect(&ipdom, succ, current),
+                    });
+                }
+            }
+            if new != ipdom[block] {
+                ipdom[block] = new;
+                changed = true;
+            }
+        }
+    }
+    ipdom
+}
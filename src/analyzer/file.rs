@@ -0,0 +1,213 @@
+//! Per-file analysis: the unit that is cached and re-analyzed on change.
+//!
+//! A file is analyzed into a module fragment holding the items declared in
+//! the file, including inline `mod name { ... }` blocks. A `mod name;`
+//! declaration that lives in another file becomes a placeholder submodule
+//! carrying only what the declaration itself says (doc comment, attributes,
+//! visibility); the analyzer fills it with the child file's fragment.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use syn::Item;
+use syn::spanned::Spanned;
+
+use super::cfg::CfgSet;
+use super::index::CrateIndex;
+use super::items::{self, ItemContext};
+use super::source::{ModuleDir, ParsedFile, SourceSet};
+use crate::entities::module::RustModule;
+
+/// A file declared by a `mod name;` item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChildFile {
+    /// Names of the modules from the file's own module down to the declared
+    /// one, e.g. `["inline", "child"]` for `mod inline { mod child; }`.
+    pub module_path: Vec<String>,
+    pub file: PathBuf,
+    /// Whether the child file owns its directory (`mod.rs` or `#[path]`).
+    pub owns_dir: bool,
+}
+
+/// What the analyzer knows about one source file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAnalysis {
+    /// Crate-wide facts declared in this file.
+    pub index: CrateIndex,
+    /// Files declared by `mod name;` items, in declaration order.
+    pub children: Vec<ChildFile>,
+    /// Fingerprint of the crate index `module` was extracted with, or `None`
+    /// if the items have not been extracted yet.
+    pub index_fingerprint: Option<String>,
+    /// The module fragment; empty until the items have been extracted.
+    pub module: RustModule,
+}
+
+impl FileAnalysis {
+    /// Collects the index facts and child files of a parsed file.
+    pub fn summarize(
+        sources: &SourceSet,
+        cfg: &CfgSet,
+        path: &Path,
+        owns_dir: bool,
+        file: &ParsedFile,
+    ) -> Self {
+        let mut index = CrateIndex::default();
+        index.add_file(&file.ast);
+        let mut children = Vec::new();
+        collect_child_files(
+            sources,
+            cfg,
+            &file.ast.items,
+            &ModuleDir::for_file(path, owns_dir),
+            &mut Vec::new(),
+            &mut children,
+        );
+        Self {
+            index,
+            children,
+            index_fingerprint: None,
+            module: RustModule::new(String::new()),
+        }
+    }
+
+    /// Extracts the items of `file` into the module fragment. Without
+    /// `with_items`, only the module structure is kept.
+    pub fn extract(
+        &mut self,
+        ctx: &ItemContext,
+        sources: &SourceSet,
+        path: &Path,
+        owns_dir: bool,
+        file: &ParsedFile,
+        with_items: bool,
+    ) {
+        let mut module = RustModule::new(String::new());
+        items::init_module(&file.source, &mut module, None, &file.ast.attrs);
+        module.file_path = Some(file.display_path.clone());
+        let dir = ModuleDir::for_file(path, owns_dir);
+        fill(
+            ctx,
+            sources,
+            file,
+            &mut module,
+            &file.ast.items,
+            &dir,
+            with_items,
+        );
+        self.module = module;
+    }
+}
+
+fn collect_child_files(
+    sources: &SourceSet,
+    cfg: &CfgSet,
+    items: &[Item],
+    dir: &ModuleDir,
+    module_path: &mut Vec<String>,
+    out: &mut Vec<ChildFile>,
+) {
+    let mut seen = HashSet::new();
+    for item in items {
+        let Item::Mod(m) = item else { continue };
+        // A module shadowed by an earlier one of the same name is not read.
+        if !cfg.is_enabled(&m.attrs) || !seen.insert(&m.ident) {
+            continue;
+        }
+        module_path.push(m.ident.to_string());
+        match &m.content {
+            Some((_, items)) => {
+                collect_child_files(sources, cfg, items, &dir.inline(m), module_path, out)
+            }
+            None => {
+                if let Some((file, owns_dir)) = dir.resolve(m, sources) {
+                    out.push(ChildFile {
+                        module_path: module_path.clone(),
+                        file,
+                        owns_dir,
+                    });
+                }
+            }
+        }
+        module_path.pop();
+    }
+}
+
+/// Extracts `items` into `module` and adds its submodules: inline modules
+/// in full, modules stored in other files as placeholders.
+fn fill(
+    ctx: &ItemContext,
+    sources: &SourceSet,
+    file: &ParsedFile,
+    module: &mut RustModule,
+    items: &[Item],
+    dir: &ModuleDir,
+    with_items: bool,
+) {
+    let submodules = if with_items {
+        items::extract_items(ctx, module, items)
+    } else {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Mod(m) if ctx.cfg.is_enabled(&m.attrs) => Some(m),
+                _ => None,
+            })
+            .collect()
+    };
+
+    for decl in submodules {
+        let name = decl.ident.to_string();
+        if module.submodules.contains_key(&name) {
+            let span = (
+                items::item_start(&decl.vis, decl.mod_token.span),
+                decl.span(),
+            );
+            items::shadow(ctx, module, "module", name, &decl.attrs, span);
+            continue;
+        }
+        let mut child = RustModule::new(name.clone());
+        items::init_module(&file.source, &mut child, Some(decl), &[]);
+        match &decl.content {
+            Some((_, items)) => {
+                let sub_path = format!("{}::{}", ctx.module_path, name);
+                let sub_ctx = ItemContext {
+                    module_path: &sub_path,
+                    ..*ctx
+                };
+                child.file_path = module.file_path.clone();
+                fill(
+                    &sub_ctx,
+                    sources,
+                    file,
+                    &mut child,
+                    items,
+                    &dir.inline(decl),
+                    with_items,
+                );
+            }
+            None => {
+                if dir.resolve(decl, sources).is_none() {
+                    continue;
+                }
+            }
+        }
+        module.submodules.insert(name, child);
+    }
+}
+
+/// Completes the fragment of a file with what its `mod name;` declaration
+/// says about it, as recorded in the placeholder `decl`.
+pub fn merge_declaration(fragment: &mut RustModule, decl: RustModule) {
+    fragment.name = decl.name;
+    fragment.visibility = decl.visibility;
+    fragment.is_unsafe = decl.is_unsafe;
+    fragment.doc_comment = match (decl.doc_comment, fragment.doc_comment.take()) {
+        (Some(outer), Some(inner)) => Some(format!("{}\n{}", outer, inner)),
+        (outer, inner) => outer.or(inner),
+    };
+    let inner = std::mem::take(&mut fragment.attributes);
+    fragment.attributes = decl.attributes;
+    fragment.attributes.extend(inner);
+}
@@ -0,0 +1,1035 @@
+//! Construction of the basic-block control-flow graph of a function body.
+//!
+//! Statements are appended to the current block until an expression branches
+//! or jumps: `if`, `match`, loops, `break`, `continue`, `return`, `?`, `&&`,
+//! `||`, `let ... else` and the panicking macros. Control flow nested in
+//! other expressions, e.g. a `?` in a call argument, is lowered in source
+//! order. After a jump the current block is unreachable; code that follows
+//! it anyway starts a block without predecessors.
+//!
+//! [`lower`] also records, for the dependence analysis of level 3, where
+//! each block defines and uses local variables, the variables and calls each
+//! definition is computed from, and the variables that each call or macro
+//! invocation passes on. The value of a `return` and of the tail expression
+//! of the body defines the pseudo-variable `return`.
+
+use std::collections::BTreeSet;
+
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{BinOp, Expr, Pat, Stmt, Token};
+
+use super::render;
+use super::source::SourceText;
+use crate::entities::dependence::{Argument, CallArguments, DefinitionKind};
+use crate::entities::flow::{
+    BasicBlock, BlockKind, CfgEdge, ControlFlowGraph, EXIT_BLOCK, EdgeKind,
+};
+
+/// Macros that never return.
+const DIVERGING_MACROS: &[&str] = &["panic", "unreachable", "todo", "unimplemented"];
+
+/// Name of the pseudo-variable defined by the values the body returns.
+pub const RETURN_VARIABLE: &str = "return";
+
+/// A definition or use of a local variable, or a call, in evaluation order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    Def(Def),
+    Use {
+        variable: String,
+        line: usize,
+    },
+    /// The call at this index of [`LoweredBody::calls`].
+    Call(usize),
+}
+
+/// A definition of a local variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Def {
+    pub variable: String,
+    pub line: usize,
+    pub kind: DefinitionKind,
+    /// Position of the parameter, for parameters.
+    pub parameter: Option<usize>,
+    /// Variables the value is computed from.
+    pub inputs: Vec<String>,
+    /// Indices of the calls made to compute the value.
+    pub calls: Vec<usize>,
+}
+
+/// A function body lowered to its control-flow graph, with the accesses to
+/// local variables.
+#[derive(Debug, Default)]
+pub struct LoweredBody {
+    pub graph: ControlFlowGraph,
+    /// The accesses of every block, by block id, in evaluation order.
+    pub accesses: Vec<Vec<Access>>,
+    pub calls: Vec<CallArguments>,
+}
+
+/// Builds the control-flow graph of `body`.
+pub fn build(src: &SourceText, body: &syn::Block) -> ControlFlowGraph {
+    Builder::new(src, None).finish(body).graph
+}
+
+/// Builds the control-flow graph of the body of the function `sig` and
+/// records its accesses to local variables. The parameters are defined in
+/// the entry block.
+pub fn lower(src: &SourceText, sig: &syn::Signature, body: &syn::Block) -> LoweredBody {
+    let mut builder = Builder::new(src, Some(Tracking::default()));
+    builder.current = Some(builder.block(BlockKind::Entry));
+    for (position, input) in sig.inputs.iter().enumerate() {
+        let params = match input {
+            syn::FnArg::Receiver(r) => vec![(
+                "self".to_owned(),
+                r.mutability.is_some(),
+                r.self_token.span.start().line,
+            )],
+            syn::FnArg::Typed(t) => {
+                let is_mut_ref =
+                    matches!(&*t.ty, syn::Type::Reference(r) if r.mutability.is_some());
+                bindings(&t.pat)
+                    .into_iter()
+                    .map(|(name, is_mut, line)| (name, is_mut || is_mut_ref, line))
+                    .collect()
+            }
+        };
+        for (name, is_mut, line) in params {
+            builder.scan(|scan| {
+                if is_mut {
+                    scan.mutable.insert(name.clone());
+                }
+                scan.define(Def {
+                    variable: name,
+                    line,
+                    kind: DefinitionKind::Parameter,
+                    parameter: Some(position),
+                    inputs: Vec::new(),
+                    calls: Vec::new(),
+                });
+            });
+        }
+    }
+    builder.finish(body)
+}
+
+struct Builder<'a> {
+    src: &'a SourceText,
+    graph: ControlFlowGraph,
+    /// The block code is appended to; `None` after a jump.
+    current: Option<usize>,
+    /// Enclosing loops and labeled blocks, innermost last.
+    scopes: Vec<Scope>,
+    /// Accesses recorded so far, if they are recorded.
+    tracking: Option<Tracking>,
+}
+
+#[derive(Default)]
+struct Tracking {
+    accesses: Vec<Vec<Access>>,
+    calls: Vec<CallArguments>,
+    /// Variables declared `mut`, or mutable parameters.
+    mutable: BTreeSet<String>,
+}
+
+/// A loop or labeled block that `break` can leave.
+struct Scope {
+    label: Option<String>,
+    /// Head of a loop; `None` for a labeled block.
+    head: Option<usize>,
+    /// Blocks that break out of the scope.
+    breaks: Vec<usize>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(src: &'a SourceText, tracking: Option<Tracking>) -> Self {
+        Builder {
+            src,
+            graph: ControlFlowGraph::default(),
+            current: None,
+            scopes: Vec::new(),
+            tracking,
+        }
+    }
+
+    fn finish(mut self, body: &syn::Block) -> LoweredBody {
+        if self.graph.blocks.is_empty() {
+            self.current = Some(self.block(BlockKind::Entry));
+        }
+        self.block(BlockKind::Exit);
+        match body.stmts.split_last() {
+            Some((Stmt::Expr(tail, None), stmts)) => {
+                for stmt in stmts {
+                    self.lower_stmt(stmt);
+                }
+                let first = self.next_call();
+                self.lower_expr(tail);
+                self.define_return(tail, first);
+            }
+            _ => self.lower_block(body),
+        }
+        self.goto(EXIT_BLOCK, EdgeKind::Next);
+        let mut lowered = LoweredBody {
+            graph: self.graph,
+            ..Default::default()
+        };
+        if let Some(mut tracking) = self.tracking {
+            tracking
+                .accesses
+                .resize_with(lowered.graph.blocks.len(), Vec::new);
+            lowered.accesses = tracking.accesses;
+            lowered.calls = tracking.calls;
+        }
+        lowered
+    }
+
+    fn block(&mut self, kind: BlockKind) -> usize {
+        let id = self.graph.blocks.len();
+        self.graph.blocks.push(BasicBlock {
+            id,
+            kind,
+            start_line: None,
+            end_line: None,
+            condition: None,
+        });
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, kind: EdgeKind, label: Option<String>) {
+        self.graph.edges.push(CfgEdge {
+            from,
+            to,
+            kind,
+            label,
+        });
+    }
+
+    /// Returns the current block, starting an unreachable one after a jump.
+    fn current(&mut self) -> usize {
+        match self.current {
+            Some(id) => id,
+            None => {
+                let id = self.block(BlockKind::Body);
+                self.current = Some(id);
+                id
+            }
+        }
+    }
+
+    /// Starts a new block entered from `from` along an edge of `kind`.
+    fn branch(&mut self, from: usize, kind: EdgeKind, label: Option<String>) -> usize {
+        let id = self.block(BlockKind::Body);
+        self.edge(from, id, kind, label);
+        self.current = Some(id);
+        id
+    }
+
+    /// Ends the current block with a jump to `to`.
+    fn goto(&mut self, to: usize, kind: EdgeKind) {
+        if let Some(from) = self.current.take() {
+            self.edge(from, to, kind, None);
+        }
+    }
+
+    /// Starts a join block entered from each of `ends`.
+    fn join(&mut self, ends: Vec<(usize, EdgeKind)>) {
+        let id = self.block(BlockKind::Join);
+        for (from, kind) in ends {
+            self.edge(from, id, kind, None);
+        }
+        self.current = Some(id);
+    }
+
+    /// Adds the lines of `node` to the current block.
+    fn span<T: Spanned>(&mut self, node: &T) {
+        let (start, end) = render::lines(node);
+        self.extend(start, end);
+    }
+
+    fn extend(&mut self, start: usize, end: usize) {
+        let id = self.current();
+        let block = &mut self.graph.blocks[id];
+        block.start_line = Some(block.start_line.map_or(start, |line| line.min(start)));
+        block.end_line = Some(block.end_line.map_or(end, |line| line.max(end)));
+    }
+
+    /// Records the accesses that `visit` finds in the current block.
+    fn scan(&mut self, visit: impl FnOnce(&mut Scan)) {
+        if self.tracking.is_none() {
+            return;
+        }
+        let block = self.current();
+        let src = self.src;
+        let Some(tracking) = &mut self.tracking else {
+            return;
+        };
+        let mut scan = Scan {
+            src,
+            block,
+            first_call: tracking.calls.len(),
+            mutable: &mut tracking.mutable,
+            closures: 0,
+            accesses: Vec::new(),
+            calls: Vec::new(),
+        };
+        visit(&mut scan);
+        let (accesses, calls) = (scan.accesses, scan.calls);
+        if tracking.accesses.len() <= block {
+            tracking.accesses.resize_with(block + 1, Vec::new);
+        }
+        tracking.accesses[block].extend(accesses);
+        tracking.calls.extend(calls);
+    }
+
+    /// Returns the index the next recorded call gets.
+    fn next_call(&self) -> usize {
+        self.tracking.as_ref().map_or(0, |t| t.calls.len())
+    }
+
+    /// Defines the variables bound by `pat` with the value of `value`,
+    /// computed with the calls recorded since `first`.
+    fn bind(&mut self, pat: &Pat, value: &Expr, first: usize) {
+        let calls: Vec<usize> = (first..self.next_call()).collect();
+        self.scan(|scan| scan.bind(pat, variables(value), calls));
+    }
+
+    /// Defines [`RETURN_VARIABLE`] with the value of `value`.
+    fn define_return(&mut self, value: &Expr, first: usize) {
+        let calls: Vec<usize> = (first..self.next_call()).collect();
+        self.scan(|scan| {
+            scan.define(Def {
+                variable: RETURN_VARIABLE.to_owned(),
+                line: value.span().start().line,
+                kind: DefinitionKind::Return,
+                parameter: None,
+                inputs: variables(value),
+                calls,
+            })
+        });
+    }
+
+    fn condition(&mut self, id: usize, text: String) {
+        self.graph.blocks[id].condition = Some(text);
+    }
+
+    fn lower_block(&mut self, block: &syn::Block) {
+        for stmt in &block.stmts {
+            self.lower_stmt(stmt);
+        }
+    }
+
+    fn lower_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Local(local) => {
+                let Some(init) = &local.init else {
+                    self.span(local);
+                    self.scan(|scan| scan.declare(&local.pat));
+                    return;
+                };
+                let first = self.next_call();
+                if has_control_flow(&init.expr) {
+                    self.lower_expr(&init.expr);
+                } else {
+                    self.span(&*init.expr);
+                    self.scan(|scan| scan.visit_expr(&init.expr));
+                }
+                self.span(&local.let_token);
+                self.span(&local.pat);
+                if init.diverge.is_none() {
+                    self.bind(&local.pat, &init.expr, first);
+                }
+                if let Some((_, diverge)) = &init.diverge {
+                    let from = self.current();
+                    self.condition(
+                        from,
+                        format!(
+                            "let {} = {}",
+                            render::text(self.src, &local.pat),
+                            render::text(self.src, &*init.expr)
+                        ),
+                    );
+                    self.branch(from, EdgeKind::False, None);
+                    self.lower_expr(diverge);
+                    // The `else` block diverges through a call, e.g. to
+                    // `std::process::exit`.
+                    self.goto(EXIT_BLOCK, EdgeKind::Next);
+                    self.branch(from, EdgeKind::True, None);
+                    self.bind(&local.pat, &init.expr, first);
+                }
+            }
+            Stmt::Item(_) => {}
+            Stmt::Expr(expr, _) => self.lower_expr(expr),
+            Stmt::Macro(stmt) => {
+                self.span(stmt);
+                self.scan(|scan| scan.visit_macro(&stmt.mac));
+                if is_diverging(&stmt.mac) {
+                    self.goto(EXIT_BLOCK, EdgeKind::Panic);
+                }
+            }
+        }
+    }
+
+    fn lower_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::If(expr) => self.lower_if(expr),
+            Expr::Match(expr) => self.lower_match(expr),
+            Expr::Loop(expr) => {
+                let head = self.loop_head(expr.label.as_ref());
+                self.span(&expr.loop_token);
+                self.lower_block(&expr.body);
+                self.goto(head, EdgeKind::Next);
+                self.end_scope(Vec::new());
+            }
+            Expr::While(expr) => {
+                let head = self.loop_head(expr.label.as_ref());
+                self.lower_expr(&expr.cond);
+                let test = self.current();
+                self.condition(test, render::text(self.src, &*expr.cond));
+                self.branch(test, EdgeKind::True, None);
+                self.lower_block(&expr.body);
+                self.goto(head, EdgeKind::Next);
+                self.end_scope(vec![(test, EdgeKind::False)]);
+            }
+            Expr::ForLoop(expr) => {
+                let first = self.next_call();
+                self.lower_expr(&expr.expr);
+                let head = self.loop_head(expr.label.as_ref());
+                self.span(&expr.pat);
+                self.condition(
+                    head,
+                    format!(
+                        "{} in {}",
+                        render::text(self.src, &*expr.pat),
+                        render::text(self.src, &*expr.expr)
+                    ),
+                );
+                self.branch(head, EdgeKind::True, None);
+                self.bind(&expr.pat, &expr.expr, first);
+                self.lower_block(&expr.body);
+                self.goto(head, EdgeKind::Next);
+                self.end_scope(vec![(head, EdgeKind::False)]);
+            }
+            Expr::Block(block) if block.label.is_some() => {
+                self.scopes.push(Scope {
+                    label: block.label.as_ref().map(|l| l.name.ident.to_string()),
+                    head: None,
+                    breaks: Vec::new(),
+                });
+                self.lower_block(&block.block);
+                let end = self.current.take().map(|id| (id, EdgeKind::Next));
+                self.end_scope(end.into_iter().collect());
+            }
+            Expr::Block(block) => self.lower_block(&block.block),
+            Expr::Unsafe(block) => self.lower_block(&block.block),
+            Expr::Const(block) => self.lower_block(&block.block),
+            Expr::Paren(expr) => self.lower_expr(&expr.expr),
+            Expr::Group(expr) => self.lower_expr(&expr.expr),
+            Expr::Break(expr) => {
+                if let Some(value) = &expr.expr {
+                    self.lower_expr(value);
+                }
+                self.span(expr);
+                let label = expr.label.as_ref().map(|l| l.ident.to_string());
+                let from = self.current();
+                self.current = None;
+                match self.scope(label.as_deref(), false) {
+                    Some(scope) => scope.breaks.push(from),
+                    None => self.edge(from, EXIT_BLOCK, EdgeKind::Break, None),
+                }
+            }
+            Expr::Continue(expr) => {
+                self.span(expr);
+                let label = expr.label.as_ref().map(|l| l.ident.to_string());
+                let target = self
+                    .scope(label.as_deref(), true)
+                    .and_then(|scope| scope.head)
+                    .unwrap_or(EXIT_BLOCK);
+                self.goto(target, EdgeKind::Continue);
+            }
+            Expr::Return(expr) => {
+                if let Some(value) = &expr.expr {
+                    let first = self.next_call();
+                    self.lower_expr(value);
+                    self.define_return(value, first);
+                }
+                self.span(expr);
+                self.goto(EXIT_BLOCK, EdgeKind::Return);
+            }
+            Expr::Try(expr) => {
+                self.lower_expr(&expr.expr);
+                self.span(&expr.question_token);
+                let from = self.current();
+                self.condition(from, render::text(self.src, &*expr.expr));
+                self.edge(from, EXIT_BLOCK, EdgeKind::Error, None);
+                self.branch(from, EdgeKind::Next, None);
+            }
+            Expr::Binary(binary) if matches!(binary.op, BinOp::And(_) | BinOp::Or(_)) => {
+                let (taken, skipped) = match binary.op {
+                    BinOp::And(_) => (EdgeKind::True, EdgeKind::False),
+                    _ => (EdgeKind::False, EdgeKind::True),
+                };
+                self.lower_expr(&binary.left);
+                let from = self.current();
+                self.condition(from, render::text(self.src, &*binary.left));
+                self.branch(from, taken, None);
+                self.lower_expr(&binary.right);
+                let mut ends = vec![(from, skipped)];
+                ends.extend(self.current.take().map(|id| (id, EdgeKind::Next)));
+                self.join(ends);
+            }
+            Expr::Macro(expr) if is_diverging(&expr.mac) => {
+                self.span(expr);
+                self.scan(|scan| scan.visit_macro(&expr.mac));
+                self.goto(EXIT_BLOCK, EdgeKind::Panic);
+            }
+            _ => {
+                let mut nested = Nested::default();
+                visit::visit_expr(&mut nested, expr);
+                if nested.found.is_empty() {
+                    self.span(expr);
+                    self.scan(|scan| scan.visit_expr(expr));
+                    return;
+                }
+                for inner in nested.found {
+                    self.lower_expr(inner);
+                }
+                let end = expr.span().end().line;
+                self.extend(end, end);
+                // The rest of the expression is evaluated after the
+                // nested control flow.
+                self.scan(|scan| scan.visit_expr(expr));
+            }
+        }
+    }
+
+    fn lower_if(&mut self, expr: &syn::ExprIf) {
+        self.span(&expr.if_token);
+        self.lower_expr(&expr.cond);
+        let from = self.current();
+        self.condition(from, render::text(self.src, &*expr.cond));
+        let mut ends = Vec::new();
+        self.branch(from, EdgeKind::True, None);
+        self.lower_block(&expr.then_branch);
+        ends.extend(self.current.take().map(|id| (id, EdgeKind::Next)));
+        match &expr.else_branch {
+            Some((_, other)) => {
+                self.branch(from, EdgeKind::False, None);
+                self.lower_expr(other);
+                ends.extend(self.current.take().map(|id| (id, EdgeKind::Next)));
+            }
+            None => ends.push((from, EdgeKind::False)),
+        }
+        self.join(ends);
+    }
+
+    fn lower_match(&mut self, expr: &syn::ExprMatch) {
+        let first = self.next_call();
+        self.lower_expr(&expr.expr);
+        self.span(&expr.match_token);
+        let from = self.current();
+        self.condition(from, render::text(self.src, &*expr.expr));
+        let mut ends = Vec::new();
+        // The block whose guard failed falls through to the next arm.
+        let mut failed_guard = None;
+        for arm in &expr.arms {
+            let label = render::text(self.src, &arm.pat);
+            let id = self.branch(from, EdgeKind::Arm, Some(label));
+            if let Some(guard) = failed_guard.take() {
+                self.edge(guard, id, EdgeKind::False, None);
+            }
+            self.span(&arm.pat);
+            self.bind(&arm.pat, &expr.expr, first);
+            if let Some((_, guard)) = &arm.guard {
+                self.lower_expr(guard);
+                let test = self.current();
+                self.condition(test, render::text(self.src, &**guard));
+                self.branch(test, EdgeKind::True, None);
+                failed_guard = Some(test);
+            }
+            self.lower_expr(&arm.body);
+            ends.extend(self.current.take().map(|id| (id, EdgeKind::Next)));
+        }
+        ends.extend(failed_guard.map(|id| (id, EdgeKind::False)));
+        self.join(ends);
+    }
+
+    /// Starts the head of a loop and its scope.
+    fn loop_head(&mut self, label: Option<&syn::Label>) -> usize {
+        let head = self.block(BlockKind::LoopHead);
+        let from = self.current();
+        self.edge(from, head, EdgeKind::Next, None);
+        self.current = Some(head);
+        self.scopes.push(Scope {
+            label: label.map(|l| l.name.ident.to_string()),
+            head: Some(head),
+            breaks: Vec::new(),
+        });
+        head
+    }
+
+    /// Ends the innermost scope with a join block entered from `ends` and
+    /// from every `break` out of the scope.
+    fn end_scope(&mut self, mut ends: Vec<(usize, EdgeKind)>) {
+        self.current = None;
+        if let Some(scope) = self.scopes.pop() {
+            ends.extend(scope.breaks.into_iter().map(|id| (id, EdgeKind::Break)));
+        }
+        self.join(ends);
+    }
+
+    /// Returns the scope a `break` or `continue` with `label` refers to: the
+    /// labeled scope, or else the innermost loop.
+    fn scope(&mut self, label: Option<&str>, is_continue: bool) -> Option<&mut Scope> {
+        self.scopes.iter_mut().rev().find(|scope| {
+            let is_loop = scope.head.is_some();
+            match label {
+                Some(label) => scope.label.as_deref() == Some(label) && (is_loop || !is_continue),
+                None => is_loop,
+            }
+        })
+    }
+}
+
+/// Collects the outermost expressions with control flow below an expression,
+/// skipping closures, async blocks and items.
+#[derive(Default)]
+struct Nested<'ast> {
+    found: Vec<&'ast Expr>,
+}
+
+impl<'ast> Visit<'ast> for Nested<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if is_control_flow(expr) {
+            self.found.push(expr);
+        } else {
+            visit::visit_expr(self, expr);
+        }
+    }
+
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_async(&mut self, _: &'ast syn::ExprAsync) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+fn is_control_flow(expr: &Expr) -> bool {
+    match expr {
+        Expr::If(_)
+        | Expr::Match(_)
+        | Expr::Loop(_)
+        | Expr::While(_)
+        | Expr::ForLoop(_)
+        | Expr::Block(_)
+        | Expr::Unsafe(_)
+        | Expr::Const(_)
+        | Expr::Break(_)
+        | Expr::Continue(_)
+        | Expr::Return(_)
+        | Expr::Try(_) => true,
+        Expr::Binary(binary) => matches!(binary.op, BinOp::And(_) | BinOp::Or(_)),
+        Expr::Macro(expr) => is_diverging(&expr.mac),
+        _ => false,
+    }
+}
+
+fn has_control_flow(expr: &Expr) -> bool {
+    let mut nested = Nested::default();
+    nested.visit_expr(expr);
+    !nested.found.is_empty()
+}
+
+fn is_diverging(mac: &syn::Macro) -> bool {
+    mac.path
+        .segments
+        .last()
+        .is_some_and(|s| DIVERGING_MACROS.iter().any(|name| s.ident == name))
+}
+
+/// Records the accesses to local variables in an expression, skipping the
+/// control flow nested in it, which is lowered separately.
+struct Scan<'s> {
+    src: &'s SourceText,
+    block: usize,
+    /// Index of the first call the scan records in [`LoweredBody::calls`].
+    first_call: usize,
+    mutable: &'s mut BTreeSet<String>,
+    /// Depth of the enclosing closures and async blocks, whose bodies do
+    /// not run where they are created.
+    closures: usize,
+    accesses: Vec<Access>,
+    calls: Vec<CallArguments>,
+}
+
+impl Scan<'_> {
+    fn define(&mut self, mut def: Def) {
+        def.kind = match def.kind {
+            // Closure parameters and locals are not variables of the body.
+            DefinitionKind::Declaration if self.closures > 0 => return,
+            _ if self.closures > 0 => DefinitionKind::Mutation,
+            kind => kind,
+        };
+        self.accesses.push(Access::Def(def));
+    }
+
+    /// Returns the index the next recorded call gets.
+    fn next_call(&self) -> usize {
+        self.first_call + self.calls.len()
+    }
+
+    /// Defines the variables bound by `pat` with a value computed from
+    /// `inputs` and `calls`.
+    fn bind(&mut self, pat: &Pat, inputs: Vec<String>, calls: Vec<usize>) {
+        for (name, is_mut, line) in bindings(pat) {
+            if is_mut {
+                self.mutable.insert(name.clone());
+            }
+            self.define(Def {
+                variable: name,
+                line,
+                kind: DefinitionKind::Declaration,
+                parameter: None,
+                inputs: inputs.clone(),
+                calls: calls.clone(),
+            });
+        }
+    }
+
+    /// Notes the `mut` bindings of a `let` without an initializer, which are
+    /// defined by a later assignment.
+    fn declare(&mut self, pat: &Pat) {
+        for (name, is_mut, _) in bindings(pat) {
+            if is_mut {
+                self.mutable.insert(name);
+            }
+        }
+    }
+
+    /// Defines the variable that the place expression `place` is part of,
+    /// with a value computed from `inputs` and the calls recorded since
+    /// `first`.
+    fn assign(&mut self, place: &Expr, line: usize, inputs: Vec<String>, first: usize) {
+        if let Some((variable, whole)) = place_root(place) {
+            let kind = if whole {
+                DefinitionKind::Assignment
+            } else {
+                DefinitionKind::Mutation
+            };
+            let calls = (first..self.next_call()).collect();
+            self.define(Def {
+                variable,
+                line,
+                kind,
+                parameter: None,
+                inputs,
+                calls,
+            });
+        }
+    }
+
+    /// Visits the receiver and arguments of a call, then records the call.
+    /// A variable passed by `&mut` is redefined by the call with a value
+    /// computed from the other arguments.
+    fn call<'a>(
+        &mut self,
+        line: usize,
+        call: String,
+        receiver: Option<&'a Expr>,
+        args: impl IntoIterator<Item = &'a Expr>,
+    ) -> usize {
+        let mut borrowed = Vec::new();
+        let mut argument = |scan: &mut Self, expr: &'a Expr| {
+            let first = scan.next_call();
+            match expr {
+                Expr::Reference(r) if r.mutability.is_some() && place_root(&r.expr).is_some() => {
+                    scan.visit_expr(&r.expr);
+                    borrowed.extend(place_root(&r.expr).map(|(variable, _)| variable));
+                }
+                _ => scan.visit_expr(expr),
+            }
+            Argument {
+                variables: variables(expr),
+                definitions: Vec::new(),
+                calls: (first..scan.next_call()).collect(),
+            }
+        };
+        let receiver = receiver.map(|expr| argument(self, expr));
+        let arguments: Vec<Argument> = args.into_iter().map(|expr| argument(self, expr)).collect();
+        let index = self.next_call();
+        let values: Vec<&Argument> = receiver.iter().chain(&arguments).collect();
+        let calls: Vec<usize> = values
+            .iter()
+            .flat_map(|arg| arg.calls.iter().copied())
+            .chain([index])
+            .collect();
+        let mutations: Vec<Def> = borrowed
+            .into_iter()
+            .map(|variable| {
+                let inputs: BTreeSet<String> = values
+                    .iter()
+                    .flat_map(|arg| arg.variables.iter())
+                    .filter(|v| **v != variable)
+                    .cloned()
+                    .collect();
+                Def {
+                    variable,
+                    line,
+                    kind: DefinitionKind::Mutation,
+                    parameter: None,
+                    inputs: inputs.into_iter().collect(),
+                    calls: calls.clone(),
+                }
+            })
+            .collect();
+        self.calls.push(CallArguments {
+            line,
+            call,
+            block: self.block,
+            receiver,
+            arguments,
+        });
+        self.accesses.push(Access::Call(index));
+        for def in mutations {
+            self.define(def);
+        }
+        index
+    }
+}
+
+impl<'ast> Visit<'ast> for Scan<'_> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if self.closures == 0 && is_control_flow(expr) {
+            return;
+        }
+        visit::visit_expr(self, expr);
+    }
+
+    fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+        if let Some(variable) = local_name(expr) {
+            self.accesses.push(Access::Use {
+                variable,
+                line: expr.span().start().line,
+            });
+        }
+    }
+
+    fn visit_expr_assign(&mut self, expr: &'ast syn::ExprAssign) {
+        let first = self.next_call();
+        self.visit_expr(&expr.right);
+        if !matches!(place_root(&expr.left), Some((_, true))) {
+            self.visit_expr(&expr.left);
+        }
+        let line = expr.span().start().line;
+        self.assign(&expr.left, line, variables(&expr.right), first);
+    }
+
+    fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+        if !is_compound_assignment(&expr.op) {
+            visit::visit_expr_binary(self, expr);
+            return;
+        }
+        let first = self.next_call();
+        self.visit_expr(&expr.right);
+        self.visit_expr(&expr.left);
+        let line = expr.span().start().line;
+        self.assign(&expr.left, line, variables(&expr.right), first);
+    }
+
+    fn visit_expr_reference(&mut self, expr: &'ast syn::ExprReference) {
+        visit::visit_expr_reference(self, expr);
+        if expr.mutability.is_some() {
+            if let Some((variable, _)) = place_root(&expr.expr) {
+                self.define(Def {
+                    variable,
+                    line: expr.span().start().line,
+                    kind: DefinitionKind::Mutation,
+                    parameter: None,
+                    inputs: Vec::new(),
+                    calls: Vec::new(),
+                });
+            }
+        }
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        match &*call.func {
+            Expr::Path(p) => {
+                let callee = render::text(self.src, &p.path).replace(' ', "");
+                self.call(call.span().start().line, callee, None, &call.args);
+            }
+            _ => visit::visit_expr_call(self, call),
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let line = call.method.span().start().line;
+        let index = self.call(
+            line,
+            call.method.to_string(),
+            Some(&call.receiver),
+            &call.args,
+        );
+        if let Some((variable, _)) = place_root(&call.receiver) {
+            if self.mutable.contains(&variable) {
+                let inputs = call
+                    .args
+                    .iter()
+                    .flat_map(variables)
+                    .collect::<BTreeSet<_>>();
+                let calls = self.calls[index - self.first_call]
+                    .arguments
+                    .iter()
+                    .flat_map(|arg| arg.calls.iter().copied())
+                    .chain([index])
+                    .collect();
+                self.define(Def {
+                    variable,
+                    line,
+                    kind: DefinitionKind::Mutation,
+                    parameter: None,
+                    inputs: inputs.into_iter().collect(),
+                    calls,
+                });
+            }
+        }
+    }
+
+    fn visit_expr_let(&mut self, expr: &'ast syn::ExprLet) {
+        let first = self.next_call();
+        self.visit_expr(&expr.expr);
+        let calls = (first..self.next_call()).collect();
+        self.bind(&expr.pat, variables(&expr.expr), calls);
+    }
+
+    fn visit_expr_closure(&mut self, expr: &'ast syn::ExprClosure) {
+        self.closures += 1;
+        self.visit_expr(&expr.body);
+        self.closures -= 1;
+    }
+
+    fn visit_expr_async(&mut self, expr: &'ast syn::ExprAsync) {
+        self.closures += 1;
+        visit::visit_expr_async(self, expr);
+        self.closures -= 1;
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        // Only reached in closures and async blocks.
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+            return;
+        };
+        let name = render::text(self.src, &mac.path).replace(' ', "");
+        let line = mac.path.span().start().line;
+        self.call(line, format!("{}!", name), None, &args);
+    }
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+/// Returns the name of a local variable that `expr` refers to.
+fn local_name(expr: &syn::ExprPath) -> Option<String> {
+    match (
+        &expr.qself,
+        expr.path.leading_colon,
+        expr.path.segments.len(),
+    ) {
+        (None, None, 1) => Some(expr.path.segments[0].ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the variable a place expression is part of, and whether it is
+/// the whole variable.
+fn place_root(place: &Expr) -> Option<(String, bool)> {
+    match place {
+        Expr::Path(path) => local_name(path).map(|name| (name, true)),
+        Expr::Field(field) => place_root(&field.base).map(|(name, _)| (name, false)),
+        Expr::Index(index) => place_root(&index.expr).map(|(name, _)| (name, false)),
+        Expr::Paren(paren) => place_root(&paren.expr),
+        Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Deref(_)) => {
+            place_root(&unary.expr).map(|(name, _)| (name, false))
+        }
+        _ => None,
+    }
+}
+
+fn is_compound_assignment(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
+
+/// Returns the variables bound by `pat`, with whether they are `mut` and
+/// their line.
+fn bindings(pat: &Pat) -> Vec<(String, bool, usize)> {
+    #[derive(Default)]
+    struct Bindings(Vec<(String, bool, usize)>);
+
+    impl<'ast> Visit<'ast> for Bindings {
+        fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+            // Unit structs, unit variants and constants.
+            let name = pat.ident.to_string();
+            if !name.starts_with(char::is_uppercase) {
+                self.0.push((
+                    name,
+                    pat.mutability.is_some(),
+                    pat.ident.span().start().line,
+                ));
+            }
+            visit::visit_pat_ident(self, pat);
+        }
+
+        fn visit_expr(&mut self, _: &'ast Expr) {}
+    }
+
+    let mut found = Bindings::default();
+    found.visit_pat(pat);
+    found.0
+}
+
+/// Returns the local variables used in `expr`, sorted.
+fn variables(expr: &Expr) -> Vec<String> {
+    #[derive(Default)]
+    struct Variables(BTreeSet<String>);
+
+    impl<'ast> Visit<'ast> for Variables {
+        fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+            self.0.extend(local_name(expr));
+        }
+
+        fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+            if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+                for arg in &args {
+                    self.visit_expr(arg);
+                }
+            }
+        }
+
+        fn visit_item(&mut self, _: &'ast syn::Item) {}
+    }
+
+    let mut found = Variables::default();
+    found.visit_expr(expr);
+    found.0.into_iter().collect()
+}
@@ -0,0 +1,75 @@
+//! Crate-wide facts that the per-callable analysis needs to look up.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use syn::visit::Visit;
+
+use super::render;
+
+/// Names declared anywhere in the crate that change how a body is analyzed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CrateIndex {
+    /// Names of `static mut` items.
+    pub static_muts: BTreeSet<String>,
+    /// Names of functions declared in `extern` blocks.
+    pub foreign_fns: BTreeSet<String>,
+    /// Names of `unsafe fn` items and methods defined in the crate.
+    pub unsafe_fns: BTreeSet<String>,
+    /// Names of `union` items.
+    pub unions: BTreeSet<String>,
+}
+
+impl CrateIndex {
+    /// Adds the declarations of one parsed file.
+    pub fn add_file(&mut self, file: &syn::File) {
+        self.visit_file(file);
+    }
+
+    /// Adds the declarations recorded in `other`.
+    pub fn merge(&mut self, other: &CrateIndex) {
+        self.static_muts.extend(other.static_muts.iter().cloned());
+        self.foreign_fns.extend(other.foreign_fns.iter().cloned());
+        self.unsafe_fns.extend(other.unsafe_fns.iter().cloned());
+        self.unions.extend(other.unions.iter().cloned());
+    }
+}
+
+impl<'ast> Visit<'ast> for CrateIndex {
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        if matches!(item.mutability, syn::StaticMutability::Mut(_)) {
+            self.static_muts.insert(item.ident.to_string());
+        }
+    }
+
+    fn visit_foreign_item(&mut self, item: &'ast syn::ForeignItem) {
+        match item {
+            syn::ForeignItem::Verbatim(tokens) => {
+                if let Some(item) = render::safe_foreign_item(tokens) {
+                    self.visit_foreign_item(&item);
+                }
+            }
+            item => syn::visit::visit_foreign_item(self, item),
+        }
+    }
+
+    fn visit_foreign_item_fn(&mut self, item: &'ast syn::ForeignItemFn) {
+        self.foreign_fns.insert(item.sig.ident.to_string());
+    }
+
+    fn visit_foreign_item_static(&mut self, item: &'ast syn::ForeignItemStatic) {
+        if matches!(item.mutability, syn::StaticMutability::Mut(_)) {
+            self.static_muts.insert(item.ident.to_string());
+        }
+    }
+
+    fn visit_signature(&mut self, sig: &'ast syn::Signature) {
+        if sig.unsafety.is_some() {
+            self.unsafe_fns.insert(sig.ident.to_string());
+        }
+    }
+
+    fn visit_item_union(&mut self, item: &'ast syn::ItemUnion) {
+        self.unions.insert(item.ident.to_string());
+    }
+}
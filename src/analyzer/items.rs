@@ -0,0 +1,934 @@
+//! Extraction of module items into entities.
+
+use std::collections::BTreeMap;
+
+use proc_macro2::{Span, TokenTree};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Expr, ExprLit, Fields, FnArg, ForeignItem, ImplItem, Item, ItemMod, Lit, Meta,
+    MetaNameValue, Signature, Token, TraitItem, UseTree,
+};
+
+use super::asm::{self, is_asm_macro};
+use super::body::{self, BodyContext};
+use super::cfg::CfgSet;
+use super::dependence;
+use super::flow;
+use super::index::CrateIndex;
+use super::render;
+use super::source::SourceText;
+use crate::entities::{
+    RustStructKind, RustVisibility, SafetyClassification,
+    attr::exported_symbol,
+    callable::RustCallable,
+    foreign::{RustForeignBlock, RustForeignFunction, RustForeignStatic, RustLink},
+    module::{RustModule, RustShadowedItem},
+    param::RustParameter,
+    renum::{RustEnum, RustEnumVariant},
+    rimpl::RustImpl,
+    rmacro::RustMacro,
+    rstruct::{RustStruct, RustStructField},
+    rtrait::RustTrait,
+    rtype::{RustType, RustTypeAlias},
+    runion::RustUnion,
+    safety::SafetyAnalysis,
+    variables::RustVariableDeclaration,
+};
+
+/// Context shared by all items of one module.
+pub struct ItemContext<'a> {
+    pub src: &'a SourceText,
+    pub index: &'a CrateIndex,
+    pub cfg: &'a CfgSet,
+    /// Analysis level; call sites are collected from level 2 on and
+    /// dependence graphs at level 3.
+    pub level: u8,
+    /// `::`-separated path of the module being extracted.
+    pub module_path: &'a str,
+    /// Name of the self type while the methods of an impl are extracted.
+    pub self_type: Option<&'a str>,
+}
+
+/// Returns the span of the first token after the attributes of an item.
+pub fn item_start(vis: &syn::Visibility, keyword: Span) -> Span {
+    match vis {
+        syn::Visibility::Inherited => keyword,
+        vis => vis.span(),
+    }
+}
+
+/// Returns the 1-based start and end line and the source text of an item.
+fn item_code(src: &SourceText, start: Span, end: Span) -> (usize, usize, String) {
+    let code = src
+        .slice_between(start, end)
+        .map(str::to_owned)
+        .unwrap_or_default();
+    (start.start().line, end.end().line, code)
+}
+
+/// Records an item that `module` already has an item of the same name and
+/// kind for, e.g. the variant of a function for another target.
+pub fn shadow(
+    ctx: &ItemContext,
+    module: &mut RustModule,
+    kind: &str,
+    name: String,
+    attrs: &[Attribute],
+    (start, end): (Span, Span),
+) {
+    module.shadowed_items.push(RustShadowedItem {
+        kind: kind.to_owned(),
+        name,
+        cfg: render::cfg_predicates(ctx.src, attrs),
+        start_line: start.start().line,
+        end_line: end.end().line,
+    });
+}
+
+/// Adds the items in `items` to `module` and returns the `mod` items to
+/// descend into. Items disabled by `#[cfg]` are skipped, and so are items
+/// named like an earlier item of the same kind, which are recorded as
+/// shadowed instead.
+pub fn extract_items<'f>(
+    ctx: &ItemContext,
+    module: &mut RustModule,
+    items: &'f [Item],
+) -> Vec<&'f ItemMod> {
+    let mut submodules = Vec::new();
+    for item in items {
+        if !ctx.cfg.is_enabled(item_attrs(item)) {
+            continue;
+        }
+        match item {
+            Item::Fn(f) => {
+                let start = item_start(&f.vis, f.sig.span());
+                let name = f.sig.ident.to_string();
+                if module.functions.contains_key(&name) {
+                    shadow(
+                        ctx,
+                        module,
+                        "function",
+                        name,
+                        &f.attrs,
+                        (start, f.block.span()),
+                    );
+                    continue;
+                }
+                let callable = callable(
+                    ctx,
+                    &f.attrs,
+                    &f.vis,
+                    &f.sig,
+                    Some(&f.block),
+                    (start, f.block.span()),
+                    None,
+                );
+                if let Some(mac) = proc_macro(&f.attrs, &callable) {
+                    module.macros.insert(mac.name.clone(), mac);
+                }
+                if callable.is_unsafe {
+                    module
+                        .unsafe_functions
+                        .insert(callable.name.clone(), callable.clone());
+                } else {
+                    module
+                        .safe_functions
+                        .insert(callable.name.clone(), callable.clone());
+                }
+                module.functions.insert(callable.name.clone(), callable);
+            }
+            Item::Struct(s) => {
+                let start = item_start(&s.vis, s.struct_token.span);
+                let name = s.ident.to_string();
+                if module.structs.contains_key(&name) {
+                    shadow(ctx, module, "struct", name, &s.attrs, (start, s.span()));
+                    continue;
+                }
+                let (start_line, end_line, _) = item_code(ctx.src, start, s.span());
+                let mut rstruct = RustStruct::new(s.ident.to_string(), start_line, end_line);
+                rstruct.visibility = render::visibility(&s.vis);
+                rstruct.is_public = rstruct.visibility == RustVisibility::Public;
+                rstruct.doc_comment = render::doc_comment(&s.attrs);
+                rstruct.attributes = render::attributes(ctx.src, &s.attrs);
+                rstruct.derives = render::derives(ctx.src, &s.attrs);
+                (
+                    rstruct.generic_params,
+                    rstruct.lifetime_params,
+                    rstruct.where_clauses,
+                ) = render::generics(ctx.src, &s.generics);
+                rstruct.kind = match &s.fields {
+                    Fields::Named(_) => RustStructKind::Normal,
+                    Fields::Unnamed(_) => RustStructKind::Tuple,
+                    Fields::Unit => RustStructKind::Unit,
+                };
+                rstruct.fields = fields(ctx, &s.fields);
+                rstruct.contains_unsafe = rstruct.fields.iter().any(|f| f.ty.contains_raw_pointers);
+                module
+                    .types
+                    .insert(rstruct.name.clone(), RustType::new(rstruct.name.clone()));
+                module.structs.insert(rstruct.name.clone(), rstruct);
+            }
+            Item::Enum(e) => {
+                let start = item_start(&e.vis, e.enum_token.span);
+                let name = e.ident.to_string();
+                if module.enums.contains_key(&name) {
+                    shadow(ctx, module, "enum", name, &e.attrs, (start, e.span()));
+                    continue;
+                }
+                let (start_line, end_line, _) = item_code(ctx.src, start, e.span());
+                let (generic_params, lifetime_params, where_clauses) =
+                    render::generics(ctx.src, &e.generics);
+                let visibility = render::visibility(&e.vis);
+                let renum = RustEnum {
+                    name: e.ident.to_string(),
+                    is_public: visibility == RustVisibility::Public,
+                    visibility,
+                    doc_comment: render::doc_comment(&e.attrs),
+                    attributes: render::attributes(ctx.src, &e.attrs),
+                    variants: e
+                        .variants
+                        .iter()
+                        .filter(|v| ctx.cfg.is_enabled(&v.attrs))
+                        .map(|v| RustEnumVariant {
+                            name: v.ident.to_string(),
+                            fields: matches!(v.fields, Fields::Named(_))
+                                .then(|| fields(ctx, &v.fields)),
+                            tuple_types: match &v.fields {
+                                Fields::Unnamed(u) => Some(
+                                    u.unnamed
+                                        .iter()
+                                        .map(|f| render::rust_type(ctx.src, &f.ty))
+                                        .collect(),
+                                ),
+                                _ => None,
+                            },
+                            tuple_attributes: match &v.fields {
+                                Fields::Unnamed(u) => Some(
+                                    u.unnamed
+                                        .iter()
+                                        .map(|f| render::attributes(ctx.src, &f.attrs))
+                                        .collect(),
+                                ),
+                                _ => None,
+                            },
+                            discriminant: v
+                                .discriminant
+                                .as_ref()
+                                .map(|(_, expr)| render::text(ctx.src, expr)),
+                            doc_comment: render::doc_comment(&v.attrs),
+                            attributes: render::attributes(ctx.src, &v.attrs),
+                        })
+                        .collect(),
+                    generic_params,
+                    lifetime_params,
+                    where_clauses,
+                    derives: render::derives(ctx.src, &e.attrs),
+                    associated_items: BTreeMap::new(),
+                    impl_traits: Vec::new(),
+                    start_line: start_line as i32,
+                    end_line: end_line as i32,
+                };
+                module
+                    .types
+                    .insert(renum.name.clone(), RustType::new(renum.name.clone()));
+                module.enums.insert(renum.name.clone(), renum);
+            }
+            Item::Union(u) => {
+                let start = item_start(&u.vis, u.union_token.span);
+                let name = u.ident.to_string();
+                if module.unions.contains_key(&name) {
+                    shadow(ctx, module, "union", name, &u.attrs, (start, u.span()));
+                    continue;
+                }
+                let (start_line, end_line, _) = item_code(ctx.src, start, u.span());
+                let mut runion = RustUnion::new(u.ident.to_string(), start_line, end_line);
+                runion.visibility = render::visibility(&u.vis);
+                runion.doc_comment = render::doc_comment(&u.attrs);
+                runion.attributes = render::attributes(ctx.src, &u.attrs);
+                runion.repr = runion
+                    .attributes
+                    .iter()
+                    .filter(|attr| attr.name == "repr")
+                    .flat_map(|attr| attr.arguments.iter().cloned())
+                    .collect();
+                runion.derives = render::derives(ctx.src, &u.attrs);
+                (
+                    runion.generic_params,
+                    runion.lifetime_params,
+                    runion.where_clauses,
+                ) = render::generics(ctx.src, &u.generics);
+                runion.fields = fields(ctx, &Fields::Named(u.fields.clone()));
+                let mut ty = RustType::new(runion.name.clone());
+                ty.is_union = true;
+                module.types.insert(runion.name.clone(), ty);
+                module.unions.insert(runion.name.clone(), runion);
+            }
+            Item::Trait(t) => {
+                let name = t.ident.to_string();
+                if module.traits.contains_key(&name) {
+                    let start = item_start(&t.vis, t.trait_token.span);
+                    shadow(ctx, module, "trait", name, &t.attrs, (start, t.span()));
+                    continue;
+                }
+                let rtrait = rtrait(ctx, t);
+                module.traits.insert(rtrait.name.clone(), rtrait);
+            }
+            Item::Impl(i) => module.impls.push(rimpl(ctx, i)),
+            Item::Type(t) => {
+                let start = item_start(&t.vis, t.type_token.span);
+                let name = t.ident.to_string();
+                if module.type_aliases.contains_key(&name) {
+                    shadow(ctx, module, "type", name, &t.attrs, (start, t.span()));
+                    continue;
+                }
+                let (start_line, end_line, _) = item_code(ctx.src, start, t.span());
+                let (generic_params, lifetime_params, where_clauses) =
+                    render::generics(ctx.src, &t.generics);
+                let alias = RustTypeAlias {
+                    name: t.ident.to_string(),
+                    visibility: render::visibility(&t.vis),
+                    doc_comment: render::doc_comment(&t.attrs),
+                    attributes: render::attributes(ctx.src, &t.attrs),
+                    generic_params,
+                    lifetime_params,
+                    where_clauses,
+                    target_type: render::rust_type(ctx.src, &t.ty),
+                    start_line,
+                    end_line,
+                };
+                module.type_aliases.insert(alias.name.clone(), alias);
+            }
+            Item::Const(c) => {
+                let mut decl = RustVariableDeclaration::new(
+                    c.ident.to_string(),
+                    item_start(&c.vis, c.const_token.span).start().line,
+                );
+                decl.is_const = true;
+                decl.type_info = Some(render::rust_type(ctx.src, &c.ty));
+                decl.initializer = Some(render::text(ctx.src, &*c.expr));
+                decl.visibility = render::visibility(&c.vis);
+                decl.doc_comment = render::doc_comment(&c.attrs);
+                decl.attributes = render::attributes(ctx.src, &c.attrs);
+                module.constants.push(decl);
+            }
+            Item::Static(s) => {
+                let mut decl = RustVariableDeclaration::new(
+                    s.ident.to_string(),
+                    item_start(&s.vis, s.static_token.span).start().line,
+                );
+                decl.is_static = true;
+                decl.is_mut = matches!(s.mutability, syn::StaticMutability::Mut(_));
+                decl.type_info = Some(render::rust_type(ctx.src, &s.ty));
+                decl.initializer = Some(render::text(ctx.src, &*s.expr));
+                decl.visibility = render::visibility(&s.vis);
+                decl.doc_comment = render::doc_comment(&s.attrs);
+                decl.attributes = render::attributes(ctx.src, &s.attrs);
+                module.constants.push(decl);
+            }
+            Item::Macro(m) => {
+                if m.ident.is_none() && is_asm_macro(&m.mac) {
+                    let mut asm = asm::inline_asm(ctx.src, &m.mac);
+                    asm.cfg = render::cfg_predicates(ctx.src, &m.attrs);
+                    module.global_asm.push(asm);
+                } else if let Some(ident) = &m.ident {
+                    let (start_line, end_line) = render::lines(m);
+                    let mut mac = RustMacro::new(ident.to_string(), start_line, end_line);
+                    mac.doc_comment = render::doc_comment(&m.attrs);
+                    mac.attributes = render::attributes(ctx.src, &m.attrs);
+                    mac.rules = macro_rules(ctx.src, &m.mac.tokens);
+                    if render::has_attr(&m.attrs, "macro_export") {
+                        mac.visibility = RustVisibility::Public;
+                        mac.exported_from_macro_use = true;
+                    }
+                    module.macros.insert(mac.name.clone(), mac);
+                }
+            }
+            Item::Use(u) => {
+                let mut paths = Vec::new();
+                flatten_use(&u.tree, String::new(), &mut paths);
+                module.use_declarations.extend(paths);
+            }
+            Item::ExternCrate(e) => {
+                let name = match &e.rename {
+                    Some((_, alias)) => format!("{} as {}", e.ident, alias),
+                    None => e.ident.to_string(),
+                };
+                module.extern_crates.push(name);
+            }
+            Item::ForeignMod(f) => module.foreign_blocks.push(foreign_block(ctx, f)),
+            Item::Mod(m) => submodules.push(m),
+            _ => {}
+        }
+    }
+    submodules
+}
+
+/// Returns the ABI named by `extern "..."`, which defaults to `"C"`.
+fn abi_name(abi: &syn::Abi) -> String {
+    abi.name
+        .as_ref()
+        .map(|n| n.value())
+        .unwrap_or_else(|| "C".to_owned())
+}
+
+/// Builds the entity of an `extern` block. Items disabled by `#[cfg]` and
+/// foreign types and macros are skipped.
+fn foreign_block(ctx: &ItemContext, block: &syn::ItemForeignMod) -> RustForeignBlock {
+    let start = match &block.unsafety {
+        Some(unsafety) => unsafety.span,
+        None => block.abi.extern_token.span,
+    };
+    let (start_line, end_line, _) = item_code(ctx.src, start, block.span());
+    let mut rblock = RustForeignBlock::new(abi_name(&block.abi), start_line, end_line);
+    rblock.is_unsafe = block.unsafety.is_some();
+    rblock.attributes = render::attributes(ctx.src, &block.attrs);
+    rblock.links = block.attrs.iter().filter_map(link).collect();
+    for item in &block.items {
+        let (item, is_safe) = match item {
+            ForeignItem::Verbatim(tokens) => match render::safe_foreign_item(tokens) {
+                Some(item) => (item, true),
+                None => continue,
+            },
+            item => (item.clone(), false),
+        };
+        match item {
+            ForeignItem::Fn(f) if ctx.cfg.is_enabled(&f.attrs) => {
+                let start = item_start(&f.vis, f.sig.span());
+                let (start_line, end_line, _) = item_code(ctx.src, start, f.span());
+                let (parameters, is_variadic) = foreign_parameters(ctx, &f.sig);
+                let function = RustForeignFunction {
+                    name: f.sig.ident.to_string(),
+                    link_name: link_name(&f.attrs),
+                    visibility: render::visibility(&f.vis),
+                    doc_comment: render::doc_comment(&f.attrs),
+                    attributes: render::attributes(ctx.src, &f.attrs),
+                    parameters,
+                    return_type: render::return_type(ctx.src, &f.sig.output),
+                    is_variadic,
+                    is_safe,
+                    start_line,
+                    end_line,
+                };
+                rblock.functions.insert(function.name.clone(), function);
+            }
+            ForeignItem::Static(s) if ctx.cfg.is_enabled(&s.attrs) => {
+                let start = item_start(&s.vis, s.static_token.span);
+                let (start_line, end_line, _) = item_code(ctx.src, start, s.span());
+                let rstatic = RustForeignStatic {
+                    name: s.ident.to_string(),
+                    link_name: link_name(&s.attrs),
+                    visibility: render::visibility(&s.vis),
+                    doc_comment: render::doc_comment(&s.attrs),
+                    attributes: render::attributes(ctx.src, &s.attrs),
+                    rust_type: render::rust_type(ctx.src, &s.ty),
+                    is_mut: matches!(s.mutability, syn::StaticMutability::Mut(_)),
+                    is_safe,
+                    start_line,
+                    end_line,
+                };
+                rblock.statics.insert(rstatic.name.clone(), rstatic);
+            }
+            _ => {}
+        }
+    }
+    rblock
+}
+
+/// Returns the parameters of a foreign function and whether it is variadic.
+fn foreign_parameters(ctx: &ItemContext, sig: &Signature) -> (Vec<RustParameter>, bool) {
+    let parameters = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(t) => Some(RustParameter::new(
+                render::text(ctx.src, &*t.pat),
+                render::rust_type(ctx.src, &t.ty),
+            )),
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    (parameters, sig.variadic.is_some())
+}
+
+/// Parses `#[link(name = "...", kind = "...")]`.
+fn link(attr: &Attribute) -> Option<RustLink> {
+    if !attr.path().is_ident("link") {
+        return None;
+    }
+    let args = attr
+        .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+        .ok()?;
+    let value = |key: &str| {
+        args.iter()
+            .filter(|nv| nv.path.is_ident(key))
+            .find_map(|nv| match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value()),
+                _ => None,
+            })
+    };
+    Some(RustLink {
+        name: value("name")?,
+        kind: value("kind"),
+    })
+}
+
+/// Returns the symbol named by `#[link_name = "..."]`.
+fn link_name(attrs: &[Attribute]) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("link_name"))
+        .find_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(s), ..
+                    }),
+                ..
+            }) => Some(s.value()),
+            _ => None,
+        })
+}
+
+/// Initializes the module-level fields of a module declared by `decl`.
+pub fn init_module(
+    src: &SourceText,
+    module: &mut RustModule,
+    decl: Option<&ItemMod>,
+    inner_attrs: &[Attribute],
+) {
+    let mut attrs: Vec<Attribute> = decl.map(|d| d.attrs.clone()).unwrap_or_default();
+    attrs.extend(inner_attrs.iter().cloned());
+    module.doc_comment = render::doc_comment(&attrs);
+    module.attributes = render::attributes(src, &attrs);
+    if let Some(decl) = decl {
+        module.visibility = render::visibility(&decl.vis);
+        module.is_unsafe = decl.unsafety.is_some();
+    }
+}
+
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(i) => &i.attrs,
+        Item::Enum(i) => &i.attrs,
+        Item::ExternCrate(i) => &i.attrs,
+        Item::Fn(i) => &i.attrs,
+        Item::ForeignMod(i) => &i.attrs,
+        Item::Impl(i) => &i.attrs,
+        Item::Macro(i) => &i.attrs,
+        Item::Mod(i) => &i.attrs,
+        Item::Static(i) => &i.attrs,
+        Item::Struct(i) => &i.attrs,
+        Item::Trait(i) => &i.attrs,
+        Item::TraitAlias(i) => &i.attrs,
+        Item::Type(i) => &i.attrs,
+        Item::Union(i) => &i.attrs,
+        Item::Use(i) => &i.attrs,
+        _ => &[],
+    }
+}
+
+fn fields(ctx: &ItemContext, fields: &Fields) -> Vec<RustStructField> {
+    fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| ctx.cfg.is_enabled(&f.attrs))
+        .map(|(i, f)| {
+            let name = f
+                .ident
+                .as_ref()
+                .map(|i| i.to_string())
+                .unwrap_or_else(|| i.to_string());
+            let mut field = RustStructField::new(name, render::rust_type(ctx.src, &f.ty));
+            field.visibility = render::visibility(&f.vis);
+            field.doc_comment = render::doc_comment(&f.attrs);
+            field.attributes = render::attributes(ctx.src, &f.attrs);
+            field
+        })
+        .collect()
+}
+
+/// Builds a callable from a function signature and optional body.
+///
+/// `span` is the first and last span of the callable's source. Methods of
+/// `unsafe impl` blocks pass the implemented trait as `unsafe_trait`.
+fn callable(
+    ctx: &ItemContext,
+    attrs: &[Attribute],
+    vis: &syn::Visibility,
+    sig: &Signature,
+    block: Option<&syn::Block>,
+    span: (Span, Span),
+    unsafe_trait: Option<&str>,
+) -> RustCallable {
+    let (start_line, end_line, code) = item_code(ctx.src, span.0, span.1);
+    let mut callable = RustCallable::new(
+        sig.ident.to_string(),
+        code,
+        start_line,
+        end_line,
+        SafetyAnalysis::new(SafetyClassification::Safe),
+    );
+    callable.visibility = render::visibility(vis);
+    callable.doc_comment = render::doc_comment(attrs);
+    callable.attributes = render::attributes(ctx.src, attrs);
+    callable.is_async = sig.asyncness.is_some();
+    callable.is_const = sig.constness.is_some();
+    callable.is_unsafe = sig.unsafety.is_some();
+    callable.is_extern = sig.abi.is_some();
+    callable.extern_abi = sig.abi.as_ref().map(abi_name);
+    callable.export_name = exported_symbol(&callable.attributes, &callable.name);
+    (
+        callable.generic_params,
+        callable.lifetime_params,
+        callable.where_clauses,
+    ) = render::generics(ctx.src, &sig.generics);
+    callable.return_type = render::return_type(ctx.src, &sig.output);
+    callable.parameters = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Receiver(r) => {
+                let mut param =
+                    RustParameter::new("self".to_owned(), render::rust_type(ctx.src, &r.ty));
+                param.is_self = true;
+                param.is_mut = r.mutability.is_some();
+                param
+            }
+            FnArg::Typed(t) => {
+                let (name, is_mut) = match &*t.pat {
+                    syn::Pat::Ident(i) => (i.ident.to_string(), i.mutability.is_some()),
+                    pat => (render::text(ctx.src, pat), false),
+                };
+                let mut param = RustParameter::new(name, render::rust_type(ctx.src, &t.ty));
+                param.is_mut = is_mut;
+                param
+            }
+        })
+        .collect();
+
+    let mut referenced: Vec<String> = Vec::new();
+    for ty in callable
+        .parameters
+        .iter()
+        .map(|p| &p.rust_type)
+        .chain(callable.return_type.iter())
+    {
+        if !referenced.contains(&ty.name) {
+            referenced.push(ty.name.clone());
+        }
+    }
+
+    let safety = &mut callable.safety_analysis;
+    safety.raw_pointer_usage = callable
+        .parameters
+        .iter()
+        .map(|p| &p.rust_type)
+        .chain(callable.return_type.iter())
+        .any(|t| t.contains_raw_pointers);
+    safety.ffi_interactions = callable.is_extern;
+    if let Some(trait_name) = unsafe_trait {
+        safety.unsafe_traits_used.push(trait_name.to_owned());
+    }
+    safety.safety_comments = callable
+        .doc_comment
+        .as_deref()
+        .and_then(|doc| doc_section(doc, "Safety"));
+
+    if let Some(block) = block {
+        let params: Vec<(String, RustType)> = callable
+            .parameters
+            .iter()
+            .map(|p| (p.name.clone(), p.rust_type.clone()))
+            .collect();
+        let body_ctx = BodyContext {
+            src: ctx.src,
+            index: ctx.index,
+            caller: &callable.name,
+            module_path: ctx.module_path,
+            collect_calls: ctx.level >= 2,
+            unsafe_fn: callable.is_unsafe,
+            async_fn: callable.is_async,
+            self_type: ctx.self_type,
+        };
+        let facts = body::analyze(&body_ctx, &params, block);
+        for ty in facts.referenced_types {
+            if !referenced.contains(&ty) {
+                referenced.push(ty);
+            }
+        }
+        callable.call_sites = facts.call_sites;
+        callable.instantiated_types = facts.instantiated_types;
+        callable.variable_declarations = facts.variable_declarations;
+        callable.accessed_variables = facts.accessed_variables;
+        let control_flow = if ctx.level >= 3 {
+            let lowered = flow::lower(ctx.src, sig, block);
+            callable.dependence = Some(dependence::analyze(&lowered));
+            lowered.graph
+        } else {
+            flow::build(ctx.src, block)
+        };
+        callable.cyclomatic_complexity = Some(control_flow.cyclomatic_complexity());
+        if ctx.level >= 2 {
+            callable.control_flow = Some(control_flow);
+        }
+        let safety = &mut callable.safety_analysis;
+        safety.unsafe_blocks = facts.unsafe_blocks;
+        safety.unsafe_fn_calls = facts.unsafe_fn_calls;
+        safety.raw_pointer_usage |= facts.raw_pointer_usage;
+        safety.ffi_interactions |= facts.ffi_interactions;
+        safety.ffi_calls = facts.ffi_calls;
+        safety.mutable_statics = facts.mutable_statics;
+        callable.inline_asm = facts.inline_asm;
+        callable.panic_sites = facts.panic_sites;
+        callable.error_propagations = facts.error_propagations;
+        callable.swallowed_errors = facts.swallowed_errors;
+        callable.await_points = facts.await_points;
+        callable.spawned_tasks = facts.spawned_tasks;
+        callable.blocking_calls = facts.blocking_calls;
+        callable.lock_acquisitions = facts.lock_acquisitions;
+        callable.guards_across_await = facts.guards_across_await;
+        gate_inline_asm(ctx.src, &mut callable, attrs);
+    }
+    callable.referenced_types = referenced;
+
+    let safety = &mut callable.safety_analysis;
+    safety.classification = if callable.is_extern {
+        SafetyClassification::Ffi
+    } else if callable.is_unsafe {
+        SafetyClassification::Unsafe
+    } else if !safety.unsafe_blocks.is_empty() {
+        SafetyClassification::UnsafeContainer
+    } else {
+        SafetyClassification::Safe
+    };
+    callable
+}
+
+/// Adds the `#[cfg]` predicates among `attrs`, those of the callable or of
+/// an item around it, in front of those of its inline assembly.
+fn gate_inline_asm(src: &SourceText, callable: &mut RustCallable, attrs: &[Attribute]) {
+    let cfg = render::cfg_predicates(src, attrs);
+    if cfg.is_empty() {
+        return;
+    }
+    for asm in &mut callable.inline_asm {
+        asm.cfg.splice(0..0, cfg.iter().cloned());
+    }
+}
+
+/// Returns the text of the `# <heading>` section of a doc comment.
+pub fn doc_section(doc: &str, heading: &str) -> Option<String> {
+    let mut lines = doc.lines().skip_while(|line| {
+        let line = line.trim();
+        !(line.starts_with('#') && line.trim_start_matches('#').trim() == heading)
+    });
+    lines.next()?;
+    let section: Vec<&str> = lines
+        .take_while(|line| !line.trim_start().starts_with('#'))
+        .collect();
+    Some(section.join("\n").trim().to_owned())
+}
+
+/// Returns a macro entity for `#[proc_macro]`, `#[proc_macro_derive]` and
+/// `#[proc_macro_attribute]` functions.
+fn proc_macro(attrs: &[Attribute], callable: &RustCallable) -> Option<RustMacro> {
+    let is_derive = render::has_attr(attrs, "proc_macro_derive");
+    let is_attribute = render::has_attr(attrs, "proc_macro_attribute");
+    let is_function_like = render::has_attr(attrs, "proc_macro");
+    if !(is_derive || is_attribute || is_function_like) {
+        return None;
+    }
+    let name = if is_derive {
+        callable
+            .attributes
+            .iter()
+            .find(|a| a.name == "proc_macro_derive")
+            .and_then(|a| a.arguments.first().cloned())
+            .unwrap_or_else(|| callable.name.clone())
+    } else {
+        callable.name.clone()
+    };
+    let mut mac = RustMacro::new(name, callable.start_line, callable.end_line);
+    mac.visibility = callable.visibility.clone();
+    mac.doc_comment = callable.doc_comment.clone();
+    mac.attributes = callable.attributes.clone();
+    mac.is_procedural = true;
+    mac.is_derive = is_derive;
+    mac.is_attribute = is_attribute;
+    mac.is_function_like = is_function_like;
+    Some(mac)
+}
+
+/// Returns the matcher of every rule of a `macro_rules!` body.
+fn macro_rules(src: &SourceText, tokens: &proc_macro2::TokenStream) -> Vec<String> {
+    let tokens: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    let mut rules = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let is_arrow = matches!(
+            (tokens.get(i + 1), tokens.get(i + 2)),
+            (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b))) if a.as_char() == '=' && b.as_char() == '>'
+        );
+        if let (TokenTree::Group(group), true) = (token, is_arrow) {
+            let text = src
+                .slice(group.span())
+                .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_else(|| group.to_string());
+            rules.push(text);
+        }
+    }
+    rules
+}
+
+fn flatten_use(tree: &UseTree, prefix: String, out: &mut Vec<String>) {
+    let join = |name: String| {
+        if prefix.is_empty() {
+            name
+        } else {
+            format!("{}::{}", prefix, name)
+        }
+    };
+    match tree {
+        UseTree::Path(p) => flatten_use(&p.tree, join(p.ident.to_string()), out),
+        UseTree::Name(n) => out.push(join(n.ident.to_string())),
+        UseTree::Rename(r) => out.push(format!("{} as {}", join(r.ident.to_string()), r.rename)),
+        UseTree::Glob(_) => out.push(join("*".to_owned())),
+        UseTree::Group(g) => {
+            for tree in &g.items {
+                flatten_use(tree, prefix.clone(), out);
+            }
+        }
+    }
+}
+
+fn rtrait(ctx: &ItemContext, t: &syn::ItemTrait) -> RustTrait {
+    let start = item_start(&t.vis, t.trait_token.span);
+    let (start_line, end_line, _) = item_code(ctx.src, start, t.span());
+    let (generic_params, lifetime_params, where_clauses) = render::generics(ctx.src, &t.generics);
+    let mut rtrait = RustTrait {
+        name: t.ident.to_string(),
+        visibility: render::visibility(&t.vis),
+        doc_comment: render::doc_comment(&t.attrs),
+        attributes: render::attributes(ctx.src, &t.attrs),
+        generic_params,
+        lifetime_params,
+        where_clauses,
+        super_traits: t
+            .supertraits
+            .iter()
+            .filter_map(|b| render::trait_bound(ctx.src, b))
+            .collect(),
+        associated_types: BTreeMap::new(),
+        associated_consts: BTreeMap::new(),
+        methods: BTreeMap::new(),
+        is_unsafe: t.unsafety.is_some(),
+        is_auto: t.auto_token.is_some(),
+        start_line,
+        end_line,
+    };
+    for item in &t.items {
+        match item {
+            TraitItem::Fn(f) if ctx.cfg.is_enabled(&f.attrs) => {
+                let end = match &f.default {
+                    Some(block) => block.span(),
+                    None => f.span(),
+                };
+                let mut method = callable(
+                    ctx,
+                    &f.attrs,
+                    &t.vis,
+                    &f.sig,
+                    f.default.as_ref(),
+                    (f.sig.span(), end),
+                    None,
+                );
+                method.visibility = render::visibility(&t.vis);
+                gate_inline_asm(ctx.src, &mut method, &t.attrs);
+                rtrait.methods.insert(method.name.clone(), method);
+            }
+            TraitItem::Type(ty) if ctx.cfg.is_enabled(&ty.attrs) => {
+                let rust_type = match &ty.default {
+                    Some((_, default)) => render::rust_type(ctx.src, default),
+                    None => RustType::new(ty.ident.to_string()),
+                };
+                rtrait
+                    .associated_types
+                    .insert(ty.ident.to_string(), rust_type);
+            }
+            TraitItem::Const(c) if ctx.cfg.is_enabled(&c.attrs) => {
+                let value = match &c.default {
+                    Some((_, expr)) => render::text(ctx.src, expr),
+                    None => render::text(ctx.src, &c.ty),
+                };
+                rtrait.associated_consts.insert(c.ident.to_string(), value);
+            }
+            _ => {}
+        }
+    }
+    rtrait
+}
+
+fn rimpl(ctx: &ItemContext, i: &syn::ItemImpl) -> RustImpl {
+    let start = match &i.unsafety {
+        Some(unsafety) => unsafety.span,
+        None => i.impl_token.span,
+    };
+    let (start_line, end_line, _) = item_code(ctx.src, start, i.span());
+    let (generic_params, lifetime_params, where_clauses) = render::generics(ctx.src, &i.generics);
+    let trait_name = i
+        .trait_
+        .as_ref()
+        .map(|(_, path, _)| render::text(ctx.src, path));
+    let mut rimpl = RustImpl {
+        type_name: render::text(ctx.src, &*i.self_ty),
+        trait_name,
+        generic_params,
+        lifetime_params,
+        where_clauses,
+        methods: BTreeMap::new(),
+        associated_types: BTreeMap::new(),
+        associated_consts: BTreeMap::new(),
+        is_unsafe: i.unsafety.is_some(),
+        is_negative: i.trait_.as_ref().is_some_and(|(bang, _, _)| bang.is_some()),
+        start_line,
+        end_line,
+    };
+    let unsafe_trait = rimpl.trait_name.clone().filter(|_| rimpl.is_unsafe);
+    let self_type = render::base_type_name(&i.self_ty);
+    let ctx = &ItemContext {
+        self_type: self_type.as_deref(),
+        ..*ctx
+    };
+    for item in &i.items {
+        match item {
+            ImplItem::Fn(f) if ctx.cfg.is_enabled(&f.attrs) => {
+                let start = item_start(&f.vis, f.sig.span());
+                let mut method = callable(
+                    ctx,
+                    &f.attrs,
+                    &f.vis,
+                    &f.sig,
+                    Some(&f.block),
+                    (start, f.block.span()),
+                    unsafe_trait.as_deref(),
+                );
+                gate_inline_asm(ctx.src, &mut method, &i.attrs);
+                rimpl.methods.insert(method.name.clone(), method);
+            }
+            ImplItem::Type(t) if ctx.cfg.is_enabled(&t.attrs) => {
+                rimpl
+                    .associated_types
+                    .insert(t.ident.to_string(), render::rust_type(ctx.src, &t.ty));
+            }
+            ImplItem::Const(c) if ctx.cfg.is_enabled(&c.attrs) => {
+                rimpl
+                    .associated_consts
+                    .insert(c.ident.to_string(), render::text(ctx.src, &c.expr));
+            }
+            _ => {}
+        }
+    }
+    rimpl
+}
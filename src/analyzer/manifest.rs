@@ -0,0 +1,228 @@
+//! Minimal reader for the parts of `Cargo.toml` the analyzer needs.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::AnalyzerError;
+use crate::entities::dep::RustDependency;
+
+#[derive(Debug, Default, Deserialize)]
+struct RawManifest {
+    package: Option<RawPackage>,
+    workspace: Option<RawWorkspace>,
+    lib: Option<RawTarget>,
+    #[serde(default)]
+    bin: Vec<RawTarget>,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawPackage {
+    name: String,
+    version: Option<toml::Value>,
+    edition: Option<toml::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawTarget {
+    name: Option<String>,
+    path: Option<String>,
+}
+
+/// A compilation target of a package: its crate name and root source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_lib: bool,
+}
+
+/// A package manifest, with targets resolved against the package directory.
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    /// Directory containing `Cargo.toml`.
+    pub dir: PathBuf,
+    /// Package name, if the manifest declares a package.
+    pub name: Option<String>,
+    pub version: String,
+    pub edition: String,
+    pub targets: Vec<Target>,
+    pub features: BTreeMap<String, Vec<String>>,
+    pub dependencies: Vec<RustDependency>,
+    /// Member directories when the manifest declares a workspace.
+    pub workspace_members: Vec<PathBuf>,
+}
+
+impl Manifest {
+    /// Reads `Cargo.toml` from `dir`.
+    pub fn read(dir: &Path) -> Result<Self, AnalyzerError> {
+        let path = dir.join("Cargo.toml");
+        let text = std::fs::read_to_string(&path).map_err(|e| AnalyzerError::io(&path, e))?;
+        let raw: RawManifest = toml::from_str(&text).map_err(|e| AnalyzerError::Manifest {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+        Ok(Self::from_raw(dir, raw))
+    }
+
+    fn from_raw(dir: &Path, raw: RawManifest) -> Self {
+        let name = raw.package.as_ref().map(|p| p.name.clone());
+        let version = raw
+            .package
+            .as_ref()
+            .and_then(|p| p.version.as_ref())
+            .and_then(|v| v.as_str())
+            .unwrap_or("0.0.0")
+            .to_owned();
+        let edition = raw
+            .package
+            .as_ref()
+            .and_then(|p| p.edition.as_ref())
+            .and_then(|v| v.as_str())
+            .unwrap_or("2015")
+            .to_owned();
+
+        let mut targets = Vec::new();
+        if let Some(package_name) = &name {
+            let lib_name = package_name.replace('-', "_");
+            match &raw.lib {
+                Some(lib) => targets.push(Target {
+                    name: lib.name.clone().unwrap_or(lib_name),
+                    path: dir.join(lib.path.as_deref().unwrap_or("src/lib.rs")),
+                    is_lib: true,
+                }),
+                None if dir.join("src/lib.rs").is_file() => targets.push(Target {
+                    name: lib_name,
+                    path: dir.join("src/lib.rs"),
+                    is_lib: true,
+                }),
+                None => {}
+            }
+            for bin in &raw.bin {
+                let bin_name = bin.name.clone().unwrap_or_else(|| package_name.clone());
+                let path = match &bin.path {
+                    Some(path) => dir.join(path),
+                    None if bin_name == *package_name => dir.join("src/main.rs"),
+                    None => dir.join("src/bin").join(format!("{}.rs", bin_name)),
+                };
+                targets.push(Target {
+                    name: bin_name,
+                    path,
+                    is_lib: false,
+                });
+            }
+            let main = dir.join("src/main.rs");
+            if main.is_file() && !targets.iter().any(|t| t.path == main) {
+                targets.push(Target {
+                    name: package_name.clone(),
+                    path: main,
+                    is_lib: false,
+                });
+            }
+            if let Ok(entries) = std::fs::read_dir(dir.join("src/bin")) {
+                let mut bins: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "rs"))
+                    .collect();
+                bins.sort();
+                for path in bins {
+                    if targets.iter().any(|t| t.path == path) {
+                        continue;
+                    }
+                    let stem = path.file_stem().unwrap_or_default();
+                    targets.push(Target {
+                        name: stem.to_string_lossy().into_owned(),
+                        path,
+                        is_lib: false,
+                    });
+                }
+            }
+        }
+
+        let dependencies = raw
+            .dependencies
+            .iter()
+            .map(|(name, spec)| {
+                let mut dependency = RustDependency::new(name.clone(), None);
+                dependency.is_external =
+                    spec.get("path").is_none() && spec.get("workspace").is_none();
+                dependency
+            })
+            .collect();
+
+        let workspace_members = raw
+            .workspace
+            .map(|ws| expand_members(dir, &ws.members, &ws.exclude))
+            .unwrap_or_default();
+
+        Manifest {
+            dir: dir.to_path_buf(),
+            name,
+            version,
+            edition,
+            targets,
+            features: raw.features,
+            dependencies,
+            workspace_members,
+        }
+    }
+
+    /// Expands `requested` features (plus `default` unless disabled) through
+    /// the `[features]` table into the full set of enabled features.
+    pub fn enabled_features(&self, requested: &[String], default_features: bool) -> Vec<String> {
+        let mut enabled = BTreeSet::new();
+        let mut pending: Vec<String> = requested.to_vec();
+        if default_features && self.features.contains_key("default") {
+            pending.push("default".to_owned());
+        }
+        while let Some(feature) = pending.pop() {
+            if !enabled.insert(feature.clone()) {
+                continue;
+            }
+            for implied in self.features.get(&feature).into_iter().flatten() {
+                // `dep:name` and `name/feature` refer to dependencies, not features of this crate.
+                if !implied.starts_with("dep:") && !implied.contains('/') {
+                    pending.push(implied.clone());
+                }
+            }
+        }
+        enabled.into_iter().collect()
+    }
+}
+
+/// Expands workspace member globs. Only a trailing `*` segment is supported,
+/// which covers the common `crates/*` layout.
+fn expand_members(dir: &Path, members: &[String], exclude: &[String]) -> Vec<PathBuf> {
+    let excluded: Vec<PathBuf> = exclude.iter().map(|e| dir.join(e)).collect();
+    let mut out = Vec::new();
+    for member in members {
+        if let Some(prefix) = member.strip_suffix("/*") {
+            if let Ok(entries) = std::fs::read_dir(dir.join(prefix)) {
+                let mut found: Vec<PathBuf> = entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.join("Cargo.toml").is_file())
+                    .collect();
+                found.sort();
+                out.extend(found);
+            }
+        } else {
+            out.push(dir.join(member));
+        }
+    }
+    out.retain(|p| !excluded.contains(p));
+    out.dedup();
+    out
+}
@@ -0,0 +1,1055 @@
+//! The analyzer: turns a Cargo project or in-memory sources into entities.
+//!
+//! ```no_run
+//! use codeanalyzer_rs::Analyzer;
+//!
+//! let krate = Analyzer::new()
+//!     .project("path/to/project")
+//!     .features(["serde"])
+//!     .analysis_level(2)
+//!     .analyze()?;
+//! println!("{} has {} root modules", krate.name, krate.modules.len());
+//! # Ok::<(), codeanalyzer_rs::AnalyzerError>(())
+//! ```
+//!
+//! Analysis runs in two phases. The first phase follows `mod` declarations
+//! from every crate root, parses each file once and records crate-wide facts
+//! such as `static mut` items and foreign functions in a [`CrateIndex`]. The
+//! second phase extracts the entities of every module with that index at
+//! hand, and a final linking step attaches impls to the structs and enums
+//! they implement.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::analysis::callgraph::{self, Dispatch};
+use crate::entities::{
+    callable::RustCallable, module::RustModule, rcrate::RustCrate, rimpl::RustImpl,
+    workspace::RustWorkspace,
+};
+
+pub mod asm;
+pub mod body;
+pub mod cache;
+pub mod cfg;
+pub mod dependence;
+pub mod file;
+pub mod flow;
+pub mod index;
+pub mod items;
+pub mod manifest;
+pub mod render;
+pub mod source;
+
+use cache::{AnalysisCache, CacheKey};
+use cfg::CfgSet;
+use file::{ChildFile, FileAnalysis};
+use index::CrateIndex;
+use items::ItemContext;
+use manifest::{Manifest, Target};
+use rayon::prelude::*;
+use source::SourceSet;
+
+/// Highest supported analysis level.
+pub const MAX_ANALYSIS_LEVEL: u8 = 3;
+
+/// Errors reported by the [`Analyzer`].
+#[derive(Debug)]
+pub enum AnalyzerError {
+    /// A file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// `Cargo.toml` could not be parsed.
+    Manifest { path: PathBuf, message: String },
+    /// A source file is not valid Rust.
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// Neither a project nor any source was given.
+    NoInput,
+    /// The project is a virtual workspace without a package of its own.
+    NotAPackage(PathBuf),
+    /// The analysis level is not supported.
+    InvalidAnalysisLevel(u8),
+    /// A [`ModuleSink`] failed to write a module.
+    Sink(std::io::Error),
+    /// The worker threads could not be started.
+    Threads(rayon::ThreadPoolBuildError),
+}
+
+impl AnalyzerError {
+    pub(crate) fn io(path: &Path, source: std::io::Error) -> Self {
+        AnalyzerError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for AnalyzerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalyzerError::Io { path, source } => {
+                write!(f, "cannot read {}: {}", path.display(), source)
+            }
+            AnalyzerError::Manifest { path, message } => {
+                write!(f, "invalid manifest {}: {}", path.display(), message)
+            }
+            AnalyzerError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            AnalyzerError::NoInput => write!(f, "no project or source to analyze"),
+            AnalyzerError::NotAPackage(path) => write!(
+                f,
+                "{} is a virtual workspace; analyze it as a workspace",
+                path.display()
+            ),
+            AnalyzerError::InvalidAnalysisLevel(level) => write!(
+                f,
+                "unsupported analysis level {} (expected 1 to {})",
+                level, MAX_ANALYSIS_LEVEL
+            ),
+            AnalyzerError::Sink(e) => write!(f, "cannot write analysis output: {}", e),
+            AnalyzerError::Threads(e) => write!(f, "cannot start worker threads: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AnalyzerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AnalyzerError::Io { source, .. } => Some(source),
+            AnalyzerError::Sink(e) => Some(e),
+            AnalyzerError::Threads(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Receives modules one at a time while a crate is being analyzed.
+///
+/// Modules are handed over in pre-order without their submodules, so a sink
+/// can write them out and the analyzer does not need to keep the module tree.
+pub trait ModuleSink {
+    /// Called once per crate before any of its modules; `krate` has no modules.
+    fn crate_started(&mut self, krate: &RustCrate) -> std::io::Result<()>;
+
+    /// Called for every module. `parent` is the path of the enclosing module,
+    /// or `None` for crate roots.
+    fn module_analyzed(
+        &mut self,
+        krate: &str,
+        parent: Option<&str>,
+        path: &str,
+        module: &RustModule,
+    ) -> std::io::Result<()>;
+
+    /// Called once per crate after all of its modules. From analysis level 2
+    /// on, `krate` holds the module trees with the call sites resolved, but
+    /// without the code, control flow and dependence graphs of callables;
+    /// below, it has no modules. Does nothing by default.
+    fn crate_finished(&mut self, krate: &RustCrate) -> std::io::Result<()> {
+        let _ = krate;
+        Ok(())
+    }
+}
+
+/// Builder for an analysis run.
+///
+/// # Determinism
+///
+/// Entities are kept in maps ordered by name and in lists ordered by source
+/// position, so the serialized result does not depend on hashing, on the
+/// number of jobs or on the cache:
+///
+/// ```
+/// use codeanalyzer_rs::Analyzer;
+///
+/// let code = "mod b { pub fn y() {} pub fn x() {} } mod a {} pub struct Z; pub struct A;";
+/// let analyze = |jobs| {
+///     Analyzer::new()
+///         .source("lib.rs", code)
+///         .analysis_level(2)
+///         .jobs(jobs)
+///         .analyze()
+/// };
+/// let first = analyze(1)?;
+/// let second = analyze(4)?;
+/// assert_eq!(
+///     serde_json::to_string(&first).unwrap(),
+///     serde_json::to_string(&second).unwrap()
+/// );
+///
+/// let root = &first.modules[0];
+/// assert_eq!(root.submodules.keys().collect::<Vec<_>>(), ["a", "b"]);
+/// assert_eq!(root.structs.keys().collect::<Vec<_>>(), ["A", "Z"]);
+/// let b = &root.submodules["b"];
+/// assert_eq!(b.functions.keys().collect::<Vec<_>>(), ["x", "y"]);
+/// # Ok::<(), codeanalyzer_rs::AnalyzerError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Analyzer {
+    project: Option<PathBuf>,
+    sources: Vec<(PathBuf, String)>,
+    target_files: Vec<PathBuf>,
+    features: Vec<String>,
+    default_features: bool,
+    analysis_level: u8,
+    crate_name: Option<String>,
+    cache: Option<AnalysisCache>,
+    jobs: usize,
+    dispatch: Dispatch,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self {
+            project: None,
+            sources: Vec::new(),
+            target_files: Vec::new(),
+            features: Vec::new(),
+            default_features: true,
+            analysis_level: 1,
+            crate_name: None,
+            cache: None,
+            jobs: 0,
+            dispatch: Dispatch::Cha,
+        }
+    }
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Analyzes the Cargo project (package or workspace) in `path`.
+    pub fn project(mut self, path: impl Into<PathBuf>) -> Self {
+        self.project = Some(path.into());
+        self
+    }
+
+    /// Adds an in-memory source file.
+    ///
+    /// Together with [`project`](Self::project), a relative `path` is taken
+    /// relative to the project and replaces the file on disk. Without a
+    /// project, every source that is not reached through a `mod` declaration
+    /// of an earlier source becomes a crate root: the root of a binary for a
+    /// `main.rs`, and of a library otherwise.
+    pub fn source(mut self, path: impl Into<PathBuf>, code: impl Into<String>) -> Self {
+        self.sources.push((path.into(), code.into()));
+        self
+    }
+
+    /// Restricts entity extraction to these files. The module tree is still
+    /// built from the whole crate, but modules stored in other files are left
+    /// empty.
+    pub fn target_files<I, P>(mut self, files: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.target_files = files.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Enables cargo features when evaluating `#[cfg(feature = "...")]`.
+    pub fn features<I, S>(mut self, features: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether the `default` feature is enabled. Defaults to `true`.
+    pub fn default_features(mut self, enabled: bool) -> Self {
+        self.default_features = enabled;
+        self
+    }
+
+    /// Level of analysis: 1 for the symbol table, 2 to also collect call sites
+    /// and control flow graphs, 3 to also build the program dependence graph
+    /// of every callable. Defaults to 1.
+    pub fn analysis_level(mut self, level: u8) -> Self {
+        self.analysis_level = level;
+        self
+    }
+
+    /// Name of the crate built from in-memory sources. Defaults to `source`.
+    pub fn crate_name(mut self, name: impl Into<String>) -> Self {
+        self.crate_name = Some(name.into());
+        self
+    }
+
+    /// Caches per-file results in `dir`, so that unchanged files are not
+    /// parsed again by later runs. Off by default.
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache = Some(AnalysisCache::new(dir));
+        self
+    }
+
+    /// Number of files analyzed in parallel; `0`, the default, uses one
+    /// thread per CPU.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// How calls dispatched through a trait are resolved when the call sites
+    /// of the crate are linked, see [`CallSite::callees`]. Defaults to
+    /// [`Dispatch::Cha`].
+    ///
+    /// [`CallSite::callees`]: crate::entities::callsite::CallSite::callees
+    pub fn dispatch(mut self, dispatch: Dispatch) -> Self {
+        self.dispatch = dispatch;
+        self
+    }
+
+    /// How calls dispatched through a trait are resolved.
+    pub fn dispatch_mode(&self) -> Dispatch {
+        self.dispatch
+    }
+
+    /// The project directory, if one was set.
+    pub fn project_dir(&self) -> Option<&Path> {
+        self.project.as_deref()
+    }
+
+    /// Links a crate whose module trees were put together from separate
+    /// analyses the way [`analyze`](Self::analyze) links its own: impls are
+    /// attached to their types and, from analysis level 2 on, call sites are
+    /// resolved and unsafe code is propagated along the call graph.
+    pub fn link(&self, krate: &mut RustCrate) {
+        link(krate, self.analysis_level, self.dispatch);
+    }
+
+    /// Analyzes a single package, or the in-memory sources.
+    pub fn analyze(&self) -> Result<RustCrate, AnalyzerError> {
+        self.run(None)
+    }
+
+    /// Analyzes the project, or every package of a workspace, handing each
+    /// module to `sink` as soon as it has been analyzed instead of building
+    /// module trees. Files are extracted one at a time, just before their
+    /// modules are handed over, so only one file's entities are in memory.
+    ///
+    /// Since modules are handed over before the crate is complete, they are
+    /// not linked: impls are not attached to the structs, enums and unions
+    /// they implement, call sites have no resolved `callees`, and unsafe code
+    /// and panics are not propagated along the call graph, so the
+    /// `transitive` safety summary and the `transitive_panics` of every
+    /// callable are `None`. Use [`analyze`](Self::analyze) for these.
+    ///
+    /// From analysis level 2 on, the modules are kept without the code,
+    /// control flow and dependence graphs of their callables, and once the
+    /// crate is complete its call sites are resolved and the crate is handed
+    /// to [`ModuleSink::crate_finished`].
+    pub fn analyze_streaming(&self, sink: &mut dyn ModuleSink) -> Result<(), AnalyzerError> {
+        self.check_level()?;
+        let Some(root) = &self.project else {
+            return self.run(Some(sink)).map(drop);
+        };
+        let manifest = Manifest::read(root)?;
+        let sources = self.source_set(root);
+        if manifest.name.is_some() {
+            self.analyze_package(&manifest, &sources, Some(&mut *sink))?;
+        }
+        for dir in &manifest.workspace_members {
+            let member = Manifest::read(dir)?;
+            if member.name.is_some() {
+                self.analyze_package(&member, &sources, Some(&mut *sink))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Analyzes every member of a workspace. A project that is a plain
+    /// package is returned as a workspace with a single member.
+    pub fn analyze_workspace(&self) -> Result<RustWorkspace, AnalyzerError> {
+        self.check_level()?;
+        let Some(root) = &self.project else {
+            let krate = self.analyze()?;
+            return Ok(RustWorkspace::new(String::new(), vec![krate]));
+        };
+        let manifest = Manifest::read(root)?;
+        let sources = self.source_set(root);
+        let mut members = Vec::new();
+        if manifest.name.is_some() {
+            members.push(self.analyze_package(&manifest, &sources, None)?);
+        }
+        for dir in &manifest.workspace_members {
+            let member = Manifest::read(dir)?;
+            if member.name.is_some() {
+                members.push(self.analyze_package(&member, &sources, None)?);
+            }
+        }
+        Ok(RustWorkspace::new(
+            root.to_string_lossy().into_owned(),
+            members,
+        ))
+    }
+
+    fn check_level(&self) -> Result<(), AnalyzerError> {
+        if (1..=MAX_ANALYSIS_LEVEL).contains(&self.analysis_level) {
+            Ok(())
+        } else {
+            Err(AnalyzerError::InvalidAnalysisLevel(self.analysis_level))
+        }
+    }
+
+    fn source_set(&self, root: &Path) -> SourceSet {
+        SourceSet {
+            root: Some(root.to_path_buf()),
+            virtual_files: self
+                .sources
+                .iter()
+                .map(|(path, code)| (root.join(path), code.clone()))
+                .collect(),
+        }
+    }
+
+    fn run(&self, sink: Option<&mut dyn ModuleSink>) -> Result<RustCrate, AnalyzerError> {
+        self.check_level()?;
+        match &self.project {
+            Some(root) => {
+                let manifest = Manifest::read(root)?;
+                if manifest.name.is_none() {
+                    return Err(AnalyzerError::NotAPackage(root.clone()));
+                }
+                self.analyze_package(&manifest, &self.source_set(root), sink)
+            }
+            None if !self.sources.is_empty() => self.analyze_sources(sink),
+            None => Err(AnalyzerError::NoInput),
+        }
+    }
+
+    fn analyze_package(
+        &self,
+        manifest: &Manifest,
+        sources: &SourceSet,
+        sink: Option<&mut dyn ModuleSink>,
+    ) -> Result<RustCrate, AnalyzerError> {
+        let features = manifest.enabled_features(&self.features, self.default_features);
+        let mut krate = RustCrate::new(
+            manifest.name.clone().unwrap_or_default(),
+            manifest.version.clone(),
+            Vec::new(),
+        );
+        krate.edition = manifest.edition.clone();
+        krate.is_lib = manifest.targets.iter().any(|t| t.is_lib);
+        krate.features = features.clone();
+        krate.dependencies = manifest.dependencies.clone();
+        let targets: Vec<Target> = manifest
+            .targets
+            .iter()
+            .filter(|t| sources.exists(&t.path))
+            .cloned()
+            .collect();
+        self.analyze_targets(krate, &targets, sources, CfgSet::new(features), sink)
+    }
+
+    fn analyze_sources(
+        &self,
+        sink: Option<&mut dyn ModuleSink>,
+    ) -> Result<RustCrate, AnalyzerError> {
+        let sources = SourceSet {
+            root: None,
+            virtual_files: self.sources.iter().cloned().collect(),
+        };
+        let name = self
+            .crate_name
+            .clone()
+            .unwrap_or_else(|| "source".to_owned());
+        let mut krate = RustCrate::new(name.clone(), "0.0.0".to_owned(), Vec::new());
+        krate.features = self.features.clone();
+
+        // Every source that is not a submodule of an earlier one is a root.
+        let cfg = CfgSet::new(self.features.clone());
+        let mut files = FileSet::new(self, &sources, &cfg, sink.is_some())?;
+        let mut targets = Vec::new();
+        for (path, _) in &self.sources {
+            if files.contains(path) {
+                continue;
+            }
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let target_name = if targets.is_empty() || stem == "lib" || stem == "main" {
+                name.clone()
+            } else {
+                stem
+            };
+            // Like Cargo, take a `main.rs` for a binary and anything else
+            // for a library.
+            let target = Target {
+                is_lib: path.file_stem().is_none_or(|s| s != "main"),
+                name: target_name,
+                path: path.clone(),
+            };
+            targets.push(target);
+            let names = root_names(&targets);
+            files.discover(path, &names[names.len() - 1], true)?;
+        }
+        krate.is_lib = targets.iter().any(|t| t.is_lib);
+        assemble(krate, &targets, files, sink, self.dispatch)
+    }
+
+    fn analyze_targets(
+        &self,
+        krate: RustCrate,
+        targets: &[Target],
+        sources: &SourceSet,
+        cfg: CfgSet,
+        sink: Option<&mut dyn ModuleSink>,
+    ) -> Result<RustCrate, AnalyzerError> {
+        let mut files = FileSet::new(self, sources, &cfg, sink.is_some())?;
+        for (target, name) in targets.iter().zip(root_names(targets)) {
+            files.discover(&target.path, &name, true)?;
+        }
+        assemble(krate, targets, files, sink, self.dispatch)
+    }
+}
+
+/// Renames targets so that every crate root has a distinct module name.
+fn root_names(targets: &[Target]) -> Vec<String> {
+    let mut used_names = BTreeSet::new();
+    targets
+        .iter()
+        .map(|target| {
+            let mut name = target.name.replace('-', "_");
+            if !used_names.insert(name.clone()) {
+                name = format!("{}_bin", name);
+                used_names.insert(name.clone());
+            }
+            name
+        })
+        .collect()
+}
+
+/// Extracts the modules of every target and links them into `krate`.
+fn assemble(
+    mut krate: RustCrate,
+    targets: &[Target],
+    mut files: FileSet,
+    mut sink: Option<&mut dyn ModuleSink>,
+    dispatch: Dispatch,
+) -> Result<RustCrate, AnalyzerError> {
+    if let Some(sink) = sink.as_deref_mut() {
+        sink.crate_started(&krate).map_err(AnalyzerError::Sink)?;
+    }
+    let mut index = CrateIndex::default();
+    for state in files.files.values() {
+        index.merge(&state.analysis.index);
+    }
+    let fingerprint = cache::fingerprint(&serde_json::to_string(&index).unwrap_or_default());
+
+    // A sink gets each file extracted just before its modules are handed
+    // over; otherwise every file is extracted up front, in parallel.
+    if sink.is_none() {
+        files.extract_all(&index, &fingerprint)?;
+    }
+
+    // Call sites are resolved once the crate is complete, so a sink's
+    // modules are kept, without their bodies, from level 2 on.
+    let keep = sink.is_none() || files.level >= 2;
+    let mut builder = ModuleBuilder {
+        krate: &krate.name,
+        files: &mut files,
+        index: &index,
+        fingerprint: &fingerprint,
+        sink,
+        keep,
+    };
+    let mut modules = Vec::new();
+    for (target, name) in targets.iter().zip(root_names(targets)) {
+        if let Some(module) = builder.file_module(&target.path, &name, None, None)? {
+            modules.push(module);
+        }
+    }
+
+    match builder.sink {
+        Some(sink) => {
+            if keep {
+                krate.modules = modules;
+                callgraph::resolve_calls(&mut krate, dispatch);
+            }
+            sink.crate_finished(&krate).map_err(AnalyzerError::Sink)?;
+        }
+        None => {
+            krate.modules = modules;
+            link(&mut krate, files.level, dispatch);
+        }
+    }
+    Ok(krate)
+}
+
+/// Links the module trees of `krate` once every module has been extracted.
+fn link(krate: &mut RustCrate, level: u8, dispatch: Dispatch) {
+    // Propagate first, so that the methods copied into associated items
+    // carry their summaries.
+    if level >= 2 {
+        callgraph::resolve_calls(krate, dispatch);
+        crate::analysis::unsafety::propagate(krate);
+        crate::analysis::panics::propagate(krate);
+    }
+    link_impls(&mut krate.modules);
+}
+
+/// A discovered file and its analysis, cached or fresh.
+struct FileState {
+    key: String,
+    module_path: String,
+    owns_dir: bool,
+    analysis: FileAnalysis,
+    /// The source text, kept until the file's items have been extracted.
+    text: Option<String>,
+    /// Whether the module of the cached analysis was dropped after discovery
+    /// and has to be read from the cache again.
+    unloaded: bool,
+}
+
+/// The files of one crate, discovered by following `mod` declarations.
+///
+/// Files are loaded and extracted in parallel, but always stored by path, so
+/// the result does not depend on which worker finished first.
+struct FileSet<'a> {
+    sources: &'a SourceSet,
+    cfg: &'a CfgSet,
+    level: u8,
+    cache: Option<&'a AnalysisCache>,
+    target_files: BTreeSet<PathBuf>,
+    /// Whether modules are handed to a sink. Discovery then keeps neither
+    /// source texts nor cached modules, since files are extracted one at a
+    /// time later.
+    streaming: bool,
+    files: BTreeMap<PathBuf, FileState>,
+    pool: rayon::ThreadPool,
+}
+
+impl<'a> FileSet<'a> {
+    fn new(
+        analyzer: &'a Analyzer,
+        sources: &'a SourceSet,
+        cfg: &'a CfgSet,
+        streaming: bool,
+    ) -> Result<Self, AnalyzerError> {
+        let target_files = analyzer
+            .target_files
+            .iter()
+            .map(|p| match &sources.root {
+                Some(root) if p.is_relative() => root.join(p),
+                _ => p.clone(),
+            })
+            .collect();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(analyzer.jobs)
+            .build()
+            .map_err(AnalyzerError::Threads)?;
+        Ok(Self {
+            sources,
+            cfg,
+            level: analyzer.analysis_level,
+            cache: analyzer.cache.as_ref(),
+            target_files,
+            streaming,
+            files: BTreeMap::new(),
+            pool,
+        })
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Loads `path`, the module `module_path`, and every file reachable from
+    /// it through `mod` declarations, one level of the module tree at a time.
+    fn discover(
+        &mut self,
+        path: &Path,
+        module_path: &str,
+        owns_dir: bool,
+    ) -> Result<(), AnalyzerError> {
+        let mut frontier = vec![(path.to_path_buf(), module_path.to_owned(), owns_dir)];
+        while !frontier.is_empty() {
+            let mut seen = BTreeSet::new();
+            frontier
+                .retain(|(path, ..)| !self.files.contains_key(path) && seen.insert(path.clone()));
+            let this = &*self;
+            let loaded: Vec<_> = self.pool.install(|| {
+                frontier
+                    .par_iter()
+                    .map(|(path, module_path, owns_dir)| this.load(path, module_path, *owns_dir))
+                    .collect()
+            });
+            let mut next = Vec::new();
+            for ((path, module_path, _), state) in frontier.into_iter().zip(loaded) {
+                let state = state?;
+                for child in &state.analysis.children {
+                    let child_path = format!("{}::{}", module_path, child.module_path.join("::"));
+                    next.push((child.file.clone(), child_path, child.owns_dir));
+                }
+                self.files.insert(path, state);
+            }
+            frontier = next;
+        }
+        Ok(())
+    }
+
+    /// Reads one file and looks it up in the cache. Files without a cache
+    /// entry are parsed to find their index facts and child files.
+    fn load(
+        &self,
+        path: &Path,
+        module_path: &str,
+        owns_dir: bool,
+    ) -> Result<FileState, AnalyzerError> {
+        let text = self.sources.read(path)?;
+        let key = CacheKey::new()
+            .add(&text)
+            .add(&self.sources.display_path(path))
+            .add(module_path)
+            .add(&owns_dir.to_string())
+            .add(&self.cfg.describe())
+            .add(&self.level.to_string())
+            .add(&with_items(&self.target_files, path).to_string())
+            .finish();
+        let cached = self
+            .cache
+            .and_then(|cache| cache.load(&key))
+            .filter(|entry| entry.children.iter().all(|c| self.sources.exists(&c.file)));
+        let (mut analysis, mut text) = match cached {
+            Some(entry) => (entry, None),
+            None => {
+                let parsed = self.sources.parse_text(path, text)?;
+                let summary =
+                    FileAnalysis::summarize(self.sources, self.cfg, path, owns_dir, &parsed);
+                (summary, Some(parsed.source.text))
+            }
+        };
+        let unloaded = self.streaming && analysis.index_fingerprint.is_some();
+        if self.streaming {
+            analysis.module = RustModule::new(String::new());
+            text = None;
+        }
+        Ok(FileState {
+            key,
+            module_path: module_path.to_owned(),
+            owns_dir,
+            analysis,
+            text,
+            unloaded,
+        })
+    }
+
+    /// Extracts the items of every file that has no cached extraction made
+    /// with the same crate index.
+    fn extract_all(&mut self, index: &CrateIndex, fingerprint: &str) -> Result<(), AnalyzerError> {
+        let mut files = std::mem::take(&mut self.files);
+        let this = &*self;
+        let results: Vec<_> = this.pool.install(|| {
+            files
+                .par_iter_mut()
+                .map(|(path, state)| this.extract(path, state, index, fingerprint))
+                .collect()
+        });
+        self.files = files;
+        // Report the error of the first file in path order, not the first to fail.
+        results.into_iter().collect()
+    }
+
+    /// Extracts the items of one file, unless it has a cached extraction made
+    /// with the same crate index.
+    fn extract(
+        &self,
+        path: &Path,
+        state: &mut FileState,
+        index: &CrateIndex,
+        fingerprint: &str,
+    ) -> Result<(), AnalyzerError> {
+        if state.analysis.index_fingerprint.as_deref() == Some(fingerprint) {
+            if !state.unloaded {
+                return Ok(());
+            }
+            let cached = self
+                .cache
+                .and_then(|cache| cache.load(&state.key))
+                .filter(|entry| entry.index_fingerprint.as_deref() == Some(fingerprint));
+            if let Some(entry) = cached {
+                state.analysis = entry;
+                state.unloaded = false;
+                return Ok(());
+            }
+        }
+        let text = match state.text.take() {
+            Some(text) => text,
+            None => self.sources.read(path)?,
+        };
+        let parsed = self.sources.parse_text(path, text)?;
+        let ctx = ItemContext {
+            src: &parsed.source,
+            index,
+            cfg: self.cfg,
+            level: self.level,
+            module_path: &state.module_path,
+            self_type: None,
+        };
+        let with_items = with_items(&self.target_files, path);
+        state.analysis.extract(
+            &ctx,
+            self.sources,
+            path,
+            state.owns_dir,
+            &parsed,
+            with_items,
+        );
+        state.analysis.index_fingerprint = Some(fingerprint.to_owned());
+        state.unloaded = false;
+        if let Some(cache) = self.cache {
+            // The cache only saves time; failing to write it is not an error.
+            let _ = cache.store(&state.key, &state.analysis);
+        }
+        Ok(())
+    }
+
+    /// Takes the analysis of `path` out of the set, extracting its items if
+    /// that has not been done yet.
+    fn take(
+        &mut self,
+        path: &Path,
+        index: &CrateIndex,
+        fingerprint: &str,
+    ) -> Result<Option<FileAnalysis>, AnalyzerError> {
+        let Some(mut state) = self.files.remove(path) else {
+            return Ok(None);
+        };
+        self.extract(path, &mut state, index, fingerprint)?;
+        Ok(Some(state.analysis))
+    }
+}
+
+/// Whether the items of `path` are extracted, or only its structure.
+fn with_items(target_files: &BTreeSet<PathBuf>, path: &Path) -> bool {
+    target_files.is_empty() || target_files.contains(path)
+}
+
+struct ModuleBuilder<'a, 'f, 's> {
+    krate: &'a str,
+    files: &'a mut FileSet<'f>,
+    index: &'a CrateIndex,
+    fingerprint: &'a str,
+    sink: Option<&'s mut dyn ModuleSink>,
+    /// Whether modules are kept after they have been handed to the sink.
+    keep: bool,
+}
+
+impl ModuleBuilder<'_, '_, '_> {
+    /// Builds the module stored in `file_path`. `decl` is the placeholder
+    /// created from the `mod` declaration in the parent file; without a
+    /// `parent`, the module is a crate root named `path`.
+    fn file_module(
+        &mut self,
+        file_path: &Path,
+        path: &str,
+        parent: Option<&str>,
+        decl: Option<RustModule>,
+    ) -> Result<Option<RustModule>, AnalyzerError> {
+        let Some(analysis) = self.files.take(file_path, self.index, self.fingerprint)? else {
+            return Ok(None);
+        };
+        let mut module = analysis.module;
+        if let Some(decl) = decl {
+            file::merge_declaration(&mut module, decl);
+        }
+        if parent.is_none() {
+            module.name = path.to_owned();
+            module.is_root_module = true;
+        }
+        module.is_mod_rs = file_path.file_name().is_some_and(|n| n == "mod.rs");
+        self.submodules(
+            &mut module,
+            path,
+            parent,
+            &analysis.children,
+            &mut Vec::new(),
+        )?;
+        Ok(Some(module))
+    }
+
+    /// Hands `module` to the sink, then completes its submodules. `inline`
+    /// is the path from the file's own module down to `module`.
+    fn submodules(
+        &mut self,
+        module: &mut RustModule,
+        path: &str,
+        parent: Option<&str>,
+        children: &[ChildFile],
+        inline: &mut Vec<String>,
+    ) -> Result<(), AnalyzerError> {
+        if let Some(sink) = self.sink.as_deref_mut() {
+            sink.module_analyzed(self.krate, parent, path, module)
+                .map_err(AnalyzerError::Sink)?;
+            strip_bodies(module);
+        }
+        for (name, mut sub) in std::mem::take(&mut module.submodules) {
+            let sub_path = format!("{}::{}", path, name);
+            inline.push(name.clone());
+            let child = children.iter().find(|c| c.module_path == *inline);
+            let sub = match child {
+                Some(child) => self.file_module(&child.file, &sub_path, Some(path), Some(sub))?,
+                None => {
+                    self.submodules(&mut sub, &sub_path, Some(path), children, inline)?;
+                    Some(sub)
+                }
+            };
+            inline.pop();
+            if let (Some(sub), true) = (sub, self.keep) {
+                module.submodules.insert(name, sub);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drops what call resolution does not need from the callables of a module
+/// that has been handed to a sink: their code, doc comments, control flow
+/// and dependence graphs, and the copies in `safe_functions` and
+/// `unsafe_functions`.
+fn strip_bodies(module: &mut RustModule) {
+    fn strip(callable: &mut RustCallable) {
+        callable.code = String::new();
+        callable.doc_comment = None;
+        callable.control_flow = None;
+        callable.dependence = None;
+    }
+    module.safe_functions.clear();
+    module.unsafe_functions.clear();
+    module.functions.values_mut().for_each(strip);
+    for imp in &mut module.impls {
+        imp.methods.values_mut().for_each(strip);
+    }
+    for rtrait in module.traits.values_mut() {
+        rtrait.methods.values_mut().for_each(strip);
+    }
+}
+
+/// Records which traits every struct, enum and union implements and attaches
+/// the methods of inherent impls as associated items.
+///
+/// The self type of an impl is resolved against the impl's module and its
+/// `use` declarations, so that types of the same name in different modules
+/// keep their own impls.
+///
+/// Whatever an earlier run linked is replaced, so module trees can be linked
+/// again after some of their modules have changed.
+fn link_impls(modules: &mut [RustModule]) {
+    let mut types: HashMap<String, Vec<String>> = HashMap::new();
+    for root in modules.iter() {
+        for (path, module) in root.module_tree() {
+            let names = module
+                .structs
+                .keys()
+                .chain(module.enums.keys())
+                .chain(module.unions.keys());
+            for name in names {
+                types
+                    .entry(name.clone())
+                    .or_default()
+                    .push(format!("{}::{}", path, name));
+            }
+        }
+    }
+    let mut impls: HashMap<String, Vec<RustImpl>> = HashMap::new();
+    for root in modules.iter() {
+        for (path, module) in root.module_tree() {
+            for imp in &module.impls {
+                if let Some(ty) = impl_type(&types, &path, module, imp) {
+                    impls.entry(ty).or_default().push(imp.clone());
+                }
+            }
+        }
+    }
+    fn visit(module: &mut RustModule, path: &str, impls: &HashMap<String, Vec<RustImpl>>) {
+        let impls_of = |name: &str| {
+            impls
+                .get(&format!("{}::{}", path, name))
+                .into_iter()
+                .flatten()
+        };
+        for rstruct in module.structs.values_mut() {
+            rstruct.impl_traits.clear();
+            rstruct.associated_items.clear();
+            rstruct.contains_unsafe = rstruct.fields.iter().any(|f| f.ty.contains_raw_pointers);
+            for imp in impls_of(&rstruct.name) {
+                match &imp.trait_name {
+                    Some(trait_name) => rstruct.impl_traits.push(trait_name.clone()),
+                    None => rstruct.associated_items.extend(imp.methods.clone()),
+                }
+                rstruct.contains_unsafe |= imp.is_unsafe;
+            }
+        }
+        for renum in module.enums.values_mut() {
+            renum.impl_traits.clear();
+            renum.associated_items.clear();
+            for imp in impls_of(&renum.name) {
+                match &imp.trait_name {
+                    Some(trait_name) => renum.impl_traits.push(trait_name.clone()),
+                    None => renum.associated_items.extend(imp.methods.clone()),
+                }
+            }
+        }
+        for runion in module.unions.values_mut() {
+            runion.impl_traits.clear();
+            runion.associated_items.clear();
+            for imp in impls_of(&runion.name) {
+                match &imp.trait_name {
+                    Some(trait_name) => runion.impl_traits.push(trait_name.clone()),
+                    None => runion.associated_items.extend(imp.methods.clone()),
+                }
+            }
+        }
+        for sub in module.submodules.values_mut() {
+            let sub_path = format!("{}::{}", path, sub.name);
+            visit(sub, &sub_path, impls);
+        }
+    }
+    for root in modules.iter_mut() {
+        let path = root.name.clone();
+        visit(root, &path, &impls);
+    }
+}
+
+/// Returns the item path of the struct, enum or union `imp` implements,
+/// resolved from the module at `module_path`, or `None` for a type of
+/// another crate. `types` holds the item paths of the crate's types by name;
+/// a type named without a path that the module does not declare, such as
+/// one imported through a re-export, is found by its name if no other type
+/// has it.
+fn impl_type(
+    types: &HashMap<String, Vec<String>>,
+    module_path: &str,
+    module: &RustModule,
+    imp: &RustImpl,
+) -> Option<String> {
+    let candidates = types.get(imp.self_type_name())?;
+    let segments: Vec<&str> = imp.self_type_path().split("::").map(str::trim).collect();
+    let module_segments: Vec<&str> = module_path.split("::").collect();
+    let resolved = callgraph::candidate_paths(&segments, &module_segments, module, None)
+        .into_iter()
+        .map(|path| path.join("::"))
+        .find(|path| candidates.contains(path));
+    match (resolved, candidates.as_slice()) {
+        (Some(path), _) => Some(path),
+        (None, [only]) if segments.len() == 1 => Some(only.clone()),
+        (None, _) => None,
+    }
+}
@@ -0,0 +1,296 @@
+//! Conversion of syntax nodes into the string-based entity representation.
+
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Expr, ExprLit, GenericArgument, GenericParam, Generics, Lit, Meta, PathArguments,
+    ReturnType, Token, Type, TypeParamBound,
+};
+
+use super::source::SourceText;
+use crate::entities::{
+    RustVisibility, attr::RustAttribute, lifetime::RustLifetimeParam, param::RustGenericParam,
+    rtrait::RustTraitBound, rtype::RustType,
+};
+
+/// Returns the source text of `node` with runs of whitespace collapsed.
+pub fn text<T: Spanned + ToTokens>(src: &SourceText, node: &T) -> String {
+    match src.slice(node.span()) {
+        Some(text) if !text.is_empty() => collapse_whitespace(text),
+        _ => collapse_whitespace(&node.to_token_stream().to_string()),
+    }
+}
+
+/// Returns the exact source text of `node`, including line breaks.
+pub fn code<T: Spanned + ToTokens>(src: &SourceText, node: &T) -> String {
+    match src.slice(node.span()) {
+        Some(text) => text.to_owned(),
+        None => node.to_token_stream().to_string(),
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns the 1-based start and end line of `node`.
+pub fn lines<T: Spanned>(node: &T) -> (usize, usize) {
+    let span = node.span();
+    (span.start().line, span.end().line)
+}
+
+pub fn visibility(vis: &syn::Visibility) -> RustVisibility {
+    match vis {
+        syn::Visibility::Public(_) => RustVisibility::Public,
+        syn::Visibility::Inherited => RustVisibility::Private,
+        syn::Visibility::Restricted(r) => {
+            let path = r.path.to_token_stream().to_string().replace(' ', "");
+            match path.as_str() {
+                "crate" => RustVisibility::Crate,
+                "super" => RustVisibility::Super,
+                "self" => RustVisibility::Private,
+                _ => RustVisibility::InPath(path),
+            }
+        }
+    }
+}
+
+/// Returns the doc comment assembled from `#[doc = "..."]` attributes.
+pub fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_owned())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Converts every non-doc attribute.
+pub fn attributes(src: &SourceText, attrs: &[Attribute]) -> Vec<RustAttribute> {
+    attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc"))
+        .map(|attr| {
+            let name = text(src, attr.path());
+            let arguments = match &attr.meta {
+                Meta::Path(_) => Vec::new(),
+                Meta::NameValue(nv) => vec![text(src, &nv.value)],
+                Meta::List(list) => list
+                    .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                    .map(|args| args.iter().map(|m| text(src, m)).collect())
+                    .unwrap_or_else(|_| vec![collapse_whitespace(&list.tokens.to_string())]),
+            };
+            let is_inner = matches!(attr.style, syn::AttrStyle::Inner(_));
+            RustAttribute::new(name, arguments, is_inner)
+        })
+        .collect()
+}
+
+/// Returns the traits named in `#[derive(...)]` attributes.
+pub fn derives(src: &SourceText, attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flat_map(|paths| paths.into_iter().map(|p| text(src, &p)).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Returns true if one of `attrs` is `#[name]` or `#[name(...)]`.
+pub fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident(name))
+}
+
+/// Returns the predicates of the `#[cfg(...)]` attributes among `attrs`.
+pub fn cfg_predicates(src: &SourceText, attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::List(list) => Some(text(src, &list.tokens)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Converts a syntactic type.
+pub fn rust_type(src: &SourceText, ty: &Type) -> RustType {
+    let mut rust_type = RustType::new(text(src, ty));
+    match ty {
+        Type::Reference(r) => {
+            rust_type.is_reference = true;
+            rust_type.is_mutable = r.mutability.is_some();
+            rust_type.lifetime = r.lifetime.as_ref().map(|l| l.to_string());
+            rust_type.is_static = r.lifetime.as_ref().is_some_and(|l| l.ident == "static");
+        }
+        Type::Ptr(p) => {
+            rust_type.is_mutable = p.mutability.is_some();
+        }
+        Type::Slice(_) | Type::TraitObject(_) => rust_type.is_sized = false,
+        Type::Path(p) if p.qself.is_none() && p.path.is_ident("str") => rust_type.is_sized = false,
+        _ => {}
+    }
+    if let Some(args) = last_segment_args(ty) {
+        rust_type.generic_params = args.iter().map(|a| text(src, a)).collect();
+    }
+    rust_type.contains_raw_pointers = contains_raw_pointer(ty);
+    rust_type
+}
+
+fn last_segment_args(ty: &Type) -> Option<&Punctuated<GenericArgument, Token![,]>> {
+    match ty {
+        Type::Path(p) => match &p.path.segments.last()?.arguments {
+            PathArguments::AngleBracketed(args) => Some(&args.args),
+            _ => None,
+        },
+        Type::Reference(r) => last_segment_args(&r.elem),
+        Type::Paren(p) => last_segment_args(&p.elem),
+        Type::Group(g) => last_segment_args(&g.elem),
+        _ => None,
+    }
+}
+
+/// Returns true if `ty` is or contains a raw pointer type.
+pub fn contains_raw_pointer(ty: &Type) -> bool {
+    struct Finder(bool);
+    impl<'ast> syn::visit::Visit<'ast> for Finder {
+        fn visit_type_ptr(&mut self, _: &'ast syn::TypePtr) {
+            self.0 = true;
+        }
+    }
+    let mut finder = Finder(false);
+    syn::visit::Visit::visit_type(&mut finder, ty);
+    finder.0
+}
+
+pub fn return_type(src: &SourceText, output: &ReturnType) -> Option<RustType> {
+    match output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some(rust_type(src, ty)),
+    }
+}
+
+/// Splits generics into type/const parameters, lifetime parameters and
+/// where-clause predicates.
+pub fn generics(
+    src: &SourceText,
+    generics: &Generics,
+) -> (Vec<RustGenericParam>, Vec<RustLifetimeParam>, Vec<String>) {
+    let mut params = Vec::new();
+    let mut lifetimes = Vec::new();
+    for param in &generics.params {
+        match param {
+            GenericParam::Type(t) => {
+                let mut p = RustGenericParam::new(t.ident.to_string());
+                p.bounds = t.bounds.iter().map(|b| text(src, b)).collect();
+                p.default_type = t.default.as_ref().map(|d| text(src, d));
+                params.push(p);
+            }
+            GenericParam::Const(c) => {
+                let mut p = RustGenericParam::new(c.ident.to_string());
+                p.bounds = vec![text(src, &c.ty)];
+                p.default_type = c.default.as_ref().map(|d| text(src, d));
+                p.is_const = true;
+                params.push(p);
+            }
+            GenericParam::Lifetime(l) => {
+                lifetimes.push(RustLifetimeParam::with_bounds(
+                    l.lifetime.to_string(),
+                    l.bounds.iter().map(|b| b.to_string()).collect(),
+                ));
+            }
+        }
+    }
+    let where_clauses = generics
+        .where_clause
+        .iter()
+        .flat_map(|w| w.predicates.iter().map(|p| text(src, p)))
+        .collect();
+    (params, lifetimes, where_clauses)
+}
+
+/// Converts a trait bound, e.g. a supertrait.
+pub fn trait_bound(src: &SourceText, bound: &TypeParamBound) -> Option<RustTraitBound> {
+    match bound {
+        TypeParamBound::Trait(t) => {
+            let path = &t.path;
+            let generic_params = match path.segments.last().map(|s| &s.arguments) {
+                Some(PathArguments::AngleBracketed(args)) => {
+                    args.args.iter().map(|a| text(src, a)).collect()
+                }
+                _ => Vec::new(),
+            };
+            let trait_name = path_without_args(path);
+            let is_optional = matches!(t.modifier, syn::TraitBoundModifier::Maybe(_));
+            Some(RustTraitBound {
+                is_sized: trait_name == "Sized" && !is_optional,
+                trait_name,
+                generic_params,
+                is_optional,
+                lifetime_bounds: Vec::new(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Renders a path without generic arguments, e.g. `std::vec::Vec`.
+pub fn path_without_args(path: &syn::Path) -> String {
+    let mut out = String::new();
+    if path.leading_colon.is_some() {
+        out.push_str("::");
+    }
+    for (i, segment) in path.segments.iter().enumerate() {
+        if i > 0 {
+            out.push_str("::");
+        }
+        out.push_str(&segment.ident.to_string());
+    }
+    out
+}
+
+/// Returns the name of a type without generic arguments or references,
+/// e.g. `Wrapper` for `&mut Wrapper<T>`.
+pub fn base_type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        Type::Reference(r) => base_type_name(&r.elem),
+        Type::Paren(p) => base_type_name(&p.elem),
+        Type::Group(g) => base_type_name(&g.elem),
+        _ => None,
+    }
+}
+
+/// Parses a `safe fn` or `safe static` declared in an `unsafe extern` block,
+/// which syn keeps as verbatim tokens, into the item without `safe`.
+pub fn safe_foreign_item(tokens: &proc_macro2::TokenStream) -> Option<syn::ForeignItem> {
+    let mut found = false;
+    let stripped: proc_macro2::TokenStream = tokens
+        .clone()
+        .into_iter()
+        .filter(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) if !found && ident == "safe" => {
+                found = true;
+                false
+            }
+            _ => true,
+        })
+        .collect();
+    if !found {
+        return None;
+    }
+    syn::parse2(stripped).ok()
+}
@@ -0,0 +1,191 @@
+//! Source files, parsing and the mapping from spans back to source text.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::Span;
+use syn::{Attribute, Expr, ExprLit, ItemMod, Lit, Meta};
+
+use super::AnalyzerError;
+
+/// The text of a parsed file, able to slice out the source of any span.
+#[derive(Debug)]
+pub struct SourceText {
+    pub text: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceText {
+    pub fn new(text: String) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { text, line_starts }
+    }
+
+    /// Returns the line with the given 1-based number, without the newline.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map(|e| e - 1)
+            .unwrap_or(self.text.len());
+        Some(self.text[start..end].trim_end_matches('\r'))
+    }
+
+    fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let rest = &self.text[start..];
+        // Columns count characters, not bytes.
+        Some(
+            start
+                + rest
+                    .char_indices()
+                    .nth(column)
+                    .map(|(i, _)| i)
+                    .unwrap_or(rest.len()),
+        )
+    }
+
+    /// Returns the exact source text covered by `span`.
+    pub fn slice(&self, span: Span) -> Option<&str> {
+        self.slice_between(span, span)
+    }
+
+    /// Returns the source text from the start of `first` to the end of `last`.
+    pub fn slice_between(&self, first: Span, last: Span) -> Option<&str> {
+        let (start, end) = (first.start(), last.end());
+        let from = self.offset(start.line, start.column)?;
+        let to = self.offset(end.line, end.column)?;
+        self.text.get(from..to)
+    }
+}
+
+/// A parsed source file.
+pub struct ParsedFile {
+    /// Path relative to the analysis root, with forward slashes.
+    pub display_path: String,
+    pub source: SourceText,
+    pub ast: syn::File,
+}
+
+/// Where source files come from: in-memory sources take precedence over the
+/// file system, so that unsaved buffers and tests can be analyzed.
+#[derive(Debug, Clone, Default)]
+pub struct SourceSet {
+    /// Root that display paths are relative to.
+    pub root: Option<PathBuf>,
+    pub virtual_files: BTreeMap<PathBuf, String>,
+}
+
+impl SourceSet {
+    pub fn exists(&self, path: &Path) -> bool {
+        self.virtual_files.contains_key(path) || path.is_file()
+    }
+
+    pub fn read(&self, path: &Path) -> Result<String, AnalyzerError> {
+        match self.virtual_files.get(path) {
+            Some(text) => Ok(text.clone()),
+            None => std::fs::read_to_string(path).map_err(|e| AnalyzerError::io(path, e)),
+        }
+    }
+
+    /// Returns `path` relative to the root, using forward slashes.
+    pub fn display_path(&self, path: &Path) -> String {
+        let relative = self
+            .root
+            .as_deref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        relative.to_string_lossy().replace('\\', "/")
+    }
+
+    pub fn parse(&self, path: &Path) -> Result<ParsedFile, AnalyzerError> {
+        self.parse_text(path, self.read(path)?)
+    }
+
+    /// Parses `text`, the content of `path`.
+    pub fn parse_text(&self, path: &Path, text: String) -> Result<ParsedFile, AnalyzerError> {
+        let ast = syn::parse_file(&text).map_err(|e| AnalyzerError::Parse {
+            path: path.to_path_buf(),
+            line: e.span().start().line,
+            message: e.to_string(),
+        })?;
+        Ok(ParsedFile {
+            display_path: self.display_path(path),
+            source: SourceText::new(text),
+            ast,
+        })
+    }
+}
+
+/// Directory context used to resolve `mod name;` declarations.
+#[derive(Debug, Clone)]
+pub struct ModuleDir {
+    /// Directory that child module files are looked up in.
+    pub dir: PathBuf,
+    /// Directory that `#[path]` attributes are relative to.
+    pub path_dir: PathBuf,
+}
+
+impl ModuleDir {
+    /// Returns the directory for children of the module stored in `file`.
+    ///
+    /// Crate roots and `mod.rs` files own their directory; any other
+    /// `name.rs` file owns the sibling directory `name/`.
+    pub fn for_file(file: &Path, owns_dir: bool) -> Self {
+        let parent = file.parent().unwrap_or(Path::new("")).to_path_buf();
+        let dir = if owns_dir {
+            parent.clone()
+        } else {
+            parent.join(file.file_stem().unwrap_or_default())
+        };
+        ModuleDir {
+            dir,
+            path_dir: parent,
+        }
+    }
+
+    /// Returns the directory for children of the inline module `name`.
+    pub fn inline(&self, item: &ItemMod) -> Self {
+        let dir = match path_attr(&item.attrs) {
+            Some(path) => self.path_dir.join(path),
+            None => self.dir.join(item.ident.to_string()),
+        };
+        ModuleDir {
+            path_dir: dir.clone(),
+            dir,
+        }
+    }
+
+    /// Resolves the file of an out-of-line `mod name;` declaration.
+    ///
+    /// Returns the file and whether it owns its directory.
+    pub fn resolve(&self, item: &ItemMod, sources: &SourceSet) -> Option<(PathBuf, bool)> {
+        if let Some(path) = path_attr(&item.attrs) {
+            // Files named through `#[path]` are treated like `mod.rs` files.
+            let file = self.path_dir.join(path);
+            return sources.exists(&file).then_some((file, true));
+        }
+        let name = item.ident.to_string();
+        let name = name.trim_start_matches("r#");
+        let flat = self.dir.join(format!("{}.rs", name));
+        if sources.exists(&flat) {
+            return Some((flat, false));
+        }
+        let nested = self.dir.join(name).join("mod.rs");
+        sources.exists(&nested).then_some((nested, true))
+    }
+}
+
+fn path_attr(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(nv) if nv.path.is_ident("path") => match &nv.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(s), ..
+            }) => Some(s.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// Represents an `asm!`, `global_asm!` or `naked_asm!` invocation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustInlineAsm {
+    /// Name of the macro, e.g. `asm`.
+    pub macro_name: String,
+    /// The template strings, in order. Templates that are not string
+    /// literals, such as `concat!(...)`, are kept as written.
+    pub templates: Vec<String>,
+    /// Input, output and other operands.
+    pub operands: Vec<RustAsmOperand>,
+    /// Options from `options(...)`, e.g. `nostack`.
+    pub options: Vec<String>,
+    /// ABIs named in `clobber_abi(...)`.
+    pub clobber_abis: Vec<String>,
+    /// `#[cfg]` predicates of the impl or trait, the function and the
+    /// statements the invocation is nested in.
+    pub cfg: Vec<String>,
+    /// The starting line number of the invocation.
+    pub start_line: usize,
+    /// The ending line number of the invocation.
+    pub end_line: usize,
+}
+
+impl RustInlineAsm {
+    /// Creates an invocation of `macro_name` without templates or operands.
+    pub fn new(macro_name: String, start_line: usize, end_line: usize) -> Self {
+        Self {
+            macro_name,
+            templates: Vec::new(),
+            operands: Vec::new(),
+            options: Vec::new(),
+            clobber_abis: Vec::new(),
+            cfg: Vec::new(),
+            start_line,
+            end_line,
+        }
+    }
+}
+
+/// What an operand passes to or from the assembly, named after its keyword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AsmOperandKind {
+    In,
+    Out,
+    LateOut,
+    InOut,
+    InLateOut,
+    Const,
+    Sym,
+    Label,
+}
+
+/// An operand of an inline assembly invocation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustAsmOperand {
+    /// Name of a named operand (`name = in(reg) x`).
+    pub name: Option<String>,
+    pub kind: AsmOperandKind,
+    /// Register class such as `reg`, or an explicit register in quotes such
+    /// as `"eax"`, for register operands.
+    pub register: Option<String>,
+    /// The expression, path or block of the operand; `_` for discarded
+    /// outputs.
+    pub expression: String,
+    /// The output expression of `inout(reg) x => y`.
+    pub output: Option<String>,
+}
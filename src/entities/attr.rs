@@ -36,3 +36,35 @@ impl AttributeOperations for RustAttribute {
         self.is_inner
     }
 }
+
+/// Returns the symbol an item is exported under: its own `name` for
+/// `#[no_mangle]`, or the name given to `#[export_name = "..."]`. Both are
+/// also recognized in their `#[unsafe(...)]` form.
+///
+/// # Examples
+///
+/// ```
+/// use codeanalyzer_rs::entities::attr::{RustAttribute, exported_symbol};
+///
+/// let attr = RustAttribute::new("unsafe".to_string(), vec!["no_mangle".to_string()], false);
+/// assert_eq!(exported_symbol(&[attr], "init"), Some("init".to_string()));
+/// ```
+pub fn exported_symbol(attributes: &[RustAttribute], name: &str) -> Option<String> {
+    let symbol = |attr: &str, value: Option<&str>| match attr {
+        "no_mangle" => Some(name.to_owned()),
+        "export_name" => value.map(|v| v.trim().trim_matches('"').to_owned()),
+        _ => None,
+    };
+    attributes.iter().find_map(|attr| {
+        if attr.name == "unsafe" {
+            attr.arguments
+                .iter()
+                .find_map(|arg| match arg.split_once('=') {
+                    Some((attr, value)) => symbol(attr.trim(), Some(value)),
+                    None => symbol(arg.trim(), None),
+                })
+        } else {
+            symbol(&attr.name, attr.arguments.first().map(String::as_str))
+        }
+    })
+}
@@ -1,8 +1,16 @@
+use serde::{Deserialize, Serialize};
+
 use super::{
     RustVisibility,
+    asm::RustInlineAsm,
     attr::RustAttribute,
     callsite::CallSite,
+    concurrency::{AwaitPoint, BlockingCall, GuardAcrossAwait, LockAcquisition, SpawnSite},
+    dependence::ProgramDependenceGraph,
+    errors::{ErrorPropagation, SwallowedError},
+    flow::ControlFlowGraph,
     lifetime::RustLifetimeParam,
+    panic::{PanicPath, PanicSite},
     param::{RustGenericParam, RustParameter},
     rtype::RustType,
     safety::SafetyAnalysis,
@@ -10,7 +18,7 @@ use super::{
 };
 
 /// Represents a Rust function or method.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct RustCallable {
     /// The name of the function or method.
     pub name: String,
@@ -34,6 +42,9 @@ pub struct RustCallable {
     pub is_extern: bool,
     /// ABI for the extern function.
     pub extern_abi: Option<String>,
+    /// Symbol the function is exported under with `#[no_mangle]` or
+    /// `#[export_name]`.
+    pub export_name: Option<String>,
     /// Generic parameters for the function.
     pub generic_params: Vec<RustGenericParam>,
     /// Lifetime parameters for the function.
@@ -48,14 +59,44 @@ pub struct RustCallable {
     pub end_line: usize,
     /// List of types referenced in the function.
     pub referenced_types: Vec<String>,
+    /// Structs and enums the body constructs with a struct literal, a tuple
+    /// struct or variant constructor or a unit struct or variant, recorded
+    /// from analysis level 2 on.
+    pub instantiated_types: Vec<String>,
     /// Variables accessed within the function.
     pub accessed_variables: Vec<String>,
     /// Call sites present in the function.
     pub call_sites: Vec<CallSite>,
     /// Variable declarations within the function.
     pub variable_declarations: Vec<RustVariableDeclaration>,
-    /// Cyclomatic complexity metric of the function.
+    /// Cyclomatic complexity metric of the function, computed from its
+    /// control-flow graph.
     pub cyclomatic_complexity: Option<usize>,
+    /// Control-flow graph of the body, built from analysis level 2 on.
+    pub control_flow: Option<ControlFlowGraph>,
+    /// Program dependence graph of the body, built at analysis level 3.
+    pub dependence: Option<ProgramDependenceGraph>,
+    /// Inline assembly in the body of the function.
+    pub inline_asm: Vec<RustInlineAsm>,
+    /// Expressions in the body of the function that can panic.
+    pub panic_sites: Vec<PanicSite>,
+    /// The callables with panic sites that the function reaches through its
+    /// callees, itself included, computed from analysis level 2 on.
+    pub transitive_panics: Option<Vec<PanicPath>>,
+    /// The `?` operators in the body of the function.
+    pub error_propagations: Vec<ErrorPropagation>,
+    /// Expressions in the body of the function that discard an error.
+    pub swallowed_errors: Vec<SwallowedError>,
+    /// The `.await`s in the body of the function.
+    pub await_points: Vec<AwaitPoint>,
+    /// Calls in the body of the function that spawn a task.
+    pub spawned_tasks: Vec<SpawnSite>,
+    /// Calls in the body of the function that block the current thread.
+    pub blocking_calls: Vec<BlockingCall>,
+    /// The locks acquired in the body of the function.
+    pub lock_acquisitions: Vec<LockAcquisition>,
+    /// Guards of synchronous locks held at an `.await` of the function.
+    pub guards_across_await: Vec<GuardAcrossAwait>,
     /// Safety analysis information.
     pub safety_analysis: SafetyAnalysis,
 }
@@ -74,11 +115,11 @@ impl RustCallable {
     /// # Example
     ///
     /// ```
-    /// use crate::entities::callable::RustCallable;
-    /// use crate::entities::RustVisibility;
-    /// use crate::entities::SafetyAnalysis;
+    /// use codeanalyzer_rs::entities::callable::RustCallable;
+    /// use codeanalyzer_rs::entities::SafetyClassification;
+    /// use codeanalyzer_rs::entities::safety::SafetyAnalysis;
     ///
-    /// let safety = SafetyAnalysis::default(); // Assuming a default implementation is available.
+    /// let safety = SafetyAnalysis::new(SafetyClassification::Safe);
     /// let callable = RustCallable::new("my_function".to_string(), "fn my_function() {}".to_string(), 1, 3, safety);
     /// ```
     pub fn new(
@@ -100,6 +141,7 @@ impl RustCallable {
             is_unsafe: false,
             is_extern: false,
             extern_abi: None,
+            export_name: None,
             generic_params: Vec::new(),
             lifetime_params: Vec::new(),
             where_clauses: Vec::new(),
@@ -107,10 +149,23 @@ impl RustCallable {
             start_line,
             end_line,
             referenced_types: Vec::new(),
+            instantiated_types: Vec::new(),
             accessed_variables: Vec::new(),
             call_sites: Vec::new(),
             variable_declarations: Vec::new(),
             cyclomatic_complexity: None,
+            control_flow: None,
+            dependence: None,
+            inline_asm: Vec::new(),
+            panic_sites: Vec::new(),
+            transitive_panics: None,
+            error_propagations: Vec::new(),
+            swallowed_errors: Vec::new(),
+            await_points: Vec::new(),
+            spawned_tasks: Vec::new(),
+            blocking_calls: Vec::new(),
+            lock_acquisitions: Vec::new(),
+            guards_across_await: Vec::new(),
             safety_analysis,
         }
     }
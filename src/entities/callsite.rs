@@ -8,6 +8,9 @@ pub struct CallSite {
     /// The line number where the function is called
     pub line_number: u32,
 
+    /// The name or path of the called function, as written at the call site (optional)
+    pub callee: Option<String>,
+
     /// The name of the function containing this call site (optional)
     pub caller_function: Option<String>,
 
@@ -19,6 +22,27 @@ pub struct CallSite {
 
     /// Indicates if the call occurs within an unsafe context
     pub is_unsafe_context: bool,
+
+    /// Indicates if the call occurs in an async fn or async block
+    pub is_async_context: bool,
+
+    /// Locks whose guards are held during the call, keyed like
+    /// [`LockAcquisition::lock`](super::concurrency::LockAcquisition::lock)
+    pub held_locks: Vec<String>,
+
+    /// Indicates if this is a method call (`receiver.method(...)`)
+    pub is_method_call: bool,
+
+    /// Source text of the receiver expression of a method call (optional)
+    pub receiver: Option<String>,
+
+    /// Item paths of the callables of the crate the call resolves to, filled
+    /// in when the crate is linked at analysis level 2 and above
+    pub callees: Vec<String>,
+
+    /// Indicates if the call is dispatched dynamically through a trait, in
+    /// which case `callees` are its candidate implementations
+    pub is_virtual: bool,
 }
 
 impl CallSite {
@@ -26,10 +50,17 @@ impl CallSite {
     pub fn new(line_number: u32) -> Self {
         Self {
             line_number,
+            callee: None,
             caller_function: None,
             caller_module: None,
             argument_types: Vec::new(),
             is_unsafe_context: false,
+            is_async_context: false,
+            held_locks: Vec::new(),
+            is_method_call: false,
+            receiver: None,
+            callees: Vec::new(),
+            is_virtual: false,
         }
     }
 }
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+/// An `.await` in a function body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AwaitPoint {
+    /// Line of the `.await`.
+    pub line: usize,
+    /// The call whose future is awaited, as written at the call site (see
+    /// [`CallSite::callee`](super::callsite::CallSite::callee)).
+    pub call: Option<String>,
+    /// Source text of the awaited expression.
+    pub code: String,
+}
+
+/// The function that starts a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnKind {
+    /// `spawn`, e.g. `tokio::spawn` or `JoinSet::spawn`.
+    Spawn,
+    /// `spawn_local`.
+    SpawnLocal,
+    /// `spawn_blocking`, which runs a closure on a thread for blocking work.
+    SpawnBlocking,
+}
+
+/// A call that starts an asynchronous task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpawnSite {
+    pub kind: SpawnKind,
+    /// Line of the spawning call.
+    pub line: usize,
+    /// The spawning function as written, e.g. `tokio::spawn`.
+    pub call: String,
+    /// The first call made by the spawned future or closure, as written at
+    /// the call site.
+    pub target: Option<String>,
+    /// Source text of the spawning call.
+    pub code: String,
+}
+
+/// What a blocking call blocks on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockingKind {
+    /// A function of `std::fs`, or opening or creating a `File` without
+    /// awaiting it.
+    Fs,
+    /// `std::thread::sleep`, or a `sleep` that is not awaited.
+    Sleep,
+    /// `lock()`, `read()` or `write()` without arguments that is not
+    /// awaited, as on a `std::sync::Mutex` or `RwLock`.
+    Lock,
+    /// A request of `reqwest::blocking` or `ureq`.
+    Http,
+    /// `block_on`, which runs a future to completion on the current thread.
+    BlockOn,
+}
+
+impl BlockingKind {
+    /// Returns the name the kind is serialized as.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockingKind::Fs => "fs",
+            BlockingKind::Sleep => "sleep",
+            BlockingKind::Lock => "lock",
+            BlockingKind::Http => "http",
+            BlockingKind::BlockOn => "block_on",
+        }
+    }
+}
+
+/// A call that blocks the current thread.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockingCall {
+    pub kind: BlockingKind,
+    /// Line of the call.
+    pub line: usize,
+    /// The called function or method, as written at the call site.
+    pub call: String,
+    /// Whether the call is made in an async fn or async block, where it
+    /// blocks the executor.
+    pub is_async_context: bool,
+    /// Source text of the call.
+    pub code: String,
+}
+
+/// A call that acquires a lock: `lock()`, `read()` or `write()` without
+/// arguments, awaited or not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockAcquisition {
+    /// The lock, keyed by its field path: `Type.field` for fields of `self`
+    /// and of locals of a known type, the name of a static, or
+    /// `function::expression` for other locks.
+    pub lock: String,
+    /// `lock`, `read` or `write`.
+    pub method: String,
+    /// Line of the call.
+    pub line: usize,
+    /// The local the guard is bound to; `None` for a guard that is dropped
+    /// at the end of the statement.
+    pub guard: Option<String>,
+    /// Whether the call is awaited, as on an async mutex.
+    pub is_async: bool,
+    /// The locks whose guards are held when this one is acquired.
+    pub held: Vec<String>,
+}
+
+/// A guard of a synchronous lock that is held at an `.await`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardAcrossAwait {
+    /// The lock, keyed like [`LockAcquisition::lock`].
+    pub lock: String,
+    /// The local the guard is bound to.
+    pub guard: String,
+    /// Line where the lock is acquired.
+    pub lock_line: usize,
+    /// Line of the `.await`.
+    pub await_line: usize,
+}
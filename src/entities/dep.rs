@@ -4,10 +4,12 @@
 //! in a Rust project. Dependencies can be internal or external, and an associated
 //! RustCrate (defined in the parent module) can be tied to the dependency.
 
+use serde::{Deserialize, Serialize};
+
 use super::rcrate::RustCrate;
 
 /// Represents a Rust dependency, which could be internal or external.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct RustDependency {
     /// The name of the dependency.
     pub name: String,
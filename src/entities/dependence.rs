@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use super::flow::EdgeKind;
+
+/// How a [`Definition`] gives a variable its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionKind {
+    /// A parameter of the function, defined on entry.
+    Parameter,
+    /// A binding of a `let`, `if let`, `for` or `match` arm pattern.
+    Declaration,
+    /// An assignment to the whole variable, e.g. `x = 1` or `x += 1`.
+    Assignment,
+    /// A change to part of the variable: an assignment to a field or
+    /// element, a `&mut` borrow or a method call on a mutable variable.
+    /// Unlike the other kinds it does not replace earlier definitions.
+    Mutation,
+    /// A value the function returns, with `return` or as the tail
+    /// expression of its body; defines the variable `return`.
+    Return,
+}
+
+impl DefinitionKind {
+    /// Returns the name the kind is serialized as.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DefinitionKind::Parameter => "parameter",
+            DefinitionKind::Declaration => "declaration",
+            DefinitionKind::Assignment => "assignment",
+            DefinitionKind::Mutation => "mutation",
+            DefinitionKind::Return => "return",
+        }
+    }
+}
+
+/// A place in a function body where a local variable gets a value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Definition {
+    /// Index of the definition in [`ProgramDependenceGraph::definitions`].
+    pub id: usize,
+    pub variable: String,
+    pub kind: DefinitionKind,
+    /// Basic block of the definition in the function's control-flow graph.
+    pub block: usize,
+    pub line: usize,
+    /// Position of the parameter, for [`DefinitionKind::Parameter`].
+    pub parameter: Option<usize>,
+    /// Ids of the definitions the value is computed from.
+    pub inputs: Vec<usize>,
+    /// Indices in [`ProgramDependenceGraph::calls`] of the calls made to
+    /// compute the value.
+    pub calls: Vec<usize>,
+}
+
+/// A use of a variable that a definition reaches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataDependence {
+    /// Id of the reaching definition.
+    pub definition: usize,
+    pub variable: String,
+    /// Basic block of the use.
+    pub block: usize,
+    /// Line of the use.
+    pub line: usize,
+}
+
+/// Block `block` runs only if `controller` branches along an edge of
+/// `kind`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlDependence {
+    pub block: usize,
+    pub controller: usize,
+    pub kind: EdgeKind,
+    /// Pattern of the `match` arm, for [`EdgeKind::Arm`].
+    pub label: Option<String>,
+}
+
+/// The receiver or an argument of a call.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argument {
+    /// Variables used in the expression.
+    pub variables: Vec<String>,
+    /// Ids of the definitions of those variables that reach the call.
+    pub definitions: Vec<usize>,
+    /// Indices in [`ProgramDependenceGraph::calls`] of the calls made in
+    /// the expression.
+    pub calls: Vec<usize>,
+}
+
+/// The values a call passes to its callee.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallArguments {
+    pub line: usize,
+    /// Callee as written at the call site, as in
+    /// [`CallSite::callee`](super::callsite::CallSite::callee). Macro
+    /// invocations whose arguments parse as expressions are recorded too,
+    /// with a `!` after the name, e.g. `format!`.
+    pub call: String,
+    /// Basic block of the call.
+    pub block: usize,
+    /// Receiver of a method call.
+    pub receiver: Option<Argument>,
+    pub arguments: Vec<Argument>,
+}
+
+/// The program dependence graph of a function body: the data dependences
+/// between the definitions and uses of its local variables, and the control
+/// dependences between the basic blocks of its
+/// [`ControlFlowGraph`](super::flow::ControlFlowGraph).
+///
+/// Variables are identified by name, so shadowed bindings share their
+/// definitions. Blocks without a control dependence run whenever the
+/// function does.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramDependenceGraph {
+    pub definitions: Vec<Definition>,
+    pub data_dependences: Vec<DataDependence>,
+    pub control_dependences: Vec<ControlDependence>,
+    pub calls: Vec<CallArguments>,
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// A `?` applied in a function body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorPropagation {
+    /// Line of the call the `?` is applied to, or of the `?` itself if the
+    /// operand is not a call. Matches the line of the call site.
+    pub line: usize,
+    /// The call whose result is propagated, as written at the call site
+    /// (see [`CallSite::callee`](super::callsite::CallSite::callee)).
+    pub call: Option<String>,
+    /// Method between the call and the `?` that replaces the error, such as
+    /// `map_err` or `context`.
+    pub adapter: Option<String>,
+    /// Source text of the operand, without the `?`.
+    pub code: String,
+}
+
+/// How a function body discards an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwallowKind {
+    /// `.ok()`, which turns the error into `None`.
+    Ok,
+    /// `let _ = call()` or `_ = call()`.
+    LetUnderscore,
+    /// `.unwrap_or_default()`.
+    UnwrapOrDefault,
+}
+
+impl SwallowKind {
+    /// Returns the name the kind is serialized as.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SwallowKind::Ok => "ok",
+            SwallowKind::LetUnderscore => "let_underscore",
+            SwallowKind::UnwrapOrDefault => "unwrap_or_default",
+        }
+    }
+}
+
+/// An expression that discards the error of a call. The result types of
+/// calls are not known during extraction, so `let _ =` and
+/// `unwrap_or_default` are recorded for every call, including calls that do
+/// not return a `Result`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwallowedError {
+    pub kind: SwallowKind,
+    /// Line of the call whose error is discarded.
+    pub line: usize,
+    /// The call whose error is discarded, as written at the call site.
+    pub call: Option<String>,
+    /// Source text of the expression.
+    pub code: String,
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+/// Id of the entry block of every [`ControlFlowGraph`].
+pub const ENTRY_BLOCK: usize = 0;
+/// Id of the exit block of every [`ControlFlowGraph`].
+pub const EXIT_BLOCK: usize = 1;
+
+/// What a basic block stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    /// The start of the body.
+    Entry,
+    /// Where every return, `?` error and panic ends; has no statements.
+    Exit,
+    /// A run of statements.
+    Body,
+    /// The head of a loop, which `continue` and the end of the body jump to.
+    LoopHead,
+    /// Where the branches of an `if`, a `match`, `&&` or `||` or the exits of
+    /// a loop or labeled block meet.
+    Join,
+}
+
+impl BlockKind {
+    /// Returns the name the kind is serialized as.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockKind::Entry => "entry",
+            BlockKind::Exit => "exit",
+            BlockKind::Body => "body",
+            BlockKind::LoopHead => "loop_head",
+            BlockKind::Join => "join",
+        }
+    }
+}
+
+/// A basic block of a [`ControlFlowGraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BasicBlock {
+    /// Index of the block in [`ControlFlowGraph::blocks`].
+    pub id: usize,
+    pub kind: BlockKind,
+    /// First line of the code in the block; `None` for blocks without code.
+    pub start_line: Option<usize>,
+    /// Last line of the code in the block.
+    pub end_line: Option<usize>,
+    /// Source text of the expression the block branches on, e.g. the
+    /// condition of an `if` or the operand of a `?`.
+    pub condition: Option<String>,
+}
+
+/// How control passes along a [`CfgEdge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// Unconditional fall-through, including the `Ok` path of a `?`.
+    Next,
+    /// The condition holds.
+    True,
+    /// The condition does not hold.
+    False,
+    /// A `match` arm is taken; the edge is labeled with its pattern.
+    Arm,
+    Break,
+    Continue,
+    Return,
+    /// The `Err` or `None` path of a `?`.
+    Error,
+    /// A panicking macro such as `panic!` or `unreachable!`.
+    Panic,
+}
+
+impl EdgeKind {
+    /// Returns the name the kind is serialized as.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Next => "next",
+            EdgeKind::True => "true",
+            EdgeKind::False => "false",
+            EdgeKind::Arm => "arm",
+            EdgeKind::Break => "break",
+            EdgeKind::Continue => "continue",
+            EdgeKind::Return => "return",
+            EdgeKind::Error => "error",
+            EdgeKind::Panic => "panic",
+        }
+    }
+}
+
+/// An edge between two basic blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CfgEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+    /// Pattern of a `match` arm.
+    pub label: Option<String>,
+}
+
+/// The basic-block control-flow graph of a function body.
+///
+/// Block [`ENTRY_BLOCK`] is the entry and block [`EXIT_BLOCK`] the exit.
+/// Closures and async blocks are opaque: their bodies are part of the block
+/// that creates them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    pub edges: Vec<CfgEdge>,
+}
+
+impl ControlFlowGraph {
+    /// Returns the cyclomatic complexity `E - N + 2` of the graph.
+    pub fn cyclomatic_complexity(&self) -> usize {
+        (self.edges.len() + 2)
+            .saturating_sub(self.blocks.len())
+            .max(1)
+    }
+
+    /// Returns the edges leaving block `id`.
+    pub fn successors(&self, id: usize) -> impl Iterator<Item = &CfgEdge> {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+
+    /// Returns the edges entering block `id`.
+    pub fn predecessors(&self, id: usize) -> impl Iterator<Item = &CfgEdge> {
+        self.edges.iter().filter(move |edge| edge.to == id)
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+use super::{RustVisibility, attr::RustAttribute, param::RustParameter, rtype::RustType};
+
+/// Represents an `extern` block declaring functions and statics that are
+/// defined outside of Rust.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustForeignBlock {
+    /// The ABI of the block, `"C"` when none is written.
+    pub abi: String,
+    /// True for `unsafe extern` blocks.
+    pub is_unsafe: bool,
+    /// Attributes applied to the block.
+    pub attributes: Vec<RustAttribute>,
+    /// Native libraries named in `#[link]` attributes.
+    pub links: Vec<RustLink>,
+    /// Map of the functions declared in the block.
+    pub functions: BTreeMap<String, RustForeignFunction>,
+    /// Map of the statics declared in the block.
+    pub statics: BTreeMap<String, RustForeignStatic>,
+    /// The starting line number of the block.
+    pub start_line: usize,
+    /// The ending line number of the block.
+    pub end_line: usize,
+}
+
+impl RustForeignBlock {
+    /// Creates an empty block with the given ABI and lines.
+    pub fn new(abi: String, start_line: usize, end_line: usize) -> Self {
+        Self {
+            abi,
+            is_unsafe: false,
+            attributes: Vec::new(),
+            links: Vec::new(),
+            functions: BTreeMap::new(),
+            statics: BTreeMap::new(),
+            start_line,
+            end_line,
+        }
+    }
+}
+
+/// A native library linked with `#[link(name = "...")]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustLink {
+    /// Name of the library.
+    pub name: String,
+    /// Kind of linking, e.g. `static`, `dylib` or `framework`.
+    pub kind: Option<String>,
+}
+
+/// Represents a function declared in an `extern` block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustForeignFunction {
+    /// The name of the function in Rust.
+    pub name: String,
+    /// The symbol the function is linked against, from `#[link_name]`.
+    pub link_name: Option<String>,
+    /// Visibility of the function.
+    pub visibility: RustVisibility,
+    /// Documentation comment associated with the function.
+    pub doc_comment: Option<String>,
+    /// Attributes attached to the function.
+    pub attributes: Vec<RustAttribute>,
+    /// Parameters of the function.
+    pub parameters: Vec<RustParameter>,
+    /// Return type of the function.
+    pub return_type: Option<RustType>,
+    /// True if the function takes a variable number of arguments (`...`).
+    pub is_variadic: bool,
+    /// True for `safe fn` declarations, which can be called outside `unsafe`.
+    pub is_safe: bool,
+    /// The starting line number of the declaration.
+    pub start_line: usize,
+    /// The ending line number of the declaration.
+    pub end_line: usize,
+}
+
+/// Represents a static declared in an `extern` block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustForeignStatic {
+    /// The name of the static in Rust.
+    pub name: String,
+    /// The symbol the static is linked against, from `#[link_name]`.
+    pub link_name: Option<String>,
+    /// Visibility of the static.
+    pub visibility: RustVisibility,
+    /// Documentation comment associated with the static.
+    pub doc_comment: Option<String>,
+    /// Attributes attached to the static.
+    pub attributes: Vec<RustAttribute>,
+    /// Type of the static.
+    pub rust_type: RustType,
+    /// True for `static mut`.
+    pub is_mut: bool,
+    /// True for `safe static` declarations.
+    pub is_safe: bool,
+    /// The starting line number of the declaration.
+    pub start_line: usize,
+    /// The ending line number of the declaration.
+    pub end_line: usize,
+}
+
+impl RustForeignFunction {
+    /// The symbol the function is linked against.
+    pub fn symbol(&self) -> &str {
+        self.link_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl RustForeignStatic {
+    /// The symbol the static is linked against.
+    pub fn symbol(&self) -> &str {
+        self.link_name.as_deref().unwrap_or(&self.name)
+    }
+}
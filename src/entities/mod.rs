@@ -1,11 +1,18 @@
 use serde::{Deserialize, Serialize};
 
+pub mod asm;
 pub mod attr;
 pub mod callable;
 pub mod callsite;
+pub mod concurrency;
 pub mod dep;
+pub mod dependence;
+pub mod errors;
+pub mod flow;
+pub mod foreign;
 pub mod lifetime;
 pub mod module;
+pub mod panic;
 pub mod param;
 pub mod rcrate;
 pub mod renum;
@@ -14,8 +21,10 @@ pub mod rmacro;
 pub mod rstruct;
 pub mod rtrait;
 pub mod rtype;
+pub mod runion;
 pub mod safety;
 pub mod variables;
+pub mod workspace;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RustVisibility {
     Public,
@@ -37,7 +46,7 @@ impl RustVisibility {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SafetyClassification {
     Safe,
     Unsafe,
@@ -72,10 +81,12 @@ impl SafetyClassification {
 /// # Examples
 ///
 /// ```
+/// use codeanalyzer_rs::entities::UnsafeReason;
+///
 /// let reason = UnsafeReason::RawPointerDeref;
 /// assert_eq!(reason.as_str(), "raw_pointer_deref");
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UnsafeReason {
     RawPointerDeref,
     MutableStatic,
@@ -120,7 +131,7 @@ impl UnsafeReason {
 /// - `Normal`: A regular struct with named fields.
 /// - `Tuple`: A tuple struct with unnamed fields.
 /// - `Unit`: A unit struct without any fields.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RustStructKind {
     /// Regular struct with named fields.
     Normal,
@@ -1,20 +1,42 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
 
 use super::{
     RustVisibility,
+    asm::RustInlineAsm,
     attr::RustAttribute,
     callable::RustCallable,
+    foreign::RustForeignBlock,
     renum::RustEnum,
     rimpl::RustImpl,
     rmacro::RustMacro,
     rstruct::RustStruct,
     rtrait::RustTrait,
     rtype::{RustType, RustTypeAlias},
+    runion::RustUnion,
     variables::RustVariableDeclaration,
 };
 
+/// An item that shares its name with an earlier item of the same kind, and
+/// that the symbol table therefore leaves out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustShadowedItem {
+    /// Kind of the item: `function`, `struct`, `enum`, `union`, `trait`,
+    /// `type` or `module`.
+    pub kind: String,
+    /// Name of the item.
+    pub name: String,
+    /// The item's `#[cfg]` predicates, e.g. `windows`.
+    pub cfg: Vec<String>,
+    /// The starting line number of the item.
+    pub start_line: usize,
+    /// The ending line number of the item.
+    pub end_line: usize,
+}
+
 /// Represents a Rust module with all possible items.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustModule {
     /// The name of the module.
     pub name: String,
@@ -27,37 +49,47 @@ pub struct RustModule {
 
     // Type definitions
     /// Map of type definitions.
-    pub types: HashMap<String, RustType>,
+    pub types: BTreeMap<String, RustType>,
     /// Map of structs.
-    pub structs: HashMap<String, RustStruct>,
+    pub structs: BTreeMap<String, RustStruct>,
     /// Map of enums.
-    pub enums: HashMap<String, RustEnum>,
+    pub enums: BTreeMap<String, RustEnum>,
+    /// Map of unions.
+    pub unions: BTreeMap<String, RustUnion>,
     /// Map of traits.
-    pub traits: HashMap<String, RustTrait>,
+    pub traits: BTreeMap<String, RustTrait>,
     /// List of implementations.
     pub impls: Vec<RustImpl>,
     /// Map of type aliases.
-    pub type_aliases: HashMap<String, RustTypeAlias>,
+    pub type_aliases: BTreeMap<String, RustTypeAlias>,
 
     // Functions and macros
     /// Map of functions.
-    pub functions: HashMap<String, RustCallable>,
+    pub functions: BTreeMap<String, RustCallable>,
     /// Map of safe functions.
-    pub safe_functions: HashMap<String, RustCallable>,
+    pub safe_functions: BTreeMap<String, RustCallable>,
     /// Map of unsafe functions.
-    pub unsafe_functions: HashMap<String, RustCallable>,
+    pub unsafe_functions: BTreeMap<String, RustCallable>,
     /// Map of macros.
-    pub macros: HashMap<String, RustMacro>,
+    pub macros: BTreeMap<String, RustMacro>,
+    /// List of `extern` blocks.
+    pub foreign_blocks: Vec<RustForeignBlock>,
+    /// Module-level `global_asm!` invocations.
+    pub global_asm: Vec<RustInlineAsm>,
 
     // Module structure
     /// Map of submodules.
-    pub submodules: HashMap<String, RustModule>,
+    pub submodules: BTreeMap<String, RustModule>,
     /// Constants declared in the module.
     pub constants: Vec<RustVariableDeclaration>,
     /// List of use declarations.
     pub use_declarations: Vec<String>,
     /// List of extern crate declarations.
     pub extern_crates: Vec<String>,
+    /// Items left out because an earlier item of the same name and kind is
+    /// in the module, such as the `#[cfg(windows)]` variant of a function
+    /// that comes after its `#[cfg(unix)]` variant.
+    pub shadowed_items: Vec<RustShadowedItem>,
 
     // Module properties
     /// Indicates if the module is marked as unsafe.
@@ -78,24 +110,62 @@ impl RustModule {
             doc_comment: None,
             attributes: Vec::new(),
             visibility: RustVisibility::Private,
-            types: HashMap::new(),
-            structs: HashMap::new(),
-            enums: HashMap::new(),
-            traits: HashMap::new(),
+            types: BTreeMap::new(),
+            structs: BTreeMap::new(),
+            enums: BTreeMap::new(),
+            unions: BTreeMap::new(),
+            traits: BTreeMap::new(),
             impls: Vec::new(),
-            type_aliases: HashMap::new(),
-            functions: HashMap::new(),
-            safe_functions: HashMap::new(),
-            unsafe_functions: HashMap::new(),
-            macros: HashMap::new(),
-            submodules: HashMap::new(),
+            type_aliases: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            safe_functions: BTreeMap::new(),
+            unsafe_functions: BTreeMap::new(),
+            macros: BTreeMap::new(),
+            foreign_blocks: Vec::new(),
+            global_asm: Vec::new(),
+            submodules: BTreeMap::new(),
             constants: Vec::new(),
             use_declarations: Vec::new(),
             extern_crates: Vec::new(),
+            shadowed_items: Vec::new(),
             is_unsafe: false,
             file_path: None,
             is_mod_rs: false,
             is_root_module: false,
         }
     }
+
+    /// Returns every callable defined directly in this module: free functions,
+    /// methods of impl blocks and default methods of traits.
+    ///
+    /// Each callable is paired with the path segment of its owner, or `None`
+    /// for free functions: see [`RustImpl::owner_name`] for impl methods, and
+    /// the trait name for trait methods.
+    pub fn callables(&self) -> impl Iterator<Item = (Option<String>, &RustCallable)> {
+        let functions = self.functions.values().map(|f| (None, f));
+        let methods = self.impls.iter().flat_map(|i| {
+            let owner = i.owner_name();
+            i.methods.values().map(move |m| (Some(owner.clone()), m))
+        });
+        let trait_methods = self
+            .traits
+            .values()
+            .flat_map(|t| t.methods.values().map(move |m| (Some(t.name.clone()), m)));
+        functions.chain(methods).chain(trait_methods)
+    }
+
+    /// Returns this module and all of its nested submodules in pre-order,
+    /// each paired with its `::`-separated path starting at `self`.
+    pub fn module_tree(&self) -> Vec<(String, &RustModule)> {
+        let mut out = Vec::new();
+        self.collect_modules(self.name.clone(), &mut out);
+        out
+    }
+
+    fn collect_modules<'a>(&'a self, path: String, out: &mut Vec<(String, &'a RustModule)>) {
+        out.push((path.clone(), self));
+        for sub in self.submodules.values() {
+            sub.collect_modules(format!("{}::{}", path, sub.name), out);
+        }
+    }
 }
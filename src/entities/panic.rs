@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// What can make an expression panic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanicKind {
+    /// `unwrap()` or `unwrap_err()`.
+    Unwrap,
+    /// `expect(..)` or `expect_err(..)`.
+    Expect,
+    /// `panic!`.
+    Panic,
+    /// `unreachable!`.
+    Unreachable,
+    /// `todo!`.
+    Todo,
+    /// `unimplemented!`.
+    Unimplemented,
+    /// `assert!`, `assert_eq!` or `assert_ne!`.
+    Assert,
+    /// Indexing a slice, array or map, which panics when out of bounds.
+    Index,
+    /// Integer division or remainder, which panics on a zero divisor.
+    Division,
+    /// `borrow_mut()`, which panics if a `RefCell` is already borrowed.
+    BorrowMut,
+}
+
+impl PanicKind {
+    /// Returns the name the kind is serialized as.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PanicKind::Unwrap => "unwrap",
+            PanicKind::Expect => "expect",
+            PanicKind::Panic => "panic",
+            PanicKind::Unreachable => "unreachable",
+            PanicKind::Todo => "todo",
+            PanicKind::Unimplemented => "unimplemented",
+            PanicKind::Assert => "assert",
+            PanicKind::Index => "index",
+            PanicKind::Division => "division",
+            PanicKind::BorrowMut => "borrow_mut",
+        }
+    }
+}
+
+/// An expression in a function body that can panic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanicSite {
+    pub kind: PanicKind,
+    /// Line of the expression.
+    pub line: usize,
+    /// Source text of the expression.
+    pub code: String,
+}
+
+/// A callable with panic sites that another callable reaches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PanicPath {
+    /// Item path of the callable with the panic sites.
+    pub target: String,
+    /// Item paths of the callables on the shortest call path, starting at
+    /// the callable the path belongs to and ending at the target.
+    pub path: Vec<String>,
+    /// The kinds of the panic sites of the target.
+    pub kinds: Vec<PanicKind>,
+}
@@ -34,7 +34,7 @@ impl RustGenericParam {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustParameter {
     /// Name of the parameter
     pub name: String,
@@ -1,7 +1,9 @@
-use super::{dep::RustDependency, module::RustModule};
+use serde::{Deserialize, Serialize};
+
+use super::{callable::RustCallable, dep::RustDependency, module::RustModule};
 
 /// Represents a complete Rust crate.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustCrate {
     /// The name of the crate.
     pub name: String,
@@ -45,4 +47,42 @@ impl RustCrate {
             dependencies: Vec::new(),
         }
     }
+
+    /// Returns the module at `path`, e.g. `my_crate::util`.
+    pub fn module(&self, path: &str) -> Option<&RustModule> {
+        let mut segments = path.split("::");
+        let root = segments.next()?;
+        let mut module = self.modules.iter().find(|m| m.name == root)?;
+        for segment in segments {
+            module = module.submodules.get(segment)?;
+        }
+        Some(module)
+    }
+
+    /// Returns the callable at `path`, e.g. `my_crate::util::parse` or
+    /// `my_crate::<Config as Default>::default`, together with the
+    /// path of its module and its owner segment as yielded by
+    /// [`RustModule::callables`].
+    pub fn callable(&self, path: &str) -> Option<(String, Option<String>, &RustCallable)> {
+        let (mut module_path, mut rest) = path.split_once("::")?;
+        let mut module = self.modules.iter().find(|m| m.name == module_path)?;
+        let mut len = module_path.len();
+        while let Some((segment, tail)) = rest.split_once("::") {
+            let Some(sub) = module.submodules.get(segment) else {
+                break;
+            };
+            module = sub;
+            len += 2 + segment.len();
+            rest = tail;
+        }
+        module_path = &path[..len];
+        let (owner, name) = match rest.rsplit_once("::") {
+            Some((owner, name)) => (Some(owner), name),
+            None => (None, rest),
+        };
+        module
+            .callables()
+            .find(|(o, c)| o.as_deref() == owner && c.name == name)
+            .map(|(o, c)| (module_path.to_owned(), o, c))
+    }
 }
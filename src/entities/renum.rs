@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
 
 use super::{
     attr::RustAttribute, callable::RustCallable, lifetime::RustLifetimeParam,
@@ -6,7 +8,7 @@ use super::{
 };
 
 /// Represents a Rust enum.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustEnum {
     /// The name of the enum.
     pub name: String,
@@ -27,7 +29,7 @@ pub struct RustEnum {
     /// Traits to derive.
     pub derives: Vec<String>,
     /// Associated items mapped by their name.
-    pub associated_items: HashMap<String, RustCallable>,
+    pub associated_items: BTreeMap<String, RustCallable>,
     /// Traits implemented for the enum.
     pub impl_traits: Vec<String>,
     /// Indicates if the enum is public.
@@ -39,7 +41,7 @@ pub struct RustEnum {
 }
 
 /// Represents a variant in a Rust enum.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustEnumVariant {
     /// The name of the variant.
     pub name: String,
@@ -47,6 +49,9 @@ pub struct RustEnumVariant {
     pub fields: Option<Vec<RustStructField>>,
     /// Types for a tuple variant.
     pub tuple_types: Option<Vec<RustType>>,
+    /// Attributes of the fields of a tuple variant, e.g. `#[from]`, in the
+    /// order of [`tuple_types`](Self::tuple_types).
+    pub tuple_attributes: Option<Vec<Vec<RustAttribute>>>,
     /// Explicit discriminant for the variant.
     pub discriminant: Option<String>,
     /// Optional documentation comment.
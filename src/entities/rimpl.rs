@@ -1,11 +1,13 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
 
 use super::{
     callable::RustCallable, lifetime::RustLifetimeParam, param::RustGenericParam, rtype::RustType,
 };
 
 /// Represents a Rust impl block.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustImpl {
     /// The type name that the impl block is for.
     pub type_name: String,
@@ -18,11 +20,11 @@ pub struct RustImpl {
     /// Where clauses associated with the impl.
     pub where_clauses: Vec<String>,
     /// Methods in the impl; the key is the method name.
-    pub methods: HashMap<String, RustCallable>,
+    pub methods: BTreeMap<String, RustCallable>,
     /// Associated types defined in the impl.
-    pub associated_types: HashMap<String, RustType>,
+    pub associated_types: BTreeMap<String, RustType>,
     /// Associated constants defined in the impl.
-    pub associated_consts: HashMap<String, String>,
+    pub associated_consts: BTreeMap<String, String>,
     /// Whether the impl is marked as unsafe.
     pub is_unsafe: bool,
     /// Whether the impl is a negative impl (e.g., `!Send`).
@@ -32,3 +34,30 @@ pub struct RustImpl {
     /// The ending line number of the impl block in the source.
     pub end_line: usize,
 }
+
+impl RustImpl {
+    /// Returns the path segment that qualifies the methods of this impl:
+    /// `Type` for inherent impls and `<Type as Trait>` for trait impls.
+    pub fn owner_name(&self) -> String {
+        match &self.trait_name {
+            Some(trait_name) => format!("<{} as {}>", self.type_name, trait_name),
+            None => self.type_name.clone(),
+        }
+    }
+
+    /// Returns the path of the implementing type as written, without
+    /// references or generic arguments, e.g. `std::vec::Vec` for
+    /// `&mut std::vec::Vec<T>`.
+    pub fn self_type_path(&self) -> &str {
+        let ty = self.type_name.trim_start_matches('&').trim_start();
+        let ty = ty.strip_prefix("mut ").unwrap_or(ty);
+        ty.split('<').next().unwrap_or(ty).trim()
+    }
+
+    /// Returns the name of the implementing type without references, generic
+    /// arguments or module path, e.g. `Vec` for `&mut std::vec::Vec<T>`.
+    pub fn self_type_name(&self) -> &str {
+        let ty = self.self_type_path();
+        ty.rsplit("::").next().unwrap_or(ty).trim()
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use super::{RustVisibility, attr::RustAttribute};
 
 /// Represents a Rust macro definition.
@@ -6,7 +8,7 @@ use super::{RustVisibility, attr::RustAttribute};
 /// in Rust source code. It contains the macro's name, visibility,
 /// documentation comment, any attributes associated with it,
 /// its defining rules, and metadata regarding its type and location.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustMacro {
     /// The name of the macro.
     pub name: String,
@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
 
 use super::{
     RustStructKind, RustVisibility, attr::RustAttribute, callable::RustCallable,
@@ -6,7 +8,7 @@ use super::{
 };
 
 /// Represents a field in a Rust struct.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustStructField {
     /// Name of the field.
     pub name: String,
@@ -35,7 +37,7 @@ impl RustStructField {
 }
 
 /// Represents a Rust struct definition.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustStruct {
     /// Name of the Rust struct.
     pub name: String,
@@ -58,7 +60,7 @@ pub struct RustStruct {
     /// Derives applied to the struct.
     pub derives: Vec<String>,
     /// Associated items (e.g., methods, functions) where key is the item name.
-    pub associated_items: HashMap<String, RustCallable>,
+    pub associated_items: BTreeMap<String, RustCallable>,
     /// Traits that this struct implements.
     pub impl_traits: Vec<String>,
     /// Indicates whether the struct is public.
@@ -86,7 +88,7 @@ impl RustStruct {
             lifetime_params: Vec::new(),
             where_clauses: Vec::new(),
             derives: Vec::new(),
-            associated_items: HashMap::new(),
+            associated_items: BTreeMap::new(),
             impl_traits: Vec::new(),
             is_public: false,
             contains_unsafe: false,
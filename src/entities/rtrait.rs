@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
 
 use super::{
     attr::RustAttribute, callable::RustCallable, lifetime::RustLifetimeParam,
@@ -6,7 +8,7 @@ use super::{
 };
 
 /// Represents a trait bound in Rust.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustTraitBound {
     /// The name of the trait.
     pub trait_name: String,
@@ -41,7 +43,7 @@ impl Default for RustTraitBound {
 /// Assumes that types like `RustVisibility`, `RustAttribute`,
 /// `RustGenericParam`, `RustLifetimeParam`, `RustType`, and
 /// `RustCallable` exist in the parent module.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RustTrait {
     /// The name of the trait.
     pub name: String,
@@ -68,13 +70,13 @@ pub struct RustTrait {
     pub super_traits: Vec<RustTraitBound>,
 
     /// A mapping of associated types.
-    pub associated_types: HashMap<String, RustType>,
+    pub associated_types: BTreeMap<String, RustType>,
 
     /// A mapping of associated constants.
-    pub associated_consts: HashMap<String, String>,
+    pub associated_consts: BTreeMap<String, String>,
 
     /// A mapping of method names to their definitions.
-    pub methods: HashMap<String, RustCallable>,
+    pub methods: BTreeMap<String, RustCallable>,
 
     /// Indicates if the trait is declared as unsafe.
     pub is_unsafe: bool,
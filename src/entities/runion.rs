@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+use super::{
+    RustVisibility, attr::RustAttribute, callable::RustCallable, lifetime::RustLifetimeParam,
+    param::RustGenericParam, rstruct::RustStructField,
+};
+
+/// Represents a Rust union definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustUnion {
+    /// Name of the union.
+    pub name: String,
+    /// Visibility of the union. Defaults to `RustVisibility::PRIVATE`.
+    pub visibility: RustVisibility,
+    /// Optional documentation comment.
+    pub doc_comment: Option<String>,
+    /// List of attributes associated with the union.
+    pub attributes: Vec<RustAttribute>,
+    /// The arguments of its `#[repr(...)]` attributes, e.g. `C`.
+    pub repr: Vec<String>,
+    /// List of fields, which all share the same storage.
+    pub fields: Vec<RustStructField>,
+    /// Generic parameters for the union.
+    pub generic_params: Vec<RustGenericParam>,
+    /// Lifetime parameters for the union.
+    pub lifetime_params: Vec<RustLifetimeParam>,
+    /// Where clauses applied to the union.
+    pub where_clauses: Vec<String>,
+    /// Derives applied to the union.
+    pub derives: Vec<String>,
+    /// Associated items (e.g., methods, functions) where key is the item name.
+    pub associated_items: BTreeMap<String, RustCallable>,
+    /// Traits that this union implements.
+    pub impl_traits: Vec<String>,
+    /// The starting line number of the union definition in the source file.
+    pub start_line: usize,
+    /// The ending line number of the union definition in the source file.
+    pub end_line: usize,
+}
+
+impl RustUnion {
+    /// Creates a new `RustUnion` with the given name, start_line, and end_line.
+    /// Default values are used for the remaining fields.
+    pub fn new(name: String, start_line: usize, end_line: usize) -> Self {
+        Self {
+            name,
+            visibility: RustVisibility::Private,
+            doc_comment: None,
+            attributes: Vec::new(),
+            repr: Vec::new(),
+            fields: Vec::new(),
+            generic_params: Vec::new(),
+            lifetime_params: Vec::new(),
+            where_clauses: Vec::new(),
+            derives: Vec::new(),
+            associated_items: BTreeMap::new(),
+            impl_traits: Vec::new(),
+            start_line,
+            end_line,
+        }
+    }
+}
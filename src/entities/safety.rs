@@ -1,10 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 use super::{SafetyClassification, UnsafeReason};
 
 /// Represents an unsafe block within Rust code.
 ///
 /// It includes the start and end line numbers, reasons for using unsafe,
 /// an optional explanation, and the name of the containing function (if any).
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UnsafeBlock {
     /// The starting line number of the unsafe block.
     pub start_line: usize,
@@ -40,7 +42,7 @@ impl UnsafeBlock {
 ///
 /// This structure aggregates various pieces of data related to unsafe operations,
 /// such as unsafe blocks, function calls, raw pointer usages, FFI interactions, and more.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SafetyAnalysis {
     /// The overall safety classification.
     pub classification: SafetyClassification,
@@ -52,12 +54,17 @@ pub struct SafetyAnalysis {
     pub raw_pointer_usage: bool,
     /// Indicates if FFI interactions were found.
     pub ffi_interactions: bool,
+    /// Names of the foreign functions called directly.
+    pub ffi_calls: Vec<String>,
     /// A list of unsafe traits used.
     pub unsafe_traits_used: Vec<String>,
     /// A list of mutable static variables.
     pub mutable_statics: Vec<String>,
     /// Optional safety comments.
     pub safety_comments: Option<String>,
+    /// Unsafe code reachable through the callees, computed from analysis
+    /// level 2 on.
+    pub transitive: Option<TransitiveSafety>,
 }
 
 impl SafetyAnalysis {
@@ -75,9 +82,36 @@ impl SafetyAnalysis {
             unsafe_fn_calls: Vec::new(),
             raw_pointer_usage: false,
             ffi_interactions: false,
+            ffi_calls: Vec::new(),
             unsafe_traits_used: Vec::new(),
             mutable_statics: Vec::new(),
             safety_comments: None,
+            transitive: None,
         }
     }
 }
+
+/// Unsafe code a callable reaches through the calls it makes, directly or
+/// through other callables of the crate.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitiveSafety {
+    /// Number of unsafe blocks in the callable and every callable it reaches.
+    pub unsafe_blocks: usize,
+    /// Reached callables that contain unsafe blocks or are `unsafe fn`s.
+    pub unsafe_callables: Vec<SafetyPath>,
+    /// Foreign functions, declared in `extern` blocks, called by the
+    /// callable or any callable it reaches.
+    pub ffi_functions: Vec<SafetyPath>,
+}
+
+/// The shortest call path from a callable to something unsafe it reaches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyPath {
+    /// Item path of the reached callable, or the name of a foreign function
+    /// declared in an `extern` block.
+    pub target: String,
+    /// Item paths of the callables on the way, starting at the callable the
+    /// summary belongs to and ending at the target or, for a foreign
+    /// function, at the callable that calls it.
+    pub path: Vec<String>,
+}
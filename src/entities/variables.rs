@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::entities::{RustVisibility, attr::RustAttribute, rtype::RustType};
 /// Represents a variable declaration in Rust.
 ///
@@ -17,11 +19,13 @@ use crate::entities::{RustVisibility, attr::RustAttribute, rtype::RustType};
 /// # Examples
 ///
 /// ```
+/// use codeanalyzer_rs::entities::variables::RustVariableDeclaration;
+///
 /// let var = RustVariableDeclaration::new(String::from("my_var"), 1);
 /// assert_eq!(var.name, "my_var");
 /// assert_eq!(var.line_number, 1);
 /// ```
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct RustVariableDeclaration {
     /// The name of the variable
     pub name: String,
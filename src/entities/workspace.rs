@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use super::rcrate::RustCrate;
+
+/// Represents a Cargo workspace and the crates of its member packages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RustWorkspace {
+    /// The directory containing the workspace's root `Cargo.toml`.
+    pub root_path: String,
+    /// The analyzed member crates, in manifest order.
+    pub members: Vec<RustCrate>,
+}
+
+impl RustWorkspace {
+    /// Creates a new `RustWorkspace` rooted at `root_path`.
+    pub fn new(root_path: String, members: Vec<RustCrate>) -> Self {
+        Self { root_path, members }
+    }
+}
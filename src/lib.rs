@@ -0,0 +1,12 @@
+#![doc = include_str!("../README.md")]
+pub mod analysis;
+pub mod analyzer;
+pub mod entities;
+pub mod mcp;
+pub mod output;
+pub mod serve;
+pub mod watch;
+
+pub use analyzer::{Analyzer, AnalyzerError};
+#[cfg(feature = "python")]
+pub mod python;
@@ -1,8 +1,101 @@
-#![doc = include_str!("../README.md")]
-pub mod entities;
-
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
 use std::path::PathBuf;
+use std::time::Duration;
+
+use codeanalyzer_rs::{
+    Analyzer,
+    analysis::{
+        asm::AsmReport,
+        callgraph::Dispatch,
+        dependence::SystemDependenceGraph,
+        errors::ErrorGraph,
+        ffi::FfiReport,
+        flow::{self, CfgReport},
+        run_checks,
+        taint::{self, TaintConfig, TaintReport},
+    },
+    analyzer::cache::AnalysisCache,
+    entities::workspace::RustWorkspace,
+    mcp::McpServer,
+    output::{ndjson::NdjsonWriter, sarif::SarifLog, sqlite::SqliteExporter},
+    serve::Server,
+    watch::{WatchSession, watch},
+};
+
+/// Output formats supported by the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// A single JSON document (`analysis.json`).
+    Json,
+    /// One JSON record per line, written while the project is analyzed (`analysis.ndjson`);
+    /// impls are not linked and the transitive unsafety and panic summaries are `null`.
+    Ndjson,
+    /// SARIF 2.1.0 report of the unsafe-code, FFI, panic, async, dead-code and taint findings (`analysis.sarif`); needs `--analysis-level 3`.
+    Sarif,
+    /// SQLite database of the symbol table (`analysis.db`); requires `--output`.
+    Sqlite,
+    /// JSON inventory of the symbols imported and exported through FFI (`ffi.json`).
+    Ffi,
+    /// JSON inventory of the inline assembly (`asm.json`).
+    Asm,
+    /// JSON graph of how errors are propagated and discarded (`errors.json`); needs `--analysis-level 2`.
+    Errors,
+    /// JSON control-flow graph of every function (`cfg.json`); needs `--analysis-level 2`.
+    Cfg,
+    /// Graphviz DOT rendering of the control-flow graphs (`cfg.dot`); needs `--analysis-level 2`.
+    Dot,
+    /// JSON system dependence graph (`sdg.json`); needs `--analysis-level 3`.
+    Sdg,
+    /// JSON flows from taint sources to sinks (`taint.json`); needs `--analysis-level 3`.
+    Taint,
+}
+
+impl OutputFormat {
+    fn file_name(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "analysis.json",
+            OutputFormat::Ndjson => "analysis.ndjson",
+            OutputFormat::Sarif => "analysis.sarif",
+            OutputFormat::Sqlite => "analysis.db",
+            OutputFormat::Ffi => "ffi.json",
+            OutputFormat::Asm => "asm.json",
+            OutputFormat::Errors => "errors.json",
+            OutputFormat::Cfg => "cfg.json",
+            OutputFormat::Dot => "cfg.dot",
+            OutputFormat::Sdg => "sdg.json",
+            OutputFormat::Taint => "taint.json",
+        }
+    }
+
+    /// The lowest analysis level that collects what the format reports.
+    fn analysis_level(self) -> u8 {
+        match self {
+            OutputFormat::Errors | OutputFormat::Cfg | OutputFormat::Dot => 2,
+            OutputFormat::Sarif | OutputFormat::Sdg | OutputFormat::Taint => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// How calls dispatched through a trait are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DispatchMode {
+    /// Class hierarchy analysis: every implementation of the method.
+    Cha,
+    /// Rapid type analysis: the implementations for the types the crate constructs.
+    Rta,
+}
+
+impl From<DispatchMode> for Dispatch {
+    fn from(mode: DispatchMode) -> Self {
+        match mode {
+            DispatchMode::Cha => Dispatch::Cha,
+            DispatchMode::Rta => Dispatch::Rta,
+        }
+    }
+}
 
 #[derive(Parser)]
 #[command(
@@ -12,8 +105,11 @@ use std::path::PathBuf;
     long_about = None,
 )]
 pub struct CodeAnalyzer {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Path to the project root directory
-    #[arg(short = 'i', long = "input")]
+    #[arg(short = 'i', long = "input", global = true)]
     pub input: Option<PathBuf>,
 
     /// Paths to files to be analyzed from the input application
@@ -26,9 +122,13 @@ pub struct CodeAnalyzer {
 
     /// Destination directory to save the output graphs.
     /// By default, the SDG formatted as a JSON will be printed to the console.
-    #[arg(short = 'o', long = "output")]
+    #[arg(short = 'o', long = "output", global = true)]
     pub output: Option<PathBuf>,
 
+    /// Output format
+    #[arg(long = "format", value_enum, default_value = "json", global = true)]
+    pub format: OutputFormat,
+
     /// Custom build command. Defaults to auto build.
     #[arg(short = 'b', long = "build-cmd")]
     pub build: Option<String>,
@@ -43,22 +143,394 @@ pub struct CodeAnalyzer {
     pub no_clean_dependencies: bool,
 
     /// Path to the root cargo.toml file of the project
-    #[arg(short = 'f', long = "project-root-path")]
+    #[arg(short = 'f', long = "project-root-path", global = true)]
     pub project_root_pom: Option<PathBuf>,
 
+    /// Cargo features to enable when evaluating `cfg` attributes
+    #[arg(long = "features", value_delimiter = ',', global = true)]
+    pub features: Vec<String>,
+
+    /// Do not enable the `default` feature
+    #[arg(long = "no-default-features", global = true)]
+    pub no_default_features: bool,
+
+    /// Directory for cached per-file results.
+    /// Defaults to `target/codeanalyzer-cache` in the project.
+    #[arg(long = "cache-dir", global = true)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Do not read or write cached results
+    #[arg(long = "no-cache", global = true)]
+    pub no_cache: bool,
+
+    /// Remove cached results before analyzing
+    #[arg(long = "clear-cache", global = true)]
+    pub clear_cache: bool,
+
+    /// Number of files to analyze in parallel. Defaults to one per CPU.
+    #[arg(short = 'j', long = "jobs", default_value = "0", global = true)]
+    pub jobs: usize,
+
     /// Level of analysis to perform.
-    /// Options: 1 (for just symbol table) or 2 (for call graph)
-    #[arg(short = 'a', long = "analysis-level", default_value = "1")]
+    /// Options: 1 (for just symbol table), 2 (for call graph) or 3 (for
+    /// dependence graphs)
+    #[arg(
+        short = 'a',
+        long = "analysis-level",
+        default_value = "1",
+        global = true
+    )]
     pub analysis_level: u8,
 
+    /// How calls through a trait are resolved to the `callees` of call sites
+    /// from analysis level 2 on
+    #[arg(long = "dispatch", value_enum, default_value = "cha", global = true)]
+    pub dispatch: DispatchMode,
+
+    /// TOML file declaring the sources, sinks and sanitizers of the taint
+    /// analysis. Defaults to the built-in ones.
+    #[arg(long = "taint-config", global = true)]
+    pub taint_config: Option<PathBuf>,
+
     /// Print logs to console
-    #[arg(short = 'v', long = "verbose")]
+    #[arg(short = 'v', long = "verbose", global = true)]
     pub verbose: bool,
 }
 
+#[derive(Subcommand)]
+pub enum Command {
+    /// Keep the analysis up to date while the project is edited.
+    ///
+    /// Changed files are analyzed again and the added, removed and changed
+    /// entities are printed to the console as JSON lines. With `--output`,
+    /// the output file is rewritten after every update.
+    Watch {
+        /// Milliseconds without further changes to wait before analyzing
+        #[arg(long = "debounce-ms", default_value = "200")]
+        debounce_ms: u64,
+    },
+    /// Answer JSON-RPC queries about the project on stdin and stdout.
+    ///
+    /// The project is analyzed once, with call sites, and kept in memory;
+    /// `didChange` notifications update it incrementally.
+    Serve,
+    /// Run a Model Context Protocol server on stdin and stdout.
+    ///
+    /// Its tools let coding agents search symbols and read callables, call
+    /// sites, callers, callees, trait implementors and safety reports.
+    Mcp,
+}
+
 impl CodeAnalyzer {
+    fn analyzer(&self) -> Analyzer {
+        let mut analyzer = Analyzer::new()
+            .target_files(self.target_files.iter().cloned())
+            .features(self.features.iter().cloned())
+            .default_features(!self.no_default_features)
+            .analysis_level(self.analysis_level)
+            .dispatch(self.dispatch.into())
+            .jobs(self.jobs);
+        if let Some(code) = &self.source_analysis {
+            analyzer = analyzer.source("lib.rs", code.as_str());
+        } else if let Some(project) = self.project() {
+            analyzer = analyzer.project(project);
+        }
+        if let Some(cache) = self.cache().filter(|_| !self.no_cache) {
+            analyzer = analyzer.cache_dir(cache.dir());
+        }
+        analyzer
+    }
+
+    fn project(&self) -> Option<PathBuf> {
+        if let Some(input) = &self.input {
+            return Some(input.clone());
+        }
+        let manifest = self.project_root_pom.as_ref()?;
+        let dir = manifest.parent().unwrap_or(manifest);
+        Some(if dir.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            dir.to_path_buf()
+        })
+    }
+
+    fn cache(&self) -> Option<AnalysisCache> {
+        if self.source_analysis.is_some() {
+            return None;
+        }
+        let dir = match &self.cache_dir {
+            Some(dir) => dir.clone(),
+            None => self.project()?.join("target").join("codeanalyzer-cache"),
+        };
+        Some(AnalysisCache::new(dir))
+    }
+
+    fn taint_config(&self) -> Result<TaintConfig, Box<dyn std::error::Error>> {
+        Ok(match &self.taint_config {
+            Some(path) => TaintConfig::load(path)?,
+            None => TaintConfig::default(),
+        })
+    }
+
+    fn writer(&self) -> io::Result<Box<dyn Write>> {
+        Ok(match &self.output {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                let path = dir.join(self.format.file_name());
+                Box::new(BufWriter::new(File::create(path)?))
+            }
+            None => Box::new(BufWriter::new(io::stdout().lock())),
+        })
+    }
+
     pub fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Implementation would go here
+        if self.clear_cache {
+            if let Some(cache) = self.cache() {
+                cache.clear()?;
+            }
+        }
+        let analyzer = self.analyzer();
+        if self.verbose {
+            eprintln!("analyzing with {:?}", analyzer);
+        }
+        let required = self.format.analysis_level();
+        if self.analysis_level < required
+            && !matches!(self.command, Some(Command::Serve | Command::Mcp))
+        {
+            let format = self.format.to_possible_value().expect("no skipped formats");
+            return Err(format!(
+                "--format {} requires --analysis-level {}",
+                format.get_name(),
+                required
+            )
+            .into());
+        }
+        match &self.command {
+            Some(Command::Watch { debounce_ms }) => {
+                return self.watch(analyzer, Duration::from_millis(*debounce_ms));
+            }
+            Some(Command::Serve) => return self.serve(analyzer),
+            Some(Command::Mcp) => return self.mcp(analyzer),
+            None => {}
+        }
+
+        if self.format == OutputFormat::Ndjson {
+            let mut writer = NdjsonWriter::new(self.writer()?);
+            analyzer.analyze_streaming(&mut writer)?;
+            writer.into_inner().flush()?;
+            return Ok(());
+        }
+
+        let workspace = analyzer.analyze_workspace()?;
+        if self.verbose {
+            eprintln!("analyzed {} crate(s)", workspace.members.len());
+        }
+        self.write(&workspace)
+    }
+
+    fn write(&self, workspace: &RustWorkspace) -> Result<(), Box<dyn std::error::Error>> {
+        match self.format {
+            OutputFormat::Json => {
+                let mut out = self.writer()?;
+                match workspace.members.as_slice() {
+                    [krate] => serde_json::to_writer_pretty(&mut out, krate)?,
+                    _ => serde_json::to_writer_pretty(&mut out, &workspace)?,
+                }
+                writeln!(out)?;
+                out.flush()?;
+            }
+            OutputFormat::Sarif => {
+                let mut diagnostics: Vec<_> =
+                    workspace.members.iter().flat_map(run_checks).collect();
+                if self.taint_config.is_some() {
+                    let config = self.taint_config()?;
+                    diagnostics.retain(|d| d.rule_id != taint::TAINTED_FLOW.id);
+                    for krate in &workspace.members {
+                        diagnostics.extend(taint::check_with(krate, &config));
+                    }
+                }
+                let mut out = self.writer()?;
+                writeln!(
+                    out,
+                    "{}",
+                    SarifLog::from_diagnostics(&diagnostics).to_json()?
+                )?;
+                out.flush()?;
+            }
+            OutputFormat::Ndjson => {
+                let mut writer = NdjsonWriter::new(self.writer()?);
+                for krate in &workspace.members {
+                    writer.write_crate(krate)?;
+                }
+                writer.into_inner().flush()?;
+            }
+            OutputFormat::Sqlite => self.write_sqlite(workspace)?,
+            OutputFormat::Ffi => {
+                let reports: Vec<FfiReport> =
+                    workspace.members.iter().map(FfiReport::build).collect();
+                let mut out = self.writer()?;
+                match reports.as_slice() {
+                    [report] => serde_json::to_writer_pretty(&mut out, report)?,
+                    _ => serde_json::to_writer_pretty(&mut out, &reports)?,
+                }
+                writeln!(out)?;
+                out.flush()?;
+            }
+            OutputFormat::Asm => {
+                let reports: Vec<AsmReport> =
+                    workspace.members.iter().map(AsmReport::build).collect();
+                let mut out = self.writer()?;
+                match reports.as_slice() {
+                    [report] => serde_json::to_writer_pretty(&mut out, report)?,
+                    _ => serde_json::to_writer_pretty(&mut out, &reports)?,
+                }
+                writeln!(out)?;
+                out.flush()?;
+            }
+            OutputFormat::Errors => {
+                let graphs: Vec<ErrorGraph> =
+                    workspace.members.iter().map(ErrorGraph::build).collect();
+                let mut out = self.writer()?;
+                match graphs.as_slice() {
+                    [graph] => serde_json::to_writer_pretty(&mut out, graph)?,
+                    _ => serde_json::to_writer_pretty(&mut out, &graphs)?,
+                }
+                writeln!(out)?;
+                out.flush()?;
+            }
+            OutputFormat::Cfg => {
+                let reports: Vec<CfgReport> =
+                    workspace.members.iter().map(CfgReport::build).collect();
+                let mut out = self.writer()?;
+                match reports.as_slice() {
+                    [report] => serde_json::to_writer_pretty(&mut out, report)?,
+                    _ => serde_json::to_writer_pretty(&mut out, &reports)?,
+                }
+                writeln!(out)?;
+                out.flush()?;
+            }
+            OutputFormat::Dot => {
+                let reports: Vec<CfgReport> =
+                    workspace.members.iter().map(CfgReport::build).collect();
+                let mut out = self.writer()?;
+                out.write_all(flow::to_dot(&reports).as_bytes())?;
+                out.flush()?;
+            }
+            OutputFormat::Sdg => {
+                let graphs: Vec<SystemDependenceGraph> = workspace
+                    .members
+                    .iter()
+                    .map(SystemDependenceGraph::build)
+                    .collect();
+                let mut out = self.writer()?;
+                match graphs.as_slice() {
+                    [graph] => serde_json::to_writer_pretty(&mut out, graph)?,
+                    _ => serde_json::to_writer_pretty(&mut out, &graphs)?,
+                }
+                writeln!(out)?;
+                out.flush()?;
+            }
+            OutputFormat::Taint => {
+                let config = self.taint_config()?;
+                let reports: Vec<TaintReport> = workspace
+                    .members
+                    .iter()
+                    .map(|krate| TaintReport::build(krate, &config))
+                    .collect();
+                let mut out = self.writer()?;
+                match reports.as_slice() {
+                    [report] => serde_json::to_writer_pretty(&mut out, report)?,
+                    _ => serde_json::to_writer_pretty(&mut out, &reports)?,
+                }
+                writeln!(out)?;
+                out.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts the session behind `serve` and `mcp`.
+    fn session(&self, analyzer: Analyzer) -> Result<WatchSession, Box<dyn std::error::Error>> {
+        if self.source_analysis.is_some() {
+            return Err("serve and mcp need a project (--input)".into());
+        }
+        // Callers and callees need the call sites of level 2.
+        let analyzer = analyzer.analysis_level(self.analysis_level.max(2));
+        Ok(WatchSession::new(analyzer)?)
+    }
+
+    fn serve(&self, analyzer: Analyzer) -> Result<(), Box<dyn std::error::Error>> {
+        let mut server = Server::new(self.session(analyzer)?);
+        if self.verbose {
+            eprintln!("serving {} file(s)", server.session().files().len());
+        }
+        server.run(io::stdin().lock(), io::stdout().lock())?;
+        Ok(())
+    }
+
+    fn mcp(&self, analyzer: Analyzer) -> Result<(), Box<dyn std::error::Error>> {
+        let mut server = McpServer::new(self.session(analyzer)?);
+        if self.verbose {
+            eprintln!("serving {} file(s)", server.session().files().len());
+        }
+        server.run(io::stdin().lock(), io::stdout().lock())?;
+        Ok(())
+    }
+
+    fn watch(
+        &self,
+        analyzer: Analyzer,
+        debounce: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.source_analysis.is_some() {
+            return Err("watch needs a project (--input)".into());
+        }
+        let mut session = WatchSession::new(analyzer)?;
+        let root = session.root().to_string_lossy().into_owned();
+        let rewrite = |session: &WatchSession| -> io::Result<()> {
+            if self.output.is_some() {
+                let workspace = RustWorkspace::new(root.clone(), vec![session.krate().clone()]);
+                self.write(&workspace)
+                    .map_err(|e| io::Error::other(e.to_string()))?;
+            }
+            Ok(())
+        };
+        rewrite(&session)?;
+        if self.verbose {
+            eprintln!("watching {} file(s)", session.files().len());
+        }
+        watch(&mut session, debounce, |session, update| {
+            let deltas = match update {
+                Ok(deltas) => deltas,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return Ok(());
+                }
+            };
+            let mut out = io::stdout().lock();
+            for delta in &deltas {
+                serde_json::to_writer(&mut out, delta)?;
+                writeln!(out)?;
+            }
+            out.flush()?;
+            rewrite(session)
+        })?;
+        Ok(())
+    }
+
+    fn write_sqlite(&self, workspace: &RustWorkspace) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(dir) = &self.output else {
+            return Err("--format sqlite requires --output".into());
+        };
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(OutputFormat::Sqlite.file_name());
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let mut exporter = SqliteExporter::open(&path)?;
+        for krate in &workspace.members {
+            exporter.write_crate(krate)?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,503 @@
+//! Model Context Protocol server exposing the analysis to coding agents.
+//!
+//! The server speaks MCP over stdio: JSON-RPC 2.0 messages, one per line.
+//! Like [`serve`](crate::serve), it analyzes the package once and answers
+//! from memory. Its tools return text built from the entity model, mainly
+//! [`RustCallable::code`], [`RustCallable::doc_comment`] and [`CallSite`]s,
+//! so an agent reads the same code the analysis saw.
+//!
+//! Tools, with their arguments:
+//!
+//! - `search_symbols` `{query, kind?, limit?}`: callables, structs, enums and
+//!   traits whose path below the crate root contains `query`, those named
+//!   exactly `query` first.
+//! - `get_callable` `{path}`: signature, documentation and code of a callable.
+//! - `list_call_sites` `{path}`: the calls made by a callable, as written.
+//! - `get_callers` / `get_callees` `{path}`: resolved calls to or from a
+//!   callable.
+//! - `find_trait_implementors` `{trait}`: types implementing a trait.
+//! - `get_safety_report` `{path}`: the safety analysis of a callable,
+//!   including the unsafe code and foreign functions it reaches.
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+use serde_json::{Value, json};
+
+use crate::analysis::callgraph::{CallEdge, CallGraph};
+use crate::entities::callable::RustCallable;
+use crate::entities::callsite::CallSite;
+use crate::entities::rcrate::RustCrate;
+use crate::serve::{
+    INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, RpcError, error_reply, impls_of_trait,
+    parse_message, read_message, str_param, write_message,
+};
+use crate::watch::WatchSession;
+
+/// MCP revision implemented by the server.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Results of `search_symbols` when no `limit` is given.
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Answers MCP tool calls about the package of a [`WatchSession`].
+pub struct McpServer {
+    session: WatchSession,
+    graph: CallGraph,
+}
+
+impl McpServer {
+    pub fn new(session: WatchSession) -> Self {
+        let graph = session.call_graph();
+        Self { session, graph }
+    }
+
+    pub fn session(&self) -> &WatchSession {
+        &self.session
+    }
+
+    /// Serves requests from `input` until it ends.
+    pub fn run(&mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        while let Some((text, framing)) = read_message(&mut input)? {
+            let reply = match parse_message(text) {
+                None => continue,
+                Some(Ok(message)) => self.handle(&message),
+                Some(Err(e)) => Some(error_reply(Value::Null, e)),
+            };
+            if let Some(reply) = reply {
+                write_message(&mut output, &reply, framing)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles one request or notification and returns the reply, if any.
+    pub fn handle(&mut self, message: &Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Some(error_reply(
+                id,
+                RpcError::new(INVALID_REQUEST, "missing method"),
+            ));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match self.call(method, &params) {
+            Ok(result) => Some(json!({"jsonrpc": "2.0", "id": id, "result": result})),
+            Err(e) => Some(error_reply(id, e)),
+        }
+    }
+
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {"tools": {}},
+                "serverInfo": {
+                    "name": "codeanalyzer",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": tools()})),
+            "tools/call" => {
+                let name = str_param(params, "name")?;
+                let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+                // Failures of the tool itself are reported to the model, not
+                // as protocol errors.
+                let (text, is_error) = match self.call_tool(name, &arguments) {
+                    Ok(text) => (text, false),
+                    Err(e) if e.code == METHOD_NOT_FOUND => return Err(e),
+                    Err(e) => (e.message, true),
+                };
+                Ok(json!({
+                    "content": [{"type": "text", "text": text}],
+                    "isError": is_error,
+                }))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method {}", method),
+            )),
+        }
+    }
+
+    fn call_tool(&self, name: &str, arguments: &Value) -> Result<String, RpcError> {
+        let krate = self.session.krate();
+        match name {
+            "search_symbols" => {
+                let query = str_param(arguments, "query")?;
+                let kind = arguments.get("kind").and_then(Value::as_str);
+                let limit = arguments
+                    .get("limit")
+                    .and_then(Value::as_u64)
+                    .map_or(DEFAULT_SEARCH_LIMIT, |n| n as usize);
+                Ok(search_symbols(krate, &self.graph, query, kind, limit))
+            }
+            "get_callable" => {
+                let (path, callable) = self.callable(arguments)?;
+                Ok(describe_callable(&self.graph, path, callable))
+            }
+            "list_call_sites" => {
+                let (path, callable) = self.callable(arguments)?;
+                Ok(describe_call_sites(path, &callable.call_sites))
+            }
+            "get_callers" => {
+                let (path, _) = self.callable(arguments)?;
+                let edges: Vec<_> = self.graph.callers(path).collect();
+                Ok(describe_edges(
+                    &format!("Callers of `{}`", path),
+                    &edges,
+                    |e| &e.caller,
+                ))
+            }
+            "get_callees" => {
+                let (path, _) = self.callable(arguments)?;
+                let edges: Vec<_> = self.graph.callees(path).collect();
+                Ok(describe_edges(
+                    &format!("Callees of `{}`", path),
+                    &edges,
+                    |e| &e.callee,
+                ))
+            }
+            "find_trait_implementors" => {
+                let name = str_param(arguments, "trait")?;
+                let impls = impls_of_trait(krate, name);
+                let mut text = format!("Implementors of `{}`: {}\n", name, impls.len());
+                for imp in impls {
+                    let _ = writeln!(
+                        text,
+                        "- `{}` in `{}` ({}:{}-{}), methods: {}",
+                        imp.type_name,
+                        imp.module,
+                        imp.file_path.unwrap_or("?"),
+                        imp.start_line,
+                        imp.end_line,
+                        if imp.methods.is_empty() {
+                            "none".to_owned()
+                        } else {
+                            imp.methods.join(", ")
+                        },
+                    );
+                }
+                Ok(text)
+            }
+            "get_safety_report" => {
+                let (path, callable) = self.callable(arguments)?;
+                Ok(describe_safety(path, callable))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown tool {}", name),
+            )),
+        }
+    }
+
+    /// Returns the callable named by the `path` argument.
+    fn callable<'a>(
+        &'a self,
+        arguments: &'a Value,
+    ) -> Result<(&'a str, &'a RustCallable), RpcError> {
+        let path = str_param(arguments, "path")?;
+        match self.session.krate().callable(path) {
+            Some((_, _, callable)) => Ok((path, callable)),
+            None => Err(RpcError::new(
+                INVALID_PARAMS,
+                format!(
+                    "No callable at `{}`. Use search_symbols to find the path of a callable.",
+                    path
+                ),
+            )),
+        }
+    }
+}
+
+/// Describes the tools offered by the server.
+fn tools() -> Value {
+    let path = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Item path of the callable, e.g. `my_crate::util::parse` or `my_crate::Config::new`; see search_symbols."
+            }
+        },
+        "required": ["path"]
+    });
+    json!([
+        {
+            "name": "search_symbols",
+            "description": "Search the functions, methods, structs, enums, unions and traits of the crate by path. Returns their paths, kinds and locations.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string", "description": "Case-insensitive substring of the item path without the crate name, e.g. `parse` or `util::parse`. Items named exactly like the query are listed first."},
+                    "kind": {"type": "string", "enum": ["function", "method", "struct", "enum", "union", "trait"]},
+                    "limit": {"type": "integer", "description": "Maximum number of results (default 50)."}
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "get_callable",
+            "description": "Get the definition of a function or method: signature, location, documentation and source code.",
+            "inputSchema": path
+        },
+        {
+            "name": "list_call_sites",
+            "description": "List the calls made by a function or method, as written in its source, with line numbers.",
+            "inputSchema": path
+        },
+        {
+            "name": "get_callers",
+            "description": "List the functions and methods of the crate that call the given callable.",
+            "inputSchema": path
+        },
+        {
+            "name": "get_callees",
+            "description": "List the functions and methods of the crate called by the given callable.",
+            "inputSchema": path
+        },
+        {
+            "name": "find_trait_implementors",
+            "description": "Find the types of the crate that implement a trait.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "trait": {"type": "string", "description": "Trait name or path, e.g. `Display` or `std::fmt::Display`."}
+                },
+                "required": ["trait"]
+            }
+        },
+        {
+            "name": "get_safety_report",
+            "description": "Get the safety analysis of a function or method: unsafe blocks and why they are unsafe, unsafe calls, raw pointers, FFI and mutable statics.",
+            "inputSchema": path
+        }
+    ])
+}
+
+fn search_symbols(
+    krate: &RustCrate,
+    graph: &CallGraph,
+    query: &str,
+    kind: Option<&str>,
+    limit: usize,
+) -> String {
+    let needle = query.to_lowercase();
+    let mut matches = Vec::new();
+    for node in graph.nodes() {
+        let node_kind = if node.owner.is_some() {
+            "method"
+        } else {
+            "function"
+        };
+        matches.push((
+            node_kind,
+            node.path.clone(),
+            node.file_path.clone(),
+            node.start_line,
+        ));
+    }
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            let file = &module.file_path;
+            for s in module.structs.values() {
+                let item = format!("{}::{}", path, s.name);
+                matches.push(("struct", item, file.clone(), s.start_line));
+            }
+            for e in module.enums.values() {
+                let item = format!("{}::{}", path, e.name);
+                matches.push(("enum", item, file.clone(), e.start_line as usize));
+            }
+            for u in module.unions.values() {
+                let item = format!("{}::{}", path, u.name);
+                matches.push(("union", item, file.clone(), u.start_line));
+            }
+            for t in module.traits.values() {
+                let item = format!("{}::{}", path, t.name);
+                matches.push(("trait", item, file.clone(), t.start_line));
+            }
+        }
+    }
+    // The crate name is the first segment of every path, so it would match
+    // a query for it, or for part of it, everywhere.
+    let relative = |path: &str| {
+        path.split_once("::")
+            .map_or(path, |(_, rest)| rest)
+            .to_lowercase()
+    };
+    let named = |path: &str| path.rsplit("::").next().unwrap_or(path).to_lowercase() == needle;
+    matches.retain(|(k, path, _, _)| {
+        kind.is_none_or(|kind| kind == *k) && relative(path).contains(&needle)
+    });
+    matches.sort_by_cached_key(|(k, path, _, _)| (!named(path), path.clone(), *k));
+
+    let mut text = format!("{} symbol(s) match `{}`", matches.len(), query);
+    if matches.len() > limit {
+        let _ = write!(text, ", showing the first {}", limit);
+    }
+    text.push('\n');
+    for (kind, path, file, line) in matches.into_iter().take(limit) {
+        let _ = writeln!(
+            text,
+            "- {} `{}` ({}:{})",
+            kind,
+            path,
+            file.as_deref().unwrap_or("?"),
+            line
+        );
+    }
+    text
+}
+
+fn describe_callable(graph: &CallGraph, path: &str, callable: &RustCallable) -> String {
+    let mut text = format!("# `{}`\n\n", path);
+    if let Some(node) = graph.node(path) {
+        let _ = writeln!(
+            text,
+            "Defined in {} lines {}-{}.\n",
+            node.file_path.as_deref().unwrap_or("?"),
+            node.start_line,
+            node.end_line
+        );
+    }
+    if let Some(doc) = &callable.doc_comment {
+        let _ = writeln!(text, "{}\n", doc.trim());
+    }
+    let _ = writeln!(text, "```rust\n{}\n```", callable.code.trim_end());
+    text
+}
+
+fn describe_call_sites(path: &str, sites: &[CallSite]) -> String {
+    if sites.is_empty() {
+        return format!(
+            "`{}` makes no calls, or call sites were not collected (analysis level 1).",
+            path
+        );
+    }
+    let mut text = format!("Call sites in `{}`: {}\n", path, sites.len());
+    for site in sites {
+        let callee = site.callee.as_deref().unwrap_or("?");
+        let call = match (&site.receiver, site.is_method_call) {
+            (Some(receiver), true) => format!("{}.{}(..)", receiver, callee),
+            _ => format!("{}(..)", callee),
+        };
+        let _ = write!(text, "- line {}: `{}`", site.line_number, call);
+        if site.is_unsafe_context {
+            text.push_str(" (in unsafe context)");
+        }
+        if site.is_async_context {
+            text.push_str(" (in async context)");
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn describe_edges(title: &str, edges: &[&CallEdge], other: impl Fn(&CallEdge) -> &str) -> String {
+    let mut text = format!("{}: {}\n", title, edges.len());
+    for edge in edges {
+        let _ = write!(
+            text,
+            "- `{}` (line {}, calls `{}`",
+            other(edge),
+            edge.line,
+            edge.call
+        );
+        if edge.is_async {
+            text.push_str(", async");
+        }
+        if edge.is_virtual {
+            let _ = write!(text, ", dynamic dispatch to {} candidates", edge.candidates);
+        }
+        text.push_str(")\n");
+    }
+    text
+}
+
+fn describe_safety(path: &str, callable: &RustCallable) -> String {
+    let safety = &callable.safety_analysis;
+    let mut text = format!(
+        "Safety of `{}`: {}{}\n",
+        path,
+        safety.classification.as_str(),
+        if callable.is_unsafe {
+            " (declared `unsafe fn`)"
+        } else {
+            ""
+        }
+    );
+    for block in &safety.unsafe_blocks {
+        let reasons: Vec<&str> = block.reasons.iter().map(|r| r.as_str()).collect();
+        let _ = writeln!(
+            text,
+            "- unsafe block, lines {}-{}: {}",
+            block.start_line,
+            block.end_line,
+            if reasons.is_empty() {
+                "no unsafe operation detected".to_owned()
+            } else {
+                reasons.join(", ")
+            }
+        );
+        match &block.explanation {
+            Some(explanation) => {
+                let _ = writeln!(text, "  SAFETY: {}", explanation.trim());
+            }
+            None => text.push_str("  no SAFETY comment\n"),
+        }
+    }
+    let mut facts = Vec::new();
+    if !safety.unsafe_fn_calls.is_empty() {
+        facts.push(format!(
+            "calls unsafe functions: {}",
+            safety.unsafe_fn_calls.join(", ")
+        ));
+    }
+    if safety.raw_pointer_usage {
+        facts.push("uses raw pointers".to_owned());
+    }
+    if !safety.ffi_calls.is_empty() {
+        facts.push(format!(
+            "calls foreign functions: {}",
+            safety.ffi_calls.join(", ")
+        ));
+    } else if safety.ffi_interactions {
+        facts.push("interacts with foreign functions".to_owned());
+    }
+    if !safety.mutable_statics.is_empty() {
+        facts.push(format!(
+            "accesses mutable statics: {}",
+            safety.mutable_statics.join(", ")
+        ));
+    }
+    if !safety.unsafe_traits_used.is_empty() {
+        facts.push(format!(
+            "uses unsafe traits: {}",
+            safety.unsafe_traits_used.join(", ")
+        ));
+    }
+    for fact in facts {
+        let _ = writeln!(text, "- {}", fact);
+    }
+    if let Some(transitive) = &safety.transitive {
+        let _ = writeln!(
+            text,
+            "\nReachable through callees: {} unsafe block(s) in {} unsafe callable(s)",
+            transitive.unsafe_blocks,
+            transitive.unsafe_callables.len()
+        );
+        for ffi in &transitive.ffi_functions {
+            let _ = writeln!(
+                text,
+                "- foreign function `{}` via {}",
+                ffi.target,
+                ffi.path.join(" -> ")
+            );
+        }
+    }
+    if let Some(comments) = &safety.safety_comments {
+        let _ = writeln!(text, "\nSafety documentation:\n{}", comments.trim());
+    }
+    text
+}
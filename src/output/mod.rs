@@ -0,0 +1,5 @@
+//! Serializers that turn the analysis results into external report formats.
+
+pub mod ndjson;
+pub mod sarif;
+pub mod sqlite;
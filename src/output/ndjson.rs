@@ -0,0 +1,572 @@
+//! Streaming newline-delimited JSON output.
+//!
+//! Instead of one nested document, every entity is written as a flat record
+//! on its own line as soon as it is handed to the writer. Records never embed
+//! other records; they refer to each other through stable string ids built
+//! from the item's module path:
+//!
+//! | record     | id                                                  |
+//! |------------|-----------------------------------------------------|
+//! | `crate`    | `crate:<name>`                                      |
+//! | `module`   | `module:<path>`                                     |
+//! | `struct`   | `struct:<path>::<name>`                             |
+//! | `enum`     | `enum:<path>::<name>`                               |
+//! | `union`    | `union:<path>::<name>`                              |
+//! | `trait`    | `trait:<path>::<name>`                              |
+//! | `impl`     | `impl:<path>::<Type>@<line>` or `impl:<path>::<Type as Trait>` |
+//! | `callable` | `callable:<path>::<name>`, `callable:<path>::<Type>::<name>` or `callable:<path>::<Type as Trait>::<name>` |
+//! | `call`     | `call:<caller path>@<line>#<index>`                 |
+//!
+//! `call` records come after every other record of their crate, since the
+//! callables a call resolves to are only known once the whole crate has been
+//! analyzed. Their `callees` are the item paths of these callables, i.e. the
+//! ids of their `callable` records without the `callable:` prefix.
+//!
+//! The writer keeps no state besides the output stream. As the sink of
+//! [`Analyzer::analyze_streaming`](crate::Analyzer::analyze_streaming), which
+//! extracts one file at a time, only the entities of the file being written
+//! are held in memory, along with the call sites and signatures the `call`
+//! records are resolved from. The crate is not linked in that mode:
+//! `impl_traits` of structs, enums and unions are empty, and the `transitive`
+//! safety summary and `transitive_panics` of callables are `null`. Writing an
+//! analyzed crate with [`NdjsonWriter::write_crate`] keeps them.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::analyzer::ModuleSink;
+use crate::entities::{
+    RustStructKind, RustVisibility,
+    asm::RustInlineAsm,
+    attr::RustAttribute,
+    callable::RustCallable,
+    callsite::CallSite,
+    concurrency::{AwaitPoint, BlockingCall, GuardAcrossAwait, LockAcquisition, SpawnSite},
+    dependence::ProgramDependenceGraph,
+    errors::{ErrorPropagation, SwallowedError},
+    flow::ControlFlowGraph,
+    foreign::RustForeignBlock,
+    lifetime::RustLifetimeParam,
+    module::{RustModule, RustShadowedItem},
+    panic::{PanicPath, PanicSite},
+    param::{RustGenericParam, RustParameter},
+    rcrate::RustCrate,
+    renum::RustEnumVariant,
+    rimpl::RustImpl,
+    rmacro::RustMacro,
+    rstruct::RustStructField,
+    rtrait::RustTrait,
+    rtype::{RustType, RustTypeAlias},
+    safety::SafetyAnalysis,
+    variables::RustVariableDeclaration,
+};
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Record<'a> {
+    Crate {
+        id: String,
+        name: &'a str,
+        version: &'a str,
+        edition: &'a str,
+        is_lib: bool,
+        features: &'a [String],
+    },
+    Module {
+        id: String,
+        parent: Option<&'a str>,
+        #[serde(rename = "crate")]
+        krate: Option<&'a str>,
+        name: &'a str,
+        visibility: &'a RustVisibility,
+        doc_comment: &'a Option<String>,
+        attributes: &'a [RustAttribute],
+        file_path: &'a Option<String>,
+        use_declarations: &'a [String],
+        extern_crates: &'a [String],
+        shadowed_items: &'a [RustShadowedItem],
+        constants: &'a [RustVariableDeclaration],
+        macros: &'a BTreeMap<String, RustMacro>,
+        foreign_blocks: &'a [RustForeignBlock],
+        global_asm: &'a [RustInlineAsm],
+        types: &'a BTreeMap<String, RustType>,
+        type_aliases: &'a BTreeMap<String, RustTypeAlias>,
+        is_unsafe: bool,
+        is_mod_rs: bool,
+        is_root_module: bool,
+    },
+    Struct {
+        id: String,
+        module: &'a str,
+        name: &'a str,
+        struct_kind: &'a RustStructKind,
+        visibility: &'a RustVisibility,
+        doc_comment: &'a Option<String>,
+        attributes: &'a [RustAttribute],
+        fields: &'a [RustStructField],
+        generic_params: &'a [RustGenericParam],
+        lifetime_params: &'a [RustLifetimeParam],
+        where_clauses: &'a [String],
+        derives: &'a [String],
+        impl_traits: &'a [String],
+        contains_unsafe: bool,
+        start_line: usize,
+        end_line: usize,
+    },
+    Enum {
+        id: String,
+        module: &'a str,
+        name: &'a str,
+        visibility: &'a RustVisibility,
+        doc_comment: &'a Option<String>,
+        attributes: &'a [RustAttribute],
+        variants: &'a [RustEnumVariant],
+        generic_params: &'a [RustGenericParam],
+        lifetime_params: &'a [RustLifetimeParam],
+        where_clauses: &'a [String],
+        derives: &'a [String],
+        impl_traits: &'a [String],
+        start_line: i32,
+        end_line: i32,
+    },
+    Union {
+        id: String,
+        module: &'a str,
+        name: &'a str,
+        visibility: &'a RustVisibility,
+        doc_comment: &'a Option<String>,
+        attributes: &'a [RustAttribute],
+        repr: &'a [String],
+        fields: &'a [RustStructField],
+        generic_params: &'a [RustGenericParam],
+        lifetime_params: &'a [RustLifetimeParam],
+        where_clauses: &'a [String],
+        derives: &'a [String],
+        impl_traits: &'a [String],
+        start_line: usize,
+        end_line: usize,
+    },
+    Trait {
+        id: String,
+        module: &'a str,
+        name: &'a str,
+        visibility: &'a RustVisibility,
+        doc_comment: &'a Option<String>,
+        attributes: &'a [RustAttribute],
+        super_traits: Vec<&'a str>,
+        associated_types: Vec<&'a String>,
+        is_unsafe: bool,
+        is_auto: bool,
+        start_line: usize,
+        end_line: usize,
+    },
+    Impl {
+        id: String,
+        module: &'a str,
+        type_name: &'a str,
+        trait_name: &'a Option<String>,
+        where_clauses: &'a [String],
+        is_unsafe: bool,
+        is_negative: bool,
+        start_line: usize,
+        end_line: usize,
+    },
+    Callable {
+        id: String,
+        module: &'a str,
+        owner: Option<&'a str>,
+        #[serde(flatten)]
+        item: Box<CallableItem<'a>>,
+    },
+    Call {
+        id: String,
+        caller: &'a str,
+        #[serde(flatten)]
+        site: &'a CallSite,
+    },
+}
+
+/// A callable without its call sites, which are written as `call` records.
+#[derive(Serialize)]
+struct CallableItem<'a> {
+    name: &'a str,
+    visibility: &'a RustVisibility,
+    doc_comment: &'a Option<String>,
+    attributes: &'a [RustAttribute],
+    parameters: &'a [RustParameter],
+    return_type: &'a Option<RustType>,
+    is_async: bool,
+    is_const: bool,
+    is_unsafe: bool,
+    is_extern: bool,
+    extern_abi: &'a Option<String>,
+    export_name: &'a Option<String>,
+    generic_params: &'a [RustGenericParam],
+    lifetime_params: &'a [RustLifetimeParam],
+    where_clauses: &'a [String],
+    code: &'a str,
+    start_line: usize,
+    end_line: usize,
+    referenced_types: &'a [String],
+    instantiated_types: &'a [String],
+    accessed_variables: &'a [String],
+    variable_declarations: &'a [RustVariableDeclaration],
+    cyclomatic_complexity: Option<usize>,
+    control_flow: &'a Option<ControlFlowGraph>,
+    dependence: &'a Option<ProgramDependenceGraph>,
+    inline_asm: &'a [RustInlineAsm],
+    panic_sites: &'a [PanicSite],
+    transitive_panics: &'a Option<Vec<PanicPath>>,
+    error_propagations: &'a [ErrorPropagation],
+    swallowed_errors: &'a [SwallowedError],
+    await_points: &'a [AwaitPoint],
+    spawned_tasks: &'a [SpawnSite],
+    blocking_calls: &'a [BlockingCall],
+    lock_acquisitions: &'a [LockAcquisition],
+    guards_across_await: &'a [GuardAcrossAwait],
+    safety_analysis: &'a SafetyAnalysis,
+}
+
+impl<'a> From<&'a RustCallable> for CallableItem<'a> {
+    fn from(c: &'a RustCallable) -> Self {
+        CallableItem {
+            name: &c.name,
+            visibility: &c.visibility,
+            doc_comment: &c.doc_comment,
+            attributes: &c.attributes,
+            parameters: &c.parameters,
+            return_type: &c.return_type,
+            is_async: c.is_async,
+            is_const: c.is_const,
+            is_unsafe: c.is_unsafe,
+            is_extern: c.is_extern,
+            extern_abi: &c.extern_abi,
+            export_name: &c.export_name,
+            generic_params: &c.generic_params,
+            lifetime_params: &c.lifetime_params,
+            where_clauses: &c.where_clauses,
+            code: &c.code,
+            start_line: c.start_line,
+            end_line: c.end_line,
+            referenced_types: &c.referenced_types,
+            instantiated_types: &c.instantiated_types,
+            accessed_variables: &c.accessed_variables,
+            variable_declarations: &c.variable_declarations,
+            cyclomatic_complexity: c.cyclomatic_complexity,
+            control_flow: &c.control_flow,
+            dependence: &c.dependence,
+            inline_asm: &c.inline_asm,
+            panic_sites: &c.panic_sites,
+            transitive_panics: &c.transitive_panics,
+            error_propagations: &c.error_propagations,
+            swallowed_errors: &c.swallowed_errors,
+            await_points: &c.await_points,
+            spawned_tasks: &c.spawned_tasks,
+            blocking_calls: &c.blocking_calls,
+            lock_acquisitions: &c.lock_acquisitions,
+            guards_across_await: &c.guards_across_await,
+            safety_analysis: &c.safety_analysis,
+        }
+    }
+}
+
+/// Returns the record id of the module at `path`.
+pub fn module_id(path: &str) -> String {
+    format!("module:{}", path)
+}
+
+/// Returns the record id of `imp`, declared in the module at `path`.
+pub fn impl_id(path: &str, imp: &RustImpl) -> String {
+    match &imp.trait_name {
+        Some(_) => format!("impl:{}::{}", path, imp.owner_name()),
+        None => format!("impl:{}::{}@{}", path, imp.type_name, imp.start_line),
+    }
+}
+
+/// Returns the record id of a callable declared in the module at `path`.
+///
+/// `owner` is the owner segment yielded by [`RustModule::callables`].
+pub fn callable_id(path: &str, owner: Option<&str>, name: &str) -> String {
+    match owner {
+        Some(owner) => format!("callable:{}::{}::{}", path, owner, name),
+        None => format!("callable:{}::{}", path, name),
+    }
+}
+
+/// Writes entities as newline-delimited JSON records.
+pub struct NdjsonWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn record(&mut self, record: &Record) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        self.out.write_all(b"\n")
+    }
+
+    /// Writes the `crate` record, without any of its modules.
+    pub fn write_crate_header(&mut self, krate: &RustCrate) -> io::Result<()> {
+        self.record(&Record::Crate {
+            id: format!("crate:{}", krate.name),
+            name: &krate.name,
+            version: &krate.version,
+            edition: &krate.edition,
+            is_lib: krate.is_lib,
+            features: &krate.features,
+        })
+    }
+
+    /// Writes `module` and all items declared directly in it, except for the
+    /// `call` records of its callables, see [`write_calls`](Self::write_calls).
+    ///
+    /// Submodules are not written; call this again for each of them, e.g. as
+    /// soon as the file declaring them has been analyzed. `path` is the
+    /// module's `::`-separated path and `parent` the path of the enclosing
+    /// module, or `None` for a crate root, in which case `krate` names the
+    /// owning crate.
+    pub fn write_module(
+        &mut self,
+        krate: Option<&str>,
+        parent: Option<&str>,
+        path: &str,
+        module: &RustModule,
+    ) -> io::Result<()> {
+        let module_ref = module_id(path);
+        let parent_ref = parent.map(module_id);
+        let crate_ref = krate.map(|name| format!("crate:{}", name));
+        self.record(&Record::Module {
+            id: module_ref.clone(),
+            parent: parent_ref.as_deref(),
+            krate: crate_ref.as_deref(),
+            name: &module.name,
+            visibility: &module.visibility,
+            doc_comment: &module.doc_comment,
+            attributes: &module.attributes,
+            file_path: &module.file_path,
+            use_declarations: &module.use_declarations,
+            extern_crates: &module.extern_crates,
+            shadowed_items: &module.shadowed_items,
+            constants: &module.constants,
+            macros: &module.macros,
+            foreign_blocks: &module.foreign_blocks,
+            global_asm: &module.global_asm,
+            types: &module.types,
+            type_aliases: &module.type_aliases,
+            is_unsafe: module.is_unsafe,
+            is_mod_rs: module.is_mod_rs,
+            is_root_module: module.is_root_module,
+        })?;
+
+        for item in module.structs.values() {
+            self.record(&Record::Struct {
+                id: format!("struct:{}::{}", path, item.name),
+                module: &module_ref,
+                name: &item.name,
+                struct_kind: &item.kind,
+                visibility: &item.visibility,
+                doc_comment: &item.doc_comment,
+                attributes: &item.attributes,
+                fields: &item.fields,
+                generic_params: &item.generic_params,
+                lifetime_params: &item.lifetime_params,
+                where_clauses: &item.where_clauses,
+                derives: &item.derives,
+                impl_traits: &item.impl_traits,
+                contains_unsafe: item.contains_unsafe,
+                start_line: item.start_line,
+                end_line: item.end_line,
+            })?;
+        }
+        for item in module.enums.values() {
+            self.record(&Record::Enum {
+                id: format!("enum:{}::{}", path, item.name),
+                module: &module_ref,
+                name: &item.name,
+                visibility: &item.visibility,
+                doc_comment: &item.doc_comment,
+                attributes: &item.attributes,
+                variants: &item.variants,
+                generic_params: &item.generic_params,
+                lifetime_params: &item.lifetime_params,
+                where_clauses: &item.where_clauses,
+                derives: &item.derives,
+                impl_traits: &item.impl_traits,
+                start_line: item.start_line,
+                end_line: item.end_line,
+            })?;
+        }
+        for item in module.unions.values() {
+            self.record(&Record::Union {
+                id: format!("union:{}::{}", path, item.name),
+                module: &module_ref,
+                name: &item.name,
+                visibility: &item.visibility,
+                doc_comment: &item.doc_comment,
+                attributes: &item.attributes,
+                repr: &item.repr,
+                fields: &item.fields,
+                generic_params: &item.generic_params,
+                lifetime_params: &item.lifetime_params,
+                where_clauses: &item.where_clauses,
+                derives: &item.derives,
+                impl_traits: &item.impl_traits,
+                start_line: item.start_line,
+                end_line: item.end_line,
+            })?;
+        }
+        for item in module.traits.values() {
+            self.write_trait(path, &module_ref, item)?;
+        }
+        for item in &module.impls {
+            self.write_impl(path, &module_ref, item)?;
+        }
+        for function in module.functions.values() {
+            self.write_callable(path, &module_ref, None, None, function)?;
+        }
+        self.out.flush()
+    }
+
+    /// Writes `krate`, all of its modules and their items, then the `call`
+    /// records of its callables.
+    pub fn write_crate(&mut self, krate: &RustCrate) -> io::Result<()> {
+        self.write_crate_header(krate)?;
+        for root in &krate.modules {
+            self.write_module_tree(Some(&krate.name), None, &root.name, root)?;
+        }
+        self.write_calls(krate)
+    }
+
+    /// Writes a `call` record for every call site of every callable of
+    /// `krate`.
+    pub fn write_calls(&mut self, krate: &RustCrate) -> io::Result<()> {
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (owner, callable) in module.callables() {
+                    let id = callable_id(&path, owner.as_deref(), &callable.name);
+                    for (index, site) in callable.call_sites.iter().enumerate() {
+                        self.record(&Record::Call {
+                            id: format!(
+                                "{}@{}#{}",
+                                id.replacen("callable:", "call:", 1),
+                                site.line_number,
+                                index
+                            ),
+                            caller: &id,
+                            site,
+                        })?;
+                    }
+                }
+            }
+        }
+        self.out.flush()
+    }
+
+    fn write_module_tree(
+        &mut self,
+        krate: Option<&str>,
+        parent: Option<&str>,
+        path: &str,
+        module: &RustModule,
+    ) -> io::Result<()> {
+        self.write_module(krate, parent, path, module)?;
+        for sub in module.submodules.values() {
+            let sub_path = format!("{}::{}", path, sub.name);
+            self.write_module_tree(None, Some(path), &sub_path, sub)?;
+        }
+        Ok(())
+    }
+
+    fn write_trait(&mut self, path: &str, module_ref: &str, item: &RustTrait) -> io::Result<()> {
+        let id = format!("trait:{}::{}", path, item.name);
+        self.record(&Record::Trait {
+            id: id.clone(),
+            module: module_ref,
+            name: &item.name,
+            visibility: &item.visibility,
+            doc_comment: &item.doc_comment,
+            attributes: &item.attributes,
+            super_traits: item
+                .super_traits
+                .iter()
+                .map(|b| b.trait_name.as_str())
+                .collect(),
+            associated_types: item.associated_types.keys().collect(),
+            is_unsafe: item.is_unsafe,
+            is_auto: item.is_auto,
+            start_line: item.start_line,
+            end_line: item.end_line,
+        })?;
+        for method in item.methods.values() {
+            self.write_callable(path, module_ref, Some(&id), Some(&item.name), method)?;
+        }
+        Ok(())
+    }
+
+    fn write_impl(&mut self, path: &str, module_ref: &str, item: &RustImpl) -> io::Result<()> {
+        let id = impl_id(path, item);
+        self.record(&Record::Impl {
+            id: id.clone(),
+            module: module_ref,
+            type_name: &item.type_name,
+            trait_name: &item.trait_name,
+            where_clauses: &item.where_clauses,
+            is_unsafe: item.is_unsafe,
+            is_negative: item.is_negative,
+            start_line: item.start_line,
+            end_line: item.end_line,
+        })?;
+        let owner = item.owner_name();
+        for method in item.methods.values() {
+            self.write_callable(path, module_ref, Some(&id), Some(&owner), method)?;
+        }
+        Ok(())
+    }
+
+    fn write_callable(
+        &mut self,
+        path: &str,
+        module_ref: &str,
+        owner_ref: Option<&str>,
+        owner: Option<&str>,
+        callable: &RustCallable,
+    ) -> io::Result<()> {
+        self.record(&Record::Callable {
+            id: callable_id(path, owner, &callable.name),
+            module: module_ref,
+            owner: owner_ref,
+            item: Box::new(CallableItem::from(callable)),
+        })
+    }
+}
+
+impl<W: Write> ModuleSink for NdjsonWriter<W> {
+    fn crate_started(&mut self, krate: &RustCrate) -> io::Result<()> {
+        self.write_crate_header(krate)
+    }
+
+    fn module_analyzed(
+        &mut self,
+        krate: &str,
+        parent: Option<&str>,
+        path: &str,
+        module: &RustModule,
+    ) -> io::Result<()> {
+        let krate = parent.is_none().then_some(krate);
+        self.write_module(krate, parent, path, module)
+    }
+
+    fn crate_finished(&mut self, krate: &RustCrate) -> io::Result<()> {
+        self.write_calls(krate)
+    }
+}
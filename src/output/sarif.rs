@@ -0,0 +1,215 @@
+//! SARIF 2.1.0 report for the built-in checks.
+//!
+//! Only the subset of the format needed by code-scanning services is modeled:
+//! a single run whose driver lists every rule, and one result per
+//! [`Diagnostic`] with a physical and a logical location.
+
+use serde::Serialize;
+
+use crate::analysis::{self, Diagnostic, ItemKind, Rule};
+use crate::entities::rcrate::RustCrate;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub information_uri: &'static str,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub short_description: SarifMessage,
+    pub help: SarifMessage,
+    pub default_configuration: SarifConfiguration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifConfiguration {
+    pub level: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_index: Option<usize>,
+    pub level: &'static str,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub physical_location: Option<SarifPhysicalLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logical_locations: Vec<SarifLogicalLocation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+    pub region: SarifRegion,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLogicalLocation {
+    pub fully_qualified_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<&'static str>,
+}
+
+impl From<&Rule> for SarifRule {
+    fn from(rule: &Rule) -> Self {
+        SarifRule {
+            id: rule.id,
+            name: rule.name,
+            short_description: SarifMessage {
+                text: rule.short_description.to_owned(),
+            },
+            help: SarifMessage {
+                text: rule.help.to_owned(),
+            },
+            default_configuration: SarifConfiguration {
+                level: rule.default_level.as_str(),
+            },
+        }
+    }
+}
+
+impl SarifLog {
+    /// Builds a SARIF log from already computed diagnostics.
+    ///
+    /// Rules are taken from every built-in check, so that the rule table is
+    /// the same regardless of which findings are present.
+    pub fn from_diagnostics(diagnostics: &[Diagnostic]) -> Self {
+        let rules = analysis::rules();
+        let results = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let physical_location =
+                    diagnostic
+                        .file_path
+                        .as_ref()
+                        .map(|path| SarifPhysicalLocation {
+                            artifact_location: SarifArtifactLocation { uri: to_uri(path) },
+                            region: SarifRegion {
+                                // SARIF lines are 1-based; clamp unknown (0) lines.
+                                start_line: diagnostic.start_line.max(1),
+                                end_line: diagnostic.end_line.max(diagnostic.start_line).max(1),
+                            },
+                        });
+                let logical_locations = diagnostic
+                    .item_path
+                    .iter()
+                    .map(|name| SarifLogicalLocation {
+                        fully_qualified_name: name.clone(),
+                        kind: diagnostic.item_kind.map(logical_kind),
+                    })
+                    .collect();
+                SarifResult {
+                    rule_id: diagnostic.rule_id.clone(),
+                    rule_index: rules.iter().position(|r| r.id == diagnostic.rule_id),
+                    level: diagnostic.level.as_str(),
+                    message: SarifMessage {
+                        text: diagnostic.message.clone(),
+                    },
+                    locations: vec![SarifLocation {
+                        physical_location,
+                        logical_locations,
+                    }],
+                }
+            })
+            .collect();
+
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "codeanalyzer",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: env!("CARGO_PKG_REPOSITORY"),
+                        rules: rules.into_iter().map(SarifRule::from).collect(),
+                    },
+                },
+                results,
+            }],
+        }
+    }
+
+    /// Runs every built-in check over `krate` and builds a SARIF log.
+    pub fn from_crate(krate: &RustCrate) -> Self {
+        Self::from_diagnostics(&analysis::run_checks(krate))
+    }
+
+    /// Serializes the log as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Returns the SARIF logical location kind of an item.
+fn logical_kind(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Function => "function",
+        ItemKind::Struct | ItemKind::Enum | ItemKind::Union | ItemKind::Trait | ItemKind::Impl => {
+            "type"
+        }
+        ItemKind::Static => "variable",
+    }
+}
+
+/// Converts a file path to a relative URI reference with forward slashes.
+fn to_uri(path: &str) -> String {
+    path.trim_start_matches("./").replace('\\', "/")
+}
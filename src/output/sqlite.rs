@@ -0,0 +1,872 @@
+//! SQLite export of the symbol table.
+//!
+//! Every entity kind gets its own table and refers to its parent through a
+//! foreign key, so the analysis of a large workspace can be queried with plain
+//! SQL instead of loading a single JSON document. For example, unsafe
+//! callables with a high complexity that are called from public functions:
+//!
+//! ```sql
+//! SELECT DISTINCT callee.qualified_name
+//! FROM call_sites cs
+//! JOIN callables caller ON caller.id = cs.callable_id
+//! JOIN callables callee ON callee.id = cs.callee_id
+//! WHERE caller.visibility = 'pub'
+//!   AND callee.is_unsafe AND callee.cyclomatic_complexity > 20;
+//! ```
+//!
+//! From analysis level 2 on, `call_targets` links every call site to the
+//! callables of the crate it resolves to: the single target of a static call,
+//! or every candidate implementation of a call marked `is_virtual`. A call
+//! site that resolves to exactly one callable also refers to it through
+//! `callee_id`, which is `NULL` for calls into other crates and for calls
+//! with several candidates.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{Connection, Transaction, params};
+
+use crate::entities::{
+    RustStructKind, UnsafeReason, attr::RustAttribute, callable::RustCallable, module::RustModule,
+    rcrate::RustCrate, rstruct::RustStructField,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS crates (
+    id          INTEGER PRIMARY KEY,
+    name        TEXT NOT NULL,
+    version     TEXT NOT NULL,
+    edition     TEXT NOT NULL,
+    is_lib      INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS modules (
+    id          INTEGER PRIMARY KEY,
+    crate_id    INTEGER NOT NULL REFERENCES crates(id) ON DELETE CASCADE,
+    parent_id   INTEGER REFERENCES modules(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    path        TEXT NOT NULL,
+    file_path   TEXT,
+    visibility  TEXT NOT NULL,
+    is_unsafe   INTEGER NOT NULL,
+    doc_comment TEXT
+);
+CREATE TABLE IF NOT EXISTS structs (
+    id              INTEGER PRIMARY KEY,
+    module_id       INTEGER NOT NULL REFERENCES modules(id) ON DELETE CASCADE,
+    name            TEXT NOT NULL,
+    qualified_name  TEXT NOT NULL,
+    kind            TEXT NOT NULL,
+    visibility      TEXT NOT NULL,
+    attributes      TEXT NOT NULL,
+    derives         TEXT NOT NULL,
+    contains_unsafe INTEGER NOT NULL,
+    start_line      INTEGER NOT NULL,
+    end_line        INTEGER NOT NULL,
+    doc_comment     TEXT
+);
+CREATE TABLE IF NOT EXISTS enums (
+    id              INTEGER PRIMARY KEY,
+    module_id       INTEGER NOT NULL REFERENCES modules(id) ON DELETE CASCADE,
+    name            TEXT NOT NULL,
+    qualified_name  TEXT NOT NULL,
+    visibility      TEXT NOT NULL,
+    attributes      TEXT NOT NULL,
+    derives         TEXT NOT NULL,
+    start_line      INTEGER NOT NULL,
+    end_line        INTEGER NOT NULL,
+    doc_comment     TEXT
+);
+CREATE TABLE IF NOT EXISTS variants (
+    id              INTEGER PRIMARY KEY,
+    enum_id         INTEGER NOT NULL REFERENCES enums(id) ON DELETE CASCADE,
+    position        INTEGER NOT NULL,
+    name            TEXT NOT NULL,
+    discriminant    TEXT,
+    tuple_types     TEXT,
+    doc_comment     TEXT
+);
+CREATE TABLE IF NOT EXISTS unions (
+    id              INTEGER PRIMARY KEY,
+    module_id       INTEGER NOT NULL REFERENCES modules(id) ON DELETE CASCADE,
+    name            TEXT NOT NULL,
+    qualified_name  TEXT NOT NULL,
+    visibility      TEXT NOT NULL,
+    attributes      TEXT NOT NULL,
+    repr            TEXT NOT NULL,
+    derives         TEXT NOT NULL,
+    start_line      INTEGER NOT NULL,
+    end_line        INTEGER NOT NULL,
+    doc_comment     TEXT
+);
+CREATE TABLE IF NOT EXISTS fields (
+    id          INTEGER PRIMARY KEY,
+    struct_id   INTEGER REFERENCES structs(id) ON DELETE CASCADE,
+    variant_id  INTEGER REFERENCES variants(id) ON DELETE CASCADE,
+    union_id    INTEGER REFERENCES unions(id) ON DELETE CASCADE,
+    position    INTEGER NOT NULL,
+    name        TEXT NOT NULL,
+    type        TEXT NOT NULL,
+    visibility  TEXT NOT NULL,
+    doc_comment TEXT,
+    CHECK ((struct_id IS NOT NULL) + (variant_id IS NOT NULL) + (union_id IS NOT NULL) = 1)
+);
+CREATE TABLE IF NOT EXISTS traits (
+    id              INTEGER PRIMARY KEY,
+    module_id       INTEGER NOT NULL REFERENCES modules(id) ON DELETE CASCADE,
+    name            TEXT NOT NULL,
+    qualified_name  TEXT NOT NULL,
+    visibility      TEXT NOT NULL,
+    is_unsafe       INTEGER NOT NULL,
+    is_auto         INTEGER NOT NULL,
+    start_line      INTEGER NOT NULL,
+    end_line        INTEGER NOT NULL,
+    doc_comment     TEXT
+);
+CREATE TABLE IF NOT EXISTS impls (
+    id          INTEGER PRIMARY KEY,
+    module_id   INTEGER NOT NULL REFERENCES modules(id) ON DELETE CASCADE,
+    type_name   TEXT NOT NULL,
+    trait_name  TEXT,
+    is_unsafe   INTEGER NOT NULL,
+    is_negative INTEGER NOT NULL,
+    start_line  INTEGER NOT NULL,
+    end_line    INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS callables (
+    id                      INTEGER PRIMARY KEY,
+    module_id               INTEGER NOT NULL REFERENCES modules(id) ON DELETE CASCADE,
+    impl_id                 INTEGER REFERENCES impls(id) ON DELETE CASCADE,
+    trait_id                INTEGER REFERENCES traits(id) ON DELETE CASCADE,
+    name                    TEXT NOT NULL,
+    qualified_name          TEXT NOT NULL,
+    visibility              TEXT NOT NULL,
+    attributes              TEXT NOT NULL,
+    return_type             TEXT,
+    is_async                INTEGER NOT NULL,
+    is_const                INTEGER NOT NULL,
+    is_unsafe               INTEGER NOT NULL,
+    is_extern               INTEGER NOT NULL,
+    extern_abi              TEXT,
+    export_name             TEXT,
+    cyclomatic_complexity   INTEGER,
+    safety_classification   TEXT NOT NULL,
+    start_line              INTEGER NOT NULL,
+    end_line                INTEGER NOT NULL,
+    doc_comment             TEXT,
+    code                    TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS parameters (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    position    INTEGER NOT NULL,
+    name        TEXT NOT NULL,
+    type        TEXT NOT NULL,
+    is_self     INTEGER NOT NULL,
+    is_mut      INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS call_sites (
+    id                  INTEGER PRIMARY KEY,
+    callable_id         INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    callee_id           INTEGER REFERENCES callables(id) ON DELETE SET NULL,
+    callee_name         TEXT,
+    line_number         INTEGER NOT NULL,
+    caller_module       TEXT,
+    argument_types      TEXT NOT NULL,
+    is_unsafe_context   INTEGER NOT NULL,
+    is_async_context    INTEGER NOT NULL,
+    is_method_call      INTEGER NOT NULL,
+    receiver            TEXT,
+    is_virtual          INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS call_targets (
+    id           INTEGER PRIMARY KEY,
+    call_site_id INTEGER NOT NULL REFERENCES call_sites(id) ON DELETE CASCADE,
+    callee_id    INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS unsafe_blocks (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    start_line  INTEGER NOT NULL,
+    end_line    INTEGER NOT NULL,
+    explanation TEXT
+);
+CREATE TABLE IF NOT EXISTS unsafe_block_reasons (
+    unsafe_block_id INTEGER NOT NULL REFERENCES unsafe_blocks(id) ON DELETE CASCADE,
+    reason          TEXT NOT NULL,
+    detail          TEXT
+);
+CREATE TABLE IF NOT EXISTS panic_sites (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    kind        TEXT NOT NULL,
+    line_number INTEGER NOT NULL,
+    code        TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS blocking_calls (
+    id               INTEGER PRIMARY KEY,
+    callable_id      INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    kind             TEXT NOT NULL,
+    line_number      INTEGER NOT NULL,
+    call             TEXT NOT NULL,
+    is_async_context INTEGER NOT NULL,
+    code             TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS lock_acquisitions (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    lock        TEXT NOT NULL,
+    method      TEXT NOT NULL,
+    line_number INTEGER NOT NULL,
+    guard       TEXT,
+    is_async    INTEGER NOT NULL,
+    held_locks  TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS error_propagations (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    line_number INTEGER NOT NULL,
+    call        TEXT,
+    adapter     TEXT,
+    code        TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS swallowed_errors (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    kind        TEXT NOT NULL,
+    line_number INTEGER NOT NULL,
+    call        TEXT,
+    code        TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS cfg_blocks (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    block       INTEGER NOT NULL,
+    kind        TEXT NOT NULL,
+    start_line  INTEGER,
+    end_line    INTEGER,
+    condition   TEXT
+);
+CREATE TABLE IF NOT EXISTS cfg_edges (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    from_block  INTEGER NOT NULL,
+    to_block    INTEGER NOT NULL,
+    kind        TEXT NOT NULL,
+    label       TEXT
+);
+CREATE TABLE IF NOT EXISTS data_dependences (
+    id              INTEGER PRIMARY KEY,
+    callable_id     INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    variable        TEXT NOT NULL,
+    definition_kind TEXT NOT NULL,
+    definition_line INTEGER NOT NULL,
+    use_line        INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS control_dependences (
+    id          INTEGER PRIMARY KEY,
+    callable_id INTEGER NOT NULL REFERENCES callables(id) ON DELETE CASCADE,
+    block       INTEGER NOT NULL,
+    controller  INTEGER NOT NULL,
+    kind        TEXT NOT NULL,
+    label       TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_modules_crate ON modules(crate_id);
+CREATE INDEX IF NOT EXISTS idx_modules_parent ON modules(parent_id);
+CREATE INDEX IF NOT EXISTS idx_modules_path ON modules(path);
+CREATE INDEX IF NOT EXISTS idx_structs_module ON structs(module_id);
+CREATE INDEX IF NOT EXISTS idx_structs_name ON structs(name);
+CREATE INDEX IF NOT EXISTS idx_enums_module ON enums(module_id);
+CREATE INDEX IF NOT EXISTS idx_enums_name ON enums(name);
+CREATE INDEX IF NOT EXISTS idx_variants_enum ON variants(enum_id);
+CREATE INDEX IF NOT EXISTS idx_unions_module ON unions(module_id);
+CREATE INDEX IF NOT EXISTS idx_unions_name ON unions(name);
+CREATE INDEX IF NOT EXISTS idx_fields_struct ON fields(struct_id);
+CREATE INDEX IF NOT EXISTS idx_fields_variant ON fields(variant_id);
+CREATE INDEX IF NOT EXISTS idx_fields_union ON fields(union_id);
+CREATE INDEX IF NOT EXISTS idx_traits_module ON traits(module_id);
+CREATE INDEX IF NOT EXISTS idx_traits_name ON traits(name);
+CREATE INDEX IF NOT EXISTS idx_impls_module ON impls(module_id);
+CREATE INDEX IF NOT EXISTS idx_impls_type ON impls(type_name);
+CREATE INDEX IF NOT EXISTS idx_impls_trait ON impls(trait_name);
+CREATE INDEX IF NOT EXISTS idx_callables_module ON callables(module_id);
+CREATE INDEX IF NOT EXISTS idx_callables_impl ON callables(impl_id);
+CREATE INDEX IF NOT EXISTS idx_callables_trait ON callables(trait_id);
+CREATE INDEX IF NOT EXISTS idx_callables_name ON callables(name);
+CREATE INDEX IF NOT EXISTS idx_callables_qualified_name ON callables(qualified_name);
+CREATE INDEX IF NOT EXISTS idx_parameters_callable ON parameters(callable_id);
+CREATE INDEX IF NOT EXISTS idx_call_sites_callable ON call_sites(callable_id);
+CREATE INDEX IF NOT EXISTS idx_call_sites_callee ON call_sites(callee_name);
+CREATE INDEX IF NOT EXISTS idx_call_sites_callee_id ON call_sites(callee_id);
+CREATE INDEX IF NOT EXISTS idx_call_targets_call_site ON call_targets(call_site_id);
+CREATE INDEX IF NOT EXISTS idx_call_targets_callee ON call_targets(callee_id);
+CREATE INDEX IF NOT EXISTS idx_unsafe_blocks_callable ON unsafe_blocks(callable_id);
+CREATE INDEX IF NOT EXISTS idx_panic_sites_callable ON panic_sites(callable_id);
+CREATE INDEX IF NOT EXISTS idx_blocking_calls_callable ON blocking_calls(callable_id);
+CREATE INDEX IF NOT EXISTS idx_lock_acquisitions_callable ON lock_acquisitions(callable_id);
+CREATE INDEX IF NOT EXISTS idx_lock_acquisitions_lock ON lock_acquisitions(lock);
+CREATE INDEX IF NOT EXISTS idx_error_propagations_callable ON error_propagations(callable_id);
+CREATE INDEX IF NOT EXISTS idx_swallowed_errors_callable ON swallowed_errors(callable_id);
+CREATE INDEX IF NOT EXISTS idx_cfg_blocks_callable ON cfg_blocks(callable_id);
+CREATE INDEX IF NOT EXISTS idx_cfg_edges_callable ON cfg_edges(callable_id);
+CREATE INDEX IF NOT EXISTS idx_data_dependences_callable ON data_dependences(callable_id);
+CREATE INDEX IF NOT EXISTS idx_control_dependences_callable ON control_dependences(callable_id);
+CREATE INDEX IF NOT EXISTS idx_unsafe_block_reasons_block ON unsafe_block_reasons(unsafe_block_id);
+";
+
+/// Writes analyzed crates into a SQLite database.
+pub struct SqliteExporter {
+    conn: Connection,
+}
+
+impl SqliteExporter {
+    /// Opens (or creates) the database at `path` and creates the schema.
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Uses an existing connection, creating the schema if needed.
+    pub fn with_connection(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Returns the underlying connection, e.g. to run queries after an export.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Writes `krate` and everything it contains in a single transaction and
+    /// returns the id of the new `crates` row.
+    pub fn write_crate(&mut self, krate: &RustCrate) -> rusqlite::Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO crates (name, version, edition, is_lib) VALUES (?1, ?2, ?3, ?4)",
+            params![krate.name, krate.version, krate.edition, krate.is_lib],
+        )?;
+        let crate_id = tx.last_insert_rowid();
+        let mut calls = CallTargets::default();
+        for module in &krate.modules {
+            write_module(&tx, &mut calls, crate_id, None, &module.name, module)?;
+        }
+        calls.write(&tx)?;
+        tx.commit()?;
+        Ok(crate_id)
+    }
+}
+
+/// Exports `krate` into a new or existing SQLite database at `path`.
+pub fn export(krate: &RustCrate, path: &Path) -> rusqlite::Result<()> {
+    SqliteExporter::open(path)?.write_crate(krate).map(|_| ())
+}
+
+/// The callables written for one crate, by qualified name, and the callees
+/// of its call sites, which are matched up once every callable has a row.
+#[derive(Default)]
+struct CallTargets<'k> {
+    callables: HashMap<String, i64>,
+    call_sites: Vec<(i64, &'k [String])>,
+}
+
+impl CallTargets<'_> {
+    fn write(&self, tx: &Transaction) -> rusqlite::Result<()> {
+        let mut insert = tx
+            .prepare_cached("INSERT INTO call_targets (call_site_id, callee_id) VALUES (?1, ?2)")?;
+        let mut update = tx.prepare_cached("UPDATE call_sites SET callee_id = ?1 WHERE id = ?2")?;
+        for (call_site_id, callees) in &self.call_sites {
+            for callee in callees.iter() {
+                if let Some(callee_id) = self.callables.get(callee) {
+                    insert.execute(params![call_site_id, callee_id])?;
+                    if callees.len() == 1 {
+                        update.execute(params![callee_id, call_site_id])?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_module<'k>(
+    tx: &Transaction,
+    calls: &mut CallTargets<'k>,
+    crate_id: i64,
+    parent_id: Option<i64>,
+    path: &str,
+    module: &'k RustModule,
+) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO modules (crate_id, parent_id, name, path, file_path, visibility, is_unsafe, doc_comment)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            crate_id,
+            parent_id,
+            module.name,
+            path,
+            module.file_path,
+            module.visibility.as_str(),
+            module.is_unsafe,
+            module.doc_comment,
+        ],
+    )?;
+    let module_id = tx.last_insert_rowid();
+
+    for rstruct in module.structs.values() {
+        tx.execute(
+            "INSERT INTO structs (module_id, name, qualified_name, kind, visibility, attributes, derives,
+                                  contains_unsafe, start_line, end_line, doc_comment)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                module_id,
+                rstruct.name,
+                format!("{}::{}", path, rstruct.name),
+                struct_kind(&rstruct.kind),
+                rstruct.visibility.as_str(),
+                attributes(&rstruct.attributes),
+                rstruct.derives.join(","),
+                rstruct.contains_unsafe,
+                rstruct.start_line,
+                rstruct.end_line,
+                rstruct.doc_comment,
+            ],
+        )?;
+        let struct_id = tx.last_insert_rowid();
+        write_fields(tx, Some(struct_id), None, None, &rstruct.fields)?;
+    }
+
+    for renum in module.enums.values() {
+        tx.execute(
+            "INSERT INTO enums (module_id, name, qualified_name, visibility, attributes, derives,
+                                start_line, end_line, doc_comment)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                module_id,
+                renum.name,
+                format!("{}::{}", path, renum.name),
+                renum.visibility.as_str(),
+                attributes(&renum.attributes),
+                renum.derives.join(","),
+                renum.start_line,
+                renum.end_line,
+                renum.doc_comment,
+            ],
+        )?;
+        let enum_id = tx.last_insert_rowid();
+        for (position, variant) in renum.variants.iter().enumerate() {
+            let tuple_types = variant.tuple_types.as_ref().map(|types| {
+                types
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            });
+            tx.execute(
+                "INSERT INTO variants (enum_id, position, name, discriminant, tuple_types, doc_comment)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    enum_id,
+                    position,
+                    variant.name,
+                    variant.discriminant,
+                    tuple_types,
+                    variant.doc_comment,
+                ],
+            )?;
+            let variant_id = tx.last_insert_rowid();
+            if let Some(fields) = &variant.fields {
+                write_fields(tx, None, Some(variant_id), None, fields)?;
+            }
+        }
+    }
+
+    for runion in module.unions.values() {
+        tx.execute(
+            "INSERT INTO unions (module_id, name, qualified_name, visibility, attributes, repr, derives,
+                                 start_line, end_line, doc_comment)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                module_id,
+                runion.name,
+                format!("{}::{}", path, runion.name),
+                runion.visibility.as_str(),
+                attributes(&runion.attributes),
+                runion.repr.join(","),
+                runion.derives.join(","),
+                runion.start_line,
+                runion.end_line,
+                runion.doc_comment,
+            ],
+        )?;
+        let union_id = tx.last_insert_rowid();
+        write_fields(tx, None, None, Some(union_id), &runion.fields)?;
+    }
+
+    for rtrait in module.traits.values() {
+        tx.execute(
+            "INSERT INTO traits (module_id, name, qualified_name, visibility, is_unsafe, is_auto,
+                                 start_line, end_line, doc_comment)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                module_id,
+                rtrait.name,
+                format!("{}::{}", path, rtrait.name),
+                rtrait.visibility.as_str(),
+                rtrait.is_unsafe,
+                rtrait.is_auto,
+                rtrait.start_line,
+                rtrait.end_line,
+                rtrait.doc_comment,
+            ],
+        )?;
+        let trait_id = tx.last_insert_rowid();
+        let owner_path = format!("{}::{}", path, rtrait.name);
+        for method in rtrait.methods.values() {
+            write_callable(
+                tx,
+                calls,
+                module_id,
+                None,
+                Some(trait_id),
+                &owner_path,
+                method,
+            )?;
+        }
+    }
+
+    for rimpl in &module.impls {
+        tx.execute(
+            "INSERT INTO impls (module_id, type_name, trait_name, is_unsafe, is_negative, start_line, end_line)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                module_id,
+                rimpl.type_name,
+                rimpl.trait_name,
+                rimpl.is_unsafe,
+                rimpl.is_negative,
+                rimpl.start_line,
+                rimpl.end_line,
+            ],
+        )?;
+        let impl_id = tx.last_insert_rowid();
+        let owner_path = format!("{}::{}", path, rimpl.owner_name());
+        for method in rimpl.methods.values() {
+            write_callable(
+                tx,
+                calls,
+                module_id,
+                Some(impl_id),
+                None,
+                &owner_path,
+                method,
+            )?;
+        }
+    }
+
+    for function in module.functions.values() {
+        write_callable(tx, calls, module_id, None, None, path, function)?;
+    }
+
+    for submodule in module.submodules.values() {
+        let sub_path = format!("{}::{}", path, submodule.name);
+        write_module(tx, calls, crate_id, Some(module_id), &sub_path, submodule)?;
+    }
+    Ok(())
+}
+
+fn write_fields(
+    tx: &Transaction,
+    struct_id: Option<i64>,
+    variant_id: Option<i64>,
+    union_id: Option<i64>,
+    fields: &[RustStructField],
+) -> rusqlite::Result<()> {
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO fields (struct_id, variant_id, union_id, position, name, type, visibility,
+                             doc_comment)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    for (position, field) in fields.iter().enumerate() {
+        stmt.execute(params![
+            struct_id,
+            variant_id,
+            union_id,
+            position,
+            field.name,
+            field.ty.name,
+            field.visibility.as_str(),
+            field.doc_comment,
+        ])?;
+    }
+    Ok(())
+}
+
+fn write_callable<'k>(
+    tx: &Transaction,
+    calls: &mut CallTargets<'k>,
+    module_id: i64,
+    impl_id: Option<i64>,
+    trait_id: Option<i64>,
+    owner_path: &str,
+    callable: &'k RustCallable,
+) -> rusqlite::Result<()> {
+    let qualified_name = format!("{}::{}", owner_path, callable.name);
+    tx.execute(
+        "INSERT INTO callables (module_id, impl_id, trait_id, name, qualified_name, visibility, attributes,
+                                return_type, is_async, is_const, is_unsafe, is_extern, extern_abi,
+                                export_name, cyclomatic_complexity, safety_classification,
+                                start_line, end_line, doc_comment, code)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+        params![
+            module_id,
+            impl_id,
+            trait_id,
+            callable.name,
+            qualified_name,
+            callable.visibility.as_str(),
+            attributes(&callable.attributes),
+            callable.return_type.as_ref().map(|t| t.name.as_str()),
+            callable.is_async,
+            callable.is_const,
+            callable.is_unsafe,
+            callable.is_extern,
+            callable.extern_abi,
+            callable.export_name,
+            callable.cyclomatic_complexity,
+            callable.safety_analysis.classification.as_str(),
+            callable.start_line,
+            callable.end_line,
+            callable.doc_comment,
+            callable.code,
+        ],
+    )?;
+    let callable_id = tx.last_insert_rowid();
+    calls.callables.insert(qualified_name, callable_id);
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO parameters (callable_id, position, name, type, is_self, is_mut)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (position, param) in callable.parameters.iter().enumerate() {
+        stmt.execute(params![
+            callable_id,
+            position,
+            param.name,
+            param.rust_type.name,
+            param.is_self,
+            param.is_mut,
+        ])?;
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO call_sites (callable_id, callee_name, line_number, caller_module, argument_types,
+                                 is_unsafe_context, is_async_context, is_method_call, receiver,
+                                 is_virtual)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+    )?;
+    for call_site in &callable.call_sites {
+        let argument_types = call_site
+            .argument_types
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        stmt.execute(params![
+            callable_id,
+            call_site.callee,
+            call_site.line_number,
+            call_site.caller_module,
+            argument_types,
+            call_site.is_unsafe_context,
+            call_site.is_async_context,
+            call_site.is_method_call,
+            call_site.receiver,
+            call_site.is_virtual,
+        ])?;
+        calls
+            .call_sites
+            .push((tx.last_insert_rowid(), &call_site.callees));
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO panic_sites (callable_id, kind, line_number, code) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for site in &callable.panic_sites {
+        stmt.execute(params![
+            callable_id,
+            site.kind.as_str(),
+            site.line,
+            site.code
+        ])?;
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO blocking_calls (callable_id, kind, line_number, call, is_async_context, code)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for call in &callable.blocking_calls {
+        stmt.execute(params![
+            callable_id,
+            call.kind.as_str(),
+            call.line,
+            call.call,
+            call.is_async_context,
+            call.code
+        ])?;
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO lock_acquisitions (callable_id, lock, method, line_number, guard, is_async,
+                                        held_locks)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    for acquisition in &callable.lock_acquisitions {
+        stmt.execute(params![
+            callable_id,
+            acquisition.lock,
+            acquisition.method,
+            acquisition.line,
+            acquisition.guard,
+            acquisition.is_async,
+            acquisition.held.join(", ")
+        ])?;
+    }
+
+    if let Some(graph) = &callable.control_flow {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO cfg_blocks (callable_id, block, kind, start_line, end_line, condition)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for block in &graph.blocks {
+            stmt.execute(params![
+                callable_id,
+                block.id,
+                block.kind.as_str(),
+                block.start_line,
+                block.end_line,
+                block.condition
+            ])?;
+        }
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO cfg_edges (callable_id, from_block, to_block, kind, label)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for edge in &graph.edges {
+            stmt.execute(params![
+                callable_id,
+                edge.from,
+                edge.to,
+                edge.kind.as_str(),
+                edge.label
+            ])?;
+        }
+    }
+
+    if let Some(pdg) = &callable.dependence {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO data_dependences (callable_id, variable, definition_kind, definition_line,
+                                           use_line)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for dependence in &pdg.data_dependences {
+            let definition = &pdg.definitions[dependence.definition];
+            stmt.execute(params![
+                callable_id,
+                dependence.variable,
+                definition.kind.as_str(),
+                definition.line,
+                dependence.line
+            ])?;
+        }
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO control_dependences (callable_id, block, controller, kind, label)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for dependence in &pdg.control_dependences {
+            stmt.execute(params![
+                callable_id,
+                dependence.block,
+                dependence.controller,
+                dependence.kind.as_str(),
+                dependence.label
+            ])?;
+        }
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO error_propagations (callable_id, line_number, call, adapter, code)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for propagation in &callable.error_propagations {
+        stmt.execute(params![
+            callable_id,
+            propagation.line,
+            propagation.call,
+            propagation.adapter,
+            propagation.code
+        ])?;
+    }
+
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO swallowed_errors (callable_id, kind, line_number, call, code)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for swallowed in &callable.swallowed_errors {
+        stmt.execute(params![
+            callable_id,
+            swallowed.kind.as_str(),
+            swallowed.line,
+            swallowed.call,
+            swallowed.code
+        ])?;
+    }
+
+    for block in &callable.safety_analysis.unsafe_blocks {
+        tx.execute(
+            "INSERT INTO unsafe_blocks (callable_id, start_line, end_line, explanation)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                callable_id,
+                block.start_line,
+                block.end_line,
+                block.explanation
+            ],
+        )?;
+        let block_id = tx.last_insert_rowid();
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO unsafe_block_reasons (unsafe_block_id, reason, detail) VALUES (?1, ?2, ?3)",
+        )?;
+        for reason in &block.reasons {
+            let detail = match reason {
+                UnsafeReason::Custom(text) => Some(text.as_str()),
+                _ => None,
+            };
+            stmt.execute(params![block_id, reason.as_str(), detail])?;
+        }
+    }
+    Ok(())
+}
+
+fn struct_kind(kind: &RustStructKind) -> &'static str {
+    match kind {
+        RustStructKind::Normal => "normal",
+        RustStructKind::Tuple => "tuple",
+        RustStructKind::Unit => "unit",
+    }
+}
+
+/// Renders attributes as they would appear in source, one per line.
+fn attributes(attributes: &[RustAttribute]) -> String {
+    attributes
+        .iter()
+        .map(|attr| {
+            let bang = if attr.is_inner { "!" } else { "" };
+            if attr.arguments.is_empty() {
+                format!("#{}[{}]", bang, attr.name)
+            } else {
+                format!("#{}[{}({})]", bang, attr.name, attr.arguments.join(", "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
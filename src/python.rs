@@ -0,0 +1,1006 @@
+//! Python bindings, enabled by the `python` feature.
+//!
+//! ```python
+//! import codeanalyzer_rs
+//!
+//! krate = codeanalyzer_rs.analyze("path/to/project", analysis_level=2)
+//! for owner, callable in krate.callables():
+//!     if callable.safety_analysis.classification != "safe":
+//!         print(owner, callable.name, callable.start_line)
+//! ```
+//!
+//! The analyzed crate is shared by every object handed to Python: a
+//! `RustModule`, `RustStruct` or `RustCallable` is a handle that locates its
+//! entity in the crate on access, so walking the model never copies it.
+//! Entities without a class of their own (types, parameters, fields, impls,
+//! ...) are returned as plain dictionaries and lists.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+
+use crate::analysis::callgraph::Dispatch;
+use crate::analyzer::{Analyzer, AnalyzerError};
+use crate::entities::{
+    RustStructKind, callable::RustCallable, callsite::CallSite, module::RustModule,
+    rcrate::RustCrate, rstruct::RustStruct, safety::SafetyAnalysis,
+};
+
+impl From<AnalyzerError> for PyErr {
+    fn from(e: AnalyzerError) -> Self {
+        match e {
+            AnalyzerError::Io { .. } | AnalyzerError::Sink(_) => PyOSError::new_err(e.to_string()),
+            _ => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+/// Converts any serializable entity to Python dictionaries, lists and scalars.
+fn to_py<'py, T: Serialize + ?Sized>(py: Python<'py>, value: &T) -> PyResult<Bound<'py, PyAny>> {
+    let value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    json_to_py(py, &value)
+}
+
+fn json_to_py<'py>(py: Python<'py>, value: &serde_json::Value) -> PyResult<Bound<'py, PyAny>> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_pyobject(py)?.into_any(),
+            (None, Some(u)) => u.into_pyobject(py)?.into_any(),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_pyobject(py)?.into_any(),
+        },
+        Value::String(s) => s.into_pyobject(py)?.into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_py(py, item)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+fn struct_kind(kind: &RustStructKind) -> &'static str {
+    match kind {
+        RustStructKind::Normal => "normal",
+        RustStructKind::Tuple => "tuple",
+        RustStructKind::Unit => "unit",
+    }
+}
+
+/// Locates a module: a crate root and the names of the submodules below it.
+#[derive(Clone)]
+struct ModuleRef {
+    krate: Arc<RustCrate>,
+    root: usize,
+    path: Vec<String>,
+}
+
+impl ModuleRef {
+    fn get(&self) -> &RustModule {
+        let mut module = &self.krate.modules[self.root];
+        for name in &self.path {
+            module = &module.submodules[name];
+        }
+        module
+    }
+
+    fn child(&self, name: &str) -> Self {
+        let mut path = self.path.clone();
+        path.push(name.to_owned());
+        Self {
+            krate: self.krate.clone(),
+            root: self.root,
+            path,
+        }
+    }
+
+    fn qualified_name(&self) -> String {
+        let mut name = self.krate.modules[self.root].name.clone();
+        for segment in &self.path {
+            name.push_str("::");
+            name.push_str(segment);
+        }
+        name
+    }
+}
+
+/// Locates a callable within its module.
+#[derive(Clone)]
+enum CallableRef {
+    Function(ModuleRef, String),
+    StructItem(ModuleRef, String, String),
+    ImplMethod(ModuleRef, usize, String),
+    TraitMethod(ModuleRef, String, String),
+}
+
+impl CallableRef {
+    fn get(&self) -> &RustCallable {
+        match self {
+            CallableRef::Function(m, name) => &m.get().functions[name],
+            CallableRef::StructItem(m, owner, name) => {
+                &m.get().structs[owner].associated_items[name]
+            }
+            CallableRef::ImplMethod(m, index, name) => &m.get().impls[*index].methods[name],
+            CallableRef::TraitMethod(m, owner, name) => &m.get().traits[owner].methods[name],
+        }
+    }
+}
+
+/// A crate produced by `analyze`.
+#[pyclass(name = "RustCrate", module = "codeanalyzer_rs", frozen)]
+pub struct PyRustCrate(Arc<RustCrate>);
+
+#[pymethods]
+impl PyRustCrate {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    #[getter]
+    fn version(&self) -> &str {
+        &self.0.version
+    }
+
+    #[getter]
+    fn edition(&self) -> &str {
+        &self.0.edition
+    }
+
+    #[getter]
+    fn is_lib(&self) -> bool {
+        self.0.is_lib
+    }
+
+    #[getter]
+    fn features(&self) -> Vec<String> {
+        self.0.features.clone()
+    }
+
+    #[getter]
+    fn dependencies<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.dependencies)
+    }
+
+    /// The crate root modules, one per target.
+    #[getter]
+    fn modules(&self) -> Vec<PyRustModule> {
+        (0..self.0.modules.len())
+            .map(|root| {
+                PyRustModule(ModuleRef {
+                    krate: self.0.clone(),
+                    root,
+                    path: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the module with the `::`-separated `path`, or `None`.
+    fn module(&self, path: &str) -> Option<PyRustModule> {
+        let mut segments = path.split("::");
+        let root_name = segments.next()?;
+        let root = self.0.modules.iter().position(|m| m.name == root_name)?;
+        let mut module = ModuleRef {
+            krate: self.0.clone(),
+            root,
+            path: Vec::new(),
+        };
+        for segment in segments {
+            if !module.get().submodules.contains_key(segment) {
+                return None;
+            }
+            module = module.child(segment);
+        }
+        Some(PyRustModule(module))
+    }
+
+    /// Returns `(owner, callable)` pairs for every callable in the crate.
+    fn callables(&self) -> Vec<(Option<String>, PyRustCallable)> {
+        self.modules()
+            .iter()
+            .flat_map(|root| root.all_modules())
+            .flat_map(|module| module.callables())
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RustCrate(name={:?}, version={:?})",
+            self.0.name, self.0.version
+        )
+    }
+}
+
+/// A module of an analyzed crate.
+#[pyclass(name = "RustModule", module = "codeanalyzer_rs", frozen)]
+#[derive(Clone)]
+pub struct PyRustModule(ModuleRef);
+
+impl PyRustModule {
+    fn all_modules(&self) -> Vec<PyRustModule> {
+        let mut out = vec![self.clone()];
+        for name in self.0.get().submodules.keys() {
+            out.extend(PyRustModule(self.0.child(name)).all_modules());
+        }
+        out
+    }
+}
+
+#[pymethods]
+impl PyRustModule {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.get().name
+    }
+
+    /// The `::`-separated path of the module, starting at the crate root.
+    #[getter]
+    fn path(&self) -> String {
+        self.0.qualified_name()
+    }
+
+    #[getter]
+    fn doc_comment(&self) -> Option<&str> {
+        self.0.get().doc_comment.as_deref()
+    }
+
+    #[getter]
+    fn attributes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().attributes)
+    }
+
+    #[getter]
+    fn visibility(&self) -> &str {
+        self.0.get().visibility.as_str()
+    }
+
+    #[getter]
+    fn file_path(&self) -> Option<&str> {
+        self.0.get().file_path.as_deref()
+    }
+
+    #[getter]
+    fn is_unsafe(&self) -> bool {
+        self.0.get().is_unsafe
+    }
+
+    #[getter]
+    fn is_mod_rs(&self) -> bool {
+        self.0.get().is_mod_rs
+    }
+
+    #[getter]
+    fn is_root_module(&self) -> bool {
+        self.0.get().is_root_module
+    }
+
+    #[getter]
+    fn use_declarations(&self) -> Vec<String> {
+        self.0.get().use_declarations.clone()
+    }
+
+    #[getter]
+    fn extern_crates(&self) -> Vec<String> {
+        self.0.get().extern_crates.clone()
+    }
+
+    #[getter]
+    fn structs(&self) -> BTreeMap<String, PyRustStruct> {
+        self.0
+            .get()
+            .structs
+            .keys()
+            .map(|name| (name.clone(), PyRustStruct(self.0.clone(), name.clone())))
+            .collect()
+    }
+
+    #[getter]
+    fn functions(&self) -> BTreeMap<String, PyRustCallable> {
+        self.0
+            .get()
+            .functions
+            .keys()
+            .map(|name| {
+                let callable = CallableRef::Function(self.0.clone(), name.clone());
+                (name.clone(), PyRustCallable(callable))
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn submodules(&self) -> BTreeMap<String, PyRustModule> {
+        self.0
+            .get()
+            .submodules
+            .keys()
+            .map(|name| (name.clone(), PyRustModule(self.0.child(name))))
+            .collect()
+    }
+
+    #[getter]
+    fn enums<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().enums)
+    }
+
+    #[getter]
+    fn unions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().unions)
+    }
+
+    #[getter]
+    fn traits<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().traits)
+    }
+
+    #[getter]
+    fn impls<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().impls)
+    }
+
+    #[getter]
+    fn types<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().types)
+    }
+
+    #[getter]
+    fn type_aliases<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().type_aliases)
+    }
+
+    #[getter]
+    fn macros<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().macros)
+    }
+
+    #[getter]
+    fn foreign_blocks<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().foreign_blocks)
+    }
+
+    #[getter]
+    fn global_asm<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().global_asm)
+    }
+
+    #[getter]
+    fn shadowed_items<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().shadowed_items)
+    }
+
+    #[getter]
+    fn constants<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().constants)
+    }
+
+    /// Returns `(owner, callable)` pairs for the free functions, impl methods
+    /// and trait methods declared directly in this module.
+    fn callables(&self) -> Vec<(Option<String>, PyRustCallable)> {
+        let module = self.0.get();
+        let functions = module.functions.keys().map(|name| {
+            let callable = CallableRef::Function(self.0.clone(), name.clone());
+            (None, PyRustCallable(callable))
+        });
+        let methods = module.impls.iter().enumerate().flat_map(|(index, imp)| {
+            let owner = imp.owner_name();
+            imp.methods.keys().map(move |name| {
+                let callable = CallableRef::ImplMethod(self.0.clone(), index, name.clone());
+                (Some(owner.clone()), PyRustCallable(callable))
+            })
+        });
+        let trait_methods = module.traits.values().flat_map(|t| {
+            t.methods.keys().map(move |name| {
+                let callable =
+                    CallableRef::TraitMethod(self.0.clone(), t.name.clone(), name.clone());
+                (Some(t.name.clone()), PyRustCallable(callable))
+            })
+        });
+        functions.chain(methods).chain(trait_methods).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RustModule(path={:?})", self.0.qualified_name())
+    }
+}
+
+/// A struct declared in a module.
+#[pyclass(name = "RustStruct", module = "codeanalyzer_rs", frozen)]
+pub struct PyRustStruct(ModuleRef, String);
+
+impl PyRustStruct {
+    fn get(&self) -> &RustStruct {
+        &self.0.get().structs[&self.1]
+    }
+}
+
+#[pymethods]
+impl PyRustStruct {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.get().name
+    }
+
+    #[getter]
+    fn kind(&self) -> &'static str {
+        struct_kind(&self.get().kind)
+    }
+
+    #[getter]
+    fn visibility(&self) -> &str {
+        self.get().visibility.as_str()
+    }
+
+    #[getter]
+    fn doc_comment(&self) -> Option<&str> {
+        self.get().doc_comment.as_deref()
+    }
+
+    #[getter]
+    fn attributes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.get().attributes)
+    }
+
+    #[getter]
+    fn fields<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.get().fields)
+    }
+
+    #[getter]
+    fn generic_params<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.get().generic_params)
+    }
+
+    #[getter]
+    fn lifetime_params<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.get().lifetime_params)
+    }
+
+    #[getter]
+    fn where_clauses(&self) -> Vec<String> {
+        self.get().where_clauses.clone()
+    }
+
+    #[getter]
+    fn derives(&self) -> Vec<String> {
+        self.get().derives.clone()
+    }
+
+    #[getter]
+    fn impl_traits(&self) -> Vec<String> {
+        self.get().impl_traits.clone()
+    }
+
+    #[getter]
+    fn associated_items(&self) -> BTreeMap<String, PyRustCallable> {
+        self.get()
+            .associated_items
+            .keys()
+            .map(|name| {
+                let callable =
+                    CallableRef::StructItem(self.0.clone(), self.1.clone(), name.clone());
+                (name.clone(), PyRustCallable(callable))
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn is_public(&self) -> bool {
+        self.get().is_public
+    }
+
+    #[getter]
+    fn contains_unsafe(&self) -> bool {
+        self.get().contains_unsafe
+    }
+
+    #[getter]
+    fn start_line(&self) -> usize {
+        self.get().start_line
+    }
+
+    #[getter]
+    fn end_line(&self) -> usize {
+        self.get().end_line
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RustStruct(name={:?})", self.1)
+    }
+}
+
+/// A function or method.
+#[pyclass(name = "RustCallable", module = "codeanalyzer_rs", frozen)]
+#[derive(Clone)]
+pub struct PyRustCallable(CallableRef);
+
+#[pymethods]
+impl PyRustCallable {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.get().name
+    }
+
+    #[getter]
+    fn visibility(&self) -> &str {
+        self.0.get().visibility.as_str()
+    }
+
+    #[getter]
+    fn doc_comment(&self) -> Option<&str> {
+        self.0.get().doc_comment.as_deref()
+    }
+
+    #[getter]
+    fn attributes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().attributes)
+    }
+
+    #[getter]
+    fn parameters<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().parameters)
+    }
+
+    #[getter]
+    fn return_type<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().return_type)
+    }
+
+    #[getter]
+    fn is_async(&self) -> bool {
+        self.0.get().is_async
+    }
+
+    #[getter]
+    fn is_const(&self) -> bool {
+        self.0.get().is_const
+    }
+
+    #[getter]
+    fn is_unsafe(&self) -> bool {
+        self.0.get().is_unsafe
+    }
+
+    #[getter]
+    fn is_extern(&self) -> bool {
+        self.0.get().is_extern
+    }
+
+    #[getter]
+    fn extern_abi(&self) -> Option<&str> {
+        self.0.get().extern_abi.as_deref()
+    }
+
+    #[getter]
+    fn export_name(&self) -> Option<&str> {
+        self.0.get().export_name.as_deref()
+    }
+
+    #[getter]
+    fn generic_params<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().generic_params)
+    }
+
+    #[getter]
+    fn lifetime_params<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().lifetime_params)
+    }
+
+    #[getter]
+    fn where_clauses(&self) -> Vec<String> {
+        self.0.get().where_clauses.clone()
+    }
+
+    #[getter]
+    fn code(&self) -> &str {
+        &self.0.get().code
+    }
+
+    #[getter]
+    fn start_line(&self) -> usize {
+        self.0.get().start_line
+    }
+
+    #[getter]
+    fn end_line(&self) -> usize {
+        self.0.get().end_line
+    }
+
+    #[getter]
+    fn referenced_types(&self) -> Vec<String> {
+        self.0.get().referenced_types.clone()
+    }
+
+    #[getter]
+    fn instantiated_types(&self) -> Vec<String> {
+        self.0.get().instantiated_types.clone()
+    }
+
+    #[getter]
+    fn accessed_variables(&self) -> Vec<String> {
+        self.0.get().accessed_variables.clone()
+    }
+
+    #[getter]
+    fn call_sites(&self) -> Vec<PyCallSite> {
+        (0..self.0.get().call_sites.len())
+            .map(|index| PyCallSite(self.0.clone(), index))
+            .collect()
+    }
+
+    #[getter]
+    fn variable_declarations<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().variable_declarations)
+    }
+
+    #[getter]
+    fn cyclomatic_complexity(&self) -> Option<usize> {
+        self.0.get().cyclomatic_complexity
+    }
+
+    #[getter]
+    fn control_flow<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().control_flow)
+    }
+
+    #[getter]
+    fn dependence<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().dependence)
+    }
+
+    #[getter]
+    fn inline_asm<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().inline_asm)
+    }
+
+    #[getter]
+    fn panic_sites<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().panic_sites)
+    }
+
+    #[getter]
+    fn transitive_panics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().transitive_panics)
+    }
+
+    #[getter]
+    fn error_propagations<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().error_propagations)
+    }
+
+    #[getter]
+    fn swallowed_errors<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().swallowed_errors)
+    }
+
+    #[getter]
+    fn await_points<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().await_points)
+    }
+
+    #[getter]
+    fn spawned_tasks<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().spawned_tasks)
+    }
+
+    #[getter]
+    fn blocking_calls<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().blocking_calls)
+    }
+
+    #[getter]
+    fn lock_acquisitions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().lock_acquisitions)
+    }
+
+    #[getter]
+    fn guards_across_await<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.0.get().guards_across_await)
+    }
+
+    #[getter]
+    fn safety_analysis(&self) -> PySafetyAnalysis {
+        PySafetyAnalysis(self.0.clone())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RustCallable(name={:?})", self.0.get().name)
+    }
+}
+
+/// A call made by a callable.
+#[pyclass(name = "CallSite", module = "codeanalyzer_rs", frozen)]
+pub struct PyCallSite(CallableRef, usize);
+
+impl PyCallSite {
+    fn get(&self) -> &CallSite {
+        &self.0.get().call_sites[self.1]
+    }
+}
+
+#[pymethods]
+impl PyCallSite {
+    #[getter]
+    fn line_number(&self) -> u32 {
+        self.get().line_number
+    }
+
+    #[getter]
+    fn callee(&self) -> Option<&str> {
+        self.get().callee.as_deref()
+    }
+
+    #[getter]
+    fn caller_function(&self) -> Option<&str> {
+        self.get().caller_function.as_deref()
+    }
+
+    #[getter]
+    fn caller_module(&self) -> Option<&str> {
+        self.get().caller_module.as_deref()
+    }
+
+    #[getter]
+    fn argument_types<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.get().argument_types)
+    }
+
+    #[getter]
+    fn is_unsafe_context(&self) -> bool {
+        self.get().is_unsafe_context
+    }
+
+    #[getter]
+    fn is_async_context(&self) -> bool {
+        self.get().is_async_context
+    }
+
+    #[getter]
+    fn held_locks(&self) -> Vec<String> {
+        self.get().held_locks.clone()
+    }
+
+    #[getter]
+    fn is_method_call(&self) -> bool {
+        self.get().is_method_call
+    }
+
+    #[getter]
+    fn receiver(&self) -> Option<&str> {
+        self.get().receiver.as_deref()
+    }
+
+    #[getter]
+    fn callees(&self) -> Vec<String> {
+        self.get().callees.clone()
+    }
+
+    #[getter]
+    fn is_virtual(&self) -> bool {
+        self.get().is_virtual
+    }
+
+    fn __repr__(&self) -> String {
+        let site = self.get();
+        let callee = match &site.callee {
+            Some(callee) => format!("{:?}", callee),
+            None => "None".to_owned(),
+        };
+        format!(
+            "CallSite(callee={}, line_number={})",
+            callee, site.line_number
+        )
+    }
+}
+
+/// The safety analysis of a callable.
+#[pyclass(name = "SafetyAnalysis", module = "codeanalyzer_rs", frozen)]
+pub struct PySafetyAnalysis(CallableRef);
+
+impl PySafetyAnalysis {
+    fn get(&self) -> &SafetyAnalysis {
+        &self.0.get().safety_analysis
+    }
+}
+
+#[pymethods]
+impl PySafetyAnalysis {
+    #[getter]
+    fn classification(&self) -> &str {
+        self.get().classification.as_str()
+    }
+
+    #[getter]
+    fn unsafe_blocks<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.get().unsafe_blocks)
+    }
+
+    #[getter]
+    fn unsafe_fn_calls(&self) -> Vec<String> {
+        self.get().unsafe_fn_calls.clone()
+    }
+
+    #[getter]
+    fn raw_pointer_usage(&self) -> bool {
+        self.get().raw_pointer_usage
+    }
+
+    #[getter]
+    fn ffi_interactions(&self) -> bool {
+        self.get().ffi_interactions
+    }
+
+    #[getter]
+    fn ffi_calls(&self) -> Vec<String> {
+        self.get().ffi_calls.clone()
+    }
+
+    #[getter]
+    fn transitive<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.get().transitive)
+    }
+
+    #[getter]
+    fn unsafe_traits_used(&self) -> Vec<String> {
+        self.get().unsafe_traits_used.clone()
+    }
+
+    #[getter]
+    fn mutable_statics(&self) -> Vec<String> {
+        self.get().mutable_statics.clone()
+    }
+
+    #[getter]
+    fn safety_comments(&self) -> Option<&str> {
+        self.get().safety_comments.as_deref()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SafetyAnalysis(classification={:?})",
+            self.get().classification.as_str()
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn analyzer(
+    path: Option<String>,
+    sources: Option<Vec<(String, String)>>,
+    target_files: Option<Vec<String>>,
+    features: Option<Vec<String>>,
+    no_default_features: bool,
+    analysis_level: u8,
+    dispatch: &str,
+    crate_name: Option<String>,
+) -> PyResult<Analyzer> {
+    let dispatch = match dispatch {
+        "cha" => Dispatch::Cha,
+        "rta" => Dispatch::Rta,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "dispatch must be \"cha\" or \"rta\", not {:?}",
+                dispatch
+            )));
+        }
+    };
+    let mut analyzer = Analyzer::new()
+        .target_files(target_files.unwrap_or_default())
+        .features(features.unwrap_or_default())
+        .default_features(!no_default_features)
+        .analysis_level(analysis_level)
+        .dispatch(dispatch);
+    if let Some(path) = path {
+        analyzer = analyzer.project(path);
+    }
+    for (path, code) in sources.unwrap_or_default() {
+        analyzer = analyzer.source(path, code);
+    }
+    if let Some(name) = crate_name {
+        analyzer = analyzer.crate_name(name);
+    }
+    Ok(analyzer)
+}
+
+/// Analyzes the package in `path` and/or the `(path, code)` pairs in `sources`.
+#[pyfunction]
+#[pyo3(signature = (
+    path=None,
+    *,
+    sources=None,
+    target_files=None,
+    features=None,
+    no_default_features=false,
+    analysis_level=1,
+    dispatch="cha",
+    crate_name=None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn analyze(
+    py: Python<'_>,
+    path: Option<String>,
+    sources: Option<Vec<(String, String)>>,
+    target_files: Option<Vec<String>>,
+    features: Option<Vec<String>>,
+    no_default_features: bool,
+    analysis_level: u8,
+    dispatch: &str,
+    crate_name: Option<String>,
+) -> PyResult<PyRustCrate> {
+    let analyzer = analyzer(
+        path,
+        sources,
+        target_files,
+        features,
+        no_default_features,
+        analysis_level,
+        dispatch,
+        crate_name,
+    )?;
+    let krate = py.allow_threads(|| analyzer.analyze())?;
+    Ok(PyRustCrate(Arc::new(krate)))
+}
+
+/// Analyzes every member package of the workspace in `path`.
+#[pyfunction]
+#[pyo3(signature = (
+    path,
+    *,
+    target_files=None,
+    features=None,
+    no_default_features=false,
+    analysis_level=1,
+    dispatch="cha",
+))]
+fn analyze_workspace(
+    py: Python<'_>,
+    path: String,
+    target_files: Option<Vec<String>>,
+    features: Option<Vec<String>>,
+    no_default_features: bool,
+    analysis_level: u8,
+    dispatch: &str,
+) -> PyResult<Vec<PyRustCrate>> {
+    let analyzer = analyzer(
+        Some(path),
+        None,
+        target_files,
+        features,
+        no_default_features,
+        analysis_level,
+        dispatch,
+        None,
+    )?;
+    let workspace = py.allow_threads(|| analyzer.analyze_workspace())?;
+    Ok(workspace
+        .members
+        .into_iter()
+        .map(|krate| PyRustCrate(Arc::new(krate)))
+        .collect())
+}
+
+#[pymodule]
+fn codeanalyzer_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(analyze, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_workspace, m)?)?;
+    m.add_class::<PyRustCrate>()?;
+    m.add_class::<PyRustModule>()?;
+    m.add_class::<PyRustStruct>()?;
+    m.add_class::<PyRustCallable>()?;
+    m.add_class::<PyCallSite>()?;
+    m.add_class::<PySafetyAnalysis>()?;
+    Ok(())
+}
@@ -0,0 +1,472 @@
+//! JSON-RPC 2.0 server that answers queries about a package from memory.
+//!
+//! The server analyzes the package once and keeps the [`RustCrate`] and its
+//! [`CallGraph`] in memory, so queries are answered without parsing anything
+//! again. Messages are read from the input and answered on the output, either
+//! one JSON document per line or framed with `Content-Length` headers as in
+//! the Language Server Protocol; replies use the framing of the request. A
+//! message that cannot be read is answered with an error and the server goes
+//! on with the next one.
+//!
+//! Methods, with their parameters:
+//!
+//! - `get-callable-by-path` `{path}`: the callable at an item path such as
+//!   `my_crate::util::parse`, or `null`.
+//! - `callers-of` / `callees-of` `{path}`: resolved calls to or from a callable.
+//! - `impls-of-trait` `{trait}`: impls of a trait, by name or path.
+//! - `items-in-file` `{file}`: the items declared in a file, by line.
+//! - `unsafe-blocks-in-module` `{module, recursive?}`: unsafe blocks of the
+//!   callables in a module and, with `recursive`, its submodules.
+//! - `didChange` `{path, text?}` or `{paths}`: files changed; `text` is the
+//!   unsaved content of `path`. Answered with the [`Delta`]s when sent as a
+//!   request rather than a notification.
+//! - `shutdown`, then the `exit` notification, stop the server.
+
+use std::io::{self, BufRead, Read, Write};
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::analysis::callgraph::{CallGraph, item_path};
+use crate::entities::module::RustModule;
+use crate::entities::rcrate::RustCrate;
+use crate::output::ndjson::impl_id;
+use crate::watch::{Delta, WatchSession};
+
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const INVALID_REQUEST: i64 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+/// The package could not be analyzed again after a change.
+pub(crate) const ANALYSIS_ERROR: i64 = -32000;
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// How a message was framed on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Framing {
+    Line,
+    ContentLength,
+}
+
+/// Answers queries about the package of a [`WatchSession`].
+pub struct Server {
+    session: WatchSession,
+    graph: CallGraph,
+    shutdown: bool,
+}
+
+impl Server {
+    pub fn new(session: WatchSession) -> Self {
+        let graph = session.call_graph();
+        Self {
+            session,
+            graph,
+            shutdown: false,
+        }
+    }
+
+    pub fn session(&self) -> &WatchSession {
+        &self.session
+    }
+
+    /// Serves requests from `input` until it ends or `exit` is received.
+    pub fn run(&mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        while let Some((text, framing)) = read_message(&mut input)? {
+            let reply = match parse_message(text) {
+                None => continue,
+                Some(Ok(message)) => {
+                    if message.get("method").and_then(Value::as_str) == Some("exit") {
+                        return Ok(());
+                    }
+                    self.handle(&message)
+                }
+                Some(Err(e)) => Some(error_reply(Value::Null, e)),
+            };
+            if let Some(reply) = reply {
+                write_message(&mut output, &reply, framing)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles one request or notification and returns the reply, if any.
+    pub fn handle(&mut self, message: &Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Some(error_reply(
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "missing method"),
+            ));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = self.call(method, &params);
+        match (id, result) {
+            (Some(id), Ok(result)) => Some(json!({"jsonrpc": "2.0", "id": id, "result": result})),
+            (Some(id), Err(e)) => Some(error_reply(id, e)),
+            (None, Err(e)) => {
+                eprintln!("{}: {}", method, e.message);
+                None
+            }
+            (None, Ok(_)) => None,
+        }
+    }
+
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        if self.shutdown {
+            return Err(RpcError::new(INVALID_REQUEST, "server is shutting down"));
+        }
+        match method {
+            "get-callable-by-path" => Ok(self.callable(str_param(params, "path")?)),
+            "callers-of" => {
+                let path = self.known_callable(params)?;
+                to_value(self.graph.callers(path).collect::<Vec<_>>())
+            }
+            "callees-of" => {
+                let path = self.known_callable(params)?;
+                to_value(self.graph.callees(path).collect::<Vec<_>>())
+            }
+            "impls-of-trait" => to_value(impls_of_trait(
+                self.session.krate(),
+                str_param(params, "trait")?,
+            )),
+            "items-in-file" => to_value(items_in_file(
+                self.session.krate(),
+                str_param(params, "file")?,
+            )),
+            "unsafe-blocks-in-module" => {
+                let path = str_param(params, "module")?;
+                let recursive = params
+                    .get("recursive")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                let module = self.session.krate().module(path).ok_or_else(|| {
+                    RpcError::new(INVALID_PARAMS, format!("unknown module {}", path))
+                })?;
+                to_value(unsafe_blocks(module, path, recursive))
+            }
+            "didChange" => to_value(self.did_change(params)?),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method {}", method),
+            )),
+        }
+    }
+
+    fn callable(&self, path: &str) -> Value {
+        match self.session.krate().callable(path) {
+            Some((module, owner, callable)) => json!({
+                "path": path,
+                "module": module,
+                "owner": owner,
+                "file_path": self.session.krate().module(&module).and_then(|m| m.file_path.clone()),
+                "callable": callable,
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn known_callable<'p>(&self, params: &'p Value) -> Result<&'p str, RpcError> {
+        let path = str_param(params, "path")?;
+        match self.graph.node(path) {
+            Some(_) => Ok(path),
+            None => Err(RpcError::new(
+                INVALID_PARAMS,
+                format!("unknown callable {}", path),
+            )),
+        }
+    }
+
+    fn did_change(&mut self, params: &Value) -> Result<Vec<Delta>, RpcError> {
+        let mut paths = Vec::new();
+        if let Some(path) = params.get("path").and_then(Value::as_str) {
+            let text = params
+                .get("text")
+                .and_then(Value::as_str)
+                .map(str::to_owned);
+            self.session.set_text(path, text);
+            paths.push(path.to_owned());
+        }
+        if let Some(list) = params.get("paths").and_then(Value::as_array) {
+            paths.extend(list.iter().filter_map(Value::as_str).map(str::to_owned));
+        }
+        if paths.is_empty() {
+            return Err(RpcError::new(INVALID_PARAMS, "expected `path` or `paths`"));
+        }
+        let deltas = self
+            .session
+            .update(&paths)
+            .map_err(|e| RpcError::new(ANALYSIS_ERROR, e.to_string()))?;
+        self.graph = self.session.call_graph();
+        Ok(deltas)
+    }
+}
+
+/// An impl of a trait, as answered by `impls-of-trait`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TraitImpl<'a> {
+    pub id: String,
+    pub module: String,
+    pub type_name: &'a str,
+    pub trait_name: &'a str,
+    pub file_path: Option<&'a str>,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub methods: Vec<&'a str>,
+}
+
+/// Returns the impls of the trait `name`, given by name (`Display`) or path
+/// (`std::fmt::Display`).
+pub(crate) fn impls_of_trait<'a>(krate: &'a RustCrate, name: &str) -> Vec<TraitImpl<'a>> {
+    let wanted = base_name(name);
+    let mut out = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            for imp in &module.impls {
+                let Some(trait_name) = &imp.trait_name else {
+                    continue;
+                };
+                if trait_name != name && base_name(trait_name) != wanted {
+                    continue;
+                }
+                out.push(TraitImpl {
+                    id: impl_id(&path, imp),
+                    module: path.clone(),
+                    type_name: &imp.type_name,
+                    trait_name,
+                    file_path: module.file_path.as_deref(),
+                    start_line: imp.start_line,
+                    end_line: imp.end_line,
+                    methods: imp.methods.keys().map(String::as_str).collect(),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Returns the last segment of a path without generic arguments.
+fn base_name(path: &str) -> &str {
+    let path = path.split('<').next().unwrap_or(path);
+    path.rsplit("::").next().unwrap_or(path).trim()
+}
+
+/// An item declared in a file, as answered by `items-in-file`.
+#[derive(Debug, Clone, Serialize)]
+struct FileItem {
+    kind: &'static str,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+}
+
+/// Returns the items declared in `file`, relative to the package, ordered by
+/// line.
+fn items_in_file(krate: &RustCrate, file: &str) -> Vec<FileItem> {
+    let file = file.trim_start_matches("./");
+    let mut out = Vec::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            if module.file_path.as_deref() != Some(file) {
+                continue;
+            }
+            let mut push = |kind, name: &str, start_line, end_line| {
+                out.push(FileItem {
+                    kind,
+                    path: format!("{}::{}", path, name),
+                    start_line,
+                    end_line,
+                })
+            };
+            for s in module.structs.values() {
+                push("struct", &s.name, s.start_line, s.end_line);
+            }
+            for e in module.enums.values() {
+                push("enum", &e.name, e.start_line as usize, e.end_line as usize);
+            }
+            for u in module.unions.values() {
+                push("union", &u.name, u.start_line, u.end_line);
+            }
+            for t in module.traits.values() {
+                push("trait", &t.name, t.start_line, t.end_line);
+            }
+            for imp in &module.impls {
+                push("impl", &imp.owner_name(), imp.start_line, imp.end_line);
+            }
+            for alias in module.type_aliases.values() {
+                push("type_alias", &alias.name, alias.start_line, alias.end_line);
+            }
+            for m in module.macros.values() {
+                push("macro", &m.name, m.start_line, m.end_line);
+            }
+            for c in &module.constants {
+                push("constant", &c.name, c.line_number, c.line_number);
+            }
+            for (owner, callable) in module.callables() {
+                let kind = if owner.is_some() {
+                    "method"
+                } else {
+                    "function"
+                };
+                out.push(FileItem {
+                    kind,
+                    path: item_path(&path, owner.as_deref(), &callable.name),
+                    start_line: callable.start_line,
+                    end_line: callable.end_line,
+                });
+            }
+        }
+    }
+    out.sort_by(|a, b| {
+        (a.start_line, a.end_line, &a.path).cmp(&(b.start_line, b.end_line, &b.path))
+    });
+    out
+}
+
+/// Returns the unsafe blocks of the callables in `module`, at `path`.
+fn unsafe_blocks(module: &RustModule, path: &str, recursive: bool) -> Vec<Value> {
+    let tree = if recursive {
+        module.module_tree()
+    } else {
+        vec![(module.name.clone(), module)]
+    };
+    // Paths in `tree` start at `module` itself.
+    let parent = match path.rsplit_once("::") {
+        Some((parent, _)) => format!("{}::", parent),
+        None => String::new(),
+    };
+    let mut out = Vec::new();
+    for (relative, module) in tree {
+        let module_path = format!("{}{}", parent, relative);
+        for (owner, callable) in module.callables() {
+            for block in &callable.safety_analysis.unsafe_blocks {
+                out.push(json!({
+                    "callable": item_path(&module_path, owner.as_deref(), &callable.name),
+                    "file_path": module.file_path,
+                    "start_line": block.start_line,
+                    "end_line": block.end_line,
+                    "reasons": block.reasons,
+                    "explanation": block.explanation,
+                }));
+            }
+        }
+    }
+    out
+}
+
+pub(crate) fn str_param<'p>(params: &'p Value, name: &str) -> Result<&'p str, RpcError> {
+    params.get(name).and_then(Value::as_str).ok_or_else(|| {
+        RpcError::new(
+            INVALID_PARAMS,
+            format!("missing string parameter `{}`", name),
+        )
+    })
+}
+
+pub(crate) fn to_value(value: impl Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(ANALYSIS_ERROR, e.to_string()))
+}
+
+pub(crate) fn error_reply(id: Value, error: RpcError) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": error})
+}
+
+/// Largest `Content-Length` accepted, far above the size of any source file
+/// sent with `didChange`.
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+/// Reads the next message, or `None` at the end of the input. A frame that
+/// cannot be read, e.g. one with an invalid or oversized `Content-Length`, is
+/// an `Err` to reply with; an oversized body is skipped, but the body of a
+/// frame whose length is not a number cannot be told apart from what follows.
+pub(crate) fn read_message(
+    input: &mut impl BufRead,
+) -> io::Result<Option<(Result<String, RpcError>, Framing)>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let Some(length) = header_value(&line, "content-length") else {
+        return Ok(Some((Ok(line), Framing::Line)));
+    };
+    let length = length.parse::<usize>();
+    // Skip the remaining headers up to the empty line.
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+    let Ok(length) = length else {
+        let error = RpcError::new(INVALID_REQUEST, "invalid Content-Length");
+        return Ok(Some((Err(error), Framing::ContentLength)));
+    };
+    if length > MAX_MESSAGE_LENGTH {
+        io::copy(&mut input.by_ref().take(length as u64), &mut io::sink())?;
+        let error = RpcError::new(
+            INVALID_REQUEST,
+            format!(
+                "Content-Length {} exceeds the limit of {} bytes",
+                length, MAX_MESSAGE_LENGTH
+            ),
+        );
+        return Ok(Some((Err(error), Framing::ContentLength)));
+    }
+    // Let the buffer grow with what actually arrives instead of trusting the
+    // header with the allocation.
+    let mut body = Vec::new();
+    input.by_ref().take(length as u64).read_to_end(&mut body)?;
+    if body.len() < length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let body = String::from_utf8(body).map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()));
+    Ok(Some((body, Framing::ContentLength)))
+}
+
+/// Parses a message returned by [`read_message`], or returns `None` for a
+/// blank line.
+pub(crate) fn parse_message(text: Result<String, RpcError>) -> Option<Result<Value, RpcError>> {
+    match text {
+        Ok(text) if text.trim().is_empty() => None,
+        Ok(text) => {
+            Some(serde_json::from_str(&text).map_err(|e| RpcError::new(PARSE_ERROR, e.to_string())))
+        }
+        Err(e) => Some(Err(e)),
+    }
+}
+
+fn header_value<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let (key, value) = line.split_once(':')?;
+    key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+}
+
+pub(crate) fn write_message(
+    output: &mut impl Write,
+    message: &Value,
+    framing: Framing,
+) -> io::Result<()> {
+    let text = message.to_string();
+    match framing {
+        Framing::Line => writeln!(output, "{}", text)?,
+        Framing::ContentLength => write!(output, "Content-Length: {}\r\n\r\n{}", text.len(), text)?,
+    }
+    output.flush()
+}
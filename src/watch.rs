@@ -0,0 +1,483 @@
+//! Watch mode: keeps the analysis of a package up to date while its files are
+//! edited.
+//!
+//! A [`WatchSession`] holds the analysis of one package. When files change,
+//! only those files are analyzed again, through the analyzer's target-files
+//! path; the items of every other module are carried over from the previous
+//! analysis. The module tree itself is rebuilt on every update, so modules
+//! added to or removed from the tree are picked up as well. Each update
+//! reports what changed as a list of [`Delta`]s.
+//!
+//! [`watch`] drives a session from file system notifications.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use codeanalyzer_rs::Analyzer;
+//! use codeanalyzer_rs::watch::{WatchSession, watch};
+//!
+//! let mut session = WatchSession::new(Analyzer::new().project("path/to/project"))?;
+//! watch(&mut session, Duration::from_millis(200), |_, update| {
+//!     match update {
+//!         Ok(deltas) => deltas.iter().for_each(|d| println!("{:?} {}", d.event, d.id)),
+//!         Err(e) => eprintln!("{}", e),
+//!     }
+//!     Ok(())
+//! })?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use crate::analysis::callgraph::CallGraph;
+use crate::analyzer::{Analyzer, AnalyzerError};
+use crate::entities::callable::RustCallable;
+use crate::entities::module::RustModule;
+use crate::entities::rcrate::RustCrate;
+use crate::entities::renum::RustEnum;
+use crate::entities::rstruct::RustStruct;
+use crate::entities::rtrait::RustTrait;
+use crate::entities::runion::RustUnion;
+use crate::output::ndjson::{callable_id, module_id};
+
+/// What happened to an entity between two analyses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeltaEvent {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A change to one module, struct, enum, union, trait or callable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Delta {
+    pub event: DeltaEvent,
+    /// Id of the entity, as used by the NDJSON output, e.g.
+    /// `callable:my_crate::util::parse` or `struct:my_crate::Config`.
+    pub id: String,
+}
+
+/// The analysis of a package, kept up to date by [`update`](Self::update).
+#[derive(Debug)]
+pub struct WatchSession {
+    analyzer: Analyzer,
+    root: PathBuf,
+    krate: RustCrate,
+    /// Unsaved file contents that replace the files on disk.
+    overlays: BTreeMap<String, String>,
+}
+
+impl WatchSession {
+    /// Analyzes the package of `analyzer` in full.
+    pub fn new(analyzer: Analyzer) -> Result<Self, AnalyzerError> {
+        let root = analyzer
+            .project_dir()
+            .ok_or(AnalyzerError::NoInput)?
+            .to_path_buf();
+        let krate = analyzer.analyze()?;
+        Ok(Self {
+            analyzer,
+            root,
+            krate,
+            overlays: BTreeMap::new(),
+        })
+    }
+
+    /// The current analysis.
+    pub fn krate(&self) -> &RustCrate {
+        &self.krate
+    }
+
+    /// Builds the call graph of the current analysis, dispatching calls
+    /// through traits the way the analyzer resolves call sites.
+    pub fn call_graph(&self) -> CallGraph {
+        CallGraph::build_with(&self.krate, self.analyzer.dispatch_mode())
+    }
+
+    /// The package directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Paths of the files in the module tree, relative to the package.
+    pub fn files(&self) -> BTreeSet<String> {
+        file_modules(&self.krate).into_keys().collect()
+    }
+
+    /// Sets the content of `path` to `text` instead of what is on disk, e.g.
+    /// for a file with unsaved edits; `None` goes back to the file on disk.
+    /// Takes effect with the next [`update`](Self::update) of `path`.
+    pub fn set_text(&mut self, path: impl AsRef<Path>, text: Option<String>) {
+        let path = self.relative(path.as_ref());
+        match text {
+            Some(text) => self.overlays.insert(path, text),
+            None => self.overlays.remove(&path),
+        };
+    }
+
+    /// Analyzes the package again from scratch.
+    pub fn reload(&mut self) -> Result<Vec<Delta>, AnalyzerError> {
+        let krate = self.analyzer().analyze()?;
+        Ok(self.replace(krate))
+    }
+
+    /// Brings the analysis up to date after `changed` files were modified,
+    /// created or removed. Paths are relative to the package or absolute.
+    ///
+    /// A changed `Cargo.toml` reloads the whole package. Items carried over
+    /// from unchanged files keep what was known about the rest of the crate
+    /// when they were analyzed, e.g. which statics are mutable; call
+    /// [`reload`](Self::reload) to refresh those as well.
+    pub fn update<I, P>(&mut self, changed: I) -> Result<Vec<Delta>, AnalyzerError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut changed: BTreeSet<String> = changed
+            .into_iter()
+            .map(|p| self.relative(p.as_ref()))
+            .collect();
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+        if changed.contains("Cargo.toml") {
+            return self.reload();
+        }
+
+        let mut krate = self.analyze_files(&changed)?;
+        // A file that moved to another place in the module tree, or joined
+        // it, has items extracted for its old module path, or none at all.
+        let old_files = file_modules(&self.krate);
+        let moved: Vec<String> = file_modules(&krate)
+            .into_iter()
+            .filter(|(file, paths)| !changed.contains(file) && old_files.get(file) != Some(paths))
+            .map(|(file, _)| file)
+            .collect();
+        if !moved.is_empty() {
+            changed.extend(moved);
+            krate = self.analyze_files(&changed)?;
+        }
+
+        let old_modules: BTreeMap<String, &RustModule> = self
+            .krate
+            .modules
+            .iter()
+            .flat_map(|root| root.module_tree())
+            .collect();
+        for root in &mut krate.modules {
+            let path = root.name.clone();
+            carry_over(root, &path, &old_modules, &changed);
+        }
+        self.analyzer.link(&mut krate);
+        Ok(self.replace(krate))
+    }
+
+    fn analyze_files(&self, files: &BTreeSet<String>) -> Result<RustCrate, AnalyzerError> {
+        self.analyzer().target_files(files).analyze()
+    }
+
+    fn analyzer(&self) -> Analyzer {
+        let mut analyzer = self.analyzer.clone();
+        for (path, text) in &self.overlays {
+            analyzer = analyzer.source(path, text.as_str());
+        }
+        analyzer
+    }
+
+    fn replace(&mut self, krate: RustCrate) -> Vec<Delta> {
+        let deltas = diff(&self.krate, &krate);
+        self.krate = krate;
+        deltas
+    }
+
+    /// Returns `path` relative to the package, with `/` separators.
+    fn relative(&self, path: &Path) -> String {
+        let relative = path
+            .strip_prefix(&self.root)
+            .ok()
+            .or_else(|| {
+                let root = self.root.canonicalize().ok()?;
+                path.strip_prefix(root).ok()
+            })
+            .unwrap_or(path);
+        relative
+            .components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Maps every file of the module tree to the paths of the modules it holds.
+fn file_modules(krate: &RustCrate) -> BTreeMap<String, BTreeSet<String>> {
+    let mut files: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for root in &krate.modules {
+        for (path, module) in root.module_tree() {
+            if let Some(file) = &module.file_path {
+                files.entry(file.clone()).or_default().insert(path);
+            }
+        }
+    }
+    files
+}
+
+/// Copies the items of modules in unchanged files from the previous analysis.
+fn carry_over(
+    module: &mut RustModule,
+    path: &str,
+    old: &BTreeMap<String, &RustModule>,
+    changed: &BTreeSet<String>,
+) {
+    let unchanged = module
+        .file_path
+        .as_ref()
+        .is_some_and(|file| !changed.contains(file));
+    if let (true, Some(previous)) = (unchanged, old.get(path)) {
+        module.types = previous.types.clone();
+        module.structs = previous.structs.clone();
+        module.enums = previous.enums.clone();
+        module.unions = previous.unions.clone();
+        module.traits = previous.traits.clone();
+        module.impls = previous.impls.clone();
+        module.type_aliases = previous.type_aliases.clone();
+        module.functions = previous.functions.clone();
+        module.safe_functions = previous.safe_functions.clone();
+        module.unsafe_functions = previous.unsafe_functions.clone();
+        module.macros = previous.macros.clone();
+        module.foreign_blocks = previous.foreign_blocks.clone();
+        module.global_asm = previous.global_asm.clone();
+        module.constants = previous.constants.clone();
+        module.use_declarations = previous.use_declarations.clone();
+        module.extern_crates = previous.extern_crates.clone();
+        module.shadowed_items = previous.shadowed_items.clone();
+    }
+    for sub in module.submodules.values_mut() {
+        let sub_path = format!("{}::{}", path, sub.name);
+        carry_over(sub, &sub_path, old, changed);
+    }
+}
+
+/// Returns the modules, structs, enums, unions, traits and callables that were added,
+/// removed or changed between `old` and `new`, ordered by id.
+pub fn diff(old: &RustCrate, new: &RustCrate) -> Vec<Delta> {
+    let old = Entities::collect(old);
+    let new = Entities::collect(new);
+    let mut deltas = Vec::new();
+    compare(&old.modules, &new.modules, same_module, &mut deltas);
+    compare(&old.structs, &new.structs, PartialEq::eq, &mut deltas);
+    compare(&old.enums, &new.enums, PartialEq::eq, &mut deltas);
+    compare(&old.unions, &new.unions, PartialEq::eq, &mut deltas);
+    compare(&old.traits, &new.traits, PartialEq::eq, &mut deltas);
+    compare(&old.callables, &new.callables, PartialEq::eq, &mut deltas);
+    deltas.sort_by(|a, b| a.id.cmp(&b.id));
+    deltas
+}
+
+/// The entities of a crate, keyed by id.
+#[derive(Default)]
+struct Entities<'a> {
+    modules: BTreeMap<String, &'a RustModule>,
+    structs: BTreeMap<String, &'a RustStruct>,
+    enums: BTreeMap<String, &'a RustEnum>,
+    unions: BTreeMap<String, &'a RustUnion>,
+    traits: BTreeMap<String, &'a RustTrait>,
+    callables: BTreeMap<String, &'a RustCallable>,
+}
+
+impl<'a> Entities<'a> {
+    fn collect(krate: &'a RustCrate) -> Self {
+        let mut entities = Self::default();
+        for root in &krate.modules {
+            for (path, module) in root.module_tree() {
+                for (name, s) in &module.structs {
+                    entities
+                        .structs
+                        .insert(format!("struct:{}::{}", path, name), s);
+                }
+                for (name, e) in &module.enums {
+                    entities.enums.insert(format!("enum:{}::{}", path, name), e);
+                }
+                for (name, u) in &module.unions {
+                    entities
+                        .unions
+                        .insert(format!("union:{}::{}", path, name), u);
+                }
+                for (name, t) in &module.traits {
+                    entities
+                        .traits
+                        .insert(format!("trait:{}::{}", path, name), t);
+                }
+                for (owner, callable) in module.callables() {
+                    let id = callable_id(&path, owner.as_deref(), &callable.name);
+                    entities.callables.insert(id, callable);
+                }
+                entities.modules.insert(module_id(&path), module);
+            }
+        }
+        entities
+    }
+}
+
+fn compare<T>(
+    old: &BTreeMap<String, &T>,
+    new: &BTreeMap<String, &T>,
+    same: impl Fn(&T, &T) -> bool,
+    deltas: &mut Vec<Delta>,
+) {
+    for (id, entity) in new {
+        let event = match old.get(id) {
+            None => DeltaEvent::Added,
+            Some(previous) if !same(previous, entity) => DeltaEvent::Changed,
+            Some(_) => continue,
+        };
+        deltas.push(Delta {
+            event,
+            id: id.clone(),
+        });
+    }
+    for id in old.keys().filter(|id| !new.contains_key(*id)) {
+        deltas.push(Delta {
+            event: DeltaEvent::Removed,
+            id: id.clone(),
+        });
+    }
+}
+
+/// Compares what a module declares itself. Structs, enums, unions, traits,
+/// callables and submodules are reported on their own.
+fn same_module(a: &RustModule, b: &RustModule) -> bool {
+    a.doc_comment == b.doc_comment
+        && a.attributes == b.attributes
+        && a.visibility == b.visibility
+        && a.is_unsafe == b.is_unsafe
+        && a.file_path == b.file_path
+        && a.types == b.types
+        && a.impls == b.impls
+        && a.type_aliases == b.type_aliases
+        && a.macros == b.macros
+        && a.foreign_blocks == b.foreign_blocks
+        && a.global_asm == b.global_asm
+        && a.constants == b.constants
+        && a.use_declarations == b.use_declarations
+        && a.extern_crates == b.extern_crates
+        && a.shadowed_items == b.shadowed_items
+}
+
+/// Errors that stop [`watch`].
+#[derive(Debug)]
+pub enum WatchError {
+    /// The file system watcher failed.
+    Notify(notify::Error),
+    /// The update callback failed.
+    Output(io::Error),
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchError::Notify(e) => write!(f, "cannot watch files: {}", e),
+            WatchError::Output(e) => write!(f, "cannot write analysis output: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WatchError::Notify(e) => Some(e),
+            WatchError::Output(e) => Some(e),
+        }
+    }
+}
+
+impl From<notify::Error> for WatchError {
+    fn from(e: notify::Error) -> Self {
+        WatchError::Notify(e)
+    }
+}
+
+/// Watches the files of `session` and updates it whenever some of them change,
+/// until the callback fails or the watcher stops.
+///
+/// Changes are collected until no file has changed for `debounce`, so a burst
+/// of saves leads to a single update. `on_update` receives the session and the
+/// outcome of every update; analysis errors, e.g. a file saved halfway
+/// through an edit, are handed to it rather than ending the watch.
+pub fn watch<F>(
+    session: &mut WatchSession,
+    debounce: Duration,
+    mut on_update: F,
+) -> Result<(), WatchError>
+where
+    F: FnMut(&WatchSession, Result<Vec<Delta>, AnalyzerError>) -> io::Result<()>,
+{
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    let root = session.root().to_path_buf();
+    watcher.watch(&root, RecursiveMode::NonRecursive)?;
+    let mut watched = BTreeSet::new();
+    watch_source_dirs(&mut watcher, session, &mut watched)?;
+
+    let mut pending = BTreeSet::new();
+    while let Ok(event) = rx.recv() {
+        collect_changes(event?, &mut pending);
+        // Debounce: keep collecting until the file system has been quiet.
+        loop {
+            match rx.recv_timeout(debounce) {
+                Ok(event) => collect_changes(event?, &mut pending),
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+        if pending.is_empty() {
+            continue;
+        }
+        let update = session.update(std::mem::take(&mut pending));
+        on_update(session, update).map_err(WatchError::Output)?;
+        watch_source_dirs(&mut watcher, session, &mut watched)?;
+    }
+    Ok(())
+}
+
+/// Watches the top-level directories that hold files of the module tree.
+/// Build output and hidden directories are never watched.
+fn watch_source_dirs(
+    watcher: &mut impl Watcher,
+    session: &WatchSession,
+    watched: &mut BTreeSet<String>,
+) -> notify::Result<()> {
+    for file in session.files() {
+        let Some((dir, _)) = file.split_once('/') else {
+            continue;
+        };
+        if dir == "target" || dir.starts_with('.') || watched.contains(dir) {
+            continue;
+        }
+        watcher.watch(&session.root().join(dir), RecursiveMode::Recursive)?;
+        watched.insert(dir.to_owned());
+    }
+    Ok(())
+}
+
+/// Adds the Rust sources and manifests touched by `event` to `pending`.
+fn collect_changes(event: notify::Event, pending: &mut BTreeSet<PathBuf>) {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    for path in event.paths {
+        let relevant = path.extension().is_some_and(|ext| ext == "rs")
+            || path.file_name().is_some_and(|name| name == "Cargo.toml");
+        if relevant {
+            pending.insert(path);
+        }
+    }
+}
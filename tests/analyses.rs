@@ -0,0 +1,327 @@
+//! Behavior of the analyses on the multi-file `bank` fixture.
+
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::analysis::{
+    asm::AsmReport, dependence::SystemDependenceGraph, errors::ErrorGraph, ffi::FfiReport,
+    flow::CfgReport, run_checks,
+};
+use codeanalyzer_rs::entities::rcrate::RustCrate;
+use codeanalyzer_rs::output::sarif::SarifLog;
+use serde_json::{Value, json};
+
+const BANK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bank");
+
+fn analyze() -> RustCrate {
+    Analyzer::new()
+        .project(BANK)
+        .analysis_level(3)
+        .analyze()
+        .expect("analysis failed")
+}
+
+fn to_value(value: impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+/// The callable at `path`, e.g. `bank::accounts::Bank::deposit`, as JSON.
+fn callable(krate: &RustCrate, path: &str) -> Value {
+    let (_, _, callable) = krate
+        .callable(path)
+        .unwrap_or_else(|| panic!("no callable {}", path));
+    to_value(callable)
+}
+
+/// Whether a finding of `rule` mentions `text`.
+fn finds(krate: &RustCrate, rule: &str, text: &str) -> bool {
+    run_checks(krate)
+        .iter()
+        .any(|d| d.rule_id == rule && d.message.contains(text))
+}
+
+#[test]
+fn sarif_results_refer_to_listed_rules_and_locations() {
+    let krate = analyze();
+    let log = to_value(SarifLog::from_diagnostics(&run_checks(&krate)));
+    let run = &log["runs"][0];
+    let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    let results = run["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    for result in results {
+        assert!(
+            rules.contains(&result["ruleId"].as_str().unwrap()),
+            "{}",
+            result
+        );
+        let location = &result["locations"][0];
+        let uri = location["physicalLocation"]["artifactLocation"]["uri"]
+            .as_str()
+            .unwrap();
+        assert!(uri.starts_with("src/"), "{}", uri);
+        assert!(
+            location["physicalLocation"]["region"]["startLine"]
+                .as_u64()
+                .unwrap()
+                > 0
+        );
+    }
+}
+
+#[test]
+fn sarif_logical_locations_have_the_kind_of_their_item() {
+    let krate = analyze();
+    let log = to_value(SarifLog::from_diagnostics(&run_checks(&krate)));
+    let kind_of = |rule: &str, name: &str| {
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        let location = results
+            .iter()
+            .filter(|result| result["ruleId"] == rule)
+            .map(|result| &result["locations"][0]["logicalLocations"][0])
+            .find(|location| location["fullyQualifiedName"] == name)
+            .unwrap_or_else(|| panic!("no {} result for {}", rule, name));
+        location["kind"].as_str().unwrap().to_owned()
+    };
+    assert_eq!(
+        kind_of("dead_code/unreachable_function", "bank::unused_helper"),
+        "function"
+    );
+    assert_eq!(
+        kind_of("dead_code/unreachable_struct", "bank::Ledger"),
+        "type"
+    );
+    assert_eq!(
+        kind_of("ffi/missing_repr_c", "bank::ffi::registers"),
+        "variable"
+    );
+}
+
+#[test]
+fn safe_functions_reaching_foreign_code_are_reported() {
+    let krate = analyze();
+    let cosine = callable(&krate, "bank::ffi::cosine");
+    let transitive = &cosine["safety_analysis"]["transitive"];
+    assert_eq!(transitive["unsafe_blocks"], 1);
+    assert_eq!(
+        transitive["ffi_functions"],
+        json!([{"target": "cos", "path": ["bank::ffi::cosine"]}])
+    );
+    assert!(finds(&krate, "unsafe/safe_api_reaches_ffi", "cosine"));
+    assert!(!finds(&krate, "unsafe/safe_api_reaches_ffi", "to_bits"));
+}
+
+#[test]
+fn ffi_inventory_lists_imports_and_exports() {
+    let report = to_value(FfiReport::build(&analyze()));
+    let symbols = |direction: &str| -> Vec<Value> {
+        report[direction]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| json!([s["kind"], s["symbol"], s["abi"], s["libraries"]]))
+            .collect()
+    };
+    assert_eq!(
+        symbols("imports"),
+        [
+            json!(["function", "cos", "C", ["m"]]),
+            json!(["static", "errno", null, ["m"]]),
+            json!(["static", "registers", null, []]),
+        ]
+    );
+    assert_eq!(
+        symbols("exports"),
+        [
+            json!(["function", "bank_name_len", "C", []]),
+            json!(["function", "bank_version", "C", []]),
+        ]
+    );
+}
+
+#[test]
+fn ffi_signatures_without_a_c_layout_are_reported() {
+    let krate = analyze();
+    assert!(finds(&krate, "ffi/improper_type", "bank_name_len"));
+    assert!(!finds(&krate, "ffi/improper_type", "bank_version"));
+    assert!(!finds(&krate, "ffi/improper_type", "cos"));
+}
+
+#[test]
+fn union_fields_and_reads_are_recorded() {
+    let krate = analyze();
+    let raw = &krate.modules[0].submodules["raw"];
+    let bits = &raw.unions["Bits"];
+    assert_eq!(bits.repr, ["C"]);
+    let fields: Vec<&str> = bits.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["float", "int"]);
+
+    let to_bits = callable(&krate, "bank::raw::to_bits");
+    let blocks = &to_bits["safety_analysis"]["unsafe_blocks"];
+    assert_eq!(blocks[0]["reasons"], json!(["UnionFieldAccess"]));
+    assert!(finds(&krate, "unsafe/union_field_access", "to_bits"));
+}
+
+#[test]
+fn inline_assembly_is_inventoried_with_its_cfg() {
+    let krate = analyze();
+    let report = to_value(AsmReport::build(&krate));
+    let entries = report["entries"].as_array().unwrap();
+    let pause = entries
+        .iter()
+        .find(|e| e["containing_callable"] == "bank::raw::pause")
+        .unwrap();
+    assert_eq!(pause["templates"], json!(["pause"]));
+    assert_eq!(pause["options"], json!(["nomem", "nostack"]));
+    assert_eq!(pause["cfg"], json!(["target_arch = \"x86_64\""]));
+    let global = entries
+        .iter()
+        .find(|e| e["macro_name"] == "global_asm")
+        .unwrap();
+    assert_eq!(global["module"], "bank::raw");
+    assert!(global["containing_callable"].is_null());
+    assert!(finds(&krate, "unsafe/inline_assembly", "pause"));
+}
+
+#[test]
+fn panics_are_found_directly_and_through_callees() {
+    let krate = analyze();
+    let first = callable(&krate, "bank::first");
+    assert_eq!(
+        first["panic_sites"],
+        json!([{"kind": "index", "line": 12, "code": "values[0]"}])
+    );
+    let deposit_all = callable(&krate, "bank::deposit_all");
+    assert_eq!(deposit_all["panic_sites"], json!([]));
+    assert_eq!(
+        deposit_all["transitive_panics"][0]["path"],
+        json!(["bank::deposit_all", "bank::accounts::Bank::deposit"])
+    );
+    // `deposit_all` documents its errors but not the panic it reaches.
+    assert!(finds(&krate, "panic/missing_panics_doc", "`first`"));
+    assert!(finds(&krate, "panic/missing_panics_doc", "`deposit_all`"));
+    assert!(!finds(&krate, "panic/missing_panics_doc", "`bank_version`"));
+}
+
+#[test]
+fn errors_flow_through_question_marks_and_from_impls() {
+    let graph = to_value(ErrorGraph::build(&analyze()));
+    let error_type = &graph["error_types"][0];
+    assert_eq!(error_type["path"], "bank::accounts::BankError");
+    assert_eq!(error_type["from"], json!(["num::ParseIntError"]));
+    let deposit_all = graph["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["path"] == "bank::deposit_all")
+        .unwrap();
+    let propagation = &deposit_all["propagations"][0];
+    assert_eq!(
+        propagation["callees"],
+        json!(["bank::accounts::Bank::deposit"])
+    );
+    assert_eq!(propagation["conversion"], "identity");
+}
+
+#[test]
+fn async_code_records_awaits_spawns_and_blocking_calls() {
+    let krate = analyze();
+    let tick = callable(&krate, "bank::service::Service::tick");
+    assert_eq!(tick["await_points"][0]["call"], "rest");
+    let start = callable(&krate, "bank::service::Service::start");
+    assert_eq!(start["spawned_tasks"][0]["call"], "tokio::spawn");
+    assert_eq!(start["spawned_tasks"][0]["target"], "tick");
+    let audit = callable(&krate, "bank::service::audit");
+    assert_eq!(
+        audit["spawned_tasks"][0]["call"],
+        "tokio::task::spawn_blocking"
+    );
+    assert_eq!(audit["spawned_tasks"][0]["target"], "checksum");
+    let load = callable(&krate, "bank::service::Service::load");
+    assert_eq!(load["blocking_calls"][0]["call"], "std::fs::read_to_string");
+    assert_eq!(load["blocking_calls"][0]["is_async_context"], true);
+    assert!(finds(&krate, "async/blocking_call", "load"));
+}
+
+#[test]
+fn locks_record_held_guards_and_awaits() {
+    let krate = analyze();
+    let deposit = callable(&krate, "bank::accounts::Bank::deposit");
+    let locks: Vec<Value> = deposit["lock_acquisitions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| json!([l["lock"], l["held"]]))
+        .collect();
+    assert_eq!(
+        locks,
+        [
+            json!(["Bank.accounts", []]),
+            json!(["Bank.log", ["Bank.accounts"]])
+        ]
+    );
+    let tick = callable(&krate, "bank::service::Service::tick");
+    assert_eq!(
+        tick["guards_across_await"],
+        json!([{"lock": "Service.ticks", "guard": "ticks", "lock_line": 9, "await_line": 11}])
+    );
+    assert!(finds(&krate, "async/guard_across_await", "Service.ticks"));
+    assert!(finds(&krate, "lock/order_inversion", "Bank.accounts"));
+}
+
+#[test]
+fn control_flow_graph_models_match_arms_and_guards() {
+    let report = CfgReport::build(&analyze());
+    let balance = report
+        .functions
+        .iter()
+        .find(|f| f.function == "bank::accounts::Bank::balance")
+        .unwrap();
+    assert_eq!(balance.cyclomatic_complexity, 4);
+    let edges: Vec<Value> = to_value(&balance.graph)["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["kind"] != "next")
+        .map(|e| json!([e["kind"], e["label"]]))
+        .collect();
+    assert_eq!(
+        edges,
+        [
+            json!(["arm", "Some(balance)"]),
+            json!(["true", null]),
+            json!(["arm", "Some(_)"]),
+            json!(["false", null]),
+            json!(["arm", "None"]),
+        ]
+    );
+}
+
+#[test]
+fn dependence_graph_links_calls_to_parameters() {
+    let sdg = to_value(SystemDependenceGraph::build(&analyze()));
+    let edges = sdg["parameter_edges"].as_array().unwrap();
+    let edge = edges
+        .iter()
+        .find(|e| e["caller"] == "bank::deposit_all" && e["parameter"] == "amount")
+        .unwrap();
+    assert_eq!(edge["callee"], "bank::accounts::Bank::deposit");
+    assert_eq!(edge["variables"], json!(["line"]));
+
+    let deposit_all = sdg["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["function"] == "bank::deposit_all")
+        .unwrap();
+    let uses: Vec<&Value> = deposit_all["data_dependences"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| &d["variable"])
+        .collect();
+    assert!(uses.contains(&&json!("total")), "{:?}", uses);
+    assert!(uses.contains(&&json!("bank")), "{:?}", uses);
+}
@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::analyzer::cache::AnalysisCache;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("codeanalyzer-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn analyze(cache: &Path) {
+    Analyzer::new()
+        .source("lib.rs", "pub mod a; pub fn f() { a::g() }")
+        .source("a.rs", "pub fn g() {}")
+        .analysis_level(2)
+        .cache_dir(cache)
+        .analyze()
+        .unwrap();
+}
+
+/// Every file below `dir`, relative to it.
+fn files(dir: &Path) -> Vec<String> {
+    let mut out = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            out.extend(
+                files(&path)
+                    .into_iter()
+                    .map(|f| format!("{}/{}", path.file_name().unwrap().to_string_lossy(), f)),
+            );
+        } else {
+            out.push(path.file_name().unwrap().to_string_lossy().into_owned());
+        }
+    }
+    out.sort();
+    out
+}
+
+#[test]
+fn clear_removes_only_cache_entries() {
+    let dir = temp_dir("cache-clear");
+    fs::write(dir.join("notes.txt"), "keep me").unwrap();
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/lib.rs"), "fn main() {}").unwrap();
+    fs::create_dir_all(dir.join("ab")).unwrap();
+    fs::write(dir.join("ab/own.txt"), "not an entry").unwrap();
+
+    analyze(&dir);
+    let entries: Vec<String> = files(&dir)
+        .into_iter()
+        .filter(|f| f.ends_with(".json"))
+        .collect();
+    assert_eq!(entries.len(), 2);
+
+    AnalysisCache::new(&dir).clear().unwrap();
+    let left = files(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(left, ["ab/own.txt", "notes.txt", "src/lib.rs"]);
+}
+
+#[test]
+fn clear_of_missing_dir_succeeds() {
+    let dir = temp_dir("cache-missing");
+    AnalysisCache::new(dir.join("absent")).clear().unwrap();
+    let exists = dir.join("absent").exists();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(!exists);
+}
+
+#[test]
+fn cached_analysis_matches_a_fresh_one() {
+    let bank = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bank");
+    let dir = temp_dir("cache-hit");
+    let run = |cache: Option<&Path>| {
+        let mut analyzer = Analyzer::new().project(bank).analysis_level(2);
+        if let Some(cache) = cache {
+            analyzer = analyzer.cache_dir(cache);
+        }
+        serde_json::to_string(&analyzer.analyze().unwrap()).unwrap()
+    };
+    let fresh = run(None);
+    let stored = run(Some(&dir));
+    let entries = files(&dir).len();
+    let reused = run(Some(&dir));
+    let after = files(&dir).len();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(entries, 5);
+    assert_eq!(after, entries);
+    assert!(fresh == stored && stored == reused);
+}
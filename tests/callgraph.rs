@@ -0,0 +1,148 @@
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::analysis::callgraph::{CallGraph, Dispatch};
+use codeanalyzer_rs::entities::rcrate::RustCrate;
+
+fn analyze() -> RustCrate {
+    analyze_with(Dispatch::Cha)
+}
+
+fn analyze_with(dispatch: Dispatch) -> RustCrate {
+    Analyzer::new()
+        .crate_name("shapes")
+        .source("main.rs", include_str!("fixtures/shapes.rs"))
+        .analysis_level(2)
+        .dispatch(dispatch)
+        .analyze()
+        .expect("analysis failed")
+}
+
+/// The callees of the calls to `call` made by `caller`, and whether they
+/// are virtual.
+fn resolve(graph: &CallGraph, caller: &str, call: &str) -> (Vec<String>, bool) {
+    let edges: Vec<_> = graph
+        .callees(&format!("shapes::{}", caller))
+        .filter(|edge| edge.call == call)
+        .collect();
+    assert!(!edges.is_empty(), "no call to {} in {}", call, caller);
+    let is_virtual = edges.iter().all(|edge| edge.is_virtual);
+    let mut callees: Vec<String> = edges
+        .iter()
+        .map(|edge| edge.callee.trim_start_matches("shapes::").to_owned())
+        .collect();
+    callees.dedup();
+    (callees, is_virtual)
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| (*s).to_owned()).collect()
+}
+
+#[test]
+fn untyped_receiver_of_trait_method_is_virtual() {
+    let graph = CallGraph::build(&analyze());
+    let (callees, is_virtual) = resolve(&graph, "untyped", "area");
+    assert!(is_virtual);
+    assert_eq!(
+        callees,
+        strings(&[
+            "<Circle as Shape>::area",
+            "<Square as Shape>::area",
+            "<Unused as Shape>::area",
+            "Other::area",
+        ])
+    );
+    let (callees, is_virtual) = resolve(&graph, "untyped", "Shape::area");
+    assert!(is_virtual);
+    assert_eq!(
+        callees,
+        strings(&[
+            "<Circle as Shape>::area",
+            "<Square as Shape>::area",
+            "<Unused as Shape>::area",
+        ])
+    );
+}
+
+#[test]
+fn self_call_of_default_method_is_static() {
+    let graph = CallGraph::build(&analyze());
+    assert_eq!(
+        resolve(&graph, "Circle::report", "describe"),
+        (strings(&["Shape::describe"]), false)
+    );
+}
+
+#[test]
+fn rta_keeps_impls_of_constructed_types() {
+    let cha = CallGraph::build_with(&analyze(), Dispatch::Cha);
+    let rta = CallGraph::build_with(&analyze(), Dispatch::Rta);
+    assert_eq!(
+        resolve(&cha, "Holder::go", "area"),
+        (
+            strings(&[
+                "<Circle as Shape>::area",
+                "<Square as Shape>::area",
+                "<Unused as Shape>::area",
+            ]),
+            true
+        )
+    );
+    assert_eq!(
+        resolve(&rta, "Holder::go", "area"),
+        (
+            strings(&["<Circle as Shape>::area", "<Square as Shape>::area"]),
+            true
+        )
+    );
+    // Receivers of unknown type resolve by name, to the same impls.
+    for caller in ["untyped", "total"] {
+        assert_eq!(
+            resolve(&rta, caller, "area"),
+            (
+                strings(&[
+                    "<Circle as Shape>::area",
+                    "<Square as Shape>::area",
+                    "Other::area",
+                ]),
+                true
+            )
+        );
+    }
+    assert_eq!(
+        resolve(&rta, "untyped", "Shape::area"),
+        (
+            strings(&["<Circle as Shape>::area", "<Square as Shape>::area"]),
+            true
+        )
+    );
+}
+
+#[test]
+fn call_sites_record_their_callees() {
+    for dispatch in [Dispatch::Cha, Dispatch::Rta] {
+        let krate = analyze_with(dispatch);
+        let graph = CallGraph::build_with(&krate, dispatch);
+        let (_, _, total) = krate.callable("shapes::total").unwrap();
+        let site = total
+            .call_sites
+            .iter()
+            .find(|site| site.callee.as_deref() == Some("area"))
+            .unwrap();
+        let (callees, is_virtual) = resolve(&graph, "total", "area");
+        let callees: Vec<String> = callees
+            .iter()
+            .map(|callee| format!("shapes::{}", callee))
+            .collect();
+        assert_eq!(site.callees, callees);
+        assert_eq!(site.is_virtual, is_virtual);
+    }
+    let krate = analyze();
+    let (_, _, main) = krate.callable("shapes::main").unwrap();
+    let show = main
+        .call_sites
+        .iter()
+        .find(|site| site.callee.as_deref() == Some("show"))
+        .unwrap();
+    assert_eq!(show.callees, strings(&["shapes::show"]));
+    assert!(!show.is_virtual);
+}
@@ -0,0 +1,258 @@
+use std::process::{Command, Output};
+
+fn codeanalyzer(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_codeanalyzer"))
+        .args(args)
+        .output()
+        .expect("cannot run codeanalyzer")
+}
+
+#[test]
+fn taint_needs_level_3() {
+    let output = codeanalyzer(&["-s", "fn main() {}", "--format", "taint", "-a", "2"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--format taint requires --analysis-level 3"),
+        "{}",
+        stderr
+    );
+
+    let output = codeanalyzer(&["-s", "fn main() {}", "--format", "taint", "-a", "3"]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["flows"], serde_json::json!([]));
+}
+
+#[test]
+fn control_flow_graphs_need_level_2() {
+    for format in ["cfg", "dot"] {
+        let output = codeanalyzer(&["-s", "fn main() {}", "--format", format]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("--format {} requires --analysis-level 2", format)),
+            "{}",
+            stderr
+        );
+    }
+
+    let output = codeanalyzer(&[
+        "-s",
+        "fn main() { if true {} }",
+        "--format",
+        "dot",
+        "-a",
+        "2",
+    ]);
+    assert!(output.status.success());
+    let dot = String::from_utf8(output.stdout).unwrap();
+    assert!(dot.starts_with("digraph"), "{}", dot);
+    assert!(dot.contains("main"), "{}", dot);
+}
+
+#[test]
+fn error_graph_needs_level_2() {
+    let code = "#[derive(Debug)] pub struct Low;
+        impl std::fmt::Display for Low {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { write!(f, \"low\") }
+        }
+        impl std::error::Error for Low {}
+        fn low() -> Result<(), Low> { Err(Low) }
+        pub fn api2() -> Result<(), Low> { low()?; Ok(()) }";
+    let output = codeanalyzer(&["-s", code, "--format", "errors"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--format errors requires --analysis-level 2"),
+        "{}",
+        stderr
+    );
+
+    let output = codeanalyzer(&["-s", code, "--format", "errors", "-a", "2"]);
+    assert!(output.status.success());
+    let graph: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let api2 = graph["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["path"] == "source::api2")
+        .unwrap();
+    assert_eq!(api2["propagations"][0]["callees"][0], "source::low");
+    assert_eq!(api2["propagations"][0]["source"], "source::Low");
+    assert_eq!(api2["reachable_errors"], serde_json::json!(["source::Low"]));
+}
+
+#[test]
+fn system_dependence_graph_needs_level_3() {
+    let output = codeanalyzer(&["-s", "fn main() {}", "--format", "sdg", "-a", "2"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--format sdg requires --analysis-level 3"),
+        "{}",
+        stderr
+    );
+
+    let code = "fn double(x: u32) -> u32 { x * 2 } fn main() { let y = double(1); }";
+    let output = codeanalyzer(&["-s", code, "--format", "sdg", "-a", "3"]);
+    assert!(output.status.success());
+    let sdg: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(sdg["parameter_edges"][0]["callee"], "source::double");
+    assert_eq!(sdg["parameter_edges"][0]["parameter"], "x");
+    assert_eq!(sdg["return_edges"][0]["caller"], "source::main");
+}
+
+#[test]
+fn sarif_needs_level_3() {
+    let bank = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bank");
+    for level in ["1", "2"] {
+        let output = codeanalyzer(&["-i", bank, "--no-cache", "--format", "sarif", "-a", level]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("--format sarif requires --analysis-level 3"),
+            "{}",
+            stderr
+        );
+    }
+
+    let output = codeanalyzer(&["-i", bank, "--no-cache", "--format", "sarif", "-a", "3"]);
+    assert!(output.status.success());
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let findings: Vec<(&str, &str)> = log["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["ruleId"].as_str().unwrap(),
+                r["message"]["text"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    // Both come from the call graph: the panic `deposit_all` reaches through
+    // `Bank::deposit` and the foreign function `cosine` calls.
+    for (rule, text) in [
+        ("panic/missing_panics_doc", "`deposit_all`"),
+        ("unsafe/safe_api_reaches_ffi", "cosine"),
+    ] {
+        assert!(
+            findings.iter().any(|(r, m)| *r == rule && m.contains(text)),
+            "no {} finding about {}",
+            rule,
+            text
+        );
+    }
+}
+
+const SHAPES: &str = include_str!("fixtures/shapes.rs");
+
+/// The callees of the call of `Holder::go`, the only call to a trait object
+/// method through a field, in the JSON, NDJSON and SQLite outputs.
+fn holder_callees(dispatch: &str) -> [Vec<String>; 3] {
+    let args = ["-s", SHAPES, "-a", "2", "--dispatch", dispatch];
+
+    let output = codeanalyzer(&args);
+    assert!(output.status.success());
+    let krate: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let holder = krate["modules"][0]["impls"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|imp| imp["type_name"] == "Holder")
+        .unwrap();
+    let site = &holder["methods"]["go"]["call_sites"][0];
+    assert_eq!(site["is_virtual"], true);
+    let json = serde_json::from_value(site["callees"].clone()).unwrap();
+
+    let output = codeanalyzer(&[&args[..], &["--format", "ndjson"]].concat());
+    assert!(output.status.success());
+    let call = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|record| record["id"] == "call:source::Holder::go@62#0")
+        .unwrap();
+    assert_eq!(call["is_virtual"], true);
+    let ndjson = serde_json::from_value(call["callees"].clone()).unwrap();
+
+    let dir = std::env::temp_dir().join(format!(
+        "codeanalyzer-dispatch-{}-{}",
+        dispatch,
+        std::process::id()
+    ));
+    let out = dir.to_str().unwrap();
+    let output = codeanalyzer(&[&args[..], &["--format", "sqlite", "-o", out]].concat());
+    assert!(output.status.success());
+    let db = rusqlite::Connection::open(dir.join("analysis.db")).unwrap();
+    let mut stmt = db
+        .prepare(
+            "SELECT callee.qualified_name
+             FROM call_sites cs
+             JOIN callables caller ON caller.id = cs.callable_id
+             JOIN call_targets ct ON ct.call_site_id = cs.id
+             JOIN callables callee ON callee.id = ct.callee_id
+             WHERE caller.qualified_name = 'source::Holder::go' AND cs.is_virtual
+             ORDER BY callee.qualified_name",
+        )
+        .unwrap();
+    let sqlite = stmt
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<Vec<String>, _>>()
+        .unwrap();
+    drop(stmt);
+    drop(db);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    [json, ndjson, sqlite]
+}
+
+#[test]
+fn dispatch_resolves_call_sites_in_every_output() {
+    let all = [
+        "source::<Circle as Shape>::area",
+        "source::<Square as Shape>::area",
+        "source::<Unused as Shape>::area",
+    ]
+    .map(String::from);
+    for callees in holder_callees("cha") {
+        assert_eq!(callees, all);
+    }
+    for callees in holder_callees("rta") {
+        assert_eq!(callees, all[..2]);
+    }
+
+    let output = codeanalyzer(&["-s", "fn main() {}", "--dispatch", "vta"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn output_does_not_depend_on_jobs() {
+    let bank = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bank");
+    for format in ["json", "ndjson", "sarif", "sdg"] {
+        let run = |jobs: &str| {
+            let output = codeanalyzer(&[
+                "-i",
+                bank,
+                "--no-cache",
+                "-a",
+                "3",
+                "--format",
+                format,
+                "--jobs",
+                jobs,
+            ]);
+            assert!(output.status.success(), "{:?}", output);
+            output.stdout
+        };
+        let sequential = run("1");
+        assert!(!sequential.is_empty());
+        assert!(
+            sequential == run("8"),
+            "{} output differs across --jobs",
+            format
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::analysis::deadcode;
+
+/// Item paths of the unreachable items of the crate made of `files`, sorted.
+fn dead(files: &[(&str, &str)]) -> Vec<String> {
+    let mut analyzer = Analyzer::new().crate_name("app").analysis_level(2);
+    for (name, code) in files {
+        analyzer = analyzer.source(*name, *code);
+    }
+    let krate = analyzer.analyze().expect("analysis failed");
+    let mut paths: Vec<String> = deadcode::check(&krate)
+        .into_iter()
+        .map(|d| d.item_path.unwrap())
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn private_helpers_of_public_functions_are_reached() {
+    let code = "pub fn api() -> u32 { helper() }
+                fn helper() -> u32 { 1 }
+                fn orphan() {}";
+    assert_eq!(dead(&[("lib.rs", code)]), ["app::orphan"]);
+}
+
+#[test]
+fn items_only_used_by_unreached_items_are_reported() {
+    let code = "pub fn api() {}
+                fn orphan() { used_by_orphan(); }
+                fn used_by_orphan() { let _ = Only; }
+                struct Only;";
+    assert_eq!(
+        dead(&[("lib.rs", code)]),
+        ["app::Only", "app::orphan", "app::used_by_orphan"]
+    );
+}
+
+#[test]
+fn public_items_of_private_modules_are_not_exported() {
+    let code = "mod inner { pub fn unused() {} pub struct Hidden; }
+                pub mod outer { pub fn api() {} mod deep { pub fn buried() {} } }";
+    assert_eq!(
+        dead(&[("lib.rs", code)]),
+        [
+            "app::inner::Hidden",
+            "app::inner::unused",
+            "app::outer::deep::buried",
+        ]
+    );
+}
+
+#[test]
+fn re_exported_items_are_entry_points() {
+    let code = "mod inner { pub fn shown() {} pub fn hidden() {} }
+                mod globbed { pub fn all() {} pub(crate) fn internal() {} }
+                pub use inner::shown;
+                pub use crate::globbed::*;";
+    assert_eq!(
+        dead(&[("lib.rs", code)]),
+        ["app::globbed::internal", "app::inner::hidden"]
+    );
+}
+
+#[test]
+fn public_methods_are_exported_with_their_type() {
+    let code = "pub struct Open; impl Open { pub fn method(&self) {} fn private(&self) {} }
+                mod inner { pub struct Closed; impl Closed { pub fn shut(&self) {} } }";
+    assert_eq!(
+        dead(&[("lib.rs", code)]),
+        [
+            "app::Open::private",
+            "app::inner::Closed",
+            "app::inner::Closed::shut",
+        ]
+    );
+}
+
+#[test]
+fn binaries_export_nothing() {
+    let code = "pub fn used() {} pub fn unused() {} pub struct Unused;
+                fn main() { used() }";
+    assert_eq!(dead(&[("main.rs", code)]), ["app::Unused", "app::unused"]);
+}
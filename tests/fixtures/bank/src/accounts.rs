@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug)]
+pub enum BankError {
+    Missing(String),
+    Invalid(std::num::ParseIntError),
+}
+
+impl std::fmt::Display for BankError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for BankError {}
+
+impl From<std::num::ParseIntError> for BankError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        BankError::Invalid(e)
+    }
+}
+
+pub struct Bank {
+    accounts: Mutex<HashMap<String, i64>>,
+    log: Mutex<Vec<String>>,
+}
+
+impl Bank {
+    pub fn new() -> Self {
+        Bank {
+            accounts: Mutex::new(HashMap::new()),
+            log: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Adds `amount` to the account of `name`.
+    pub fn deposit(&self, name: &str, amount: &str) -> Result<i64, BankError> {
+        let amount: i64 = amount.parse()?;
+        let mut accounts = self.accounts.lock().unwrap();
+        let balance = accounts.entry(name.to_owned()).or_insert(0);
+        *balance += amount;
+        let mut log = self.log.lock().unwrap();
+        log.push(format!("deposit {} {}", name, amount));
+        Ok(*balance)
+    }
+
+    /// Number of log lines and accounts.
+    pub fn audit(&self) -> usize {
+        let log = self.log.lock().unwrap();
+        let accounts = self.accounts.lock().unwrap();
+        log.len() + accounts.len()
+    }
+
+    pub fn balance(&self, name: &str) -> Result<i64, BankError> {
+        let accounts = self.accounts.lock().unwrap();
+        match accounts.get(name) {
+            Some(balance) if *balance >= 0 => Ok(*balance),
+            Some(_) => Ok(0),
+            None => Err(BankError::Missing(name.to_owned())),
+        }
+    }
+}
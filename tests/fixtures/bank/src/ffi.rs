@@ -0,0 +1,27 @@
+#[link(name = "m")]
+extern "C" {
+    fn cos(x: f64) -> f64;
+    static errno: i32;
+}
+
+#[no_mangle]
+pub extern "C" fn bank_version() -> u32 {
+    1
+}
+
+#[no_mangle]
+pub extern "C" fn bank_name_len(name: String) -> usize {
+    name.len()
+}
+
+pub fn cosine(x: f64) -> f64 {
+    unsafe { cos(x) }
+}
+
+pub struct Registers {
+    pub pc: u64,
+}
+
+extern "C" {
+    static registers: Registers;
+}
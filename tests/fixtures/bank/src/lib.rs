@@ -0,0 +1,32 @@
+//! A small bank that exercises every analysis of the integration tests.
+
+pub mod accounts;
+pub mod ffi;
+pub mod raw;
+pub mod service;
+
+pub use accounts::Bank;
+
+/// Returns the first of `values`.
+pub fn first(values: &[i64]) -> i64 {
+    values[0]
+}
+
+/// Parses and deposits every line of `input`.
+///
+/// # Errors
+///
+/// Fails on the first line that is not a number.
+pub fn deposit_all(bank: &Bank, input: &str) -> Result<i64, accounts::BankError> {
+    let mut total = 0;
+    for line in input.lines() {
+        total += bank.deposit("all", line)?;
+    }
+    Ok(total)
+}
+
+fn unused_helper() -> u32 {
+    7
+}
+
+struct Ledger;
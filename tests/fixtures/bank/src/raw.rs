@@ -0,0 +1,19 @@
+#[repr(C)]
+pub union Bits {
+    pub float: f32,
+    pub int: u32,
+}
+
+pub fn to_bits(x: f32) -> u32 {
+    let bits = Bits { float: x };
+    unsafe { bits.int }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub fn pause() {
+    unsafe {
+        std::arch::asm!("pause", options(nomem, nostack));
+    }
+}
+
+std::arch::global_asm!(".globl bank_marker", "bank_marker:");
@@ -0,0 +1,32 @@
+use std::sync::{Arc, Mutex};
+
+pub struct Service {
+    ticks: Mutex<u64>,
+}
+
+impl Service {
+    pub async fn tick(&self) {
+        let mut ticks = self.ticks.lock().unwrap();
+        *ticks += 1;
+        rest().await;
+        drop(ticks);
+    }
+
+    pub async fn load(&self) -> String {
+        std::fs::read_to_string("ticks.txt").unwrap_or_default()
+    }
+
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move { self.tick().await });
+    }
+}
+
+async fn rest() {}
+
+pub async fn audit() -> u64 {
+    tokio::task::spawn_blocking(checksum).await.unwrap_or_default()
+}
+
+fn checksum() -> u64 {
+    0
+}
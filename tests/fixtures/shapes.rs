@@ -0,0 +1,82 @@
+use std::fmt::Debug;
+
+pub trait Named {
+    fn name(&self) -> String;
+}
+
+pub trait Shape: Named {
+    fn area(&self) -> f64;
+    fn describe(&self) -> String {
+        format!("{} {}", self.name(), self.area())
+    }
+}
+
+struct Circle(f64);
+struct Square { side: f64 }
+struct Unused;
+
+impl Named for Circle { fn name(&self) -> String { "circle".into() } }
+impl Named for Square { fn name(&self) -> String { "square".into() } }
+impl Named for Unused { fn name(&self) -> String { "unused".into() } }
+
+impl Shape for Circle {
+    fn area(&self) -> f64 { self.0 * self.0 * 3.14 }
+}
+impl Shape for Square {
+    fn area(&self) -> f64 { self.side * self.side }
+    fn describe(&self) -> String { "square!".into() }
+}
+impl Shape for Unused {
+    fn area(&self) -> f64 { 0.0 }
+}
+
+impl Circle {
+    fn report(&self) -> String {
+        self.describe()
+    }
+}
+
+struct Other;
+impl Other { fn area(&self) -> f64 { 1.0 } }
+
+fn total(shapes: &[Box<dyn Shape>]) -> f64 {
+    let mut sum = 0.0;
+    for s in shapes { sum += s.area(); }
+    sum
+}
+
+fn show(shape: &dyn Shape) -> String {
+    shape.describe()
+}
+
+fn generic<T: Shape + Debug>(shape: &T) -> String {
+    shape.name()
+}
+
+fn where_generic<T>(shape: T) -> f64 where T: Shape {
+    shape.area()
+}
+
+struct Holder { inner: Box<dyn Shape> }
+impl Holder {
+    fn go(&self) -> f64 { self.inner.area() }
+}
+
+fn main() {
+    let c = Circle(1.0);
+    let s = Square { side: 2.0 };
+    let o = Other;
+    println!("{}", show(&c));
+    println!("{}", generic(&s));
+    println!("{}", where_generic(Circle(2.0)));
+    println!("{}", o.area());
+    let _ = total(&[]);
+}
+
+fn untyped(shapes: Vec<Circle>) -> f64 {
+    let mut sum = 0.0;
+    for s in &shapes {
+        sum += s.area();
+    }
+    sum + Shape::area(&shapes[0])
+}
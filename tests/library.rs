@@ -0,0 +1,79 @@
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::entities::rcrate::RustCrate;
+
+fn analyze(files: &[(&str, &str)]) -> RustCrate {
+    let mut analyzer = Analyzer::new();
+    for (name, code) in files {
+        analyzer = analyzer.source(*name, *code);
+    }
+    analyzer.analyze().expect("analysis failed")
+}
+
+#[test]
+fn impls_attach_to_the_type_they_name() {
+    let krate = analyze(&[
+        (
+            "lib.rs",
+            "mod a { pub struct Foo; impl Foo { pub fn only_a(&self) {} } impl Clone for Foo { fn clone(&self) -> Self { Foo } } }
+             mod b { pub struct Foo(*const u8); unsafe impl Send for Foo {} impl Foo { pub fn only_b(&self) {} } }
+             mod c { use crate::a::Foo; impl Foo { pub fn from_c(&self) {} } }
+             mod d { impl super::b::Foo { pub fn from_d(&self) {} } }",
+        ),
+    ]);
+    let root = &krate.modules[0];
+    let a = &root.submodules["a"].structs["Foo"];
+    let b = &root.submodules["b"].structs["Foo"];
+    let names = |items: &std::collections::BTreeMap<String, _>| -> Vec<String> {
+        items.keys().cloned().collect()
+    };
+    assert_eq!(names(&a.associated_items), ["from_c", "only_a"]);
+    assert_eq!(names(&b.associated_items), ["from_d", "only_b"]);
+    assert_eq!(a.impl_traits, ["Clone"]);
+    assert_eq!(b.impl_traits, ["Send"]);
+    assert!(!a.contains_unsafe);
+    assert!(b.contains_unsafe);
+}
+
+#[test]
+fn impls_of_foreign_types_attach_to_nothing() {
+    let krate = analyze(&[(
+        "lib.rs",
+        "pub struct String; trait Shout { fn shout(&self); }
+         impl Shout for std::string::String { fn shout(&self) {} }",
+    )]);
+    assert!(krate.modules[0].structs["String"].impl_traits.is_empty());
+}
+
+#[test]
+fn cfg_variants_of_an_item_are_listed_as_shadowed() {
+    let krate = analyze(&[(
+        "lib.rs",
+        "#[cfg(unix)] pub fn plat() -> u8 { 1 }
+         #[cfg(windows)]
+         pub fn plat() -> u8 { 2 }
+         #[cfg(unix)] mod sys { pub struct Handle; }
+         #[cfg(windows)] mod sys { pub struct Socket; }",
+    )]);
+    let root = &krate.modules[0];
+    assert!(root.functions["plat"].code.contains("1"));
+    assert!(root.submodules["sys"].structs.contains_key("Handle"));
+    let shadowed: Vec<_> = root
+        .shadowed_items
+        .iter()
+        .map(|item| {
+            (
+                item.kind.as_str(),
+                item.name.as_str(),
+                item.cfg.clone(),
+                item.start_line,
+            )
+        })
+        .collect();
+    assert_eq!(
+        shadowed,
+        [
+            ("function", "plat", vec!["windows".to_owned()], 3),
+            ("module", "sys", vec!["windows".to_owned()], 5),
+        ]
+    );
+}
@@ -0,0 +1,243 @@
+use std::path::PathBuf;
+
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::mcp::McpServer;
+use codeanalyzer_rs::watch::WatchSession;
+use serde_json::{Value, json};
+
+/// A package named `area`, in a fresh directory named after `name`.
+fn project(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("codeanalyzer-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"area\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("src/lib.rs"),
+        r#"pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+pub struct Circle {
+    pub r: f64,
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.r * self.r
+    }
+}
+
+pub struct Square(pub f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+
+/// Area of a circle of radius `r`.
+pub fn circle_area(r: f64) -> f64 {
+    let circle = Circle { r };
+    circle.area()
+}
+
+pub fn magnitude(x: i32) -> i32 {
+    // SAFETY: `abs` is defined for every `i32` but `i32::MIN`.
+    unsafe { abs(x) }
+}
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+fn request(server: &mut McpServer, method: &str, params: Value) -> Value {
+    let message = json!({"jsonrpc": "2.0", "id": 7, "method": method, "params": params});
+    let reply = server.handle(&message).expect("no reply to a request");
+    assert_eq!(reply["id"], 7);
+    reply
+}
+
+/// Calls a tool and returns the text it answered and whether it failed.
+fn call(server: &mut McpServer, tool: &str, arguments: Value) -> (String, bool) {
+    let reply = request(
+        server,
+        "tools/call",
+        json!({"name": tool, "arguments": arguments}),
+    );
+    let result = &reply["result"];
+    assert_eq!(result["content"][0]["type"], "text");
+    let text = result["content"][0]["text"].as_str().unwrap().to_owned();
+    (text, result["isError"].as_bool().unwrap())
+}
+
+/// The server for a fresh `area` project; `name` keeps the directories of
+/// concurrent tests apart.
+fn area_server(name: &str) -> McpServer {
+    let dir = project(name);
+    let analyzer = Analyzer::new().project(&dir).analysis_level(3);
+    let server = McpServer::new(WatchSession::new(analyzer).unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+    server
+}
+
+#[test]
+fn tools_are_listed_with_their_schemas() {
+    let mut server = area_server("mcp-list");
+    let reply = request(&mut server, "tools/list", json!({}));
+    let tools = reply["result"]["tools"].as_array().unwrap();
+    let names: Vec<&str> = tools
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "search_symbols",
+            "get_callable",
+            "list_call_sites",
+            "get_callers",
+            "get_callees",
+            "find_trait_implementors",
+            "get_safety_report",
+        ]
+    );
+    for tool in tools {
+        assert_eq!(tool["inputSchema"]["type"], "object", "{}", tool["name"]);
+    }
+}
+
+#[test]
+fn search_ignores_the_crate_name_and_ranks_exact_names_first() {
+    let mut server = area_server("mcp-search");
+    let (text, is_error) = call(&mut server, "search_symbols", json!({"query": "area"}));
+    assert!(!is_error);
+    assert_eq!(
+        text,
+        "4 symbol(s) match `area`\n\
+         - method `area::<Circle as Shape>::area` (src/lib.rs:10)\n\
+         - method `area::<Square as Shape>::area` (src/lib.rs:18)\n\
+         - method `area::Shape::area` (src/lib.rs:2)\n\
+         - function `area::circle_area` (src/lib.rs:28)\n"
+    );
+    let (text, _) = call(
+        &mut server,
+        "search_symbols",
+        json!({"query": "CIRCLE", "kind": "struct"}),
+    );
+    assert_eq!(
+        text,
+        "1 symbol(s) match `CIRCLE`\n- struct `area::Circle` (src/lib.rs:5)\n"
+    );
+    let (text, _) = call(
+        &mut server,
+        "search_symbols",
+        json!({"query": "area", "limit": 1}),
+    );
+    assert!(text.starts_with("4 symbol(s) match `area`, showing the first 1\n"));
+    assert_eq!(text.lines().count(), 2);
+}
+
+#[test]
+fn get_callable_returns_documentation_and_code() {
+    let mut server = area_server("mcp-callable");
+    let (text, is_error) = call(
+        &mut server,
+        "get_callable",
+        json!({"path": "area::circle_area"}),
+    );
+    assert!(!is_error);
+    assert!(text.starts_with("# `area::circle_area`\n\nDefined in src/lib.rs lines 28-31.\n"));
+    assert!(text.contains("Area of a circle of radius `r`."));
+    assert!(text.contains("```rust\npub fn circle_area(r: f64) -> f64 {"));
+
+    let (text, is_error) = call(
+        &mut server,
+        "get_callable",
+        json!({"path": "area::missing"}),
+    );
+    assert!(is_error);
+    assert!(text.starts_with("No callable at `area::missing`."));
+}
+
+#[test]
+fn list_call_sites_shows_calls_as_written() {
+    let mut server = area_server("mcp-sites");
+    let (text, _) = call(
+        &mut server,
+        "list_call_sites",
+        json!({"path": "area::circle_area"}),
+    );
+    assert_eq!(
+        text,
+        "Call sites in `area::circle_area`: 1\n- line 30: `circle.area(..)`\n"
+    );
+}
+
+#[test]
+fn get_callers_and_callees_follow_the_call_graph() {
+    let mut server = area_server("mcp-edges");
+    let (text, _) = call(
+        &mut server,
+        "get_callers",
+        json!({"path": "area::<Circle as Shape>::area"}),
+    );
+    assert!(text.starts_with("Callers of `area::<Circle as Shape>::area`: 1\n"));
+    assert!(text.contains("- `area::circle_area` (line 30, calls `area`"));
+
+    let (text, _) = call(
+        &mut server,
+        "get_callees",
+        json!({"path": "area::circle_area"}),
+    );
+    assert!(text.starts_with("Callees of `area::circle_area`: "));
+    assert!(text.contains("- `area::<Circle as Shape>::area` (line 30"));
+}
+
+#[test]
+fn find_trait_implementors_lists_types_and_methods() {
+    let mut server = area_server("mcp-implementors");
+    let (text, _) = call(
+        &mut server,
+        "find_trait_implementors",
+        json!({"trait": "Shape"}),
+    );
+    assert_eq!(
+        text,
+        "Implementors of `Shape`: 2\n\
+         - `Circle` in `area` (src/lib.rs:9-13), methods: area\n\
+         - `Square` in `area` (src/lib.rs:17-21), methods: area\n"
+    );
+}
+
+#[test]
+fn get_safety_report_explains_unsafe_code() {
+    let mut server = area_server("mcp-safety");
+    let (text, _) = call(
+        &mut server,
+        "get_safety_report",
+        json!({"path": "area::magnitude"}),
+    );
+    assert!(text.starts_with("Safety of `area::magnitude`: unsafe_container\n"));
+    assert!(text.contains("- unsafe block, lines 35-35: ffi_call\n"));
+    assert!(text.contains("  SAFETY: `abs` is defined for every `i32` but `i32::MIN`.\n"));
+    assert!(text.contains("- calls foreign functions: abs\n"));
+}
+
+#[test]
+fn unknown_tools_are_protocol_errors() {
+    let mut server = area_server("mcp-unknown");
+    let reply = request(
+        &mut server,
+        "tools/call",
+        json!({"name": "rename", "arguments": {}}),
+    );
+    assert_eq!(reply["error"]["code"], -32601);
+}
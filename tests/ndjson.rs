@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::output::ndjson::NdjsonWriter;
+use serde_json::Value;
+
+const LIB: &str = "\
+pub mod ffi;
+
+pub struct Buffer {
+    len: usize,
+}
+
+impl Buffer {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl Clone for Buffer {
+    fn clone(&self) -> Self {
+        Buffer { len: ffi::checked(self.len) }
+    }
+}
+";
+
+const FFI: &str = "\
+unsafe extern \"C\" {
+    fn abs(x: i32) -> i32;
+}
+
+pub fn checked(len: usize) -> usize {
+    let x = unsafe { abs(len as i32) };
+    if x < 0 {
+        panic!(\"negative\");
+    }
+    x as usize
+}
+";
+
+fn analyzer() -> Analyzer {
+    Analyzer::new()
+        .crate_name("buf")
+        .source("lib.rs", LIB)
+        .source("ffi.rs", FFI)
+        .analysis_level(2)
+}
+
+/// Parses NDJSON output into records by id.
+fn records(out: Vec<u8>) -> BTreeMap<String, Value> {
+    String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let record: Value = serde_json::from_str(line).unwrap();
+            (record["id"].as_str().unwrap().to_owned(), record)
+        })
+        .collect()
+}
+
+fn streamed(analyzer: &Analyzer) -> BTreeMap<String, Value> {
+    let mut writer = NdjsonWriter::new(Vec::new());
+    analyzer.analyze_streaming(&mut writer).unwrap();
+    records(writer.into_inner())
+}
+
+fn written(analyzer: &Analyzer) -> BTreeMap<String, Value> {
+    let krate = analyzer.analyze().unwrap();
+    let mut writer = NdjsonWriter::new(Vec::new());
+    writer.write_crate(&krate).unwrap();
+    records(writer.into_inner())
+}
+
+#[test]
+fn streamed_root_module_is_named() {
+    let records = streamed(&analyzer());
+    let root = &records["module:buf"];
+    assert_eq!(root["name"], "buf");
+    assert_eq!(root["is_root_module"], true);
+    assert_eq!(root["crate"], "crate:buf");
+    let ffi = &records["module:buf::ffi"];
+    assert_eq!(ffi["is_root_module"], false);
+    assert_eq!(ffi["parent"], "module:buf");
+}
+
+#[test]
+fn streaming_leaves_out_only_linked_fields() {
+    let mut streamed = streamed(&analyzer());
+    let mut written = written(&analyzer());
+
+    let clone = "callable:buf::<Buffer as Clone>::clone";
+    assert!(written[clone]["safety_analysis"]["transitive"].is_object());
+    assert!(written[clone]["transitive_panics"].is_array());
+    assert_eq!(
+        written["struct:buf::Buffer"]["impl_traits"],
+        serde_json::json!(["Clone"])
+    );
+    assert!(streamed[clone]["safety_analysis"]["transitive"].is_null());
+    assert!(streamed[clone]["transitive_panics"].is_null());
+    assert_eq!(
+        streamed["struct:buf::Buffer"]["impl_traits"],
+        serde_json::json!([])
+    );
+    assert_eq!(
+        streamed["call:buf::<Buffer as Clone>::clone@15#0"]["callees"],
+        serde_json::json!(["buf::ffi::checked"])
+    );
+
+    for records in [&mut streamed, &mut written] {
+        for record in records.values_mut() {
+            record["impl_traits"].take();
+            record["transitive_panics"].take();
+            record["safety_analysis"]["transitive"].take();
+        }
+    }
+    assert_eq!(streamed, written);
+}
+
+#[test]
+fn call_records_follow_every_other_record() {
+    let mut writer = NdjsonWriter::new(Vec::new());
+    analyzer().analyze_streaming(&mut writer).unwrap();
+    let out = String::from_utf8(writer.into_inner()).unwrap();
+    let kinds: Vec<Value> = out
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["kind"].take())
+        .collect();
+    let first_call = kinds.iter().position(|kind| kind == "call").unwrap();
+    assert!(kinds[first_call..].iter().all(|kind| kind == "call"));
+}
+
+#[test]
+fn streaming_reads_modules_back_from_cache() {
+    let dir = std::env::temp_dir().join(format!("codeanalyzer-ndjson-{}", std::process::id()));
+    let cached = analyzer().cache_dir(&dir);
+    let first = streamed(&cached);
+    let second = streamed(&cached);
+    let entries = std::fs::read_dir(&dir).unwrap().count();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(entries > 0);
+    assert_eq!(first, second);
+    assert_eq!(first, streamed(&analyzer()));
+}
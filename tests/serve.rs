@@ -0,0 +1,99 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::mcp::McpServer;
+use codeanalyzer_rs::serve::Server;
+use codeanalyzer_rs::watch::WatchSession;
+use serde_json::{Value, json};
+
+/// A package with one function, in a fresh directory named after `name`.
+fn project(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("codeanalyzer-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "pub fn answer() -> u32 { 42 }\n").unwrap();
+    dir
+}
+
+fn session(dir: &PathBuf) -> WatchSession {
+    WatchSession::new(Analyzer::new().project(dir).analysis_level(2)).unwrap()
+}
+
+#[test]
+fn framed_request_is_answered() {
+    let dir = project("serve-framed");
+    let mut server = Server::new(session(&dir));
+    std::fs::remove_dir_all(&dir).unwrap();
+    let body = r#"{"jsonrpc":"2.0","id":1,"method":"get-callable-by-path","params":{"path":"demo::answer"}}"#;
+    let input = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let mut output = Vec::new();
+    server.run(input.as_bytes(), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let (header, reply) = output.split_once("\r\n\r\n").unwrap();
+    assert_eq!(header, format!("Content-Length: {}", reply.len()));
+    let reply: Value = serde_json::from_str(reply).unwrap();
+    assert_eq!(reply["id"], 1);
+    assert_eq!(reply["result"]["callable"]["name"], "answer");
+}
+
+/// Replies of `run` to `input`, with the `Content-Length` headers removed.
+fn replies(
+    input: &[u8],
+    run: impl FnOnce(&[u8], &mut Vec<u8>) -> std::io::Result<()>,
+) -> Vec<Value> {
+    let mut output = Vec::new();
+    run(input, &mut output).unwrap();
+    String::from_utf8(output)
+        .unwrap()
+        .split("Content-Length: ")
+        .filter_map(|frame| frame.split_once("\r\n\r\n"))
+        .map(|(_, reply)| serde_json::from_str(reply).unwrap())
+        .collect()
+}
+
+#[test]
+fn bad_content_length_is_answered_and_serving_goes_on() {
+    let dir = project("serve-bad-length");
+    let mut server = Server::new(session(&dir));
+    let mut mcp = McpServer::new(session(&dir));
+    std::fs::remove_dir_all(&dir).unwrap();
+    let ping = r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#;
+    let input = format!(
+        "Content-Length: x\r\n\r\nContent-Length: {}\r\n\r\n{}{}",
+        ping.len(),
+        ping,
+        // The rest of the input is taken for the body of this frame.
+        "Content-Length: 99999999999\r\n\r\n{}"
+    );
+
+    let answers = replies(input.as_bytes(), |input, output| mcp.run(input, output));
+    let codes: Vec<&Value> = answers
+        .iter()
+        .map(|reply| &reply["error"]["code"])
+        .collect();
+    assert_eq!(codes, [&json!(-32600), &Value::Null, &json!(-32600)]);
+    assert_eq!(answers[1]["id"], 2);
+    assert_eq!(answers[1]["result"], json!({}));
+    let message = answers[2]["error"]["message"].as_str().unwrap();
+    assert!(message.contains("exceeds the limit"));
+
+    let invalid_utf8 = b"Content-Length: 1\r\n\r\n\xff";
+    let answers = replies(invalid_utf8, |input, output| server.run(input, output));
+    assert_eq!(answers[0]["error"]["code"], -32700);
+}
+
+#[test]
+fn truncated_body_is_an_error() {
+    let dir = project("serve-truncated");
+    let mut server = Server::new(session(&dir));
+    std::fs::remove_dir_all(&dir).unwrap();
+    let error = server
+        .run(&b"Content-Length: 100\r\n\r\n{}"[..], Vec::new())
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+}
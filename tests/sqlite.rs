@@ -0,0 +1,123 @@
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::output::sqlite::SqliteExporter;
+use rusqlite::Connection;
+
+fn export(code: &str) -> SqliteExporter {
+    let krate = Analyzer::new()
+        .crate_name("db")
+        .source("lib.rs", code)
+        .analysis_level(2)
+        .analyze()
+        .unwrap();
+    let mut exporter =
+        SqliteExporter::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+    exporter.write_crate(&krate).unwrap();
+    exporter
+}
+
+fn strings(conn: &Connection, sql: &str) -> Vec<String> {
+    let mut stmt = conn.prepare(sql).unwrap();
+    stmt.query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+const CALLS: &str = "
+pub trait Speak { fn speak(&self) -> String; }
+pub struct Dog;
+pub struct Cat;
+impl Speak for Dog { fn speak(&self) -> String { helper() } }
+impl Speak for Cat { fn speak(&self) -> String { String::new() } }
+fn helper() -> String { String::from(\"woof\") }
+pub fn chorus(animal: &dyn Speak) -> String {
+    let line = animal.speak();
+    std::mem::drop(helper());
+    line
+}
+";
+
+#[test]
+fn call_sites_refer_to_their_single_callee() {
+    let exporter = export(CALLS);
+    let conn = exporter.connection();
+    let rows = strings(
+        conn,
+        "SELECT cs.callee_name || ' -> ' || coalesce(callee.qualified_name, 'NULL')
+         FROM call_sites cs
+         JOIN callables caller ON caller.id = cs.callable_id
+         LEFT JOIN callables callee ON callee.id = cs.callee_id
+         WHERE caller.qualified_name = 'db::chorus'
+         ORDER BY cs.id",
+    );
+    assert_eq!(
+        rows,
+        [
+            "speak -> NULL",
+            "std::mem::drop -> NULL",
+            "helper -> db::helper",
+        ]
+    );
+
+    let targets = strings(
+        conn,
+        "SELECT callee.qualified_name
+         FROM call_sites cs
+         JOIN call_targets ct ON ct.call_site_id = cs.id
+         JOIN callables callee ON callee.id = ct.callee_id
+         WHERE cs.callee_name = 'speak'
+         ORDER BY callee.qualified_name",
+    );
+    assert_eq!(
+        targets,
+        ["db::<Cat as Speak>::speak", "db::<Dog as Speak>::speak"]
+    );
+}
+
+#[test]
+fn documented_query_joins_on_callee_id() {
+    let code = "
+pub fn entry() { unsafe { risky(1) } }
+unsafe fn risky(x: u32) -> u32 { x }
+fn safe() -> u32 { 0 }
+pub fn other() { let _ = safe(); }
+";
+    let exporter = export(code);
+    let callees = strings(
+        exporter.connection(),
+        "SELECT DISTINCT callee.qualified_name
+         FROM call_sites cs
+         JOIN callables caller ON caller.id = cs.callable_id
+         JOIN callables callee ON callee.id = cs.callee_id
+         WHERE caller.visibility = 'pub' AND callee.is_unsafe",
+    );
+    assert_eq!(callees, ["db::risky"]);
+}
+
+#[test]
+fn unions_are_exported_with_their_fields() {
+    let code = "
+mod raw {
+    /// Bits of a float.
+    #[repr(C)]
+    pub union Bits {
+        pub float: f32,
+        int: u32,
+    }
+}
+";
+    let exporter = export(code);
+    let conn = exporter.connection();
+    let unions = strings(
+        conn,
+        "SELECT qualified_name || ' ' || visibility || ' ' || repr || ' ' || doc_comment FROM unions",
+    );
+    assert_eq!(unions, ["db::raw::Bits pub C Bits of a float."]);
+    let fields = strings(
+        conn,
+        "SELECT f.position || ' ' || f.name || ': ' || f.type || ' ' || f.visibility
+         FROM fields f JOIN unions u ON u.id = f.union_id
+         ORDER BY f.position",
+    );
+    assert_eq!(fields, ["0 float: f32 pub", "1 int: u32 "]);
+}
@@ -0,0 +1,136 @@
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::analysis::taint::{TaintConfig, TaintReport};
+use codeanalyzer_rs::entities::rcrate::RustCrate;
+
+fn analyze(code: &str) -> RustCrate {
+    Analyzer::new()
+        .crate_name("tp")
+        .source("lib.rs", code)
+        .analysis_level(3)
+        .analyze()
+        .expect("analysis failed")
+}
+
+fn flows(krate: &RustCrate, config: &str) -> Vec<(String, String, usize)> {
+    let config = TaintConfig::from_toml(config).expect("invalid configuration");
+    TaintReport::build(krate, &config)
+        .flows
+        .into_iter()
+        .map(|flow| (flow.source, flow.sink, flow.line))
+        .collect()
+}
+
+const LOCAL_SANITIZER: &str = "\
+pub mod util {
+    pub fn clean(s: String) -> String {
+        s.replace(';', \"\")
+    }
+}
+
+pub fn run() {
+    let name = std::env::var(\"NAME\").unwrap();
+    let safe = util::clean(name);
+    std::process::Command::new(safe);
+}
+";
+
+#[test]
+fn reports_flow_through_crate_function() {
+    let krate = analyze(LOCAL_SANITIZER);
+    assert_eq!(
+        flows(&krate, ""),
+        [(
+            "std::env::var".to_owned(),
+            "std::process::Command::new".to_owned(),
+            10
+        )]
+    );
+}
+
+#[test]
+fn crate_sanitizer_stops_flow() {
+    let krate = analyze(LOCAL_SANITIZER);
+    for spelling in ["tp::util::clean", "crate::util::clean", "util::clean"] {
+        let config = format!("[[sanitizers]]\ncall = \"{}\"", spelling);
+        assert_eq!(flows(&krate, &config), [], "sanitizer {}", spelling);
+    }
+}
+
+#[test]
+fn crate_qualified_sources_and_sinks_match() {
+    let krate = analyze(
+        "\
+pub fn my_source() -> String {
+    String::new()
+}
+
+pub mod db {
+    pub fn exec(_query: &str) {}
+}
+
+pub fn run() {
+    let input = my_source();
+    db::exec(&input);
+}
+",
+    );
+    for (source, sink) in [
+        ("tp::my_source", "tp::db::exec"),
+        ("crate::my_source", "crate::db::exec"),
+        ("my_source", "db::exec"),
+    ] {
+        let config = format!(
+            "defaults = false\n[[sources]]\ncall = \"{}\"\n[[sinks]]\ncall = \"{}\"",
+            source, sink
+        );
+        assert_eq!(
+            flows(&krate, &config),
+            [(source.to_owned(), sink.to_owned(), 11)]
+        );
+    }
+}
+
+#[test]
+fn unsanitized_path_is_kept_next_to_sanitized_one() {
+    let krate = analyze(
+        "\
+pub fn clean(s: &str) -> String {
+    s.replace(';', \"\")
+}
+
+pub fn run() {
+    let name = std::env::var(\"NAME\").unwrap();
+    let safe = clean(&name);
+    let raw = name.clone();
+    std::process::Command::new(safe).arg(raw);
+}
+",
+    );
+    let config = "[[sanitizers]]\ncall = \"tp::clean\"";
+    assert_eq!(
+        flows(&krate, config),
+        [(
+            "std::env::var".to_owned(),
+            "std::process::Command::arg".to_owned(),
+            9
+        )]
+    );
+}
+
+#[test]
+fn format_sink_needs_formatted_argument() {
+    let krate = analyze(
+        "\
+pub async fn run() {
+    let id = std::env::var(\"ID\").unwrap();
+    sqlx::query(&id);
+    let query = format!(\"SELECT * FROM t WHERE id = {}\", id);
+    sqlx::query(&query);
+}
+",
+    );
+    assert_eq!(
+        flows(&krate, ""),
+        [("std::env::var".to_owned(), "sqlx::query".to_owned(), 5)]
+    );
+}
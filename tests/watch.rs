@@ -0,0 +1,220 @@
+//! Incremental updates of a [`WatchSession`] on a package on disk.
+
+use std::path::PathBuf;
+
+use codeanalyzer_rs::Analyzer;
+use codeanalyzer_rs::watch::{Delta, DeltaEvent, WatchSession, diff};
+
+const MANIFEST: &str = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n";
+
+/// A package in a fresh directory named after `name`, removed on drop.
+struct Project(PathBuf);
+
+impl Project {
+    fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("codeanalyzer-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let project = Self(dir);
+        project.write("Cargo.toml", MANIFEST);
+        for (path, text) in files {
+            project.write(path, text);
+        }
+        project
+    }
+
+    fn write(&self, path: &str, text: &str) {
+        let path = self.0.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    fn remove(&self, path: &str) {
+        std::fs::remove_file(self.0.join(path)).unwrap();
+    }
+
+    fn session(&self) -> WatchSession {
+        WatchSession::new(Analyzer::new().project(&self.0).analysis_level(2)).unwrap()
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Renders deltas as `<event> <id>` lines, in the order they were reported.
+fn show(deltas: &[Delta]) -> Vec<String> {
+    deltas
+        .iter()
+        .map(|delta| {
+            let event = match delta.event {
+                DeltaEvent::Added => "added",
+                DeltaEvent::Removed => "removed",
+                DeltaEvent::Changed => "changed",
+            };
+            format!("{} {}", event, delta.id)
+        })
+        .collect()
+}
+
+/// The analysis of `session` from scratch, for comparison with its
+/// incrementally updated one.
+fn fresh(session: &WatchSession) -> Vec<Delta> {
+    let krate = Analyzer::new()
+        .project(session.root())
+        .analysis_level(2)
+        .analyze()
+        .unwrap();
+    diff(session.krate(), &krate)
+}
+
+#[test]
+fn edited_file_reports_its_changes_and_keeps_the_others() {
+    let project = Project::new(
+        "watch-edit",
+        &[
+            (
+                "src/lib.rs",
+                "mod util;\npub fn run() -> u32 { util::one() }\n",
+            ),
+            (
+                "src/util.rs",
+                "pub fn one() -> u32 { 1 }\npub struct Unit;\n",
+            ),
+        ],
+    );
+    let mut session = project.session();
+    let before = session.krate().modules[0].submodules["util"].clone();
+
+    project.write(
+        "src/lib.rs",
+        "mod util;\npub fn run() -> u32 { util::one() + 1 }\npub fn stop() {}\n",
+    );
+    let deltas = session.update(["src/lib.rs"]).unwrap();
+    assert_eq!(
+        show(&deltas),
+        ["changed callable:demo::run", "added callable:demo::stop"]
+    );
+    assert_eq!(session.krate().modules[0].submodules["util"], before);
+    assert!(fresh(&session).is_empty());
+    assert!(session.update(Vec::<PathBuf>::new()).unwrap().is_empty());
+}
+
+#[test]
+fn modules_join_and_leave_the_tree() {
+    let project = Project::new("watch-modules", &[("src/lib.rs", "pub fn run() {}\n")]);
+    let mut session = project.session();
+
+    // The new file is picked up through the declaration alone.
+    project.write("src/extra.rs", "pub fn more() {}\npub enum Mode { A }\n");
+    project.write("src/lib.rs", "pub fn run() {}\npub mod extra;\n");
+    let deltas = session.update(["src/lib.rs"]).unwrap();
+    assert_eq!(
+        show(&deltas),
+        [
+            "added callable:demo::extra::more",
+            "added enum:demo::extra::Mode",
+            "added module:demo::extra",
+        ]
+    );
+    assert!(session.files().contains("src/extra.rs"));
+    assert!(fresh(&session).is_empty());
+
+    project.write("src/lib.rs", "pub fn run() {}\n");
+    project.remove("src/extra.rs");
+    let deltas = session.update(["src/lib.rs", "src/extra.rs"]).unwrap();
+    assert_eq!(
+        show(&deltas),
+        [
+            "removed callable:demo::extra::more",
+            "removed enum:demo::extra::Mode",
+            "removed module:demo::extra",
+        ]
+    );
+    assert!(!session.files().contains("src/extra.rs"));
+}
+
+#[test]
+fn moved_file_is_analyzed_for_its_new_module() {
+    let project = Project::new(
+        "watch-moved",
+        &[
+            ("src/lib.rs", "mod util;\n"),
+            ("src/util.rs", "pub fn one() -> u32 { 1 }\n"),
+        ],
+    );
+    let mut session = project.session();
+
+    project.write("src/lib.rs", "#[path = \"util.rs\"]\nmod helpers;\n");
+    let deltas = session.update(["src/lib.rs"]).unwrap();
+    assert_eq!(
+        show(&deltas),
+        [
+            "added callable:demo::helpers::one",
+            "removed callable:demo::util::one",
+            "added module:demo::helpers",
+            "removed module:demo::util",
+        ]
+    );
+    assert!(fresh(&session).is_empty());
+}
+
+#[test]
+fn overlays_replace_the_file_on_disk_until_cleared() {
+    let project = Project::new("watch-overlay", &[("src/lib.rs", "pub fn run() {}\n")]);
+    let mut session = project.session();
+
+    session.set_text(
+        "src/lib.rs",
+        Some("pub fn run() {}\npub fn draft() {}\n".to_owned()),
+    );
+    let deltas = session.update([project.0.join("src/lib.rs")]).unwrap();
+    assert_eq!(show(&deltas), ["added callable:demo::draft"]);
+
+    session.set_text(project.0.join("src/lib.rs"), None);
+    let deltas = session.update(["src/lib.rs"]).unwrap();
+    assert_eq!(show(&deltas), ["removed callable:demo::draft"]);
+}
+
+#[test]
+fn manifest_change_reloads_the_package() {
+    let project = Project::new(
+        "watch-manifest",
+        &[(
+            "src/lib.rs",
+            "pub fn run() {}\n#[cfg(feature = \"extra\")]\npub fn extra() {}\n",
+        )],
+    );
+    let mut session = project.session();
+    assert!(!session.krate().modules[0].functions.contains_key("extra"));
+
+    project.write(
+        "Cargo.toml",
+        &format!(
+            "{}\n[features]\ndefault = [\"extra\"]\nextra = []\n",
+            MANIFEST
+        ),
+    );
+    let deltas = session.update(["Cargo.toml"]).unwrap();
+    assert_eq!(show(&deltas), ["added callable:demo::extra"]);
+}
+
+#[test]
+fn diff_reports_changed_items_by_id() {
+    let analyze = |code: &str| Analyzer::new().source("lib.rs", code).analyze().unwrap();
+    let old = analyze("pub struct A;\npub fn f() {}\npub trait T {}\npub union U { x: u8 }\n");
+    let new = analyze("pub struct A(u8);\npub fn f() {}\npub trait T {}\npub fn g() {}\n");
+    assert_eq!(
+        show(&diff(&old, &new)),
+        [
+            "added callable:source::g",
+            // The module lists the names of its types.
+            "changed module:source",
+            "changed struct:source::A",
+            "removed union:source::U",
+        ]
+    );
+    assert!(diff(&new, &new).is_empty());
+}